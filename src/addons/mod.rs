@@ -1,4 +1,9 @@
-use crate::{ADDON_DIR, ADDON_TRANSPOSE, reset_noctavox};
+use crate::{
+    ADDON_DIR, ADDON_TRANSPOSE, Database, PLAYLIST_DIR, SOCKET_PATH,
+    app_core::NoctaVox,
+    control::{self, Status},
+    reset_noctavox,
+};
 use anyhow::Result;
 use clap::{ArgGroup, Parser};
 use std::{path::PathBuf, process::Command};
@@ -11,7 +16,17 @@ use std::{path::PathBuf, process::Command};
 )]
 #[command(group(
       ArgGroup::new("mode")
-          .args(["import_playlist", "export_playlist", "list", "reset"]),
+          .args([
+              "import_playlist",
+              "export_playlist",
+              "list",
+              "reset",
+              "export_loved",
+              "import_loved",
+              "daemon",
+              "send",
+              "status",
+          ]),
   ))]
 struct Cli {
     /// Import a playlist from a csv or m3u file
@@ -29,6 +44,27 @@ struct Cli {
     /// Destroy database completely
     #[arg(long)]
     reset: bool,
+
+    /// Export loved tracks to m3u8 [default: playlists/loved.m3u8]
+    #[arg(long, value_name = "FILE")]
+    export_loved: Option<Option<PathBuf>>,
+
+    /// Mark the tracks of an m3u file as loved [default: playlists/loved.m3u8]
+    #[arg(long, value_name = "FILE")]
+    import_loved: Option<Option<PathBuf>>,
+
+    /// Play without the TUI, controlled through a Unix socket
+    #[arg(long)]
+    daemon: bool,
+
+    /// Send a command to a running daemon, e.g. `--send enqueue song.flac`
+    #[arg(long, value_name = "COMMAND", num_args = 1..)]
+    send: Option<Vec<String>>,
+
+    /// Print the playing track as JSON, from a running player or else the
+    /// last session
+    #[arg(long)]
+    status: bool,
 }
 
 pub fn parse_args() {
@@ -42,9 +78,76 @@ pub fn parse_args() {
         let _ = run_addon(ADDON_TRANSPOSE, &["--list"]);
     } else if cli.reset {
         let _ = reset_noctavox();
+    } else if let Some(file) = cli.export_loved {
+        let _ = export_loved(file).inspect_err(|e| eprintln!("Error: {e}"));
+    } else if let Some(file) = cli.import_loved {
+        let _ = import_loved(file).inspect_err(|e| eprintln!("Error: {e}"));
+    } else if cli.daemon {
+        if let Err(e) = NoctaVox::new().and_then(|mut app| app.run_daemon()) {
+            eprintln!("Error: {e}");
+            std::process::exit(1)
+        }
+    } else if cli.status {
+        match status_line() {
+            Ok(status) => println!("{status}"),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1)
+            }
+        }
+    } else if let Some(words) = cli.send {
+        match send_command(words) {
+            Ok(reply) => println!("{reply}"),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1)
+            }
+        }
     };
 }
 
+/// Paths are made absolute here, as the daemon has a working directory of
+/// its own
+fn send_command(mut words: Vec<String>) -> Result<String> {
+    if words.len() > 1 && control::Command::takes_path(&words[0]) {
+        let path = words[1..].join(" ");
+        let path = std::fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(path));
+        words.truncate(1);
+        words.push(path.to_string_lossy().into_owned());
+    }
+    control::send(&SOCKET_PATH, &words.join(" "))
+}
+
+/// A running player knows best, whichever front-end it has
+fn status_line() -> Result<String> {
+    if let Ok(status) = control::send(&SOCKET_PATH, "status") {
+        return Ok(status);
+    }
+    Ok(Status::saved(&mut Database::open()?)?.to_json())
+}
+
+fn export_loved(file: Option<PathBuf>) -> Result<()> {
+    let dest = file.unwrap_or_else(|| PLAYLIST_DIR.join("loved.m3u8"));
+    let count = Database::open()?.export_loved_m3u(&dest)?;
+
+    println!("Exported {count} loved tracks to {}", dest.display());
+    Ok(())
+}
+
+fn import_loved(file: Option<PathBuf>) -> Result<()> {
+    let file = file.unwrap_or_else(|| PLAYLIST_DIR.join("loved.m3u8"));
+    let imported = Database::open()?.import_loved_m3u(&file)?;
+
+    println!("Marked {} tracks as loved", imported.added);
+    if !imported.missing.is_empty() {
+        println!("{} not found in library:", imported.missing.len());
+        for entry in &imported.missing {
+            println!("  {entry}");
+        }
+    }
+    Ok(())
+}
+
 fn addon_path(name: &str) -> PathBuf {
     if let Ok(entries) = std::fs::read_dir(&*ADDON_DIR) {
         let mut matches: Vec<PathBuf> = entries
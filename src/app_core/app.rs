@@ -1,17 +1,29 @@
 use crate::{
-    Library, USER_CONFIG, UserConfig,
-    app_core::{NoctaVox, key_loop},
-    config::{TIMING, Timing},
+    Library, SOCKET_PATH, USER_CONFIG, UserConfig,
+    app_core::{NoctaVox, input_loop},
+    config::{TIMING, Timing, timing},
+    control::ControlServer,
     overwrite_line,
     playback::ValidatedSong,
-    player::PlayerHandle,
+    player::{Output, OutputGain, PlayerHandle},
     tui,
     ui_state::{Mode, PopupType, SettingsMode, UiState},
     user_config,
+    visualization::TapSubscriber,
 };
-use anyhow::Result;
-use std::sync::Arc;
-use voxio::Vox;
+use anyhow::{Result, anyhow};
+use crossbeam_channel::bounded;
+use ratatui::crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+};
+use std::{
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use voxio::{Vox, VoxConfig, VoxEvents};
+
+const OUTPUT_RETRY_DELAY: Duration = Duration::from_secs(2);
 
 impl NoctaVox {
     pub fn new() -> Result<Self> {
@@ -20,26 +32,49 @@ impl NoctaVox {
 
         let lib = Arc::new(Library::init_and_build()?);
 
-        let (mut vox, events) = Vox::new()?;
-        let tap = vox.take_tap().expect("Vox yields its tap on first call");
-        vox.set_replaygain(user_config().general.replay_gain);
-        let vox = Arc::new(vox);
+        let output = Arc::new(Output::new(user_config().general.replay_gain));
+        let gain = Rc::new(OutputGain::new(Arc::clone(&output)));
 
-        let player = PlayerHandle::new(Arc::clone(&vox), events)?;
+        let player = PlayerHandle::new(Arc::clone(&output), Rc::clone(&gain))?;
 
-        let media_controls = crate::media_controls::MediaControlsHandle::new()
-            .map_err(|e| eprintln!("OS media controls unavailable: {e}"))
-            .ok();
+        // Not being able to register is no reason not to start
+        let media_controls = match user_config().general.media_keys {
+            true => crate::media_controls::MediaControlsHandle::new()
+                .map_err(|e| eprintln!("OS media controls unavailable: {e}"))
+                .ok(),
+            false => None,
+        };
 
         let mut nv = NoctaVox {
             player,
-            ui: UiState::new(lib, vox, tap),
+            ui: UiState::new(lib, output, None, gain),
             library_refresh_rec: None,
             media_controls,
+            #[cfg(feature = "discord")]
+            presence: Self::init_discord_presence(),
+            #[cfg(feature = "notifications")]
+            notifier: Self::init_notifier(),
+            #[cfg(feature = "scrobble")]
+            scrobbler: None,
             tick_sync: 0,
             restored_song_id: None,
+            advance_on_stop: false,
+            device_error: None,
+            output_pending: None,
+            resume_on_connect: false,
+            cue_seeked_at: None,
+            status_file: Self::init_status_file(),
+            loudness_scan: None,
+            silence: Self::init_silence_trimmer(),
+            control: None,
         };
+        nv.open_output();
+        nv.update_loudness_scan();
+        nv.init_scrobbler();
 
+        if let Some(warning) = nv.ui.theme.skipped_warning() {
+            nv.ui.set_error(warning);
+        }
         if let Some(e) = config_err {
             nv.ui.set_error(e);
         }
@@ -47,29 +82,62 @@ impl NoctaVox {
         Ok(nv)
     }
 
+    /// A live copy of the output samples, for tools embedding the player to
+    /// run their own visualizations or analysis. See [`TapSubscriber`].
+    pub fn subscribe_tap(&mut self) -> TapSubscriber {
+        self.ui.viz.subscribe_tap()
+    }
+
     pub fn run(&mut self) {
         match ratatui::run(|t| -> anyhow::Result<()> {
-            self.preload_lib();
-            self.restore_last_session();
-            let _ = self.restore_last_played();
+            self.start_session();
+            self.ui.match_terminal_background();
 
             if self.ui.library().roots.is_empty() {
                 self.ui
                     .show_popup(PopupType::Settings(SettingsMode::AddRoot));
             }
 
-            let key_rx = key_loop();
+            let input_rx = input_loop();
+            // Scripts can drive the TUI as they would the daemon, unless
+            // another player already holds the socket
+            self.control = ControlServer::bind(SOCKET_PATH.clone()).ok();
+            let _ = ratatui::crossterm::execute!(std::io::stdout(), EnableFocusChange);
+            if self.ui.mouse.enabled {
+                let _ = ratatui::crossterm::execute!(std::io::stdout(), EnableMouseCapture);
+            }
 
+            let mut drawn_at = Instant::now();
             loop {
-                self.select_shortcut(&key_rx);
-                t.draw(|f| tui::render(f, &mut self.ui))?;
+                let woke = self.select_shortcut(&input_rx);
+                self.update_core();
+                self.ui.watch_themes();
+                self.ui.update_idle();
 
-                if self.ui.get_mode() == Mode::QUIT {
-                    self.ui.update_now_playing_elapsed();
-                    self.player.stop();
-                    if let Some(mc) = self.media_controls.take() {
-                        std::thread::spawn(move || drop(mc));
+                // Anything that happened is shown at once, otherwise frames
+                // come only as often as the cap for now allows
+                let animating = self.player.is_active() && !self.player.is_paused();
+                let period = match animating && !self.ui.power_save() {
+                    true => timing().viz_period,
+                    false => timing().idle_period,
+                };
+                if woke || drawn_at.elapsed() >= period {
+                    t.draw(|f| tui::render(f, &mut self.ui))?;
+                    if self.ui.art.needs_repaint() {
+                        t.clear()?;
+                        t.draw(|f| tui::render(f, &mut self.ui))?;
                     }
+                    self.ui.art.flush();
+                    drawn_at = Instant::now();
+                }
+
+                if self.ui.get_mode() == Mode::QUIT {
+                    self.shut_down();
+                    let _ = ratatui::crossterm::execute!(
+                        std::io::stdout(),
+                        DisableFocusChange,
+                        DisableMouseCapture
+                    );
                     break;
                 }
             }
@@ -83,6 +151,64 @@ impl NoctaVox {
         };
     }
 
+    /// The library and whatever was left playing, shared by the TUI and
+    /// the daemon
+    pub(super) fn start_session(&mut self) {
+        self.preload_lib();
+        self.restore_last_session();
+        if user_config().general.restore_session {
+            // With no device yet, the saved track waits for one rather than
+            // being taken for missing
+            let restored = match self.ui.metrics.is_connected() {
+                true => self.restore_last_played(),
+                false => {
+                    self.resume_on_connect = true;
+                    true
+                }
+            };
+            let _ = self.restore_queue();
+            self.resume_queue(restored);
+        }
+        self.ui.open_startup_view();
+    }
+
+    /// Everything playback needs done each time round the main loop,
+    /// whether or not anything is drawn
+    pub(super) fn update_core(&mut self) {
+        self.update_output();
+        let handoff = self.ui.playback.gapless_is_enabled() && self.preload_target().is_some();
+        self.player.update_crossfade(handoff);
+        self.player.update_pause_fade();
+        self.update_prefetch();
+        self.update_sleep_timer();
+        self.update_ab_loop();
+        match self.ui.update_play_tally() {
+            Ok(true) => self.scrobble(),
+            Ok(false) => (),
+            Err(e) => self.ui.set_error(e),
+        }
+        self.ui.update_resume_point();
+        if let Err(e) = self.update_cue_track() {
+            self.ui.set_error(e);
+        }
+        if let Err(e) = self.update_silence_trim() {
+            self.ui.set_error(e);
+        }
+    }
+
+    pub(super) fn shut_down(&mut self) {
+        let _ = self.ui.save_session();
+        self.player.stop();
+        if let Some(mc) = self.media_controls.take() {
+            std::thread::spawn(move || drop(mc));
+        }
+        #[cfg(feature = "discord")]
+        drop(self.presence.take());
+        drop(self.status_file.take());
+        drop(self.loudness_scan.take());
+        drop(self.control.take());
+    }
+
     fn load_config() -> Option<anyhow::Error> {
         match UserConfig::load() {
             Ok(cfg) => {
@@ -96,13 +222,88 @@ impl NoctaVox {
         }
     }
 
+    /// Without a device the library can still be browsed, while the output
+    /// is retried in the background. Bluetooth outputs in particular can
+    /// take a moment to appear after waking.
+    fn open_output(&mut self) {
+        let error = match Vox::new_with_config(Self::output_config()) {
+            Ok(output) => return self.connect_output(output),
+            Err(e) => e,
+        };
+
+        let msg = format!("No audio output device\n\n{error}\n\nRetrying in the background...");
+        self.ui.set_error(anyhow!(msg.clone()));
+        self.device_error = Some(msg);
+
+        let (tx, rx) = bounded(1);
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(OUTPUT_RETRY_DELAY);
+                if let Ok(output) = Vox::new_with_config(Self::output_config()) {
+                    let _ = tx.send(output);
+                    return;
+                }
+            }
+        });
+        self.output_pending = Some(rx);
+    }
+
+    /// Picks up the output once the background retry has opened it
+    fn update_output(&mut self) {
+        let Some(output) = self
+            .output_pending
+            .as_ref()
+            .and_then(|rx| rx.try_recv().ok())
+        else {
+            return;
+        };
+        self.output_pending = None;
+        self.connect_output(output);
+        self.close_device_error();
+
+        if std::mem::take(&mut self.resume_on_connect) {
+            let restored = self.restore_last_played();
+            self.force_sync();
+            self.resume_queue(restored);
+        }
+    }
+
+    fn connect_output(&mut self, output: (Vox, VoxEvents)) {
+        if let Some(tap) = self.ui.metrics.connect(output) {
+            self.ui.viz.set_tap(tap);
+        }
+    }
+
+    /// Leaves any other error on screen
+    pub(super) fn close_device_error(&mut self) {
+        if let Some(msg) = self.device_error.take()
+            && self.ui.get_error() == Some(msg.as_str())
+        {
+            self.ui.close_popup();
+        }
+    }
+
+    /// The engine's tap holds the visualizers' history in stereo, and never
+    /// less than its own default, so a smaller history doesn't start losing
+    /// audio between frames
+    fn output_config() -> VoxConfig {
+        let defaults = VoxConfig::default();
+        VoxConfig {
+            tap_capacity: (user_config().general.tap_capacity * 2).max(defaults.tap_capacity),
+            ..defaults
+        }
+    }
+
     fn init_timings() {
-        let fps = USER_CONFIG
+        let general = &USER_CONFIG
             .get()
             .expect("Failed to read user config")
-            .general
-            .framerate;
-        let _ = TIMING.set(Timing::from_fps(fps));
+            .general;
+        let _ = TIMING.set(Timing::from_fps(
+            general.framerate,
+            general.viz_framerate,
+            general.idle_framerate,
+        ));
     }
 
     fn preload_lib(&mut self) {
@@ -117,21 +318,56 @@ impl NoctaVox {
         let _ = self.ui.restore_last_state();
     }
 
-    fn restore_last_played(&mut self) -> Result<()> {
-        if let Ok((song_id, elapsed_secs)) = self.ui.restore_last_played()
-            && let Some(song) = self.ui.library().get_song_by_id(song_id)
-        {
-            let song = ValidatedSong::new(song)?;
+    /// False when there was a track to come back to, but it has left the
+    /// library or its file has gone missing
+    fn restore_last_played(&mut self) -> bool {
+        let Ok((song_id, elapsed_secs)) = self.ui.restore_last_played() else {
+            return true;
+        };
+        let Some(song) = self
+            .ui
+            .library()
+            .get_song_by_id(song_id)
+            .and_then(|s| ValidatedSong::new(s).ok())
+        else {
+            return false;
+        };
 
-            self.restored_song_id = Some(song_id);
-            self.play_song(song.as_ref())?;
+        self.restored_song_id = Some(song_id);
+        if self.play_song(song.as_ref()).is_err() {
+            return false;
+        }
+        self.player.seek_to(elapsed_secs);
+        self.settle_restored();
+        true
+    }
 
-            self.player.seek_to(elapsed_secs);
+    /// The saved track is gone, so the queue carries on without it
+    fn resume_queue(&mut self, restored: bool) {
+        if !restored && !self.ui.playback.queue_is_empty() && self.play_next().is_ok() {
+            self.settle_restored();
+        }
+    }
 
-            if !user_config().general.auto_resume {
-                self.player.pause();
-            }
+    /// Left paused, or faded in from the saved position so it doesn't cut
+    /// in at full volume mid-phrase
+    fn settle_restored(&mut self) {
+        let general = &user_config().general;
+        match general.auto_resume {
+            true => self
+                .player
+                .fade_in(Duration::from_millis(general.resume_fade_ms as u64)),
+            false => self.player.pause_now(),
         }
+    }
+
+    /// Songs whose files have gone missing are dropped along the way
+    fn restore_queue(&mut self) -> Result<()> {
+        let songs = self.ui.load_session()?;
+        self.ui.playback.enqueue_multi(&songs)?;
+
+        self.force_sync();
+        self.ui.set_legal_songs();
         Ok(())
     }
 }
@@ -0,0 +1,81 @@
+use crate::{
+    SOCKET_PATH,
+    app_core::NoctaVox,
+    control::{self, Command, ControlServer},
+    key_handler::Action,
+    ui_state::{Mode, PopupType},
+};
+use anyhow::Result;
+use crossbeam_channel::never;
+
+impl NoctaVox {
+    /// Plays without a terminal, taking its orders from the control socket
+    /// instead of the keyboard. The player and library underneath are the
+    /// same ones the TUI drives.
+    pub fn run_daemon(&mut self) -> Result<()> {
+        self.control = Some(ControlServer::bind(SOCKET_PATH.clone())?);
+        self.start_session();
+        self.report_errors();
+        eprintln!("NoctaVox listening on {}", SOCKET_PATH.display());
+
+        loop {
+            self.select_shortcut(&never());
+            self.update_core();
+            self.report_errors();
+
+            if self.ui.get_mode() == Mode::QUIT {
+                self.shut_down();
+                return Ok(());
+            }
+        }
+    }
+
+    /// Nobody sees popups here, so errors go to stderr for the service
+    /// manager to log, and anything else to stdout
+    fn report_errors(&mut self) {
+        match &self.ui.popup.current {
+            PopupType::Error(msg) => eprintln!("{msg}"),
+            PopupType::Info(msg) => println!("{msg}"),
+            _ => return,
+        }
+        self.ui.close_popup();
+    }
+
+    pub(super) fn handle_control(&mut self, command: &Command) -> String {
+        let result = match command {
+            Command::Status => return self.status_json(),
+            Command::Play => self.play_or_resume(),
+            Command::PlayPath(path) => self.ui.song_at_path(path).and_then(|song| {
+                self.push_queue_front(&song)?;
+                self.play_next()
+            }),
+            Command::Pause => {
+                self.player.pause();
+                Ok(())
+            }
+            Command::Toggle => self.handle_action(Action::TogglePlayback),
+            Command::Stop => self.handle_action(Action::Stop),
+            Command::Next => self.handle_action(Action::PlayNext),
+            Command::Prev => self.handle_action(Action::PlayPrev),
+            Command::Seek(to) => self.seek_to_input(to),
+            Command::Enqueue(path) => self
+                .ui
+                .song_at_path(path)
+                .and_then(|song| self.queue_song(&song)),
+            Command::Quit => self.handle_action(Action::QUIT),
+        };
+
+        match result {
+            Ok(()) => control::ok_reply(),
+            Err(e) => control::error_reply(&e),
+        }
+    }
+
+    fn play_or_resume(&mut self) -> Result<()> {
+        match self.player.is_active() {
+            true => self.player.resume(),
+            false => self.play_next()?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::app_core::NoctaVox;
+use anyhow::Result;
+
+#[cfg(feature = "discord")]
+use crate::{
+    discord::{DiscordPresence, Presence},
+    library::SongInfo,
+    user_config,
+};
+#[cfg(feature = "discord")]
+use anyhow::bail;
+
+#[cfg(feature = "discord")]
+impl NoctaVox {
+    pub(super) fn init_discord_presence() -> Option<DiscordPresence> {
+        let general = &user_config().general;
+        match general.discord_presence && !general.discord_client_id.is_empty() {
+            true => Some(DiscordPresence::new(general.discord_client_id.clone())),
+            false => None,
+        }
+    }
+
+    /// Driven off the same tick as the OS media controls. Unchanged state is
+    /// filtered out by the presence thread, so this is cheap to call often.
+    pub(super) fn sync_discord_presence(&self) {
+        let Some(presence) = self.presence.as_ref() else {
+            return;
+        };
+
+        match self.ui.get_now_playing() {
+            Some(song) if self.player.is_active() => presence.set(Presence::new(
+                song.get_title(),
+                song.get_artist(),
+                song.get_album(),
+                self.player.elapsed(),
+                song.get_duration(),
+                self.player.is_paused(),
+            )),
+            _ => presence.clear(),
+        }
+    }
+
+    pub fn toggle_discord_presence(&mut self) -> Result<()> {
+        if self.presence.take().is_some() {
+            self.ui
+                .set_notice(String::from("Discord Rich Presence disabled"));
+            return Ok(());
+        }
+
+        let client_id = &user_config().general.discord_client_id;
+        if client_id.is_empty() {
+            bail!("Set `discord_client_id` in config.toml to use Discord Rich Presence");
+        }
+
+        self.presence = Some(DiscordPresence::new(client_id.clone()));
+        self.sync_discord_presence();
+        self.ui
+            .set_notice(String::from("Discord Rich Presence enabled"));
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "discord"))]
+impl NoctaVox {
+    pub(super) fn sync_discord_presence(&self) {}
+
+    pub fn toggle_discord_presence(&mut self) -> Result<()> {
+        anyhow::bail!("NoctaVox was built without Discord Rich Presence")
+    }
+}
@@ -1,7 +1,19 @@
 use crate::{app_core::NoctaVox, key_handler::Action, ui_state::Mode};
 use anyhow::Result;
 use crossbeam_channel::Receiver;
-use ratatui::crossterm::{self, event::KeyEvent};
+use ratatui::crossterm::{
+    self,
+    event::{Event, KeyEventKind, MouseEventKind},
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Only known if the terminal reports focus changes; assumed unfocused otherwise
+static TERMINAL_FOCUSED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "notifications")]
+pub fn terminal_focused() -> bool {
+    TERMINAL_FOCUSED.load(Ordering::Relaxed)
+}
 
 impl NoctaVox {
     #[rustfmt::skip]
@@ -11,23 +23,44 @@ impl NoctaVox {
             Action::Play(c)         => self.play_selected_song(c)?,
             Action::TogglePlayback  => self.player.toggle_playback(),
             Action::Stop            => self.stop(),
-            Action::Seek(s)         => self.player.seek(s),
-            Action::PlayNext        => self.play_next()?,
+            Action::Undo            => self.undo()?,
+            Action::Seek(s)         => self.seek(s),
+            Action::SeekPercent(p)  => self.seek_percent(p),
+            Action::SeekFraction(f) => self.seek_fraction(f),
+            Action::SeekInput       => self.ui.show_seek_popup(),
+            Action::SeekInputConfirm=> self.confirm_seek_input(),
+            Action::CommandLine     => self.ui.open_command_line(),
+            Action::CommandConfirm  => self.confirm_command()?,
+            Action::CommandComplete => self.ui.complete_command(),
+            Action::MarkAbLoop      => self.mark_ab_loop(),
+            Action::PlayNext        => self.skip_to_next()?,
             Action::PlayPrev        => self.play_prev()?,
             Action::VolumeShift(v)  => self.player.adjust_volume(v),
+            Action::ToggleMute      => self.player.toggle_mute(),
 
             // UI 
             Action::Scroll(s)       => self.ui.scroll(s),
+            Action::JumpTo(p)       => self.ui.jump_to(&p),
             Action::GoToTrack(c)    => self.ui.go_to_track(c)?,
             Action::GoToAlbum       => self.ui.go_to_album()?,
+            Action::PlayAlbum       => self.play_album()?,
             Action::GoTo(r)         => self.ui.go_to(r),
             Action::GoToNowPlaying  => self.ui.go_to_now_playing()?,
             Action::ChangeMode(m)   => self.ui.set_mode(m),
             Action::ChangePane(p)   => self.ui.set_pane(p),
             Action::SortColumnsNext => self.ui.next_song_column(),
             Action::SortColumnsPrev => self.ui.prev_song_column(),
+            Action::ReverseSort     => self.ui.reverse_sort(),
+            Action::ColumnPicker    => self.ui.open_column_picker(),
+            Action::ToggleColumn    => self.ui.toggle_selected_column(),
+            Action::VizSettings     => self.ui.open_viz_settings(),
+            Action::ShiftVizSetting(d)      => self.ui.shift_viz_setting(d),
             Action::ToggleAlbumSort(next)   => self.ui.toggle_album_sort(next),
 
+            Action::TagEdit         => self.ui.open_tag_editor()?,
+            Action::TagEditField(i) => self.ui.cycle_tag_entry(i),
+            Action::TagEditConfirm  => self.confirm_tag_edit()?,
+
 
             Action::SidebarToggle   => self.ui.sidebar_toggle(),
             Action::SidebarCollapse => self.ui.sidebar_collapse(),
@@ -37,6 +70,7 @@ impl NoctaVox {
             // Search Related
             Action::UpdateSearch(k) => self.ui.process_search(k),
             Action::SendSearch      => self.ui.send_search(),
+            Action::ToggleSearchRegex => self.ui.toggle_search_regex(),
 
             //Playlist
             Action::CreatePlaylist  => self.ui.create_playlist_popup(),
@@ -48,31 +82,76 @@ impl NoctaVox {
             Action::RenamePlaylist  => self.ui.rename_playlist_popup(),
             Action::RenamePlaylistConfirm => self.ui.rename_playlist()?,
 
+            Action::MovePlaylist    => self.ui.move_playlist_popup(),
+            Action::MovePlaylistConfirm => self.ui.move_playlist()?,
+            Action::NewPlaylistFolder => self.ui.new_folder_popup(),
+            Action::NewPlaylistFolderConfirm => self.ui.create_playlist_folder()?,
+
             Action::DeletePlaylist  => self.ui.delete_playlist_popup(),
             Action::DeletePlaylistConfirm => self.ui.delete_playlist()?,
 
+            Action::SmartPlaylist   => self.ui.smart_playlist_popup(),
+            Action::SmartPlaylistNameConfirm => self.ui.confirm_smart_playlist_name()?,
+            Action::SmartPlaylistRulesConfirm => self.ui.confirm_smart_rules()?,
+
+            Action::ImportPlaylist  => self.ui.import_playlist_popup(),
+            Action::ImportPlaylistConfirm => self.ui.import_playlist()?,
+            Action::ExportPlaylist  => self.ui.export_playlist()?,
+
             // Queue
             Action::QueueSong       => self.queue_handler(None)?,
             Action::QueueMany{sel_type, shuffle} => self.queue_selection(sel_type, shuffle)?,
+            Action::QueueNext(sel_type) => self.queue_next(sel_type)?,
+            Action::PlayMany(sel_type)  => self.play_selection(sel_type)?,
             Action::RemoveSong      => self.remove_song()?,
+            Action::RateSong(r)     => self.ui.rate_selection(r)?,
+            Action::RateNowPlaying(r) => self.ui.rate_now_playing(r)?,
+            Action::LoveSong        => self.ui.toggle_love_selection()?,
+            Action::LoveNowPlaying  => self.ui.toggle_love_now_playing()?,
             Action::AddToPlaylist   => self.ui.add_to_playlist_popup(),
             Action::AddToPlaylistConfirm => self.ui.add_to_playlist()?,
 
-            Action::ToggleRepeat => self.toggle_repeat(),
+            Action::CycleRepeat => self.cycle_repeat(),
+            Action::CycleShuffleMode => self.cycle_shuffle_mode(),
+            Action::ToggleRadio => self.toggle_radio(),
+            Action::CycleReplayGain => self.cycle_replay_gain(),
+            Action::ToggleDiscordPresence => self.toggle_discord_presence()?,
+            Action::ToggleNotifications => self.toggle_notifications()?,
+            Action::ToggleMouse => self.ui.toggle_mouse()?,
+            Action::ToggleLyrics => self.ui.toggle_lyrics()?,
+            Action::ToggleArtistPane => self.ui.toggle_artist_pane()?,
+            Action::ScrollLyrics(lines) => self.ui.scroll_lyrics(lines),
+            Action::TogglePowerSave => self.ui.toggle_power_save(),
+            Action::SleepTimer(m)   => self.set_sleep_timer(m),
 
             Action::ShuffleElements => self.shuffle_queue(),
 
             Action::MultiSelect(x)   => self.ui.toggle_multi_selection(x)?,
             Action::MultiSelectAll   => self.ui.multi_select_all()?,
+            Action::MultiSelectRange(d) => self.ui.extend_multi_selection(d)?,
             Action::ClearMultiSelect => self.ui.clear_multi_select(),
             Action::ClearKeyBuffer   => self.ui.key_buffer.clear(),
 
             Action::ShiftPosition(direction) => self.shift_position(direction)?,
+            Action::ShiftToEdge(direction) => self.shift_queue_to_edge(direction)?,
             Action::IncrementWFSmoothness(direction) => self.ui.viz.increment_wf_smoothness(direction),
             Action::IncrementSidebarSize(x) => self.ui.adjust_sidebar_size(x),
 
             Action::NextProgressDisplay     => self.ui.viz.next_progress_display(),
             Action::SetProgressDisplay(p)   => self.ui.viz.set_progress_display(p),
+            Action::NextFftSize             => self.ui.viz.next_fft_size(),
+            Action::NextWindowFn            => self.ui.viz.next_window_fn(),
+            Action::ToggleAmplitudeScale    => self.ui.viz.toggle_amplitude_scale(),
+            Action::ToggleStereoScope       => self.ui.viz.toggle_stereo_scope(),
+            Action::ToggleScopeTrigger      => self.ui.viz.toggle_scope_trigger(),
+            Action::ToggleSpectrumPeaks     => self.ui.viz.toggle_spectrum_peaks(),
+            Action::ToggleSpectrumMirror    => self.ui.toggle_spectrum_mirror(),
+            Action::ToggleSpectrumColoring  => self.ui.toggle_spectrum_coloring(),
+            Action::ToggleSpectrumAutoGain  => self.ui.viz.toggle_spectrum_auto_gain(),
+            Action::ShiftVizGain(direction) => self.ui.viz.shift_gain(direction),
+            Action::ShiftSpectrumDecay(d)   => self.ui.viz.shift_spectrum_decay(d),
+            Action::ToggleAutoGain          => self.ui.viz.gain_mut().toggle_auto(),
+            Action::NextGradientSpeed       => self.ui.viz.next_gradient_speed(),
             Action::RevertFullscreen        => self.ui.revert_fullscreen(),
 
             Action::SwapLayout      => self.ui.swap_layout(),
@@ -84,6 +163,8 @@ impl NoctaVox {
             // Ops
 
             Action::ShowStats       => self.ui.show_stats_popup()?,
+            Action::ExportHistory(f)=> self.ui.export_history(f)?,
+            Action::CycleStatsWindow=> self.ui.cycle_stats_window()?,
             Action::ShowKeymaps     => self.ui.show_keymap_guide(),
             Action::PopupInput(key) => self.ui.process_popup_input(&key),
             Action::ClosePopup      => self.ui.close_popup(),
@@ -97,6 +178,12 @@ impl NoctaVox {
             Action::RootRemove      => self.settings_remove_root(),
             Action::RootConfirm     => self.settings_root_confirm()?,
 
+            Action::FindDuplicates      => self.ui.find_duplicates()?,
+            Action::ReviewDuplicates(m) => self.ui.review_duplicates(m),
+            Action::IgnoreDuplicates    => self.ui.ignore_duplicates(),
+            Action::ConfirmDuplicates   => self.confirm_duplicates()?,
+            Action::CloseDuplicates     => self.close_duplicates()?,
+
             _ => (),
         }
         self.ui.key_buffer.clear();
@@ -104,19 +191,27 @@ impl NoctaVox {
     }
 }
 
-pub fn key_loop() -> Receiver<KeyEvent> {
-    let (key_tx, key_rx) = crossbeam_channel::bounded(16);
+/// Key presses and mouse events, read off the terminal on their own thread
+pub fn input_loop() -> Receiver<Event> {
+    let (input_tx, input_rx) = crossbeam_channel::bounded(16);
 
     // 2. SPAWN the input thread (offloading)
     std::thread::spawn(move || {
         loop {
-            if let Ok(crossterm::event::Event::Key(key)) = crossterm::event::read()
-                && key.kind == crossterm::event::KeyEventKind::Press
-            {
-                let _ = key_tx.try_send(key);
+            match crossterm::event::read() {
+                Ok(event @ Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    let _ = input_tx.try_send(event);
+                }
+                // Movement alone is never acted on, and would crowd out the rest
+                Ok(event @ Event::Mouse(mouse)) if mouse.kind != MouseEventKind::Moved => {
+                    let _ = input_tx.try_send(event);
+                }
+                Ok(Event::FocusGained) => TERMINAL_FOCUSED.store(true, Ordering::Relaxed),
+                Ok(Event::FocusLost) => TERMINAL_FOCUSED.store(false, Ordering::Relaxed),
+                _ => (),
             }
         }
     });
 
-    key_rx
+    input_rx
 }
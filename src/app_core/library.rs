@@ -1,10 +1,19 @@
-use crate::{Library, app_core::NoctaVox, library::RefreshProgress};
+use crate::{
+    Library,
+    app_core::NoctaVox,
+    library::{LoudnessScan, RefreshProgress, ScanSummary},
+    user_config,
+};
 use anyhow::Result;
 use std::{sync::Arc, thread};
 
 impl NoctaVox {
     pub(crate) fn update_library(&mut self) -> Result<()> {
+        // Asking again while a scan is running cancels it
         if self.library_refresh_rec.is_some() {
+            if let Some(progress) = &self.ui.library_refresh {
+                progress.cancel();
+            }
             return Ok(());
         }
 
@@ -16,8 +25,8 @@ impl NoctaVox {
 
         thread::spawn(move || {
             let result = Library::init().and_then(|mut lib| {
-                lib.rebuild_library(&progress)?;
-                Ok(lib)
+                let summary = lib.rebuild_library(&progress)?;
+                Ok((lib, summary))
             });
             let _ = tx.send(result);
         });
@@ -25,13 +34,16 @@ impl NoctaVox {
         Ok(())
     }
 
-    pub(super) fn handle_library_result(&mut self, result: Result<Library>) {
+    pub(super) fn handle_library_result(&mut self, result: Result<(Library, ScanSummary)>) {
         match result {
-            Ok(new_library) => {
-                if let Err(e) = self.ui.sync_library(Arc::new(new_library)) {
-                    self.ui.set_error(e);
+            Ok((new_library, summary)) => {
+                match self.ui.sync_library(Arc::new(new_library)) {
+                    Ok(()) if summary.is_up_to_date() => self.ui.set_notice(summary.to_string()),
+                    Ok(()) => self.ui.set_info(summary.to_string()),
+                    Err(e) => self.ui.set_error(e),
                 }
                 self.ui.set_legal_songs();
+                self.update_loudness_scan();
             }
             Err(e) => self.ui.set_error(e),
         }
@@ -39,4 +51,16 @@ impl NoctaVox {
         self.ui.library_refresh = None;
         self.library_refresh_rec = None;
     }
+
+    /// Starts measuring whatever isn't yet, unless a pass is already
+    /// under way. Newly scanned songs are picked up by the next one.
+    pub(super) fn update_loudness_scan(&mut self) {
+        let running = self
+            .loudness_scan
+            .as_ref()
+            .is_some_and(|s| !s.is_finished());
+        if user_config().general.analyze_loudness && !running {
+            self.loudness_scan = Some(LoudnessScan::start());
+        }
+    }
 }
@@ -1,23 +1,50 @@
 use crate::{
-    Library, media_controls::MediaControlsHandle, player::PlayerHandle, ui_state::UiState,
+    Library, library::ScanSummary, media_controls::MediaControlsHandle, player::PlayerHandle,
+    ui_state::UiState,
 };
 use anyhow::Result;
 use crossbeam_channel::Receiver;
 
 mod app;
+mod daemon;
+mod discord;
 mod key_events;
 mod library;
+mod notify;
 mod playback;
 mod player;
+mod scrobble;
 mod select;
+mod silence;
+mod status_file;
 
-pub use key_events::key_loop;
+pub use key_events::input_loop;
 
 pub struct NoctaVox {
     pub(crate) ui: UiState,
     player: PlayerHandle,
-    library_refresh_rec: Option<Receiver<Result<Library>>>,
+    library_refresh_rec: Option<Receiver<Result<(Library, ScanSummary)>>>,
     media_controls: Option<MediaControlsHandle>,
+    #[cfg(feature = "discord")]
+    presence: Option<crate::discord::DiscordPresence>,
+    #[cfg(feature = "notifications")]
+    notifier: Option<crate::notify::Notifier>,
+    #[cfg(feature = "scrobble")]
+    scrobbler: Option<crate::scrobble::Scrobbler>,
     tick_sync: u32,
     restored_song_id: Option<u64>,
+    advance_on_stop: bool,
+    /// The device popup on screen, so sound coming back closes only that
+    device_error: Option<String>,
+    /// Hands over the output once it opens, when there was none at launch
+    output_pending: Option<Receiver<(voxio::Vox, voxio::VoxEvents)>>,
+    /// The saved session is picked up once the output opens
+    resume_on_connect: bool,
+    /// When playback last jumped to the start of a cue track
+    cue_seeked_at: Option<std::time::Instant>,
+    status_file: Option<status_file::StatusFile>,
+    loudness_scan: Option<crate::library::LoudnessScan>,
+    silence: Option<silence::SilenceTrimmer>,
+    /// Only listening in daemon mode
+    control: Option<crate::control::ControlServer>,
 }
@@ -0,0 +1,58 @@
+use crate::app_core::NoctaVox;
+use anyhow::Result;
+
+#[cfg(feature = "notifications")]
+use crate::{
+    app_core::key_events::terminal_focused,
+    library::SongInfo,
+    notify::{Notification, Notifier},
+    user_config,
+};
+#[cfg(feature = "notifications")]
+use std::path::Path;
+
+#[cfg(feature = "notifications")]
+impl NoctaVox {
+    pub(super) fn init_notifier() -> Option<Notifier> {
+        user_config().general.notifications.then(Notifier::new)
+    }
+
+    /// Skipped while the terminal has focus, as the track is already on screen
+    pub(super) fn notify_track_change(&self, path: &Path) {
+        let (Some(notifier), Some(song)) = (self.notifier.as_ref(), self.ui.get_now_playing())
+        else {
+            return;
+        };
+
+        if !terminal_focused() {
+            notifier.send(Notification::new(
+                song.get_title(),
+                song.get_artist(),
+                song.get_album(),
+                path,
+            ));
+        }
+    }
+
+    pub fn toggle_notifications(&mut self) -> Result<()> {
+        let msg = match self.notifier.take() {
+            Some(_) => "Desktop notifications disabled",
+            None => {
+                self.notifier = Some(Notifier::new());
+                "Desktop notifications enabled"
+            }
+        };
+
+        self.ui.set_notice(msg.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "notifications"))]
+impl NoctaVox {
+    pub(super) fn notify_track_change(&self, _: &std::path::Path) {}
+
+    pub fn toggle_notifications(&mut self) -> Result<()> {
+        anyhow::bail!("NoctaVox was built without desktop notifications")
+    }
+}
@@ -1,12 +1,18 @@
 use crate::{
     app_core::NoctaVox,
+    gen_config,
     key_handler::{Director, Incrementor, SelectionType},
     library::{SimpleSong, SongDatabase},
-    ui_state::Mode,
+    playback::{RepeatMode, SeekTarget, ValidatedSong, pick_radio_track},
+    player::{Fade, PREFETCH_LEAD},
+    ui_state::{Mode, PopupType, UndoEntry},
+    user_config,
 };
-use anyhow::Result;
-use rand::seq::SliceRandom;
-use std::sync::Arc;
+use anyhow::{Result, anyhow, bail};
+use std::{sync::Arc, time::Duration};
+
+/// Picks radio tries before giving up on files that won't open
+const RADIO_ATTEMPTS: usize = 5;
 
 impl NoctaVox {
     pub fn advance_to_next_gapless(&mut self) {
@@ -40,7 +46,11 @@ impl NoctaVox {
         }
 
         if shuffle {
-            songs.shuffle(&mut rand::rng());
+            let plays = self.ui.shuffle_plays();
+            let after = self.ui.playback.queue_tail().map(Arc::clone);
+            self.ui
+                .shuffle_mode
+                .apply(&mut songs, |s| s, after.as_deref(), &plays);
         }
 
         let first = self.ui.playback.enqueue_multi(&songs)?;
@@ -57,6 +67,67 @@ impl NoctaVox {
         Ok(())
     }
 
+    /// Replaces the queue with the selected song's whole album and starts it
+    pub fn play_album(&mut self) -> Result<()> {
+        let mut songs = self.ui.album_tracks()?;
+
+        if gen_config().album_shuffle {
+            let plays = self.ui.shuffle_plays();
+            self.ui.shuffle_mode.apply(&mut songs, |s| s, None, &plays);
+        }
+
+        self.clear_queue();
+        if let Some(first) = self.ui.playback.enqueue_multi(&songs)? {
+            self.ui.playback.remove_from_queue(0);
+            self.play_song(&first)?;
+        }
+
+        self.force_sync();
+        self.ui.set_legal_songs();
+        Ok(())
+    }
+
+    /// Like queueing, but the songs go in right after the playing track.
+    /// Works on the multi-selection when there is one.
+    pub fn queue_next(&mut self, sel_type: Option<SelectionType>) -> Result<()> {
+        let songs = match sel_type {
+            Some(sel_type) => self.ui.get_songs_by_selection(sel_type)?,
+            None if !self.ui.multi_select_empty() => {
+                self.ui.get_songs_by_selection(SelectionType::Multi)?
+            }
+            None => match self.ui.get_selected_song() {
+                Ok(song) => vec![song],
+                Err(_) => return Ok(()),
+            },
+        };
+        if songs.is_empty() {
+            return Ok(());
+        }
+
+        let first = self.ui.playback.enqueue_next(&songs)?;
+
+        if !self.player.is_active()
+            && let Some(validated) = first
+        {
+            self.ui.playback.remove_from_queue(0);
+            self.play_song(&validated)?;
+        }
+
+        self.force_sync();
+        self.ui.set_legal_songs();
+        Ok(())
+    }
+
+    /// Plays the songs right away, in the order given. Whatever was queued
+    /// before stays behind them.
+    pub fn play_selection(&mut self, sel_type: SelectionType) -> Result<()> {
+        let songs = self.ui.get_songs_by_selection(sel_type)?;
+        if self.ui.playback.enqueue_next(&songs)?.is_some() {
+            self.skip_to_next()?;
+        }
+        Ok(())
+    }
+
     pub fn push_queue_front(&mut self, song: &Arc<SimpleSong>) -> Result<()> {
         self.ui.playback.queue_push_front(song)?;
         self.force_sync();
@@ -64,7 +135,8 @@ impl NoctaVox {
     }
 
     pub fn shuffle_queue(&mut self) {
-        self.ui.playback.shuffle_queue();
+        let plays = self.ui.shuffle_plays();
+        self.ui.playback.shuffle_queue(self.ui.shuffle_mode, &plays);
         self.force_sync();
         self.ui.set_legal_songs();
     }
@@ -113,6 +185,36 @@ impl NoctaVox {
         Ok(())
     }
 
+    /// Sends the selection to the top or bottom of the queue. The playing
+    /// track isn't part of the queue, so playback carries on undisturbed.
+    pub fn shift_queue_to_edge(&mut self, dir: Incrementor) -> Result<()> {
+        if self.ui.get_mode() != Mode::Queue {
+            return Ok(());
+        }
+
+        let front = matches!(dir, Incrementor::Up);
+        let moved = match self.ui.multi_select_empty() {
+            true => {
+                let idx = self.ui.nav.get_table_idx()?;
+                self.ui.playback.move_to_edge(&[idx], front)
+            }
+            false => {
+                let indices = self.ui.get_multi_select_indices().iter().copied();
+                let moved = self
+                    .ui
+                    .playback
+                    .move_to_edge(&indices.collect::<Vec<_>>(), front);
+                self.ui.update_multi_select(moved.clone());
+                moved
+            }
+        };
+
+        self.force_sync();
+        self.ui.set_legal_songs();
+        self.ui.nav.table_pos.select(moved.first().copied());
+        Ok(())
+    }
+
     fn shift_qposition_single(&mut self, dir: Incrementor) -> Result<()> {
         let display_idx = self.ui.nav.get_table_idx()?;
 
@@ -164,23 +266,285 @@ impl NoctaVox {
     }
 
     pub fn force_sync(&self) {
-        let next = match self.ui.playback.repeat_is_enabled() {
-            true => self.ui.get_now_playing().and_then(|np| np.get_path().ok()),
-            false => self
-                .ui
-                .playback
-                .peek_queue_validated()
-                .map(|s| s.path.to_string()),
-        };
+        // Discrete mode never pre-loads; the next track is opened once the
+        // engine reports a natural end (see `handle_player_events`).
+        if !self.ui.playback.gapless_is_enabled() {
+            let _ = self.player.set_next(None);
+            return;
+        }
+
+        let next = self.preload_target();
+        let _ = self.player.set_next(next.as_deref());
+    }
 
-        let dr = next.as_deref();
-        let _ = self.player.set_next(dr);
+    /// Called once per frame. Near the end of a track, reads ahead the one
+    /// that follows; re-targeted whenever the queue changes what that is.
+    pub fn update_prefetch(&mut self) {
+        let next = match self.player.is_active() && self.player.time_left() <= PREFETCH_LEAD {
+            true => self.preload_target(),
+            false => None,
+        };
+        self.player.prefetch(next.as_deref());
     }
 
-    pub fn toggle_repeat(&mut self) {
-        self.ui
+    /// The file to hand over to once the current one runs out, provided
+    /// the next track starts at the top of it
+    pub(super) fn preload_target(&self) -> Option<String> {
+        let now_playing = self.ui.get_now_playing();
+
+        // The file carries on past this track; `update_cue_track` moves on
+        if now_playing
+            .and_then(|s| s.cue())
+            .is_some_and(|c| c.end.is_some())
+        {
+            return None;
+        }
+
+        let current = || now_playing.and_then(|np| Some((np.get_path().ok()?, np.cue())));
+        let queued = self
+            .ui
             .playback
-            .set_repeat(!self.ui.playback.repeat_is_enabled());
+            .peek_queue_validated()
+            .map(|s| (s.path.to_string(), s.meta.cue()));
+
+        // Repeating the whole of a queue that's down to one track is the
+        // same as repeating that track
+        let next = match self.ui.playback.repeat_mode() {
+            RepeatMode::One => current(),
+            RepeatMode::All if queued.is_none() => current(),
+            _ => queued,
+        };
+
+        next.filter(|(_, cue)| cue.is_none_or(|c| c.start <= 0.0))
+            .map(|(path, _)| path)
+    }
+
+    /// Measured from the position on screen rather than the engine's, so
+    /// quick repeated seeks add up even before the first one lands
+    pub fn seek(&mut self, secs: f64) {
+        // Seeking back stops at the start of a cue track, or of the file.
+        // Going past the end moves on to the next one, same as playing
+        // through.
+        let start = self
+            .ui
+            .get_now_playing()
+            .and_then(|s| s.cue())
+            .map_or(0.0, |c| c.start);
+        let target = (self.ui.displayed_position().as_secs_f64() + secs).max(start);
+
+        if !user_config().general.ab_loop_keep_on_seek
+            && self.ui.ab_loop.region().is_some()
+            && !self.ui.ab_loop.contains(target)
+        {
+            self.ui.ab_loop.clear();
+        }
+
+        if self.player.is_active() {
+            let end = self.player.duration().as_secs_f64();
+            self.ui
+                .hold_seek_position(Duration::from_secs_f64(target.min(end)));
+        }
+        self.player.seek_to(target as f32);
+    }
+
+    /// Jumps `percent` of the way into the track, or into the cue track
+    pub fn seek_percent(&mut self, percent: usize) {
+        self.seek_fraction(percent.min(100) as f64 / 100.0);
+    }
+
+    /// Seeks to a point between the start (0.0) and end (1.0) of the track
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        if !self.player.is_active() {
+            return;
+        }
+
+        let (elapsed, length) = self.ui.track_progress();
+        let target = length.as_secs_f64() * fraction.clamp(0.0, 1.0);
+        self.seek(target - elapsed.as_secs_f64());
+    }
+
+    /// Seeks to the time typed into the seek popup, leaving it open with the
+    /// reason when the input doesn't parse
+    pub fn confirm_seek_input(&mut self) {
+        match self.seek_to_input(&self.ui.get_popup_string()) {
+            Ok(()) => self.ui.close_popup(),
+            Err(e) => self.ui.popup.current = PopupType::Seek(Some(e.to_string())),
+        }
+    }
+
+    /// Seeks to a time such as `1:23` or `+90`, counted within a cue track
+    pub(crate) fn seek_to_input(&mut self, input: &str) -> Result<()> {
+        if !self.player.is_active() {
+            bail!("Nothing is playing");
+        }
+
+        match SeekTarget::parse(input)? {
+            SeekTarget::Relative(secs) => self.seek(secs),
+            SeekTarget::Absolute(secs) => {
+                let (elapsed, _) = self.ui.track_progress();
+                self.seek(secs - elapsed.as_secs_f64());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn mark_ab_loop(&mut self) {
+        if self.player.is_active() {
+            let position = self.player.elapsed().as_secs_f64();
+            self.ui.ab_loop.mark(position);
+        }
+    }
+
+    /// Called once per frame. Jumps back to A as soon as playback passes B.
+    pub fn update_ab_loop(&self) {
+        let Some((start, end)) = self.ui.ab_loop.region() else {
+            return;
+        };
+
+        if !self.player.is_paused() && self.player.elapsed().as_secs_f64() >= end {
+            self.player.seek_to(start as f32);
+        }
+    }
+
+    /// `0` cycles through the presets, any other count sets that many minutes
+    pub fn set_sleep_timer(&mut self, minutes: usize) {
+        match minutes {
+            0 => self.ui.sleep_timer.next_preset(),
+            m => self.ui.sleep_timer.set(m as u64),
+        }
+    }
+
+    /// Called once per frame. Pauses rather than stops so the queue and
+    /// position survive until morning.
+    pub fn update_sleep_timer(&mut self) {
+        // The sleep fade has already reached silence, and a second fade from
+        // full volume would be heard once its envelope is lifted
+        if self.ui.sleep_timer.expired() {
+            match user_config().general.sleep_fade {
+                true => self.player.pause_now(),
+                false => self.player.pause(),
+            }
+            return;
+        }
+
+        // A faded-out envelope is held through the pause and lifted once
+        // playback resumes
+        let envelope = match self.ui.sleep_timer.is_active() {
+            true if user_config().general.sleep_fade => self.ui.sleep_timer.fade_envelope(),
+            _ if self.player.is_paused() => return,
+            _ => 1.0,
+        };
+        self.ui.gain.set_envelope(Fade::SleepTimer, envelope);
+    }
+
+    pub fn cycle_replay_gain(&mut self) {
+        let mode = self.ui.playback.next_replay_gain();
+        self.player.set_replay_gain(mode);
+        self.ui.apply_measured_gain();
+    }
+
+    pub fn cycle_shuffle_mode(&mut self) {
+        self.ui.shuffle_mode = self.ui.shuffle_mode.next();
+    }
+
+    pub fn toggle_radio(&mut self) {
+        self.ui.radio = !self.ui.radio;
+        let msg = match self.ui.radio {
+            true => "Radio on, the queue keeps itself going",
+            false => "Radio off",
+        };
+        self.ui.set_notice(msg.to_string());
+        self.top_up_radio();
+    }
+
+    /// With radio on, a track is queued whenever the queue runs dry, so
+    /// gapless has it ready. Nothing is queued if nothing fits.
+    pub(crate) fn top_up_radio(&mut self) {
+        // Repeating the queue already never runs out
+        if !self.ui.radio
+            || !self.ui.playback.queue_is_empty()
+            || self.ui.playback.repeat_mode() == RepeatMode::All
+        {
+            return;
+        }
+        let Some(last) = self.ui.get_now_playing().cloned() else {
+            return;
+        };
+
+        let songs = self.ui.library().get_all_songs();
+        let mut recent = self.ui.playback.recent_ids();
+        let plays = self.ui.radio_plays();
+
+        // A file gone missing is passed over for the next best
+        for _ in 0..RADIO_ATTEMPTS {
+            let Some(song) = pick_radio_track(&songs, Some(&last), &recent, &plays) else {
+                return;
+            };
+            match self.ui.playback.enqueue(&song) {
+                Ok(()) => break,
+                Err(_) => recent.insert(song.id),
+            };
+        }
+
+        self.force_sync();
+        if self.ui.get_mode() == Mode::Queue {
+            self.ui.set_legal_songs();
+        }
+    }
+
+    pub fn cycle_repeat(&mut self) {
+        self.ui.playback.next_repeat();
+        self.force_sync();
+    }
+
+    pub(crate) fn undo(&mut self) -> Result<()> {
+        let entry = self
+            .ui
+            .undo
+            .pop()
+            .ok_or_else(|| anyhow!("Nothing to undo!"))?;
+
+        match entry {
+            UndoEntry::Queue { queue, playing } => self.restore_cleared_queue(queue, playing),
+            entry => self.ui.undo_playlist(entry),
+        }
+    }
+
+    /// Empties the queue, keeping it and the playing song for an undo
+    pub(crate) fn clear_queue(&mut self) {
+        let queue = self.ui.playback.clear_queue();
+        let playing = self
+            .ui
+            .get_now_playing()
+            .map(|song| (Arc::clone(song), self.ui.displayed_position()));
+
+        if !queue.is_empty() || playing.is_some() {
+            self.ui.undo.push(UndoEntry::Queue { queue, playing });
+        }
+    }
+
+    /// The song that was playing picks up where it left off, unless
+    /// something else has started since, in which case it's queued next
+    fn restore_cleared_queue(
+        &mut self,
+        queue: Vec<Arc<ValidatedSong>>,
+        playing: Option<(Arc<SimpleSong>, Duration)>,
+    ) -> Result<()> {
+        self.ui.playback.restore_queue(queue);
+
+        if let Some((song, position)) = playing {
+            let validated = ValidatedSong::new(&song)?;
+            match self.player.is_active() {
+                true => self.ui.playback.restore_queue(vec![validated]),
+                false => {
+                    self.play_song(&validated)?;
+                    self.player.seek_to(position.as_secs_f32());
+                }
+            }
+        }
+
         self.force_sync();
+        self.ui.set_legal_songs();
+        Ok(())
     }
 }
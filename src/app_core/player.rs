@@ -1,22 +1,99 @@
 use anyhow::{Result, anyhow, bail};
-use std::{sync::Arc, time::Duration};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use voxio::{EndReason, StartReason, VoxEvent};
 
 use crate::{
+    DurationStyle,
     app_core::NoctaVox,
+    get_readable_duration,
     key_handler::SelectionType,
     library::{SimpleSong, SongDatabase, SongInfo},
-    playback::ValidatedSong,
+    playback::{RepeatMode, ValidatedSong},
     ui_state::Mode,
 };
 
+/// Leaving a track before this fraction of it has played counts as a skip
+const SKIP_THRESHOLD: f32 = 0.5;
+/// How long a seek into a cue track takes to show up in the position.
+/// Until then the position still reads from wherever playback was before.
+const CUE_SETTLE: Duration = Duration::from_millis(300);
+
 impl NoctaVox {
     pub(crate) fn play_song(&mut self, song: &ValidatedSong) -> Result<()> {
+        let cue = song.meta.cue();
+
+        // Moving between tracks cut from the file that's already open is
+        // only a seek, and the engine won't report a new track starting
+        if let Some(cue) = cue
+            && self.player.is_active()
+            && self.playing_cue_file().as_deref() == Some(song.path.as_str())
+        {
+            self.seek_into_cue(cue.start);
+            self.ui.set_now_playing(Some(Arc::clone(&song.meta)));
+            return self.on_track_started(Path::new(&song.path), false);
+        }
+
         self.player.play(&song.path)?;
+        if let Some(cue) = cue
+            && cue.start > 0.0
+        {
+            self.seek_into_cue(cue.start);
+        }
         self.ui.set_now_playing(Some(Arc::clone(&song.meta)));
         Ok(())
     }
 
+    /// The audio file behind the playing song, if it's a cue track
+    fn playing_cue_file(&self) -> Option<String> {
+        self.ui
+            .get_now_playing()
+            .filter(|s| s.cue().is_some())
+            .and_then(|s| s.get_path().ok())
+    }
+
+    fn seek_into_cue(&mut self, start: f64) {
+        self.player.seek_to(start as f32);
+        self.cue_seeked_at = Some(Instant::now());
+    }
+
+    /// Picks a long track back up where it was left
+    fn resume_from(&mut self, song: &SimpleSong, at: Duration) {
+        let target = song.cue().map_or(0.0, |c| c.start) + at.as_secs_f64();
+        match song.cue() {
+            Some(_) => self.seek_into_cue(target),
+            None => self.player.seek_to(target as f32),
+        }
+        self.ui.hold_seek_position(Duration::from_secs_f64(target));
+
+        let at = get_readable_duration(at, DurationStyle::Clean);
+        self.ui.set_notice(format!("Resumed at {at}"));
+    }
+
+    /// Called once per frame. A cue track usually ends partway through its
+    /// file, where the engine has no reason to stop.
+    pub fn update_cue_track(&mut self) -> Result<()> {
+        let Some(end) = self.ui.get_now_playing().and_then(|s| s.cue()?.end) else {
+            return Ok(());
+        };
+
+        let settling = self.cue_seeked_at.is_some_and(|t| t.elapsed() < CUE_SETTLE);
+        if settling || !self.player.is_active() || self.player.is_paused() {
+            return Ok(());
+        }
+
+        if self.player.elapsed().as_secs_f64() >= end {
+            self.ui.finish_resume_point();
+            if !self.advance_discrete()? {
+                self.player.stop();
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn play_selected_song(&mut self, count: usize) -> Result<()> {
         match count {
             0 => (),
@@ -30,6 +107,7 @@ impl NoctaVox {
         }
 
         let validated = ValidatedSong::new(&song)?;
+        self.record_skip();
 
         if let Some(current) = self.ui.playback.get_now_playing() {
             let song = Arc::clone(current);
@@ -61,6 +139,24 @@ impl NoctaVox {
         Ok(())
     }
 
+    /// Moving on by hand, as opposed to the track running out
+    pub(crate) fn skip_to_next(&mut self) -> Result<()> {
+        self.record_skip();
+        self.play_next()
+    }
+
+    fn record_skip(&self) {
+        let Some(song) = self.ui.get_now_playing() else {
+            return;
+        };
+
+        let duration = song.get_duration().as_secs_f32();
+        let elapsed = self.ui.track_progress().0.as_secs_f32();
+        if self.player.is_active() && duration > 0.0 && elapsed < duration * SKIP_THRESHOLD {
+            self.ui.record_skip(song);
+        }
+    }
+
     pub(crate) fn play_prev(&mut self) -> Result<()> {
         let popped = self
             .ui
@@ -77,7 +173,7 @@ impl NoctaVox {
     }
 
     pub fn stop(&mut self) {
-        self.ui.playback.clear_queue();
+        self.clear_queue();
         self.player.stop();
     }
 
@@ -120,47 +216,91 @@ impl NoctaVox {
         Ok(())
     }
 
+    /// Starts the following track after a natural end in discrete mode.
+    /// Returns false when there is nothing left to play.
+    pub(super) fn advance_discrete(&mut self) -> Result<bool> {
+        if self.ui.playback.repeats_track() {
+            let Some(song) = self.ui.playback.get_now_playing().cloned() else {
+                return Ok(false);
+            };
+            let validated = ValidatedSong::new(&song)?;
+            self.play_song(&validated)?;
+            return Ok(true);
+        }
+
+        self.top_up_radio();
+        let wraps = self.ui.playback.repeat_mode() == RepeatMode::All;
+        if self.ui.playback.queue_is_empty() && !wraps {
+            return Ok(false);
+        }
+
+        self.play_next()?;
+        Ok(true)
+    }
+
+    /// What follows a new track starting, whether the engine opened it or
+    /// it's one more cue track in the file already playing. `repeated` is a
+    /// gapless repeat of the same track.
+    fn on_track_started(&mut self, path: &Path, repeated: bool) -> Result<()> {
+        let Some(song) = self.ui.playback.get_now_playing().cloned() else {
+            return Ok(());
+        };
+
+        // Counted once enough of it has been heard
+        let is_restore = self.restored_song_id.take() == Some(song.get_id());
+        self.ui.play_tally.start(song.get_id(), is_restore);
+        let resume_at = self.ui.take_resume_point(&song, path);
+        // Picking up from a saved spot is no time to skip the lead-in
+        let trim_lead = !is_restore && resume_at.is_none();
+        self.start_silence_trim(&song, path.to_path_buf(), trim_lead);
+        self.top_up_radio();
+
+        if !repeated {
+            self.ui.ab_loop.clear();
+            self.ui.viz.clear_waveform();
+            self.ui.viz.flush_tap();
+            self.ui.viz.request_waveform(&song);
+            self.notify_track_change(path);
+            if !is_restore {
+                self.scrobble_now_playing();
+                if let Some(at) = resume_at {
+                    self.resume_from(&song, at);
+                }
+            }
+
+            if let Some(mc) = self.media_controls.as_mut() {
+                mc.update_metadata(
+                    song.get_title(),
+                    song.get_artist(),
+                    song.get_album(),
+                    song.get_duration(),
+                );
+                mc.set_playing(Duration::ZERO);
+            }
+        }
+        Ok(())
+    }
+
     pub(super) fn handle_player_events(&mut self, event: VoxEvent) -> Result<()> {
         match event {
             VoxEvent::TrackStarted { reason, path, .. } => {
-                let is_repeat = self.ui.playback.repeat_is_enabled();
+                let is_repeat = self.ui.playback.repeats_track();
                 let gapless = matches!(reason, StartReason::Gapless);
+                self.player.begin_track(gapless);
 
                 if gapless && !is_repeat {
                     self.advance_to_next_gapless();
                 }
 
-                let Some(song) = self.ui.playback.get_now_playing().cloned() else {
+                if self.ui.playback.get_now_playing().is_none() {
                     return Ok(());
-                };
-
-                if is_repeat {
-                    let _ = self.player.set_next(path.to_str());
                 }
 
-                let is_restore = self.restored_song_id.take() == Some(song.get_id());
-
-                if !is_restore {
-                    song.update_play_count()?;
+                if is_repeat && self.ui.playback.gapless_is_enabled() {
+                    self.force_sync();
                 }
 
-                // Update if not on repeat and not gapless
-                if !(is_repeat && gapless) {
-                    self.ui.viz.clear_waveform();
-                    self.ui.viz.flush_tap();
-                    self.ui.viz.request_waveform(&song);
-
-                    if let Some(mc) = self.media_controls.as_mut() {
-                        mc.update_metadata(
-                            song.get_title(),
-                            song.get_artist(),
-                            song.get_album(),
-                            song.get_duration(),
-                        );
-                        mc.set_playing(Duration::ZERO);
-                    }
-                }
-                Ok(())
+                self.on_track_started(&path, is_repeat && gapless)
             }
 
             VoxEvent::DurationResolved { duration, .. } => {
@@ -177,10 +317,25 @@ impl NoctaVox {
                         path.display()
                     )
                 }
+
+                // With gapless disabled the engine stops after every track,
+                // so remember that this stop should move on to the next one.
+                // The same goes for anything gapless couldn't pre-load.
+                if matches!(reason, EndReason::EndOfStream) && self.ui.resume.is_for(&path) {
+                    self.ui.finish_resume_point();
+                }
+
+                self.advance_on_stop = matches!(reason, EndReason::EndOfStream)
+                    && (!self.ui.playback.gapless_is_enabled() || self.preload_target().is_none());
                 Ok(())
             }
-
             VoxEvent::Stopped => {
+                self.player.reset_fade();
+
+                if std::mem::take(&mut self.advance_on_stop) && self.advance_discrete()? {
+                    return Ok(());
+                }
+
                 if let Some(np) = self.ui.playback.get_now_playing() {
                     let song = Arc::clone(np);
                     self.ui.insert_history_entry(&song);
@@ -195,6 +350,7 @@ impl NoctaVox {
                 }
 
                 self.ui.set_now_playing(None);
+                self.ui.ab_loop.clear();
                 self.ui.viz.clear_waveform();
                 self.ui.viz.flush_tap();
                 self.ui.set_legal_songs();
@@ -213,6 +369,27 @@ impl NoctaVox {
                 Ok(())
             }
 
+            // voxio keeps retrying in the background; hold the position so
+            // playback can resume once the device is back
+            VoxEvent::DeviceLost { name, error } => {
+                if self.device_error.is_none() {
+                    if self.player.is_active() {
+                        self.player.pause_now();
+                    }
+                    let msg = format!(
+                        "Lost audio device: {name}\n\n{error}\n\nRetrying in the background..."
+                    );
+                    self.ui.set_error(anyhow!(msg.clone()));
+                    self.device_error = Some(msg);
+                }
+                Ok(())
+            }
+
+            VoxEvent::DeviceChanged { .. } => {
+                self.close_device_error();
+                Ok(())
+            }
+
             VoxEvent::Error { error: e, .. } => {
                 self.ui.set_error(anyhow!(e));
                 Ok(())
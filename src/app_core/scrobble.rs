@@ -0,0 +1,70 @@
+use crate::app_core::NoctaVox;
+
+#[cfg(feature = "scrobble")]
+use crate::{
+    library::SongInfo,
+    scrobble::{Listen, ScrobbleConfig, Scrobbler},
+};
+#[cfg(feature = "scrobble")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "scrobble")]
+impl NoctaVox {
+    /// Nothing is started without a service in `scrobble.toml`
+    pub(super) fn init_scrobbler(&mut self) {
+        match ScrobbleConfig::load() {
+            Ok(config) => self.scrobbler = Scrobbler::new(config),
+            Err(e) => self.ui.set_error(e),
+        }
+    }
+
+    /// As it stands now, dated back to when the track started
+    fn current_listen(&self) -> Option<Listen> {
+        let song = self.ui.get_now_playing()?;
+        let (position, length) = self.ui.track_progress();
+        let length = match length.is_zero() {
+            true => song.get_duration(),
+            false => length,
+        };
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        Some(Listen {
+            title: song.get_title().to_string(),
+            artist: song.get_artist().to_string(),
+            album: song.get_album().to_string(),
+            duration_secs: length.as_secs() as u32,
+            listened_at: now.saturating_sub(position).as_secs() as i64,
+        })
+    }
+
+    pub(super) fn scrobble_now_playing(&self) {
+        if let Some(scrobbler) = &self.scrobbler
+            && let Some(listen) = self.current_listen()
+        {
+            scrobbler.now_playing(listen);
+        }
+    }
+
+    /// Called once the play has been counted
+    pub(super) fn scrobble(&self) {
+        if let Some(scrobbler) = &self.scrobbler
+            && let Some(listen) = self.current_listen()
+        {
+            scrobbler.scrobble(listen);
+        }
+    }
+
+    pub(super) fn sync_scrobbler(&mut self) {
+        if let Some(e) = self.scrobbler.as_ref().and_then(|s| s.take_error()) {
+            self.ui.set_error(anyhow::anyhow!(e));
+        }
+    }
+}
+
+#[cfg(not(feature = "scrobble"))]
+impl NoctaVox {
+    pub(super) fn init_scrobbler(&mut self) {}
+    pub(super) fn scrobble_now_playing(&self) {}
+    pub(super) fn scrobble(&self) {}
+    pub(super) fn sync_scrobbler(&mut self) {}
+}
@@ -1,5 +1,5 @@
 use crossbeam_channel::{Receiver, never, select};
-use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::Event;
 use souvlaki::{MediaControlEvent, SeekDirection};
 
 use crate::{
@@ -10,19 +10,27 @@ use crate::{
 };
 
 impl NoctaVox {
+    /// Waits for the next thing to happen, or a tick. True for anything
+    /// but the tick, which always draws a frame straight away.
     #[inline]
-    pub fn select_shortcut(&mut self, key_rx: &Receiver<KeyEvent>) {
+    pub fn select_shortcut(&mut self, input_rx: &Receiver<Event>) -> bool {
+        let tick = match self.ui.power_save() {
+            true => timing().power_save_tick,
+            false => timing().refresh_rate,
+        };
         select! {
             recv(self.player.events()) -> event => {
                 if let Ok(event) = event && let Err(e) = self.handle_player_events(event) {
                     self.ui.set_error(e);
                 }
+                true
             }
 
             recv(self.library_refresh_rec.as_ref().unwrap_or(&never())) -> result => {
                 if let Ok(result) = result {
                     self.handle_library_result(result)
                 }
+                true
             }
 
             recv(&self.ui.viz.wf_reciever().unwrap_or(&never())) -> result => {
@@ -30,24 +38,44 @@ impl NoctaVox {
                     let now_playing = &self.ui.get_now_playing().cloned();
                     self.ui.viz.handle_wf_result(res, now_playing.as_ref());
                 }
+                true
             }
 
             recv(self.media_controls.as_ref().map(|m| m.event_rx()).unwrap_or(&never())) -> event => {
                 if let Ok(event) = event && let Err(e) = self.handle_media_control_event(event) {
                    self.ui.set_error(e);
                 }
+                true
             }
 
-            recv(key_rx) -> key => {
-                if let Ok(key) = key
-                    && let Some(action) = key_handler::handle_key_event(key, &mut self.ui)
-                        && let Err(e) = self.handle_action(action) {
-                            self.ui.set_error(e);
+            recv(self.control.as_ref().map(|c| c.requests()).unwrap_or(&never())) -> request => {
+                if let Ok(request) = request {
+                    let reply = self.handle_control(&request.command);
+                    request.respond(reply);
                 }
+                true
             }
 
-            default(timing().refresh_rate) => {
+            recv(input_rx) -> event => {
+                self.ui.marquee.hold();
+                // The first key or click out of the screensaver only wakes it
+                if matches!(event, Ok(Event::Key(_) | Event::Mouse(_))) && self.ui.idle.wake() {
+                    return true;
+                }
+                let action = match event {
+                    Ok(Event::Key(key)) => key_handler::handle_key_event(key, &mut self.ui),
+                    Ok(Event::Mouse(mouse)) => key_handler::handle_mouse_event(mouse, &mut self.ui),
+                    _ => None,
+                };
+                if let Some(action) = action && let Err(e) = self.handle_action(action) {
+                    self.ui.set_error(e);
+                }
+                true
+            }
+
+            default(tick) => {
                 self.sync_media_controls_position();
+                false
             }
         }
     }
@@ -57,16 +85,14 @@ impl NoctaVox {
             MediaControlEvent::Play => self.player.resume(),
             MediaControlEvent::Pause => self.player.pause(),
             MediaControlEvent::Toggle => self.player.toggle_playback(),
-            MediaControlEvent::Next => self.play_next()?,
+            MediaControlEvent::Next => self.skip_to_next()?,
             MediaControlEvent::Previous => self.play_prev()?,
             MediaControlEvent::Stop => self.stop(),
-            MediaControlEvent::Seek(SeekDirection::Forward) => self.player.seek(*SEEK_SMALL),
-            MediaControlEvent::Seek(SeekDirection::Backward) => self.player.seek(-*SEEK_SMALL),
-            MediaControlEvent::SeekBy(SeekDirection::Forward, dur) => {
-                self.player.seek(dur.as_secs_f64())
-            }
+            MediaControlEvent::Seek(SeekDirection::Forward) => self.seek(*SEEK_SMALL),
+            MediaControlEvent::Seek(SeekDirection::Backward) => self.seek(-*SEEK_SMALL),
+            MediaControlEvent::SeekBy(SeekDirection::Forward, dur) => self.seek(dur.as_secs_f64()),
             MediaControlEvent::SeekBy(SeekDirection::Backward, dur) => {
-                self.player.seek(dur.as_secs_f64())
+                self.seek(-dur.as_secs_f64())
             }
             _ => {}
         }
@@ -74,17 +100,21 @@ impl NoctaVox {
     }
 
     /// Called on every default tick (8ms), so we rate-limit with a counter.
+    /// Power save's tick is already slow enough to take every one.
     fn sync_media_controls_position(&mut self) {
         self.tick_sync = self.tick_sync.wrapping_add(1);
+        let due = self.ui.power_save() || self.tick_sync.is_multiple_of(timing().media_tick);
 
-        if user_config().general.broadcast
-            && self.player.is_active()
-            && self.tick_sync.is_multiple_of(timing().media_tick)
-        {
+        if user_config().general.broadcast && self.player.is_active() && due {
             self.ui.update_now_playing_elapsed();
         }
 
-        if self.tick_sync.is_multiple_of(timing().media_tick) {
+        if due {
+            self.ui.sync_power_save();
+            self.sync_discord_presence();
+            self.sync_status_file();
+            self.sync_scrobbler();
+
             if let Some(ref mut mc) = self.media_controls {
                 let elapsed = self.player.elapsed();
                 if self.player.is_paused() {
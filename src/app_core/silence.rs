@@ -0,0 +1,144 @@
+use crate::{
+    app_core::NoctaVox,
+    gen_config,
+    library::{
+        SimpleSong,
+        silence::{self, Trim},
+    },
+};
+use anyhow::Result;
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+/// Results kept before the lot is dropped, well past any queue's lookahead
+const KNOWN_LIMIT: usize = 256;
+
+/// Finds the silence at either end of songs on a thread of its own: the
+/// playing one as it starts, and the one after it ahead of time, so its
+/// lead-in can be skipped the moment it begins.
+pub(super) struct SilenceTrimmer {
+    requests: Sender<(u64, PathBuf)>,
+    results: Receiver<(u64, Option<Trim>)>,
+    requested: HashSet<u64>,
+    /// `None` for songs that couldn't be decoded here
+    known: HashMap<u64, Option<Trim>>,
+    /// The song whose lead-in has been dealt with, one way or another
+    lead_done: Option<u64>,
+}
+
+impl SilenceTrimmer {
+    fn new() -> Self {
+        let (requests, jobs) = unbounded::<(u64, PathBuf)>();
+        let (done, results) = unbounded();
+        let threshold = gen_config().silence_threshold_db;
+        let min_silence = Duration::from_secs_f64(gen_config().silence_min_secs);
+
+        thread::spawn(move || {
+            for (id, path) in jobs {
+                let trim = silence::find_trim(&path, threshold, min_silence).ok();
+                if done.send((id, trim)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        SilenceTrimmer {
+            requests,
+            results,
+            requested: HashSet::new(),
+            known: HashMap::new(),
+            lead_done: None,
+        }
+    }
+
+    fn request(&mut self, id: u64, path: PathBuf) {
+        if self.requested.len() >= KNOWN_LIMIT {
+            self.requested.clear();
+            self.known.clear();
+        }
+        if self.requested.insert(id) {
+            let _ = self.requests.send((id, path));
+        }
+    }
+
+    fn collect(&mut self) {
+        while let Ok((id, trim)) = self.results.try_recv() {
+            self.known.insert(id, trim);
+        }
+    }
+}
+
+impl NoctaVox {
+    pub(super) fn init_silence_trimmer() -> Option<SilenceTrimmer> {
+        gen_config().trim_silence.then(SilenceTrimmer::new)
+    }
+
+    /// Cue tracks share their file with others, so only the file's own
+    /// ends would ever be silence worth skipping
+    pub(super) fn start_silence_trim(&mut self, song: &SimpleSong, path: PathBuf, lead: bool) {
+        let Some(trimmer) = self.silence.as_mut() else {
+            return;
+        };
+        if song.cue().is_some() {
+            trimmer.lead_done = Some(song.id);
+            return;
+        }
+
+        trimmer.lead_done = match lead {
+            true => None,
+            false => Some(song.id),
+        };
+        trimmer.request(song.id, path);
+    }
+
+    /// Called once a frame. Skips the lead-in once it's known and moves on
+    /// as soon as the tail goes quiet for good.
+    pub fn update_silence_trim(&mut self) -> Result<()> {
+        let Some(trimmer) = self.silence.as_mut() else {
+            return Ok(());
+        };
+        trimmer.collect();
+
+        let Some(song) = self.ui.get_now_playing().filter(|s| s.cue().is_none()) else {
+            return Ok(());
+        };
+        let id = song.id;
+        if !self.player.is_active() || self.player.is_paused() {
+            return Ok(());
+        }
+        let Some(trim) = trimmer.known.get(&id).copied() else {
+            return Ok(());
+        };
+
+        if let Some(next) = self.ui.playback.peek_queue_validated() {
+            trimmer.request(next.meta.id, PathBuf::from(&next.path));
+        }
+
+        let Some(trim) = trim else {
+            return Ok(());
+        };
+        let position = self.player.elapsed().as_secs_f64();
+
+        if trimmer.lead_done != Some(id) {
+            trimmer.lead_done = Some(id);
+            if position < trim.start {
+                self.player.seek_to(trim.start as f32);
+                self.ui
+                    .hold_seek_position(Duration::from_secs_f64(trim.start));
+            }
+        }
+
+        if trim.end.is_some_and(|end| position >= end) {
+            self.ui.finish_resume_point();
+            if !self.advance_discrete()? {
+                self.player.stop();
+            }
+        }
+        Ok(())
+    }
+}
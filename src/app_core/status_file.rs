@@ -0,0 +1,112 @@
+use crate::{
+    app_core::NoctaVox,
+    control::{Status, StatusTrack},
+    expand_tilde,
+    library::SongInfo,
+    user_config,
+};
+use anyhow::{Context, Result};
+use std::{fs, path::PathBuf};
+
+/// The playing track as a single JSON line, for status bars and scripts to
+/// poll. See [`Status`] for what's in it. Rewritten whenever the line changes, by writing a sibling file and
+/// renaming it over the old one, so a reader never catches it half written.
+pub(super) struct StatusFile {
+    path: PathBuf,
+    last: String,
+}
+
+impl StatusFile {
+    fn write(&mut self, line: String) -> Result<()> {
+        if line == self.last {
+            return Ok(());
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        fs::write(&tmp, format!("{line}\n"))
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .with_context(|| format!("Could not write {}", self.path.display()))?;
+
+        self.last = line;
+        Ok(())
+    }
+}
+
+/// Nothing is playing once the player has closed
+impl Drop for StatusFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl NoctaVox {
+    pub(super) fn init_status_file() -> Option<StatusFile> {
+        let path = &user_config().general.status_file;
+        if path.is_empty() {
+            return None;
+        }
+
+        let path = expand_tilde(path).ok()?;
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        Some(StatusFile {
+            path,
+            last: String::new(),
+        })
+    }
+
+    /// Driven off the same tick as the OS media controls. A file that can't
+    /// be written is reported once and then given up on.
+    pub(super) fn sync_status_file(&mut self) {
+        if self.status_file.is_none() {
+            return;
+        }
+
+        let line = self.status_json();
+        if let Some(file) = self.status_file.as_mut()
+            && let Err(e) = file.write(line)
+        {
+            self.status_file = None;
+            self.ui.set_error(e);
+        }
+    }
+
+    pub(super) fn status(&self) -> Status {
+        let (state, track, position) = match self.ui.get_now_playing() {
+            Some(song) if self.player.is_active() => {
+                let (elapsed, length) = self.ui.track_progress();
+                let track = StatusTrack {
+                    title: song.get_title().to_string(),
+                    artist: song.get_artist().to_string(),
+                    album: song.get_album().to_string(),
+                    duration_secs: length.as_secs(),
+                };
+                let state = match self.player.is_paused() {
+                    true => "paused",
+                    false => "playing",
+                };
+                (state, Some(track), elapsed.as_secs())
+            }
+            _ => ("stopped", None, 0),
+        };
+
+        Status {
+            running: true,
+            state,
+            track,
+            position_secs: position,
+            queued: self.ui.playback.queue_len(),
+            volume: (self.ui.gain.volume() * 100.0).round() as u32,
+            muted: self.ui.gain.is_muted(),
+        }
+    }
+
+    /// The status file, the control socket and `--status` share one format
+    pub(super) fn status_json(&self) -> String {
+        self.status().to_json()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::visualization::{BarGap, MAX_MIN_HEIGHT, MAX_TAP_CAPACITY, MIN_TAP_CAPACITY};
 use serde::Deserialize;
 use voxio::ReplayGainMode;
 
@@ -10,6 +11,32 @@ pub struct GeneralConfig {
     )]
     pub framerate: u16,
 
+    /// Frames a second while music plays, 0 to match `framerate`
+    #[serde(
+        default = "defaults::viz_framerate",
+        deserialize_with = "deserialize_viz_framerate"
+    )]
+    pub viz_framerate: u16,
+
+    /// Frames a second while paused or stopped
+    #[serde(
+        default = "defaults::idle_framerate",
+        deserialize_with = "deserialize_idle_framerate"
+    )]
+    pub idle_framerate: u16,
+
+    /// Turns power save on and off with the power source
+    #[serde(default = "defaults::power_save_on_battery")]
+    pub power_save_on_battery: bool,
+
+    /// Seconds without input before the visualizer takes the whole screen,
+    /// 0 never
+    #[serde(
+        default = "defaults::idle_timeout_secs",
+        deserialize_with = "deserialize_idle_timeout"
+    )]
+    pub idle_timeout_secs: u32,
+
     #[serde(
         default = "defaults::history",
         deserialize_with = "deserialize_history"
@@ -34,14 +61,238 @@ pub struct GeneralConfig {
     #[serde(default = "defaults::auto_resume")]
     pub auto_resume: bool,
 
+    #[serde(default = "defaults::restore_session")]
+    pub restore_session: bool,
+
+    #[serde(
+        default = "defaults::startup_view",
+        deserialize_with = "deserialize_startup_view"
+    )]
+    pub startup_view: StartupView,
+
     #[serde(
         default = "defaults::replay_gain",
         deserialize_with = "deserialize_replay_gain"
     )]
     pub replay_gain: ReplayGainMode,
 
+    #[serde(default = "defaults::analyze_loudness")]
+    pub analyze_loudness: bool,
+
+    /// Off files every album under its track artists instead
+    #[serde(default = "defaults::group_by_album_artist")]
+    pub group_by_album_artist: bool,
+
+    /// Whether playing a whole album shuffles it by the shuffle mode
+    #[serde(default = "defaults::album_shuffle")]
+    pub album_shuffle: bool,
+
     #[serde(default = "defaults::broadcast")]
     pub broadcast: bool,
+
+    #[serde(default = "defaults::discord_presence")]
+    pub discord_presence: bool,
+
+    #[serde(default = "defaults::discord_client_id")]
+    pub discord_client_id: String,
+
+    #[serde(default = "defaults::notifications")]
+    pub notifications: bool,
+
+    #[serde(default = "defaults::media_keys")]
+    pub media_keys: bool,
+
+    #[serde(default = "defaults::mouse")]
+    pub mouse: bool,
+
+    /// Empty leaves the status file off
+    #[serde(default = "defaults::status_file")]
+    pub status_file: String,
+
+    #[serde(
+        default = "defaults::vu_window_ms",
+        deserialize_with = "deserialize_vu_window"
+    )]
+    pub vu_window_ms: u32,
+
+    /// The stretch of audio the oscilloscope and spectrum show, 0 leaving
+    /// it to `tap_capacity`
+    #[serde(
+        default = "defaults::viz_window_ms",
+        deserialize_with = "deserialize_viz_window"
+    )]
+    pub viz_window_ms: u32,
+
+    /// Frames of audio history kept for the visualizers
+    #[serde(
+        default = "defaults::tap_capacity",
+        deserialize_with = "deserialize_tap_capacity"
+    )]
+    pub tap_capacity: usize,
+
+    /// Seconds the ending track fades out and the next fades in over. The
+    /// two never overlap, so it's a dip between them.
+    #[serde(
+        default = "defaults::crossfade_secs",
+        deserialize_with = "deserialize_crossfade"
+    )]
+    pub crossfade_secs: f64,
+
+    #[serde(default = "defaults::gapless")]
+    pub gapless: bool,
+
+    /// Skips long silence at the top and tail of each track
+    #[serde(default = "defaults::trim_silence")]
+    pub trim_silence: bool,
+
+    /// Quieter than this counts as silence
+    #[serde(
+        default = "defaults::silence_threshold_db",
+        deserialize_with = "deserialize_silence_threshold"
+    )]
+    pub silence_threshold_db: f32,
+
+    /// Silence has to last this long to be trimmed, so quiet intros and
+    /// pauses within a song are left alone
+    #[serde(
+        default = "defaults::silence_min_secs",
+        deserialize_with = "deserialize_silence_min"
+    )]
+    pub silence_min_secs: f64,
+
+    #[serde(default = "defaults::sleep_fade")]
+    pub sleep_fade: bool,
+
+    #[serde(default = "defaults::ab_loop_keep_on_seek")]
+    pub ab_loop_keep_on_seek: bool,
+
+    #[serde(
+        default = "defaults::album_art",
+        deserialize_with = "deserialize_album_art"
+    )]
+    pub album_art: AlbumArtMode,
+
+    #[serde(default = "defaults::theme_hot_reload")]
+    pub theme_hot_reload: bool,
+
+    #[serde(default = "defaults::beat_pulse")]
+    pub beat_pulse: bool,
+
+    #[serde(default = "defaults::disc_separators")]
+    pub disc_separators: bool,
+
+    /// `None` fits one bar to every column of the pane
+    #[serde(
+        default = "defaults::spectrum_bars",
+        deserialize_with = "deserialize_spectrum_bars"
+    )]
+    pub spectrum_bars: Option<u16>,
+
+    /// The floor every spectrum bar is drawn up to, as a share of the height
+    #[serde(
+        default = "defaults::spectrum_min_height",
+        deserialize_with = "deserialize_spectrum_min_height"
+    )]
+    pub spectrum_min_height: f32,
+
+    #[serde(
+        default = "defaults::spectrum_gap",
+        deserialize_with = "deserialize_spectrum_gap"
+    )]
+    pub spectrum_gap: BarGap,
+
+    #[serde(
+        default = "defaults::oscilloscope_trace",
+        deserialize_with = "deserialize_oscilloscope_trace"
+    )]
+    pub oscilloscope_trace: ScopeTrace,
+
+    #[serde(
+        default = "defaults::pause_fade_ms",
+        deserialize_with = "deserialize_pause_fade"
+    )]
+    pub pause_fade_ms: u32,
+
+    /// How long a restored session takes to fade back in
+    #[serde(
+        default = "defaults::resume_fade_ms",
+        deserialize_with = "deserialize_resume_fade"
+    )]
+    pub resume_fade_ms: u32,
+
+    /// A play counts once this much of the track has been heard, or
+    /// `play_count_secs` of it, whichever comes first
+    #[serde(
+        default = "defaults::play_count_percent",
+        deserialize_with = "deserialize_play_count_percent"
+    )]
+    pub play_count_percent: u8,
+
+    /// Zero leaves it to the percentage alone
+    #[serde(default = "defaults::play_count_secs")]
+    pub play_count_secs: u32,
+
+    /// Tracks at least this many minutes long pick up where they were left.
+    /// Zero turns it off.
+    #[serde(default = "defaults::resume_min_mins")]
+    pub resume_min_mins: u32,
+}
+
+/// `Auto` only shows the cover pane on terminals that can draw images,
+/// `On` keeps it around with a placeholder everywhere else
+#[derive(Clone, Copy, PartialEq)]
+pub enum AlbumArtMode {
+    Auto,
+    On,
+    Off,
+}
+
+impl std::fmt::Display for AlbumArtMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlbumArtMode::Auto => write!(f, "auto"),
+            AlbumArtMode::On => write!(f, "on"),
+            AlbumArtMode::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// Where the app opens. `Last` is whichever view was up at shutdown, as
+/// long as it still has something in it.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StartupView {
+    Library,
+    Playlists,
+    Last,
+}
+
+impl std::fmt::Display for StartupView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupView::Library => write!(f, "library"),
+            StartupView::Playlists => write!(f, "playlists"),
+            StartupView::Last => write!(f, "last"),
+        }
+    }
+}
+
+/// How the oscilloscope draws its trace: the bare line, the line a pixel
+/// thicker on either side, or the line with the area under it shaded
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScopeTrace {
+    Line,
+    Thick,
+    Filled,
+}
+
+impl std::fmt::Display for ScopeTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeTrace::Line => write!(f, "line"),
+            ScopeTrace::Thick => write!(f, "thick"),
+            ScopeTrace::Filled => write!(f, "filled"),
+        }
+    }
 }
 
 #[rustfmt::skip]
@@ -49,13 +300,59 @@ mod defaults {
     pub fn seek_small() -> f64 { 5.0 }
     pub fn seek_large() -> f64 { 30.0 }
     pub fn framerate() -> u16 { 60 }
+    pub fn viz_framerate() -> u16 { 0 }
+    pub fn idle_framerate() -> u16 { 10 }
+    pub fn power_save_on_battery() -> bool { false }
+    pub fn idle_timeout_secs() -> u32 { 0 }
     pub fn history() -> u32 { 64 }
     pub fn update_on_start() -> bool { true }
     pub fn auto_resume() -> bool { false }
+    pub fn restore_session() -> bool { true }
+    pub fn analyze_loudness() -> bool { false }
+    pub fn group_by_album_artist() -> bool { true }
+    pub fn album_shuffle() -> bool { false }
     pub fn broadcast() -> bool { false }
+    pub fn discord_presence() -> bool { false }
+    pub fn discord_client_id() -> String { String::new() }
+    pub fn notifications() -> bool { false }
+    pub fn media_keys() -> bool { true }
+    pub fn mouse() -> bool { true }
+    pub fn status_file() -> String { String::new() }
+    pub fn vu_window_ms() -> u32 { 300 }
+    pub fn viz_window_ms() -> u32 { 0 }
+    pub fn tap_capacity() -> usize { 2048 }
+    pub fn crossfade_secs() -> f64 { 0.0 }
+    pub fn trim_silence() -> bool { false }
+    pub fn silence_threshold_db() -> f32 { -60.0 }
+    pub fn silence_min_secs() -> f64 { 2.0 }
+    pub fn gapless() -> bool { true }
+    pub fn sleep_fade() -> bool { true }
+    pub fn ab_loop_keep_on_seek() -> bool { false }
+    pub fn theme_hot_reload() -> bool { false }
+    pub fn beat_pulse() -> bool { false }
+    pub fn disc_separators() -> bool { true }
+    pub fn spectrum_bars() -> Option<u16> { None }
+    pub fn spectrum_min_height() -> f32 { 0.0 }
+    pub fn pause_fade_ms() -> u32 { 100 }
+    pub fn resume_fade_ms() -> u32 { 1500 }
+    pub fn play_count_percent() -> u8 { 50 }
+    pub fn play_count_secs() -> u32 { 240 }
+    pub fn resume_min_mins() -> u32 { 20 }
     
     use voxio::ReplayGainMode;
     pub fn replay_gain() -> ReplayGainMode { ReplayGainMode::Off } 
+
+    use super::AlbumArtMode;
+    pub fn album_art() -> AlbumArtMode { AlbumArtMode::Auto }
+
+    use super::BarGap;
+    pub fn spectrum_gap() -> BarGap { BarGap::Auto }
+
+    use super::StartupView;
+    pub fn startup_view() -> StartupView { StartupView::Library }
+
+    use super::ScopeTrace;
+    pub fn oscilloscope_trace() -> ScopeTrace { ScopeTrace::Line }
 }
 
 fn deserialize_seek<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
@@ -66,10 +363,64 @@ fn deserialize_framerate<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u16,
     u16::deserialize(d).map(|x| x.clamp(20, 360))
 }
 
+fn deserialize_viz_framerate<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u16, D::Error> {
+    u16::deserialize(d).map(|x| match x {
+        0 => 0,
+        x => x.clamp(5, 360),
+    })
+}
+
+fn deserialize_idle_framerate<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u16, D::Error> {
+    u16::deserialize(d).map(|x| x.clamp(1, 360))
+}
+
 fn deserialize_history<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
     u32::deserialize(d).map(|x| x.clamp(8, 1024))
 }
 
+fn deserialize_vu_window<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    u32::deserialize(d).map(|x| x.clamp(10, 3000))
+}
+
+fn deserialize_viz_window<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    u32::deserialize(d).map(|x| match x {
+        0 => 0,
+        x => x.clamp(5, 1000),
+    })
+}
+
+fn deserialize_tap_capacity<'de, D: serde::Deserializer<'de>>(d: D) -> Result<usize, D::Error> {
+    usize::deserialize(d).map(|x| x.clamp(MIN_TAP_CAPACITY, MAX_TAP_CAPACITY))
+}
+
+fn deserialize_crossfade<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    f64::deserialize(d).map(|x| x.clamp(0.0, 12.0))
+}
+
+fn deserialize_silence_threshold<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+    f32::deserialize(d).map(|x| x.clamp(-90.0, -20.0))
+}
+
+fn deserialize_silence_min<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    f64::deserialize(d).map(|x| x.clamp(0.5, 60.0))
+}
+
+fn deserialize_play_count_percent<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u8, D::Error> {
+    u8::deserialize(d).map(|x| x.clamp(1, 100))
+}
+
+fn deserialize_pause_fade<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    u32::deserialize(d).map(|x| x.min(1000))
+}
+
+fn deserialize_idle_timeout<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    u32::deserialize(d).map(|x| x.min(86_400))
+}
+
+fn deserialize_resume_fade<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    u32::deserialize(d).map(|x| x.min(10_000))
+}
+
 fn deserialize_replay_gain<'de, D: serde::Deserializer<'de>>(
     d: D,
 ) -> Result<ReplayGainMode, D::Error> {
@@ -80,17 +431,123 @@ fn deserialize_replay_gain<'de, D: serde::Deserializer<'de>>(
     })
 }
 
+/// `"auto"`, or a bar count, quoted or not
+fn deserialize_spectrum_bars<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<Option<u16>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bars {
+        Count(u16),
+        Word(String),
+    }
+
+    Bars::deserialize(d).map(|b| {
+        match b {
+            Bars::Count(n) => Some(n),
+            Bars::Word(w) => w.trim().parse().ok(),
+        }
+        .map(|n: u16| n.clamp(4, 256))
+    })
+}
+
+fn deserialize_spectrum_min_height<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<f32, D::Error> {
+    f32::deserialize(d).map(|x| x.clamp(0.0, MAX_MIN_HEIGHT))
+}
+
+fn deserialize_spectrum_gap<'de, D: serde::Deserializer<'de>>(d: D) -> Result<BarGap, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Gap {
+        Dots(u8),
+        Word(String),
+    }
+
+    Gap::deserialize(d).map(|g| match g {
+        Gap::Dots(n) => BarGap::from(n.to_string().as_str()),
+        Gap::Word(w) => BarGap::from(w.as_str()),
+    })
+}
+
+fn deserialize_album_art<'de, D: serde::Deserializer<'de>>(d: D) -> Result<AlbumArtMode, D::Error> {
+    String::deserialize(d).map(|s| match s.to_lowercase().as_str() {
+        "on" => AlbumArtMode::On,
+        "off" => AlbumArtMode::Off,
+        _ => AlbumArtMode::Auto,
+    })
+}
+
+fn deserialize_startup_view<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<StartupView, D::Error> {
+    String::deserialize(d).map(|s| match s.to_lowercase().as_str() {
+        "playlists" | "playlist" => StartupView::Playlists,
+        "last" | "last-used" | "last_used" => StartupView::Last,
+        _ => StartupView::Library,
+    })
+}
+
+fn deserialize_oscilloscope_trace<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<ScopeTrace, D::Error> {
+    String::deserialize(d).map(|s| match s.to_lowercase().as_str() {
+        "thick" => ScopeTrace::Thick,
+        "filled" | "fill" => ScopeTrace::Filled,
+        _ => ScopeTrace::Line,
+    })
+}
+
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             framerate: defaults::framerate(),
+            viz_framerate: defaults::viz_framerate(),
+            idle_framerate: defaults::idle_framerate(),
+            power_save_on_battery: defaults::power_save_on_battery(),
+            idle_timeout_secs: defaults::idle_timeout_secs(),
             history_capacity: defaults::history(),
             seek_small: defaults::seek_small(),
             seek_large: defaults::seek_large(),
             update_on_start: defaults::update_on_start(),
             auto_resume: defaults::auto_resume(),
+            restore_session: defaults::restore_session(),
+            startup_view: defaults::startup_view(),
+            analyze_loudness: defaults::analyze_loudness(),
+            group_by_album_artist: defaults::group_by_album_artist(),
+            album_shuffle: defaults::album_shuffle(),
             broadcast: defaults::broadcast(),
+            discord_presence: defaults::discord_presence(),
+            discord_client_id: defaults::discord_client_id(),
+            notifications: defaults::notifications(),
+            media_keys: defaults::media_keys(),
+            mouse: defaults::mouse(),
+            status_file: defaults::status_file(),
+            vu_window_ms: defaults::vu_window_ms(),
+            viz_window_ms: defaults::viz_window_ms(),
+            tap_capacity: defaults::tap_capacity(),
+            crossfade_secs: defaults::crossfade_secs(),
+            gapless: defaults::gapless(),
+            trim_silence: defaults::trim_silence(),
+            silence_threshold_db: defaults::silence_threshold_db(),
+            silence_min_secs: defaults::silence_min_secs(),
+            sleep_fade: defaults::sleep_fade(),
+            ab_loop_keep_on_seek: defaults::ab_loop_keep_on_seek(),
             replay_gain: ReplayGainMode::Off,
+            album_art: defaults::album_art(),
+            theme_hot_reload: defaults::theme_hot_reload(),
+            beat_pulse: defaults::beat_pulse(),
+            disc_separators: defaults::disc_separators(),
+            spectrum_bars: defaults::spectrum_bars(),
+            spectrum_min_height: defaults::spectrum_min_height(),
+            spectrum_gap: defaults::spectrum_gap(),
+            oscilloscope_trace: defaults::oscilloscope_trace(),
+            pause_fade_ms: defaults::pause_fade_ms(),
+            resume_fade_ms: defaults::resume_fade_ms(),
+            play_count_percent: defaults::play_count_percent(),
+            play_count_secs: defaults::play_count_secs(),
+            resume_min_mins: defaults::resume_min_mins(),
         }
     }
 }
@@ -3,7 +3,7 @@ mod icons;
 mod timing;
 mod user_config;
 
-pub use general::GeneralConfig;
+pub use general::{AlbumArtMode, GeneralConfig, ScopeTrace, StartupView};
 pub use icons::UserIcons;
 pub use timing::{TIMING, Timing, timing};
 pub use user_config::UserConfig;
@@ -1,16 +1,35 @@
 use std::{sync::OnceLock, time::Duration};
 pub static TIMING: OnceLock<Timing> = OnceLock::new();
 
+/// Still often enough for crossfades and pause fades to stay smooth
+const POWER_SAVE_FPS: u16 = 20;
+
 pub struct Timing {
     pub refresh_rate: Duration,
+    /// Least time between frames while music plays
+    pub viz_period: Duration,
+    /// Least time between frames while paused or stopped
+    pub idle_period: Duration,
+    /// The main loop's tick in power save
+    pub power_save_tick: Duration,
     pub media_tick: u32,
     pub db_tick: u32,
 }
 
 impl Timing {
-    pub fn from_fps(fps: u16) -> Self {
+    /// A `viz_fps` of 0 draws every tick. Neither cap can draw faster than
+    /// the loop runs.
+    pub fn from_fps(fps: u16, viz_fps: u16, idle_fps: u16) -> Self {
+        let refresh_rate = frame_period(fps);
+        let viz_period = match viz_fps {
+            0 => refresh_rate,
+            n => frame_period(n).max(refresh_rate),
+        };
         Self {
-            refresh_rate: frame_period(fps),
+            refresh_rate,
+            viz_period,
+            idle_period: frame_period(idle_fps).max(refresh_rate),
+            power_save_tick: frame_period(POWER_SAVE_FPS).max(refresh_rate),
             media_tick: ticks_per(48, fps),
             db_tick: ticks_per(600, fps),
         }
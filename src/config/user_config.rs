@@ -1,6 +1,7 @@
 use crate::{
     CONFIG_DIR,
     config::{GeneralConfig, icons::UserIcons},
+    visualization::BarGap,
 };
 use anyhow::{Context, anyhow};
 use serde::Deserialize;
@@ -62,6 +63,22 @@ fn default_config() -> String {
     );
 
     let _ = writeln!(conf, "# {:<17}= {}", "framerate", general.framerate);
+    let _ = writeln!(conf, "# {:<17}= {}", "viz_framerate", general.viz_framerate);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "idle_framerate", general.idle_framerate
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "power_save_on_battery", general.power_save_on_battery
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "idle_timeout_secs", general.idle_timeout_secs
+    );
     let _ = writeln!(
         conf,
         "# {:<17}= {}",
@@ -75,8 +92,120 @@ fn default_config() -> String {
         "update_on_start", general.update_on_start
     );
     let _ = writeln!(conf, "# {:<17}= {}", "auto_resume", general.auto_resume);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "restore_session", general.restore_session
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= \"{}\"",
+        "startup_view", general.startup_view
+    );
     let _ = writeln!(conf, "# {:<17}= \"{}\"", "replay_gain", replay_gain);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "analyze_loudness", general.analyze_loudness
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "group_by_album_artist", general.group_by_album_artist
+    );
+    let _ = writeln!(conf, "# {:<17}= {}", "album_shuffle", general.album_shuffle);
     let _ = writeln!(conf, "# {:<17}= {}", "broadcast", general.broadcast);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "discord_presence", general.discord_presence
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= \"{}\"",
+        "discord_client_id", general.discord_client_id
+    );
+    let _ = writeln!(conf, "# {:<17}= {}", "notifications", general.notifications);
+    let _ = writeln!(conf, "# {:<17}= {}", "media_keys", general.media_keys);
+    let _ = writeln!(conf, "# {:<17}= {}", "mouse", general.mouse);
+    let _ = writeln!(conf, "# {:<17}= \"{}\"", "status_file", general.status_file);
+    let _ = writeln!(conf, "# {:<17}= {}", "vu_window_ms", general.vu_window_ms);
+    let _ = writeln!(conf, "# {:<17}= {}", "viz_window_ms", general.viz_window_ms);
+    let _ = writeln!(conf, "# {:<17}= {}", "tap_capacity", general.tap_capacity);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {:?}",
+        "crossfade_secs", general.crossfade_secs
+    );
+    let _ = writeln!(conf, "# {:<17}= {}", "gapless", general.gapless);
+    let _ = writeln!(conf, "# {:<17}= {}", "trim_silence", general.trim_silence);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {:?}",
+        "silence_threshold_db", general.silence_threshold_db
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {:?}",
+        "silence_min_secs", general.silence_min_secs
+    );
+    let _ = writeln!(conf, "# {:<17}= {}", "sleep_fade", general.sleep_fade);
+    let _ = writeln!(conf, "# {:<17}= {}", "pause_fade_ms", general.pause_fade_ms);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "resume_fade_ms", general.resume_fade_ms
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "play_count_percent", general.play_count_percent
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "play_count_secs", general.play_count_secs
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "resume_min_mins", general.resume_min_mins
+    );
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "ab_loop_keep_on_seek", general.ab_loop_keep_on_seek
+    );
+    let _ = writeln!(conf, "# {:<17}= \"{}\"", "album_art", general.album_art);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "theme_hot_reload", general.theme_hot_reload
+    );
+    let _ = writeln!(conf, "# {:<17}= {}", "beat_pulse", general.beat_pulse);
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {}",
+        "disc_separators", general.disc_separators
+    );
+    let _ = match general.spectrum_bars {
+        Some(n) => writeln!(conf, "# {:<17}= {}", "spectrum_bars", n),
+        None => writeln!(conf, "# {:<17}= \"auto\"", "spectrum_bars"),
+    };
+    let _ = writeln!(
+        conf,
+        "# {:<17}= {:?}",
+        "spectrum_min_height", general.spectrum_min_height
+    );
+    let _ = match general.spectrum_gap {
+        BarGap::Dots(n) => writeln!(conf, "# {:<17}= {}", "spectrum_gap", n),
+        BarGap::Auto => writeln!(conf, "# {:<17}= \"auto\"", "spectrum_gap"),
+    };
+    let _ = writeln!(
+        conf,
+        "# {:<17}= \"{}\"",
+        "oscilloscope_trace", general.oscilloscope_trace
+    );
 
     conf.push_str("\n[icons]\n");
 
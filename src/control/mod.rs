@@ -0,0 +1,165 @@
+#[cfg(unix)]
+mod socket;
+mod status;
+
+pub use status::{STATUS_VERSION, Status, StatusTrack};
+
+use crate::json_string;
+use anyhow::{Result, bail};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// What the control socket takes, one to a line. Every line gets one line
+/// of JSON back: `{"ok":true}`, or `{"ok":false,"error":"..."}` when it
+/// couldn't be done. `status` answers with the [`Status`] object itself, so
+/// a client can pass it on as it is.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Resumes, or starts the queue when nothing is playing
+    Play,
+    /// Plays a file from the library now, ahead of the queue
+    PlayPath(PathBuf),
+    Pause,
+    Toggle,
+    Stop,
+    Next,
+    Prev,
+    /// A time such as `1:23` or `+90`, as the seek popup takes
+    Seek(String),
+    Enqueue(PathBuf),
+    Status,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        let (name, arg) = match line.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name.to_ascii_lowercase(), arg.trim()),
+            None => (line.to_ascii_lowercase(), ""),
+        };
+
+        let command = match (name.as_str(), arg) {
+            ("play", "") => Command::Play,
+            ("play", path) => Command::PlayPath(PathBuf::from(path)),
+            ("seek", "") => bail!("seek needs a time, like 1:23 or +30"),
+            ("seek", to) => Command::Seek(to.to_string()),
+            ("enqueue", "") => bail!("enqueue needs the path of a song"),
+            ("enqueue", path) => Command::Enqueue(PathBuf::from(path)),
+            ("pause", "") => Command::Pause,
+            ("toggle", "") => Command::Toggle,
+            ("stop", "") => Command::Stop,
+            ("next", "") => Command::Next,
+            ("prev" | "previous", "") => Command::Prev,
+            ("status", "") => Command::Status,
+            ("quit", "") => Command::Quit,
+            ("pause" | "toggle" | "stop" | "next" | "prev" | "previous" | "status" | "quit", _) => {
+                bail!("{name} takes nothing after it")
+            }
+            _ => bail!("Unknown command: {name}"),
+        };
+        Ok(command)
+    }
+
+    /// Whether the argument is a path, which a client should make absolute
+    /// before sending, as the player may be running somewhere else
+    pub fn takes_path(name: &str) -> bool {
+        matches!(name.to_ascii_lowercase().as_str(), "play" | "enqueue")
+    }
+}
+
+/// A command waiting for the main loop, and the way back to its client
+pub struct Request {
+    pub command: Command,
+    reply: Sender<String>,
+}
+
+impl Request {
+    pub fn respond(&self, reply: String) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+pub fn ok_reply() -> String {
+    String::from("{\"ok\":true}")
+}
+
+pub fn error_reply(e: &anyhow::Error) -> String {
+    format!(
+        "{{\"ok\":false,\"error\":\"{}\"}}",
+        json_string(&e.to_string())
+    )
+}
+
+/// Listens on a Unix socket, handing each command to the main loop. The
+/// socket goes away with the server.
+pub struct ControlServer {
+    requests: Receiver<Request>,
+    path: PathBuf,
+}
+
+impl ControlServer {
+    /// Fails when another player is already listening at `path`
+    pub fn bind(path: PathBuf) -> Result<Self> {
+        #[cfg(unix)]
+        {
+            let requests = socket::listen(&path)?;
+            Ok(ControlServer { requests, path })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            bail!("The control socket needs a Unix system")
+        }
+    }
+
+    pub fn requests(&self) -> &Receiver<Request> {
+        &self.requests
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Sends one command to the player listening at `path`, and waits for its
+/// answer
+pub fn send(path: &Path, line: &str) -> Result<String> {
+    #[cfg(unix)]
+    {
+        socket::send(path, line)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, line);
+        bail!("The control socket needs a Unix system")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_arguments() {
+        assert_eq!(Command::parse(" PAUSE ").unwrap(), Command::Pause);
+        assert_eq!(Command::parse("play").unwrap(), Command::Play);
+        assert_eq!(
+            Command::parse("play /music/a b.flac").unwrap(),
+            Command::PlayPath(PathBuf::from("/music/a b.flac"))
+        );
+        assert_eq!(
+            Command::parse("seek +30").unwrap(),
+            Command::Seek("+30".to_string())
+        );
+
+        assert!(Command::parse("enqueue").is_err());
+        assert!(Command::parse("next please").is_err());
+        assert!(Command::parse("rewind").is_err());
+    }
+}
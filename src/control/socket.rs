@@ -0,0 +1,84 @@
+use super::{Command, Request, error_reply};
+use anyhow::{Context, Result, bail};
+use crossbeam_channel::{Receiver, Sender, bounded, unbounded};
+use std::{
+    fs::{self, Permissions},
+    io::{BufRead, BufReader, Write},
+    net::Shutdown,
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    thread,
+};
+
+/// Each client gets a thread of its own, which waits on the main loop for
+/// every answer
+pub fn listen(path: &Path) -> Result<Receiver<Request>> {
+    if UnixStream::connect(path).is_ok() {
+        bail!("A player is already listening on {}", path.display());
+    }
+    // Left behind by a player that never got to clean up
+    let _ = fs::remove_file(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Could not listen on {}", path.display()))?;
+    // Nobody else on the machine gets to drive the player
+    fs::set_permissions(path, Permissions::from_mode(0o600))?;
+
+    let (sender, requests) = unbounded();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || serve(stream, sender));
+        }
+    });
+    Ok(requests)
+}
+
+/// One answer a line, until the client hangs up or the player goes away
+fn serve(stream: UnixStream, requests: Sender<Request>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { return };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match Command::parse(&line) {
+            Ok(command) => {
+                let (reply, answer) = bounded(1);
+                if requests.send(Request { command, reply }).is_err() {
+                    return;
+                }
+                match answer.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(e) => error_reply(&e),
+        };
+
+        if writeln!(writer, "{reply}").is_err() {
+            return;
+        }
+    }
+}
+
+pub fn send(path: &Path, line: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(path)
+        .with_context(|| format!("No player is listening on {}", path.display()))?;
+    writeln!(stream, "{line}")?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
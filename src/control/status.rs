@@ -0,0 +1,75 @@
+use crate::{Database, json_string, ui_state::PlayerSnapshot};
+use anyhow::Result;
+
+/// Bumped whenever a field changes meaning or goes away. New fields can
+/// turn up without it, so scripts should ignore what they don't know.
+pub const STATUS_VERSION: u32 = 1;
+
+pub struct StatusTrack {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration_secs: u64,
+}
+
+/// What's playing, as the status file, the control socket and
+/// `--status` all report it
+pub struct Status {
+    /// Whether a player answered, rather than this coming from the last
+    /// saved session
+    pub running: bool,
+    pub state: &'static str,
+    pub track: Option<StatusTrack>,
+    pub position_secs: u64,
+    /// Songs waiting after the current one
+    pub queued: usize,
+    /// Percent
+    pub volume: u32,
+    pub muted: bool,
+}
+
+impl Status {
+    /// With no player running, the track it would pick up again on start
+    pub fn saved(db: &mut Database) -> Result<Self> {
+        let volume = PlayerSnapshot::from_values(db.load_snapshot("player_%")?).volume;
+        let queued = db.load_queue()?.len();
+
+        let (track, position) = match db.get_last_played() {
+            Ok((id, elapsed)) => (db.get_status_track(id)?, elapsed),
+            Err(_) => (None, 0.0),
+        };
+
+        Ok(Status {
+            running: false,
+            state: "stopped",
+            position_secs: match track {
+                Some(_) => position as u64,
+                None => 0,
+            },
+            track,
+            queued,
+            volume: (volume * 100.0).round() as u32,
+            muted: false,
+        })
+    }
+
+    /// One line, the track's fields left out when there isn't one
+    pub fn to_json(&self) -> String {
+        let track = match &self.track {
+            Some(t) => format!(
+                "\"title\":\"{}\",\"artist\":\"{}\",\"album\":\"{}\",\"position\":{},\"duration\":{},",
+                json_string(&t.title),
+                json_string(&t.artist),
+                json_string(&t.album),
+                self.position_secs,
+                t.duration_secs,
+            ),
+            None => String::new(),
+        };
+
+        format!(
+            "{{\"version\":{STATUS_VERSION},\"running\":{},\"state\":\"{}\",{track}\"queue\":{},\"volume\":{},\"muted\":{}}}",
+            self.running, self.state, self.queued, self.volume, self.muted,
+        )
+    }
+}
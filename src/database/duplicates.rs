@@ -0,0 +1,217 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+    library::FileType,
+};
+use anyhow::Result;
+use rusqlite::params;
+use std::{collections::HashMap, fs};
+
+/// Lengths further apart than this are treated as different recordings
+const DURATION_TOLERANCE: f32 = 2.0;
+
+pub struct DuplicateSong {
+    pub id: u64,
+    pub title: String,
+    pub artist: String,
+    pub path: String,
+    pub duration: f32,
+    pub format: FileType,
+    /// Average kbps over the whole file
+    pub bitrate: u32,
+    fingerprint: Option<u64>,
+    ignored: bool,
+}
+
+/// Copies of what's likely the same song, highest bitrate first
+pub struct DuplicateGroup {
+    pub songs: Vec<DuplicateSong>,
+}
+
+impl DuplicateGroup {
+    /// The copy kept when the group is resolved
+    pub fn best(&self) -> &DuplicateSong {
+        &self.songs[0]
+    }
+
+    pub fn extras(&self) -> &[DuplicateSong] {
+        &self.songs[1..]
+    }
+
+    pub fn ids(&self) -> Vec<u64> {
+        self.songs.iter().map(|s| s.id).collect()
+    }
+}
+
+impl Database {
+    /// Groups songs with identical contents, or with the same artist and
+    /// title (ignoring case and punctuation) and lengths within a couple of
+    /// seconds of each other. Groups marked as ignored are left out.
+    pub(crate) fn find_duplicates(&mut self) -> Result<Vec<DuplicateGroup>> {
+        let songs = self
+            .conn
+            .prepare(GET_DUPLICATE_CANDIDATES)?
+            .query_map([], |row| {
+                let path: String = row.get("path")?;
+                let duration = row.get::<_, Option<f32>>("duration")?.unwrap_or(0.0);
+
+                // Scans never filled in the bitrate, so work it out from the
+                // size. Songs scanned before sizes were stored read it off disk.
+                let size = match row.get::<_, Option<i64>>("size")? {
+                    Some(size) => size as u64,
+                    None => fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                };
+                let bitrate = match duration > 0.0 {
+                    true => (size as f32 * 8.0 / duration / 1000.0) as u32,
+                    false => row.get::<_, Option<u32>>("bit_rate")?.unwrap_or(0),
+                };
+
+                Ok(DuplicateSong {
+                    id: convert_from_bytes(row.get("id")?),
+                    title: row.get("title")?,
+                    artist: row.get::<_, Option<String>>("artist")?.unwrap_or_default(),
+                    path,
+                    duration,
+                    format: row.get("format")?,
+                    bitrate,
+                    fingerprint: row
+                        .get::<_, Option<i64>>("fingerprint")?
+                        .map(|fp| fp as u64),
+                    ignored: row.get("ignored")?,
+                })
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(group_duplicates(songs))
+    }
+
+    pub(crate) fn ignore_duplicates(&mut self, ids: &[u64]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(IGNORE_DUPLICATE)?;
+            for id in ids {
+                stmt.execute(params![id.to_le_bytes()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn group_duplicates(songs: Vec<DuplicateSong>) -> Vec<DuplicateGroup> {
+    let mut parent = (0..songs.len()).collect::<Vec<_>>();
+
+    let mut by_name: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, song) in songs.iter().enumerate() {
+        let title = dedupe_key(&song.title);
+        if !title.is_empty() {
+            by_name
+                .entry((dedupe_key(&song.artist), title))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    // Compared against the shortest copy so far rather than the previous
+    // one, so a run of slightly longer edits can't chain into one group
+    for mut idxs in by_name.into_values() {
+        idxs.sort_by(|&a, &b| songs[a].duration.total_cmp(&songs[b].duration));
+
+        let mut shortest = idxs[0];
+        for &i in &idxs[1..] {
+            match songs[i].duration - songs[shortest].duration <= DURATION_TOLERANCE {
+                true => union(&mut parent, shortest, i),
+                false => shortest = i,
+            }
+        }
+    }
+
+    // Identical files belong together however they're tagged
+    let mut by_fingerprint = HashMap::new();
+    for (i, song) in songs.iter().enumerate() {
+        if let Some(fp) = song.fingerprint {
+            match by_fingerprint.get(&fp) {
+                Some(&first) => union(&mut parent, first, i),
+                None => {
+                    by_fingerprint.insert(fp, i);
+                }
+            }
+        }
+    }
+
+    let mut grouped: HashMap<usize, Vec<DuplicateSong>> = HashMap::new();
+    for (i, song) in songs.into_iter().enumerate() {
+        grouped.entry(find(&mut parent, i)).or_default().push(song);
+    }
+
+    let mut groups = grouped
+        .into_values()
+        .filter(|songs| songs.len() > 1 && !songs.iter().all(|s| s.ignored))
+        .map(|mut songs| {
+            songs.sort_by(|a, b| b.bitrate.cmp(&a.bitrate).then_with(|| a.path.cmp(&b.path)));
+            DuplicateGroup { songs }
+        })
+        .collect::<Vec<_>>();
+
+    groups.sort_by_key(|g| {
+        (
+            g.best().artist.to_lowercase(),
+            g.best().title.to_lowercase(),
+        )
+    });
+    groups
+}
+
+/// Lowercase letters and digits only, so "Don't Stop" matches "dont stop"
+fn dedupe_key(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    parent[b] = a;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: u64, artist: &str, title: &str, duration: f32, bitrate: u32) -> DuplicateSong {
+        DuplicateSong {
+            id,
+            title: title.into(),
+            artist: artist.into(),
+            path: format!("/music/{id}"),
+            duration,
+            format: FileType::MP3,
+            bitrate,
+            fingerprint: None,
+            ignored: false,
+        }
+    }
+
+    #[test]
+    fn groups_near_equal_lengths_only() {
+        let groups = group_duplicates(vec![
+            song(1, "The Band", "Don't Stop", 200.0, 128),
+            song(2, "the band", "dont stop", 201.5, 320),
+            song(3, "The Band", "Don't Stop", 260.0, 256),
+            song(4, "Other Band", "Don't Stop", 200.0, 128),
+        ]);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].ids(), vec![2, 1]);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+impl Database {
+    /// Every genre in the library with its track count, `Unknown` last
+    pub(crate) fn get_genres(&mut self) -> Result<Vec<(Arc<String>, usize)>> {
+        let genres = self
+            .conn
+            .prepare(GET_GENRES)?
+            .query_map([], |row| {
+                Ok((
+                    Arc::new(row.get::<_, String>("name")?),
+                    row.get::<_, i64>("count")? as usize,
+                ))
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(genres)
+    }
+
+    /// In album order. `Unknown` holds the songs with no genre at all.
+    pub(crate) fn get_genre_songs(&mut self, genre: &str) -> Result<Vec<u64>> {
+        let ids = self
+            .conn
+            .prepare_cached(GET_GENRE_SONGS)?
+            .query_map([genre], |row| Ok(convert_from_bytes(row.get("id")?)))?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(ids)
+    }
+
+    pub(crate) fn get_song_genres(&mut self, id: u64) -> Result<Vec<String>> {
+        let genres = self
+            .conn
+            .prepare_cached(GET_SONG_GENRES)?
+            .query_map([id.to_le_bytes()], |row| row.get("name"))?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(genres)
+    }
+
+    /// Whether genres were read for the songs already in the library, which
+    /// were scanned before they were kept
+    pub(crate) fn genres_read(&self) -> Result<bool> {
+        Ok(self.conn.query_row(GENRES_READ, [], |row| row.get(0))?)
+    }
+
+    pub(crate) fn set_genres_read(&self) -> Result<()> {
+        self.conn.execute(SET_GENRES_READ, [])?;
+        Ok(())
+    }
+}
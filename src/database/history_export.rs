@@ -0,0 +1,112 @@
+use crate::{Database, database::queries::EXPORT_HISTORY, json_string};
+use anyhow::{Context, Result};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HistoryFormat {
+    Csv,
+    Json,
+}
+
+impl HistoryFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            HistoryFormat::Csv => "csv",
+            HistoryFormat::Json => "json",
+        }
+    }
+}
+
+struct HistoryRow {
+    played_at: String,
+    title: String,
+    artist: String,
+    album: String,
+    plays: u32,
+}
+
+impl Database {
+    /// Writes every history entry, oldest first, alongside the track's total
+    /// play count. Rows are streamed to disk rather than collected first.
+    ///
+    /// Returns the number of entries written.
+    pub fn export_history(&mut self, dest: &Path, format: HistoryFormat) -> Result<usize> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create {}", parent.display()))?;
+        }
+        let file =
+            File::create(dest).with_context(|| format!("Could not write to {}", dest.display()))?;
+        let mut out = BufWriter::new(file);
+
+        let mut stmt = self.conn.prepare(EXPORT_HISTORY)?;
+        let mut rows = stmt.query([])?;
+
+        match format {
+            HistoryFormat::Csv => writeln!(out, "played_at,title,artist,album,play_count")?,
+            HistoryFormat::Json => write!(out, "[")?,
+        }
+
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let entry = HistoryRow {
+                played_at: row.get("played_at")?,
+                title: row.get("title")?,
+                artist: row.get::<_, Option<String>>("artist")?.unwrap_or_default(),
+                album: row.get::<_, Option<String>>("album")?.unwrap_or_default(),
+                plays: row.get("plays")?,
+            };
+
+            match format {
+                HistoryFormat::Csv => write_csv_row(&mut out, &entry)?,
+                HistoryFormat::Json => write_json_row(&mut out, &entry, count == 0)?,
+            }
+            count += 1;
+        }
+
+        if format == HistoryFormat::Json {
+            writeln!(out, "\n]")?;
+        }
+        out.flush()
+            .with_context(|| format!("Could not write to {}", dest.display()))?;
+
+        Ok(count)
+    }
+}
+
+fn write_csv_row(out: &mut impl Write, e: &HistoryRow) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{},{},{},{},{}",
+        e.played_at,
+        csv_field(&e.title),
+        csv_field(&e.artist),
+        csv_field(&e.album),
+        e.plays
+    )
+}
+
+fn write_json_row(out: &mut impl Write, e: &HistoryRow, first: bool) -> std::io::Result<()> {
+    write!(
+        out,
+        "{}\n  {{\"played_at\":\"{}\",\"title\":\"{}\",\"artist\":\"{}\",\"album\":\"{}\",\"play_count\":{}}}",
+        if first { "" } else { "," },
+        e.played_at,
+        json_string(&e.title),
+        json_string(&e.artist),
+        json_string(&e.album),
+        e.plays
+    )
+}
+
+/// Fields holding a comma, quote or line break are quoted, per RFC 4180
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
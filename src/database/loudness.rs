@@ -0,0 +1,80 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+    library::{Loudness, Measurement},
+};
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+
+/// A song still to be measured: id, stored path and cue span
+pub(crate) type Unmeasured = (u64, String, Option<(f64, Option<f64>)>);
+
+impl Database {
+    pub(crate) fn get_unmeasured(&mut self, limit: usize) -> Result<Vec<Unmeasured>> {
+        let songs = self
+            .conn
+            .prepare(GET_UNMEASURED)?
+            .query_map([limit as i64], |row| {
+                let start = row.get::<_, Option<f64>>("cue_start")?;
+                let end = row.get::<_, Option<f64>>("cue_end")?;
+                Ok((
+                    convert_from_bytes(row.get("id")?),
+                    row.get::<_, String>("path")?,
+                    start.map(|s| (s, end)),
+                ))
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(songs)
+    }
+
+    pub(crate) fn set_loudness(&mut self, id: u64, measurement: &Measurement) -> Result<()> {
+        let (tagged, peak, histogram) = match measurement {
+            Measurement::Tagged => (true, None, None),
+            Measurement::Measured(l) => (false, Some(l.peak()), Some(l.histogram_bytes())),
+            Measurement::Failed => (false, None, None),
+        };
+
+        self.conn.execute(
+            SET_LOUDNESS,
+            params![id.to_le_bytes(), tagged, peak, histogram],
+        )?;
+        Ok(())
+    }
+
+    /// `None` unless the song was measured, rather than tagged or skipped
+    pub(crate) fn get_loudness(&mut self, id: u64) -> Result<Option<Loudness>> {
+        let loudness = self
+            .conn
+            .query_row(GET_LOUDNESS, [id.to_le_bytes()], |row| {
+                Ok(Loudness::from_parts(
+                    &row.get::<_, Vec<u8>>("histogram")?,
+                    row.get("peak")?,
+                ))
+            })
+            .optional()?;
+
+        Ok(loudness)
+    }
+
+    /// Every measured track on the song's album, taken together
+    pub(crate) fn get_album_loudness(&mut self, id: u64) -> Result<Option<Loudness>> {
+        let album = self
+            .conn
+            .prepare(GET_ALBUM_LOUDNESS)?
+            .query_map([id.to_le_bytes()], |row| {
+                Ok(Loudness::from_parts(
+                    &row.get::<_, Vec<u8>>("histogram")?,
+                    row.get("peak")?,
+                ))
+            })?
+            .filter_map(Result::ok)
+            .reduce(|mut album, track| {
+                album.merge(&track);
+                album
+            });
+
+        Ok(album)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+    strip_win_prefix,
+};
+use anyhow::{Context, Result, bail};
+use rusqlite::{OptionalExtension, params};
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Outcome of an import. Entries that couldn't be matched to a song in the
+/// library are reported back rather than failing the whole playlist.
+pub struct M3uImport {
+    pub name: String,
+    pub added: usize,
+    pub missing: Vec<String>,
+}
+
+struct M3uContents {
+    name: Option<String>,
+    song_ids: Vec<u64>,
+    missing: Vec<String>,
+}
+
+impl Database {
+    /// Writes the songs to `dest` as an extended M3U8 playlist, in the given
+    /// order. Songs that have since left the library are skipped.
+    pub fn export_playlist_m3u(&mut self, name: &str, song_ids: &[u64], dest: &Path) -> Result<()> {
+        let mut m3u = format!("#EXTM3U\n#PLAYLIST:{name}\n");
+
+        let mut stmt = self.conn.prepare_cached(GET_M3U_ENTRY)?;
+        for id in song_ids {
+            let entry = stmt
+                .query_row(params![id.to_le_bytes()], |row| {
+                    Ok((
+                        row.get::<_, String>("path")?,
+                        row.get::<_, String>("title")?,
+                        row.get::<_, Option<f64>>("duration")?,
+                        row.get::<_, Option<String>>("artist")?,
+                    ))
+                })
+                .optional()?;
+
+            let Some((path, title, duration, artist)) = entry else {
+                continue;
+            };
+
+            let secs = duration.map_or(-1, |d| d.round() as i64);
+            let _ = match artist {
+                Some(artist) => writeln!(m3u, "#EXTINF:{secs},{artist} - {title}"),
+                None => writeln!(m3u, "#EXTINF:{secs},{title}"),
+            };
+            let _ = writeln!(m3u, "{}", strip_win_prefix(&path));
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(dest, m3u).with_context(|| format!("Could not write {}", dest.display()))?;
+
+        Ok(())
+    }
+
+    /// Creates a playlist named after the file from an M3U or M3U8 playlist.
+    /// Relative entries resolve against the playlist's own directory.
+    pub fn import_playlist_m3u(&mut self, file: &Path) -> Result<M3uImport> {
+        let contents = self.read_m3u(file)?;
+
+        let name = contents.name.unwrap_or_else(|| {
+            file.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let name = self.unique_playlist_name(&name)?;
+        self.create_playlist(&name)?;
+        let playlist_id = self.conn.last_insert_rowid();
+
+        let added = contents.song_ids.len();
+        self.add_to_playlist_multi(contents.song_ids, playlist_id)?;
+
+        Ok(M3uImport {
+            name,
+            added,
+            missing: contents.missing,
+        })
+    }
+
+    /// Returns the number of loved songs written
+    pub fn export_loved_m3u(&mut self, dest: &Path) -> Result<usize> {
+        let loved = self.get_loved()?;
+        self.export_playlist_m3u("Loved", &loved, dest)?;
+
+        Ok(loved.len())
+    }
+
+    /// Marks every song in the playlist as loved. Songs already loved stay so.
+    pub fn import_loved_m3u(&mut self, file: &Path) -> Result<M3uImport> {
+        let contents = self.read_m3u(file)?;
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(SET_SONG_LOVED)?;
+            for id in &contents.song_ids {
+                stmt.execute(params![true, id.to_le_bytes()])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(M3uImport {
+            name: "Loved".to_string(),
+            added: contents.song_ids.len(),
+            missing: contents.missing,
+        })
+    }
+
+    fn read_m3u(&mut self, file: &Path) -> Result<M3uContents> {
+        let bytes = fs::read(file).with_context(|| format!("Could not read {}", file.display()))?;
+        let contents = String::from_utf8_lossy(&bytes);
+        let base = file.parent().unwrap_or(Path::new(""));
+
+        let mut name = None;
+        let mut song_ids = Vec::new();
+        let mut missing = Vec::new();
+
+        let mut stmt = self.conn.prepare_cached(GET_SONG_ID_BY_PATH)?;
+        for line in contents.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+
+            if let Some(title) = line.strip_prefix("#PLAYLIST:") {
+                if !title.trim().is_empty() {
+                    name = Some(title.trim().to_string());
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let path = resolve_entry(line, base);
+            let id = stmt
+                .query_row(params![path.to_str()], |row| {
+                    Ok(convert_from_bytes(row.get("id")?))
+                })
+                .optional()?;
+
+            match id {
+                Some(id) => song_ids.push(id),
+                None => missing.push(line.to_string()),
+            }
+        }
+
+        if song_ids.is_empty() && missing.is_empty() {
+            bail!("{} contains no tracks!", file.display());
+        }
+
+        Ok(M3uContents {
+            name,
+            song_ids,
+            missing,
+        })
+    }
+
+    /// `name`, or `name (2)`, `name (3)`... if it's already taken
+    fn unique_playlist_name(&mut self, name: &str) -> Result<String> {
+        let name = match name.trim() {
+            "" => "Imported Playlist",
+            n => n,
+        };
+
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while self
+            .conn
+            .query_row(PLAYLIST_NAME_TAKEN, params![candidate], |row| row.get(0))?
+        {
+            candidate = format!("{name} ({n})");
+            n += 1;
+        }
+
+        Ok(candidate)
+    }
+}
+
+/// Library paths are stored canonicalized, so entries are too. An entry
+/// that no longer exists can't be canonicalized and simply won't match.
+fn resolve_entry(entry: &str, base: &Path) -> PathBuf {
+    let entry = entry.strip_prefix("file://").unwrap_or(entry);
+    let path = match Path::new(entry).is_absolute() {
+        true => PathBuf::from(entry),
+        false => base.join(entry),
+    };
+
+    path.canonicalize().unwrap_or(path)
+}
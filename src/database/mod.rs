@@ -1,7 +1,8 @@
 use crate::{
     DB_PATH, SongMap,
-    database::schema::CREATE_SCHEMA,
-    library::{LongSong, SimpleSong, SongInfo},
+    control::StatusTrack,
+    database::schema::{BACKFILL_PLAY_LOG, CREATE_SCHEMA, MIGRATIONS},
+    library::{LongSong, SimpleSong, SongInfo, cue},
     ui_state::LibraryStats,
     user_config,
 };
@@ -12,18 +13,36 @@ use std::{
     collections::{HashMap, HashSet, VecDeque},
     fs,
     path::Path,
-    sync::{Arc, atomic::AtomicU64},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU8, AtomicU64},
+    },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+mod duplicates;
+mod genres;
+mod history_export;
+mod loudness;
+mod m3u;
 mod playlists;
 mod queries;
+mod resume;
+mod scan;
 mod schema;
+#[cfg(feature = "scrobble")]
+mod scrobbles;
+mod smart_playlists;
 mod snapshot;
 mod worker;
 
 pub(crate) const DB_BOUND: usize = 128;
 
+pub use duplicates::{DuplicateGroup, DuplicateSong};
+pub use history_export::HistoryFormat;
+pub use m3u::M3uImport;
+pub use playlists::{PlaylistItem, PlaylistMap};
+pub(crate) use scan::{Rekey, ScanEntry};
 pub use worker::DbWorker;
 
 pub struct Database {
@@ -59,6 +78,15 @@ impl Database {
     pub fn _open() -> Result<Self> {
         let conn = Connection::open(&*DB_PATH)?;
         conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::with_conn(conn)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self> {
+        Self::with_conn(Connection::open_in_memory()?)
+    }
+
+    fn with_conn(conn: Connection) -> Result<Self> {
         conn.pragma_update(None, "foreign_keys", "ON")?;
         conn.pragma_update(None, "cache_size", "1000")?;
 
@@ -73,7 +101,24 @@ impl Database {
 
     fn create_tables(&mut self) -> Result<()> {
         let tx = self.conn.transaction()?;
+        let has_play_log: bool = tx.query_row(PLAY_LOG_EXISTS, [], |row| row.get(0))?;
         tx.execute_batch(CREATE_SCHEMA)?;
+
+        if !has_play_log {
+            tx.execute(BACKFILL_PLAY_LOG, [])?;
+        }
+
+        for (table, column, decl) in MIGRATIONS {
+            let exists = tx
+                .prepare(&format!("PRAGMA table_info({table})"))?
+                .query_map([], |row| row.get::<_, String>("name"))?
+                .filter_map(Result::ok)
+                .any(|name| name == *column);
+
+            if !exists {
+                tx.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+            }
+        }
         tx.commit()?;
 
         Ok(())
@@ -90,6 +135,8 @@ impl Database {
         let tx = self.conn.transaction()?;
         {
             let mut stmt = tx.prepare_cached(INSERT_SONG)?;
+            let mut clear_genres = tx.prepare_cached(DELETE_SONG_GENRES)?;
+            let mut insert_genre = tx.prepare_cached(INSERT_SONG_GENRE)?;
 
             for song in song_list {
                 // Get artist ID for the song's artist
@@ -123,8 +170,18 @@ impl Database {
                     &song.channels,
                     &song.bitrate,
                     &song.sample_rate,
-                    &song.filetype
+                    &song.filetype,
+                    song.size as i64,
+                    song.mtime,
+                    song.fingerprint.map(|fp| fp as i64),
+                    song.cue.map(|c| c.start),
+                    song.cue.and_then(|c| c.end),
                 ])?;
+
+                clear_genres.execute([song.id.to_le_bytes()])?;
+                for genre in &song.genres {
+                    insert_genre.execute(params![song.id.to_le_bytes(), genre])?;
+                }
             }
         }
 
@@ -169,6 +226,14 @@ impl Database {
                     disc_no: row.get("disc_no")?,
                     duration,
                     filetype: row.get("format")?,
+                    rating: AtomicU8::new(row.get::<_, Option<u8>>("rating")?.unwrap_or(0)),
+                    loved: AtomicBool::new(row.get("loved")?),
+                    cue: row
+                        .get::<_, Option<f64>>("cue_start")?
+                        .map(|start| cue::CueSpan {
+                            start,
+                            end: row.get("cue_end").ok().flatten(),
+                        }),
                 };
 
                 Ok((hash, Arc::new(song)))
@@ -191,18 +256,90 @@ impl Database {
         Ok(())
     }
 
+    /// A rating of 0 clears it
+    pub(crate) fn set_rating(&mut self, id: u64, rating: u8) -> Result<()> {
+        let rating = (rating > 0).then_some(rating);
+        self.conn
+            .execute(SET_SONG_RATING, params![rating, id.to_le_bytes()])?;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_loved(&mut self, id: u64, loved: bool) -> Result<()> {
+        self.conn
+            .execute(SET_SONG_LOVED, params![loved, id.to_le_bytes()])?;
+
+        Ok(())
+    }
+
+    pub(crate) fn get_loved(&mut self) -> Result<Vec<u64>> {
+        let ids = self
+            .conn
+            .prepare_cached(GET_LOVED_SONGS)?
+            .query_map([], |row| Ok(convert_from_bytes(row.get("id")?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ids)
+    }
+
     pub(crate) fn update_play_count(&mut self, id: u64) -> Result<()> {
         let id = id.to_le_bytes();
-        self.conn.execute(UPDATE_PLAY_COUNT, params![id])?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+
+        let tx = self.conn.transaction()?;
+        tx.execute(UPDATE_PLAY_COUNT, params![id])?;
+        tx.execute(INSERT_PLAY_LOG, params![id, timestamp])?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn update_skip_count(&mut self, id: u64) -> Result<()> {
+        let id = id.to_le_bytes();
+        self.conn.execute(UPDATE_SKIP_COUNT, params![id])?;
 
         Ok(())
     }
 
+    /// The file to open, which for a cue track is the one it was cut from
     pub(crate) fn get_song_path(&mut self, id: u64) -> Result<String> {
-        let output = self
+        let output: String = self
             .conn
             .query_row(GET_PATH, [id.to_le_bytes()], |r| r.get(0))?;
-        Ok(output)
+        Ok(cue::split_track_path(&output).0.to_string())
+    }
+
+    /// For `--status`, read without loading the whole library
+    pub(crate) fn get_status_track(&mut self, id: u64) -> Result<Option<StatusTrack>> {
+        let track = self
+            .conn
+            .query_row(GET_STATUS_TRACK, [id.to_le_bytes()], |r| {
+                Ok(StatusTrack {
+                    title: r.get("title")?,
+                    artist: r.get::<_, Option<String>>("artist")?.unwrap_or_default(),
+                    album: r.get::<_, Option<String>>("album")?.unwrap_or_default(),
+                    duration_secs: r.get::<_, Option<f64>>("duration")?.unwrap_or(0.0) as u64,
+                })
+            })
+            .optional()?;
+        Ok(track)
+    }
+
+    pub(crate) fn get_song_id_by_path(&mut self, path: &Path) -> Result<Option<u64>> {
+        let id = self
+            .conn
+            .query_row(GET_SONG_ID_BY_PATH, params![path.to_str()], |r| {
+                Ok(convert_from_bytes(r.get("id")?))
+            })
+            .optional()?;
+        Ok(id)
+    }
+
+    pub(crate) fn get_sample_rate(&mut self, id: u64) -> Result<Option<u32>> {
+        let rate = self
+            .conn
+            .query_row(GET_SAMPLE_RATE, [id.to_le_bytes()], |r| r.get(0))?;
+        Ok(rate)
     }
 
     pub(crate) fn get_hashes(&mut self) -> Result<HashSet<u64>> {
@@ -438,6 +575,8 @@ impl Database {
             let total_plays: u32 = row.get("total_plays").unwrap_or(0);
             let play_percentage: f32 = row.get("play_percentage")?;
             let total_duration: f32 = row.get("total_duration")?;
+            let loved: u32 = row.get("loved")?;
+            let total_skips: u32 = row.get("total_skips").unwrap_or(0);
 
             Ok(LibraryStats {
                 total_tracks,
@@ -450,6 +589,8 @@ impl Database {
                 total_plays,
                 play_percentage,
                 total_duration,
+                loved,
+                total_skips,
             })
         })?;
 
@@ -457,10 +598,63 @@ impl Database {
     }
 
     pub fn get_most_played(&mut self, count: u16) -> Result<Vec<(u64, u16)>> {
-        let mut stmt = self.conn.prepare(GET_TOP_SONGS)?;
+        self.get_song_counts(GET_TOP_SONGS, params![count])
+    }
+
+    pub fn get_play_counts(&mut self) -> Result<HashMap<u64, u16>> {
+        Ok(self
+            .get_song_counts(GET_PLAY_COUNTS, params![])?
+            .into_iter()
+            .collect())
+    }
+
+    pub fn get_most_played_since(&mut self, since: i64, count: u16) -> Result<Vec<(u64, u16)>> {
+        self.get_song_counts(GET_TOP_SONGS_SINCE, params![since, count])
+    }
+
+    /// Newest first, with repeats of the same track collapsed into one
+    pub fn get_recently_played(&mut self, count: u16) -> Result<Vec<u64>> {
+        let mut stmt = self.conn.prepare_cached(GET_RECENTLY_PLAYED)?;
+        let ids = stmt
+            .query_map(params![count], |row| Ok(convert_from_bytes(row.get("id")?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Total and unique plays from `since` (a UTC timestamp) onwards
+    pub fn get_plays_since(&mut self, since: i64) -> Result<(u32, u32)> {
+        let plays = self
+            .conn
+            .query_row(GET_PLAYS_SINCE, params![since], |row| {
+                Ok((row.get("total_plays")?, row.get("unique_plays")?))
+            })?;
+
+        Ok(plays)
+    }
+
+    /// Resolves SQLite date modifiers such as `start of month` against the
+    /// local clock, returning the matching UTC timestamp
+    pub fn get_local_period_start(&mut self, modifiers: &str) -> Result<i64> {
+        let query = format!(
+            "SELECT CAST(strftime('%s', 'now', 'localtime', {modifiers}, 'utc') AS INTEGER)"
+        );
+        Ok(self.conn.query_row(&query, [], |row| row.get(0))?)
+    }
+
+    pub fn get_most_skipped(&mut self, count: u16) -> Result<Vec<(u64, u16)>> {
+        self.get_song_counts(GET_MOST_SKIPPED, params![count])
+    }
+
+    fn get_song_counts(
+        &mut self,
+        query: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<(u64, u16)>> {
+        let mut stmt = self.conn.prepare(query)?;
 
         let rows = stmt
-            .query_map(params![count], |row| {
+            .query_map(params, |row| {
                 let hash_bytes: Vec<u8> = row.get("id")?;
                 let hash = convert_from_bytes(hash_bytes);
 
@@ -488,6 +682,19 @@ impl Database {
         Ok(())
     }
 
+    /// What albums were grouped by in the last finished scan
+    pub fn get_album_grouping(&self) -> Result<Option<String>> {
+        self.conn
+            .query_row(GET_ALBUM_GROUPING, [], |row| row.get(0))
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn set_album_grouping(&self, grouping: &str) -> Result<()> {
+        self.conn.execute(SET_ALBUM_GROUPING, [grouping])?;
+        Ok(())
+    }
+
     pub fn get_last_played(&self) -> Result<(u64, f32)> {
         Ok(self.conn.query_row(GET_NOW_PLAYING, [], |row| {
             let raw_id = row.get(0)?;
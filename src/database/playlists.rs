@@ -1,9 +1,14 @@
-use crate::{Database, database::queries::*};
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+    library::PlaylistFolder,
+};
 use anyhow::Result;
 use indexmap::IndexMap;
 use rusqlite::params;
 
-type PlaylistMap = IndexMap<(i64, String), Vec<(i64, u64)>>;
+/// Keyed by id, name and folder
+pub type PlaylistMap = IndexMap<(i64, String, Option<i64>), Vec<(i64, u64)>>;
 
 impl Database {
     pub fn create_playlist(&mut self, name: &str) -> Result<()> {
@@ -13,7 +18,60 @@ impl Database {
     }
 
     pub fn delete_playlist(&mut self, id: i64) -> Result<()> {
-        self.conn.execute(DELETE_PLAYLIST, params![id])?;
+        let tx = self.conn.transaction()?;
+        tx.execute(DELETE_PLAYLIST, params![id])?;
+        while tx.execute(DELETE_EMPTY_FOLDERS, [])? > 0 {}
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn get_playlist_folders(&mut self) -> Result<Vec<PlaylistFolder>> {
+        let folders = self
+            .conn
+            .prepare_cached(GET_PLAYLIST_FOLDERS)?
+            .query_map([], |row| {
+                Ok(PlaylistFolder {
+                    id: row.get("id")?,
+                    name: row.get("name")?,
+                    parent: row.get("parent_id")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(folders)
+    }
+
+    /// A new folder starts out empty, so `move_to` is put straight into it
+    /// rather than it being swept away as soon as anything else moves
+    pub fn create_playlist_folder(
+        &mut self,
+        name: &str,
+        parent: Option<i64>,
+        move_to: PlaylistItem,
+    ) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(CREATE_PLAYLIST_FOLDER, params![name, parent])?;
+        let id = tx.last_insert_rowid();
+        move_item(&tx, move_to, Some(id))?;
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    pub fn rename_playlist_folder(&mut self, id: i64, name: &str) -> Result<()> {
+        self.conn
+            .execute(RENAME_PLAYLIST_FOLDER, params![name, id])?;
+
+        Ok(())
+    }
+
+    /// Puts a playlist or folder in `folder`, `None` being the top level.
+    /// Folders left with nothing in them are removed.
+    pub fn move_playlist_item(&mut self, item: PlaylistItem, folder: Option<i64>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        move_item(&tx, item, folder)?;
+        tx.commit()?;
 
         Ok(())
     }
@@ -69,19 +127,75 @@ impl Database {
         Ok(())
     }
 
-    pub fn remove_from_playlist(&mut self, ps_id: &[i64]) -> Result<()> {
+    /// Returns the position and song of each entry removed, so they can be
+    /// put back
+    pub fn remove_from_playlist(&mut self, ps_id: &[i64]) -> Result<Vec<(i64, u64)>> {
         let tx = self.conn.transaction()?;
+        let mut removed = Vec::with_capacity(ps_id.len());
         {
+            let mut entry = tx.prepare_cached(GET_PLAYLIST_ENTRY)?;
             let mut stmt = tx.prepare_cached(REMOVE_SONG_FROM_PLAYLIST)?;
             for id in ps_id {
+                removed.push(entry.query_row(params![id], |row| {
+                    Ok((row.get(0)?, convert_from_bytes(row.get(1)?)))
+                })?);
                 stmt.execute(params![id])?;
             }
         }
 
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Puts songs back at the positions they were removed from, moving along
+    /// anything that's been added in one since
+    pub fn restore_playlist_songs(&mut self, playlist_id: i64, songs: &[(i64, u64)]) -> Result<()> {
+        let mut songs = songs.to_vec();
+        songs.sort_unstable();
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut taken = tx.prepare_cached(PLAYLIST_POS_TAKEN)?;
+            let mut make_room = tx.prepare_cached(MAKE_ROOM_AT_POS)?;
+            let mut settle = tx.prepare_cached(SETTLE_PLAYLIST_POS)?;
+            let mut insert = tx.prepare_cached(ADD_SONG_TO_PLAYLIST_WITH_POSITION)?;
+            for (position, song_id) in songs {
+                if taken.query_row(params![playlist_id, position], |row| row.get(0))? {
+                    make_room.execute(params![playlist_id, position])?;
+                    settle.execute(params![playlist_id])?;
+                }
+                insert.execute(params![song_id.to_le_bytes(), playlist_id, position])?;
+            }
+            tx.execute(UPDATE_PLAYLIST, params![playlist_id])?;
+        }
+
         tx.commit()?;
         Ok(())
     }
 
+    /// Recreates a deleted playlist with its songs in order, returning its
+    /// new id
+    pub fn restore_playlist(
+        &mut self,
+        name: &str,
+        rules: Option<&str>,
+        folder: Option<i64>,
+        songs: &[u64],
+    ) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(RESTORE_PLAYLIST, params![name, rules, folder])?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare_cached(ADD_SONG_TO_PLAYLIST_WITH_POSITION)?;
+            for (i, song) in songs.iter().enumerate() {
+                stmt.execute(params![song.to_le_bytes(), id, i as i64 + 1])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(id)
+    }
+
     pub fn swap_position(&mut self, ps_id1: i64, ps_id2: i64, playlist_id: i64) -> Result<()> {
         let tx = self.conn.transaction()?;
         {
@@ -110,6 +224,7 @@ impl Database {
             let ps_id: Option<i64> = r.get("id")?;
             let name: String = r.get("name")?;
             let playlist_id: i64 = r.get("playlist_id")?;
+            let folder: Option<i64> = r.get("folder_id")?;
 
             let song_id: Option<u64> = match r.get::<_, Option<Vec<u8>>>("song_id")? {
                 Some(hash_bytes) => {
@@ -125,12 +240,14 @@ impl Database {
                 None => None,
             };
 
-            Ok((playlist_id, song_id, ps_id, name))
+            Ok((playlist_id, song_id, ps_id, name, folder))
         })?;
 
         let playlist_map: PlaylistMap = rows.try_fold(PlaylistMap::new(), |mut map, row| {
-            let (playlist_id, song_id_opt, ps_id_opt, name) = row?;
-            let entry = map.entry((playlist_id, name)).or_insert_with(Vec::new);
+            let (playlist_id, song_id_opt, ps_id_opt, name, folder) = row?;
+            let entry = map
+                .entry((playlist_id, name, folder))
+                .or_insert_with(Vec::new);
             if let (Some(song_id), Some(ps_id)) = (song_id_opt, ps_id_opt) {
                 entry.push((ps_id, song_id));
             }
@@ -140,3 +257,20 @@ impl Database {
         Ok(playlist_map)
     }
 }
+
+/// Either thing that can be filed in a playlist folder
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlaylistItem {
+    Playlist(i64),
+    Folder(i64),
+}
+
+fn move_item(tx: &rusqlite::Transaction, item: PlaylistItem, folder: Option<i64>) -> Result<()> {
+    match item {
+        PlaylistItem::Playlist(id) => tx.execute(SET_PLAYLIST_FOLDER, params![folder, id])?,
+        PlaylistItem::Folder(id) => tx.execute(SET_FOLDER_PARENT, params![folder, id])?,
+    };
+    while tx.execute(DELETE_EMPTY_FOLDERS, [])? > 0 {}
+
+    Ok(())
+}
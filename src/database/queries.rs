@@ -20,17 +20,23 @@ pub const GET_ALL_SONGS: &str = "
         s.artist_id,
         s.album_id,
         s.format,
+        s.rating,
+        s.loved,
+        s.cue_start,
+        s.cue_end,
         a.title as album
     from songs s
     INNER JOIN albums a ON a.id = s.album_id
     ORDER BY 
         album ASC, 
-        disc_no ASC, 
+        COALESCE(disc_no, 1) ASC, 
         track_no ASC
 ";
 
+// A modified file hashes to a new id, so its rating and loved flag are
+// carried over by path
 pub const INSERT_SONG: &str = "
-    INSERT OR REPLACE INTO songs (
+    INSERT INTO songs (
         id,
         title, 
         year,
@@ -43,9 +49,77 @@ pub const INSERT_SONG: &str = "
         channels,
         bit_rate,
         sample_rate, 
-        format
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
-)";
+        format,
+        size,
+        mtime,
+        fingerprint,
+        cue_start,
+        cue_end
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+    ON CONFLICT(id) DO UPDATE SET
+        title = excluded.title,
+        year = excluded.year,
+        path = excluded.path,
+        artist_id = excluded.artist_id,
+        album_id = excluded.album_id,
+        track_no = excluded.track_no,
+        disc_no = excluded.disc_no,
+        duration = excluded.duration,
+        channels = excluded.channels,
+        bit_rate = excluded.bit_rate,
+        sample_rate = excluded.sample_rate,
+        format = excluded.format,
+        size = excluded.size,
+        mtime = excluded.mtime,
+        fingerprint = excluded.fingerprint,
+        cue_start = excluded.cue_start,
+        cue_end = excluded.cue_end
+";
+
+pub const DELETE_SONG_GENRES: &str = "
+    DELETE FROM song_genres WHERE song_id = ?
+";
+
+pub const INSERT_SONG_GENRE: &str = "
+    INSERT OR IGNORE INTO song_genres (song_id, name)
+    VALUES (?1, ?2)
+";
+
+pub const GET_SONG_GENRES: &str = "
+    SELECT name FROM song_genres WHERE song_id = ?
+    ORDER BY name COLLATE NOCASE
+";
+
+// Spellings that only differ in case count as one genre, shown as whichever
+// sorts first. Songs without any are counted under 'Unknown', listed last.
+pub const GET_GENRES: &str = "
+    SELECT name, count FROM (
+        SELECT MIN(name) AS name, COUNT(*) AS count FROM song_genres
+        GROUP BY name COLLATE NOCASE
+        UNION ALL
+        SELECT 'Unknown', COUNT(*) FROM songs
+        WHERE id NOT IN (SELECT song_id FROM song_genres)
+    )
+    WHERE count > 0
+    ORDER BY name = 'Unknown', name COLLATE NOCASE
+";
+
+pub const GET_GENRE_SONGS: &str = "
+    SELECT s.id FROM songs s
+    INNER JOIN albums a ON a.id = s.album_id
+    WHERE s.id IN (SELECT song_id FROM song_genres WHERE name = ?1 COLLATE NOCASE)
+        OR (?1 = 'Unknown' AND s.id NOT IN (SELECT song_id FROM song_genres))
+    ORDER BY a.title ASC, COALESCE(s.disc_no, 1) ASC, s.track_no ASC
+";
+
+pub const GENRES_READ: &str = "
+    SELECT EXISTS(SELECT 1 FROM scan_cache WHERE key = 'genres_read')
+";
+
+pub const SET_GENRES_READ: &str = "
+    INSERT OR REPLACE INTO scan_cache
+    (key, value) VALUES ('genres_read', 1)
+";
 
 pub const INSERT_ARTIST: &str = "
     INSERT OR IGNORE INTO artists (
@@ -65,6 +139,11 @@ pub const GET_PATH: &str = "
     WHERE id = ?
 ";
 
+pub const GET_SAMPLE_RATE: &str = "
+    SELECT sample_rate FROM songs
+    WHERE id = ?
+";
+
 pub const GET_ARTIST_MAP: &str = "
     SELECT id, name FROM artists
 ";
@@ -100,6 +179,99 @@ pub const DELETE_SONGS: &str = "
     DELETE FROM songs WHERE id = ?
 ";
 
+pub const GET_DUPLICATE_CANDIDATES: &str = "
+    SELECT
+        s.id,
+        s.title,
+        s.path,
+        s.duration,
+        s.format,
+        s.size,
+        s.bit_rate,
+        s.fingerprint,
+        a.name AS artist,
+        s.id IN (SELECT song_id FROM duplicate_ignores) AS ignored
+    FROM songs s
+    LEFT JOIN artists a ON a.id = s.artist_id
+    -- Cue tracks share one audio file, so neither its size nor deleting it
+    -- says anything about a single track
+    WHERE s.cue_start IS NULL
+";
+
+pub const IGNORE_DUPLICATE: &str = "
+    INSERT OR IGNORE INTO duplicate_ignores (song_id) VALUES (?)
+";
+
+pub const GET_SCAN_INDEX: &str = "
+    SELECT id, path, fingerprint FROM songs
+";
+
+pub const SET_FINGERPRINT: &str = "
+    UPDATE songs SET size = ?1, mtime = ?2, fingerprint = ?3 WHERE id = ?4
+";
+
+/// Every column holding a song id, found through the foreign keys
+pub const GET_SONG_REFERENCES: &str = "
+    SELECT m.name, f.\"from\"
+    FROM sqlite_master m, pragma_foreign_key_list(m.name) f
+    WHERE m.type = 'table' AND f.\"table\" = 'songs'
+";
+
+pub const REKEY_SONG: &str = "
+    UPDATE songs SET id = ?1 WHERE id = ?2
+";
+
+pub const DELETE_WAVEFORM: &str = "
+    DELETE FROM waveforms WHERE song_id = ?
+";
+
+pub const DELETE_LOUDNESS: &str = "
+    DELETE FROM loudness WHERE song_id = ?
+";
+
+pub const GET_UNMEASURED: &str = "
+    SELECT s.id, s.path, s.cue_start, s.cue_end
+    FROM songs s
+    LEFT JOIN loudness l ON l.song_id = s.id
+    WHERE l.song_id IS NULL
+    LIMIT ?
+";
+
+pub const SET_LOUDNESS: &str = "
+    INSERT OR REPLACE INTO loudness (song_id, tagged, peak, histogram)
+    VALUES (?1, ?2, ?3, ?4)
+";
+
+pub const GET_LOUDNESS: &str = "
+    SELECT peak, histogram FROM loudness
+    WHERE song_id = ? AND histogram IS NOT NULL
+";
+
+pub const GET_ALBUM_LOUDNESS: &str = "
+    SELECT l.peak, l.histogram
+    FROM loudness l
+    INNER JOIN songs s ON s.id = l.song_id
+    WHERE l.histogram IS NOT NULL
+        AND s.album_id = (SELECT album_id FROM songs WHERE id = ?)
+";
+
+pub const SET_SONG_RATING: &str = "
+    UPDATE songs SET rating = ?1 WHERE id = ?2
+";
+
+pub const SET_SONG_LOVED: &str = "
+    UPDATE songs SET loved = ?1 WHERE id = ?2
+";
+
+pub const GET_LOVED_SONGS: &str = "
+    SELECT s.id
+    FROM songs s
+    LEFT JOIN artists ar ON ar.id = s.artist_id
+    LEFT JOIN albums al ON al.id = s.album_id
+    WHERE s.loved = 1
+    ORDER BY ar.name COLLATE NOCASE, al.title COLLATE NOCASE, COALESCE(s.disc_no, 1), s.track_no
+";
+
 pub const LOAD_HISTORY: &str = "
     SELECT song_id FROM history
     ORDER BY timestamp DESC
@@ -128,6 +300,23 @@ pub const UPDATE_PLAY_COUNT: &str = "
         WHERE song_id = ?1
 ";
 
+pub const INSERT_PLAY_LOG: &str = "
+    INSERT INTO play_log (song_id, timestamp) VALUES (?, ?)
+";
+
+pub const PLAY_LOG_EXISTS: &str = "
+    SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'play_log')
+";
+
+pub const UPDATE_SKIP_COUNT: &str = "
+    INSERT INTO skips 
+        (song_id, count)
+    VALUES (?1, 1)
+    ON CONFLICT(song_id) DO UPDATE SET
+        count = count + 1 
+        WHERE song_id = ?1
+";
+
 pub const GET_SESSION_PREFIX: &str = "
     SELECT key, value 
         FROM session_state 
@@ -138,6 +327,20 @@ pub const SET_SESSION_STATE: &str = "
         VALUES (?, ?)
 ";
 
+pub const CLEAR_SAVED_QUEUE: &str = "
+    DELETE FROM saved_queue
+";
+
+pub const INSERT_SAVED_QUEUE: &str = "
+    INSERT INTO saved_queue (position, song_id)
+        VALUES (?, ?)
+";
+
+pub const GET_SAVED_QUEUE: &str = "
+    SELECT song_id FROM saved_queue
+        ORDER BY position
+";
+
 pub const CREATE_NEW_PLAYLIST: &str = "
     INSERT OR IGNORE INTO playlists (name, updated_at) 
         VALUES (?, strftime('%s', 'now'))
@@ -191,7 +394,8 @@ pub const PLAYLIST_BUILDER: &str = "
         ps.id,
         ps.song_id, 
         p.id as playlist_id, 
-        p.name 
+        p.name,
+        p.folder_id
     FROM playlists p
     LEFT JOIN playlist_songs ps 
         ON p.id = ps.playlist_id
@@ -207,6 +411,32 @@ pub const GET_PLAYLIST_POS: &str = "
     SELECT position FROM playlist_songs WHERE id = ?
 ";
 
+pub const GET_PLAYLIST_ENTRY: &str = "
+    SELECT position, song_id FROM playlist_songs WHERE id = ?
+";
+
+pub const PLAYLIST_POS_TAKEN: &str = "
+    SELECT EXISTS(SELECT 1 FROM playlist_songs WHERE playlist_id = ? AND position = ?)
+";
+
+// Negated and then flipped back, as shifting in place would trip over the
+// unique positions partway through
+pub const MAKE_ROOM_AT_POS: &str = "
+    UPDATE playlist_songs SET position = -(position + 1)
+    WHERE playlist_id = ?1 AND position >= ?2
+";
+
+pub const SETTLE_PLAYLIST_POS: &str = "
+    UPDATE playlist_songs SET position = -position
+    WHERE playlist_id = ? AND position < 0
+";
+
+// A folder that's been removed since leaves the playlist at the top level
+pub const RESTORE_PLAYLIST: &str = "
+    INSERT INTO playlists (name, rules, folder_id, updated_at)
+        VALUES (?1, ?2, (SELECT id FROM playlist_folders WHERE id = ?3), strftime('%s', 'now'))
+";
+
 pub const UPDATE_PLAYLIST_POS: &str = "
     UPDATE playlist_songs SET position = ? WHERE id = ?
 ";
@@ -215,6 +445,34 @@ pub const RENAME_PLAYLIST: &str = "
     UPDATE playlists SET name = ? WHERE id = ?
 ";
 
+pub const GET_PLAYLIST_FOLDERS: &str = "
+    SELECT id, name, parent_id FROM playlist_folders
+    ORDER BY name COLLATE NOCASE
+";
+
+pub const CREATE_PLAYLIST_FOLDER: &str = "
+    INSERT INTO playlist_folders (name, parent_id) VALUES (?1, ?2)
+";
+
+pub const RENAME_PLAYLIST_FOLDER: &str = "
+    UPDATE playlist_folders SET name = ?1 WHERE id = ?2
+";
+
+pub const SET_PLAYLIST_FOLDER: &str = "
+    UPDATE playlists SET folder_id = ?1 WHERE id = ?2
+";
+
+pub const SET_FOLDER_PARENT: &str = "
+    UPDATE playlist_folders SET parent_id = ?1 WHERE id = ?2
+";
+
+// Run until nothing changes, as emptying a folder can empty its parent
+pub const DELETE_EMPTY_FOLDERS: &str = "
+    DELETE FROM playlist_folders
+    WHERE id NOT IN (SELECT folder_id FROM playlists WHERE folder_id IS NOT NULL)
+        AND id NOT IN (SELECT parent_id FROM playlist_folders WHERE parent_id IS NOT NULL)
+";
+
 pub const GET_STATS: &str = "
     SELECT
         (SELECT COUNT(*) FROM songs)                  AS total_tracks,
@@ -226,6 +484,8 @@ pub const GET_STATS: &str = "
         (SELECT SUM(duration) FROM songs)             AS total_duration,
         (SELECT COUNT(*) FROM plays)                  AS unique_plays,
         (SELECT SUM(count) FROM plays)                AS total_plays,
+        (SELECT COUNT(*) FROM songs WHERE loved = 1)  AS loved,
+        (SELECT SUM(count) FROM skips)                AS total_skips,
         ROUND(
         (SELECT COUNT(*) FROM plays) * 100.0
         / (SELECT COUNT(*) FROM songs),
@@ -240,6 +500,58 @@ pub const GET_TOP_SONGS: &str = "
     LIMIT ?
 ";
 
+pub const GET_PLAY_COUNTS: &str = "
+    SELECT song_id as id, count
+    FROM plays
+";
+
+pub const GET_TOP_SONGS_SINCE: &str = "
+    SELECT s.id as id, COUNT(*) as count
+    FROM songs s
+    INNER JOIN play_log l ON s.id = l.song_id
+    WHERE l.timestamp >= ?1
+    GROUP BY s.id
+    ORDER BY count DESC
+    LIMIT ?2
+";
+
+pub const GET_RECENTLY_PLAYED: &str = "
+    SELECT song_id AS id FROM (
+        SELECT song_id, timestamp, id,
+            LAG(song_id) OVER (ORDER BY timestamp, id) AS prev
+        FROM play_log
+        WHERE timestamp IS NOT NULL
+    )
+    WHERE prev IS NULL OR prev != song_id
+    ORDER BY timestamp DESC, id DESC
+    LIMIT ?
+";
+
+pub const GET_PLAYS_SINCE: &str = "
+    SELECT
+        COUNT(*)                AS total_plays,
+        COUNT(DISTINCT song_id) AS unique_plays
+    FROM play_log
+    WHERE timestamp >= ?
+";
+
+pub const GET_MOST_SKIPPED: &str = "
+    SELECT s.id as id, k.count as count
+    FROM songs s
+    INNER JOIN skips k ON s.id = k.song_id
+    ORDER BY k.count DESC
+    LIMIT ?
+";
+
+pub const GET_ALBUM_GROUPING: &str = "
+    SELECT value FROM scan_cache WHERE key = 'album_grouping'
+";
+
+pub const SET_ALBUM_GROUPING: &str = "
+    INSERT OR REPLACE INTO scan_cache
+    (key, value) VALUES ('album_grouping', ?)
+";
+
 pub const GET_LAST_SCAN: &str = "
     SELECT value FROM scan_cache WHERE key = 'last_scan'
 ";
@@ -275,3 +587,108 @@ pub const UPDATE_DURATION: &str = "
     SET duration = ?1
     WHERE id = ?2
 ";
+
+pub const CREATE_SMART_PLAYLIST: &str = "
+    INSERT OR IGNORE INTO playlists (name, rules, updated_at)
+        VALUES (?, ?, strftime('%s', 'now'))
+";
+
+pub const SET_PLAYLIST_RULES: &str = "
+    UPDATE playlists
+        SET rules = ?, updated_at = strftime('%s', 'now')
+        WHERE id = ?
+";
+
+pub const GET_SMART_RULES: &str = "
+    SELECT id, rules FROM playlists
+    WHERE rules IS NOT NULL
+";
+
+pub const SMART_PLAYLIST_BASE: &str = "
+    SELECT s.id
+    FROM songs s
+    LEFT JOIN artists ar ON ar.id = s.artist_id
+    LEFT JOIN albums al ON al.id = s.album_id
+    LEFT JOIN plays p ON p.song_id = s.id
+";
+
+/// Completed by a condition on `g.name`, inside an `EXISTS`
+pub const SMART_PLAYLIST_GENRE: &str = "
+    SELECT 1 FROM song_genres g WHERE g.song_id = s.id AND
+";
+
+pub const SMART_PLAYLIST_ORDER: &str = "
+    ORDER BY ar.name COLLATE NOCASE, al.title COLLATE NOCASE, COALESCE(s.disc_no, 1), s.track_no
+";
+
+pub const GET_M3U_ENTRY: &str = "
+    SELECT s.path, s.title, s.duration, ar.name AS artist
+    FROM songs s
+    LEFT JOIN artists ar ON ar.id = s.artist_id
+    WHERE s.id = ?
+";
+
+pub const GET_STATUS_TRACK: &str = "
+    SELECT s.title, s.duration, ar.name AS artist, al.title AS album
+    FROM songs s
+    LEFT JOIN artists ar ON ar.id = s.artist_id
+    LEFT JOIN albums al ON al.id = s.album_id
+    WHERE s.id = ?
+";
+
+pub const GET_SONG_ID_BY_PATH: &str = "
+    SELECT id FROM songs WHERE path = ?
+";
+
+pub const PLAYLIST_NAME_TAKEN: &str = "
+    SELECT EXISTS(SELECT 1 FROM playlists WHERE LOWER(name) = LOWER(?))
+";
+
+pub const EXPORT_HISTORY: &str = "
+    SELECT
+        strftime('%Y-%m-%dT%H:%M:%SZ', h.timestamp, 'unixepoch') AS played_at,
+        s.title,
+        ar.name AS artist,
+        al.title AS album,
+        COALESCE(p.count, 0) AS plays
+    FROM history h
+    JOIN songs s ON s.id = h.song_id
+    LEFT JOIN artists ar ON ar.id = s.artist_id
+    LEFT JOIN albums al ON al.id = s.album_id
+    LEFT JOIN plays p ON p.song_id = h.song_id
+    ORDER BY h.timestamp, h.id
+";
+
+#[cfg(feature = "scrobble")]
+pub const QUEUE_SCROBBLE: &str = "
+    INSERT INTO scrobble_queue (service, title, artist, album, duration_secs, listened_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+";
+
+#[cfg(feature = "scrobble")]
+pub const GET_QUEUED_SCROBBLES: &str = "
+    SELECT id, title, artist, album, duration_secs, listened_at
+    FROM scrobble_queue
+    WHERE service = ?1
+    ORDER BY listened_at, id
+    LIMIT ?2
+";
+
+#[cfg(feature = "scrobble")]
+pub const REMOVE_QUEUED_SCROBBLE: &str = "
+    DELETE FROM scrobble_queue WHERE id = ?
+";
+
+pub const SET_RESUME_POINT: &str = "
+    INSERT INTO resume_points (song_id, position)
+    VALUES (?1, ?2)
+    ON CONFLICT(song_id) DO UPDATE SET position = ?2
+";
+
+pub const GET_RESUME_POINT: &str = "
+    SELECT position FROM resume_points WHERE song_id = ?
+";
+
+pub const CLEAR_RESUME_POINT: &str = "
+    DELETE FROM resume_points WHERE song_id = ?
+";
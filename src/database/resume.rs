@@ -0,0 +1,27 @@
+use crate::{
+    Database,
+    database::queries::{CLEAR_RESUME_POINT, GET_RESUME_POINT, SET_RESUME_POINT},
+};
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+
+impl Database {
+    pub(crate) fn set_resume_point(&mut self, id: u64, position: f64) -> Result<()> {
+        self.conn
+            .execute(SET_RESUME_POINT, params![id.to_le_bytes(), position])?;
+        Ok(())
+    }
+
+    pub(crate) fn clear_resume_point(&mut self, id: u64) -> Result<()> {
+        self.conn.execute(CLEAR_RESUME_POINT, [id.to_le_bytes()])?;
+        Ok(())
+    }
+
+    /// Where the track was left, in seconds from its start
+    pub(crate) fn get_resume_point(&mut self, id: u64) -> Result<Option<f64>> {
+        Ok(self
+            .conn
+            .query_row(GET_RESUME_POINT, [id.to_le_bytes()], |r| r.get(0))
+            .optional()?)
+    }
+}
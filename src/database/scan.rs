@@ -0,0 +1,147 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+};
+use anyhow::Result;
+use rusqlite::params;
+use std::path::PathBuf;
+
+/// A song as the scanner last saw it
+pub(crate) struct ScanEntry {
+    pub id: u64,
+    pub path: PathBuf,
+    pub fingerprint: Option<u64>,
+}
+
+/// A known song whose signature changed, either because the file was
+/// edited in place or because it was moved to a new path
+pub(crate) struct Rekey {
+    pub old: u64,
+    pub new: u64,
+    pub moved: bool,
+}
+
+impl Database {
+    pub(crate) fn get_scan_index(&mut self) -> Result<Vec<ScanEntry>> {
+        let entries = self
+            .conn
+            .prepare(GET_SCAN_INDEX)?
+            .query_map([], |row| {
+                Ok(ScanEntry {
+                    id: convert_from_bytes(row.get("id")?),
+                    path: PathBuf::from(row.get::<_, String>("path")?),
+                    fingerprint: row
+                        .get::<_, Option<i64>>("fingerprint")?
+                        .map(|fp| fp as u64),
+                })
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Stores size, mtime and fingerprint for songs scanned before they
+    /// were recorded
+    pub(crate) fn set_fingerprints(&mut self, stamps: &[(u64, u64, i64, u64)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(SET_FINGERPRINT)?;
+            for (id, size, mtime, fingerprint) in stamps {
+                stmt.execute(params![
+                    *size as i64,
+                    mtime,
+                    *fingerprint as i64,
+                    id.to_le_bytes()
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Moves songs to their new ids so plays, history, ratings and playlist
+    /// entries carry over instead of being dropped with the old row.
+    ///
+    /// The metadata itself is refreshed by the insert that follows.
+    pub(crate) fn rekey_songs(&mut self, rekeys: &[Rekey]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        // Parent and children are updated one after another, so the
+        // constraint can only hold once all of them are done
+        tx.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+        {
+            let mut rekey_song = tx.prepare_cached(REKEY_SONG)?;
+            let mut delete_waveform = tx.prepare_cached(DELETE_WAVEFORM)?;
+            let mut delete_loudness = tx.prepare_cached(DELETE_LOUDNESS)?;
+            // Read from the schema, so a table added later follows too
+            let references = tx
+                .prepare(GET_SONG_REFERENCES)?
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let mut rekey_refs = references
+                .iter()
+                .map(|(t, c)| tx.prepare(&format!("UPDATE {t} SET {c} = ?1 WHERE {c} = ?2")))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            for rekey in rekeys {
+                let (old, new) = (rekey.old.to_le_bytes(), rekey.new.to_le_bytes());
+
+                // An edited file may not sound the same anymore
+                if !rekey.moved {
+                    delete_waveform.execute([old])?;
+                    delete_loudness.execute([old])?;
+                }
+
+                rekey_song.execute(params![new, old])?;
+                for stmt in &mut rekey_refs {
+                    stmt.execute(params![new, old])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_song(db: &mut Database, id: u64) {
+        db.conn
+            .execute(
+                "INSERT INTO songs (id, title, path) VALUES (?1, 'song', '/music/song.flac')",
+                [id.to_le_bytes()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn rekey_carries_resume_point_and_duplicate_ignore() {
+        let mut db = Database::open_in_memory().unwrap();
+        insert_song(&mut db, 1);
+        db.set_resume_point(1, 42.5).unwrap();
+        db.ignore_duplicates(&[1]).unwrap();
+
+        let rekey = Rekey {
+            old: 1,
+            new: 2,
+            moved: true,
+        };
+        db.rekey_songs(&[rekey]).unwrap();
+
+        assert_eq!(db.get_resume_point(1).unwrap(), None);
+        assert_eq!(db.get_resume_point(2).unwrap(), Some(42.5));
+        let ignored: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM duplicate_ignores WHERE song_id = ?",
+                [2u64.to_le_bytes()],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(ignored, 1);
+    }
+}
@@ -1,3 +1,28 @@
+/// Columns added after the initial schema: (table, column, declaration).
+/// Older databases gain them on open.
+pub const MIGRATIONS: &[(&str, &str, &str)] = &[
+    ("playlists", "rules", "TEXT"),
+    ("playlists", "folder_id", "INTEGER"),
+    ("songs", "rating", "INTEGER"),
+    ("songs", "loved", "INTEGER NOT NULL DEFAULT 0"),
+    ("songs", "size", "INTEGER"),
+    ("songs", "mtime", "INTEGER"),
+    ("songs", "fingerprint", "INTEGER"),
+    ("songs", "cue_start", "REAL"),
+    ("songs", "cue_end", "REAL"),
+];
+
+/// Plays counted before the play log existed are carried over with no
+/// timestamp, so they still count towards all-time totals
+pub const BACKFILL_PLAY_LOG: &str = "
+    WITH RECURSIVE n(song_id, remaining) AS (
+        SELECT song_id, count FROM plays WHERE count > 0
+        UNION ALL
+        SELECT song_id, remaining - 1 FROM n WHERE remaining > 1
+    )
+    INSERT INTO play_log (song_id, timestamp) SELECT song_id, NULL FROM n
+";
+
 pub const CREATE_SCHEMA: &str = r"
     CREATE TABLE IF NOT EXISTS roots(
         id INTEGER PRIMARY KEY,
@@ -18,6 +43,13 @@ pub const CREATE_SCHEMA: &str = r"
         bit_rate INTEGER,
         sample_rate INTEGER,
         format INTEGER,
+        rating INTEGER,
+        loved INTEGER NOT NULL DEFAULT 0,
+        size INTEGER,
+        mtime INTEGER,
+        fingerprint INTEGER,
+        cue_start REAL,
+        cue_end REAL,
         FOREIGN KEY(artist_id) REFERENCES artists(id),
         FOREIGN KEY(album_id) REFERENCES albums(id)
     );
@@ -41,6 +73,23 @@ pub const CREATE_SCHEMA: &str = r"
         FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
     );
 
+    CREATE TABLE IF NOT EXISTS loudness(
+        song_id BLOB PRIMARY KEY,
+        tagged INTEGER NOT NULL DEFAULT 0,
+        peak REAL,
+        histogram BLOB,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS song_genres(
+        song_id BLOB NOT NULL,
+        name TEXT NOT NULL,
+        PRIMARY KEY(song_id, name),
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_song_genres_name ON song_genres(name COLLATE NOCASE);
+
     CREATE TABLE IF NOT EXISTS history(
         id INTEGER PRIMARY KEY,
         song_id BLOB NOT NULL,
@@ -54,6 +103,32 @@ pub const CREATE_SCHEMA: &str = r"
         FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
     );
 
+    CREATE TABLE IF NOT EXISTS play_log(
+        id INTEGER PRIMARY KEY,
+        song_id BLOB NOT NULL,
+        timestamp INTEGER,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_play_log_timestamp ON play_log(timestamp);
+
+    CREATE TABLE IF NOT EXISTS skips(
+        song_id BLOB PRIMARY KEY,
+        count INTEGER,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS resume_points(
+        song_id BLOB PRIMARY KEY,
+        position REAL NOT NULL,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS duplicate_ignores(
+        song_id BLOB PRIMARY KEY,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
     CREATE TABLE IF NOT EXISTS session_state(
         key TEXT PRIMARY KEY,
         value TEXT NOT NULL
@@ -62,7 +137,15 @@ pub const CREATE_SCHEMA: &str = r"
     CREATE TABLE IF NOT EXISTS playlists(
         id INTEGER PRIMARY KEY,
         name TEXT UNIQUE NOT NULL,
-        updated_at INTEGER NOT NULL
+        updated_at INTEGER NOT NULL,
+        rules TEXT,
+        folder_id INTEGER
+    );
+
+    CREATE TABLE IF NOT EXISTS playlist_folders(
+        id INTEGER PRIMARY KEY,
+        name TEXT UNIQUE NOT NULL,
+        parent_id INTEGER
     );
 
     CREATE TABLE IF NOT EXISTS playlist_songs(
@@ -88,6 +171,22 @@ pub const CREATE_SCHEMA: &str = r"
         FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
     );
 
+    CREATE TABLE IF NOT EXISTS scrobble_queue(
+        id INTEGER PRIMARY KEY,
+        service TEXT NOT NULL,
+        title TEXT NOT NULL,
+        artist TEXT NOT NULL,
+        album TEXT NOT NULL,
+        duration_secs INTEGER NOT NULL,
+        listened_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS saved_queue(
+        position INTEGER PRIMARY KEY,
+        song_id BLOB NOT NULL,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
     CREATE VIEW IF NOT EXISTS now_playing_v1 AS
     SELECT
       s.title           AS title,
@@ -0,0 +1,63 @@
+use crate::{
+    Database,
+    database::queries::{GET_QUEUED_SCROBBLES, QUEUE_SCROBBLE, REMOVE_QUEUED_SCROBBLE},
+    scrobble::Listen,
+};
+use anyhow::Result;
+use rusqlite::params;
+
+impl Database {
+    pub(crate) fn queue_scrobble(&mut self, service: &str, listen: &Listen) -> Result<()> {
+        self.conn.execute(
+            QUEUE_SCROBBLE,
+            params![
+                service,
+                listen.title,
+                listen.artist,
+                listen.album,
+                listen.duration_secs,
+                listen.listened_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The oldest listens still waiting for `service`, with their queue ids
+    pub(crate) fn get_queued_scrobbles(
+        &mut self,
+        service: &str,
+        limit: usize,
+    ) -> Result<Vec<(i64, Listen)>> {
+        let queued = self
+            .conn
+            .prepare(GET_QUEUED_SCROBBLES)?
+            .query_map(params![service, limit as i64], |row| {
+                Ok((
+                    row.get("id")?,
+                    Listen {
+                        title: row.get("title")?,
+                        artist: row.get("artist")?,
+                        album: row.get("album")?,
+                        duration_secs: row.get("duration_secs")?,
+                        listened_at: row.get("listened_at")?,
+                    },
+                ))
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(queued)
+    }
+
+    pub(crate) fn remove_queued_scrobbles(&mut self, ids: &[i64]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(REMOVE_QUEUED_SCROBBLE)?;
+            for id in ids {
+                stmt.execute([id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
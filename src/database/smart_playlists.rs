@@ -0,0 +1,146 @@
+use crate::{
+    Database,
+    database::{convert_from_bytes, queries::*},
+    library::{RuleField, RuleOp, RuleValue, SmartRules},
+};
+use anyhow::Result;
+use rusqlite::{params, params_from_iter, types::Value};
+use std::collections::HashMap;
+
+impl Database {
+    pub fn create_smart_playlist(&mut self, name: &str, rules: &str) -> Result<()> {
+        self.conn
+            .execute(CREATE_SMART_PLAYLIST, params![name, rules])?;
+
+        Ok(())
+    }
+
+    pub fn set_playlist_rules(&mut self, playlist_id: i64, rules: &str) -> Result<()> {
+        self.conn
+            .execute(SET_PLAYLIST_RULES, params![rules, playlist_id])?;
+
+        Ok(())
+    }
+
+    pub fn get_smart_rules(&mut self) -> Result<HashMap<i64, String>> {
+        let rules = self
+            .conn
+            .prepare_cached(GET_SMART_RULES)?
+            .query_map([], |row| Ok((row.get("id")?, row.get("rules")?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(rules)
+    }
+
+    /// Compiles the rules into a single WHERE clause and returns the matching
+    /// song ids in album order. Contradictory rules simply match nothing.
+    pub fn query_smart_playlist(&mut self, rules: &SmartRules) -> Result<Vec<u64>> {
+        let mut clauses = Vec::with_capacity(rules.conditions().len());
+        let mut values = Vec::with_capacity(rules.conditions().len());
+
+        for condition in rules.conditions() {
+            let column = match condition.field {
+                RuleField::Title => "s.title",
+                RuleField::Artist => "ar.name",
+                RuleField::Album => "al.title",
+                RuleField::Genre => "g.name",
+                RuleField::Year => "s.year",
+                RuleField::Plays => "COALESCE(p.count, 0)",
+                RuleField::Duration => "s.duration",
+                RuleField::Format => "s.format",
+                RuleField::Rating => "COALESCE(s.rating, 0)",
+            };
+
+            let clause = match (condition.field, condition.op) {
+                // A song can have several genres, so it matches on any of
+                // them, and `is not` means none of them is it
+                (RuleField::Genre, RuleOp::Is) => {
+                    format!("EXISTS ({SMART_PLAYLIST_GENRE} LOWER({column}) = LOWER(?))")
+                }
+                (RuleField::Genre, RuleOp::IsNot) => {
+                    format!("NOT EXISTS ({SMART_PLAYLIST_GENRE} LOWER({column}) = LOWER(?))")
+                }
+                (RuleField::Genre, _) => {
+                    format!("EXISTS ({SMART_PLAYLIST_GENRE} INSTR(LOWER({column}), LOWER(?)) > 0)")
+                }
+                _ => Self::compare(column, &condition.value, condition.op),
+            };
+
+            clauses.push(clause);
+            values.push(match &condition.value {
+                RuleValue::Text(t) => Value::Text(t.clone()),
+                RuleValue::Number(n) => Value::Real(*n),
+                RuleValue::Format(f) => Value::Integer(*f as i64),
+            });
+        }
+
+        let sql = format!(
+            "{SMART_PLAYLIST_BASE} WHERE {} {SMART_PLAYLIST_ORDER}",
+            clauses.join(" AND ")
+        );
+
+        let ids = self
+            .conn
+            .prepare(&sql)?
+            .query_map(params_from_iter(values), |row| {
+                Ok(convert_from_bytes(row.get("id")?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ids)
+    }
+
+    fn compare(column: &str, value: &RuleValue, op: RuleOp) -> String {
+        match (value, op) {
+            (RuleValue::Text(_), RuleOp::Is) => format!("LOWER({column}) = LOWER(?)"),
+            (RuleValue::Text(_), RuleOp::IsNot) => {
+                format!("LOWER(COALESCE({column}, '')) != LOWER(?)")
+            }
+            (RuleValue::Text(_), _) => format!("INSTR(LOWER({column}), LOWER(?)) > 0"),
+            (_, op) => {
+                let op = match op {
+                    RuleOp::Is | RuleOp::Contains => "=",
+                    RuleOp::IsNot => "!=",
+                    RuleOp::Greater => ">",
+                    RuleOp::GreaterEq => ">=",
+                    RuleOp::Less => "<",
+                    RuleOp::LessEq => "<=",
+                };
+                format!("{column} {op} ?")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_any_of_a_songs_genres() {
+        let mut db = Database::open_in_memory().unwrap();
+        for (id, genres) in [(1u64, &["Jazz", "Fusion"][..]), (2, &["Rock"]), (3, &[])] {
+            db.conn
+                .execute(
+                    "INSERT INTO songs (id, title, path) VALUES (?1, 'song', ?2)",
+                    params![id.to_le_bytes(), format!("/music/{id}.flac")],
+                )
+                .unwrap();
+            for genre in genres {
+                db.conn
+                    .execute(INSERT_SONG_GENRE, params![id.to_le_bytes(), genre])
+                    .unwrap();
+            }
+        }
+
+        let mut query = |rules| {
+            let ids = db.query_smart_playlist(&SmartRules::parse(rules).unwrap());
+            let mut ids = ids.unwrap();
+            ids.sort();
+            ids
+        };
+        assert_eq!(query("genre is jazz"), [1]);
+        assert_eq!(query("genre has us"), [1]);
+        assert_eq!(query("genre is not fusion"), [2, 3]);
+    }
+}
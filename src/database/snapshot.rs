@@ -3,7 +3,13 @@ use rusqlite::params;
 
 use crate::{
     Database,
-    database::queries::{GET_SESSION_PREFIX, SET_SESSION_STATE},
+    database::{
+        convert_from_bytes,
+        queries::{
+            CLEAR_SAVED_QUEUE, GET_SAVED_QUEUE, GET_SESSION_PREFIX, INSERT_SAVED_QUEUE,
+            SET_SESSION_STATE,
+        },
+    },
 };
 
 impl Database {
@@ -27,4 +33,27 @@ impl Database {
             .filter_map(Result::ok)
             .collect())
     }
+
+    /// Replaces the stored queue with `song_ids`, in order
+    pub fn save_queue(&mut self, song_ids: &[u64]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(CLEAR_SAVED_QUEUE, [])?;
+        {
+            let mut stmt = tx.prepare(INSERT_SAVED_QUEUE)?;
+            for (position, id) in song_ids.iter().enumerate() {
+                stmt.execute(params![position as i64, id.to_le_bytes()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn load_queue(&mut self) -> Result<Vec<u64>> {
+        let mut stmt = self.conn.prepare(GET_SAVED_QUEUE)?;
+
+        Ok(stmt
+            .query_map([], |row| Ok(convert_from_bytes(row.get(0)?)))?
+            .filter_map(Result::ok)
+            .collect())
+    }
 }
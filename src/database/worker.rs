@@ -1,16 +1,19 @@
 use crate::{
-    SongMap,
-    database::{DB_BOUND, Database},
-    library::SimpleSong,
+    Library, SongMap,
+    database::{
+        DB_BOUND, Database, DuplicateGroup, HistoryFormat, M3uImport, PlaylistItem, PlaylistMap,
+    },
+    library::{PlaylistFolder, SimpleSong, SmartRules},
     ui_state::LibraryStats,
 };
 use anyhow::{Result, anyhow};
-use indexmap::IndexMap;
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
     sync::Arc,
     thread,
 };
+use voxio::ReplayGainMode;
 
 pub enum DbMessage {
     Operation(Box<dyn FnOnce(&mut Database) + Send>),
@@ -112,22 +115,123 @@ impl DbWorker {
         self.execute_sync(move |db| db.rename_playlist(&new_name, id))
     }
 
-    pub fn remove_from_playlist(&self, ps_ids: Vec<i64>) -> Result<()> {
+    pub fn remove_from_playlist(&self, ps_ids: Vec<i64>) -> Result<Vec<(i64, u64)>> {
         self.execute_sync(move |db| db.remove_from_playlist(&ps_ids))
     }
 
+    pub fn restore_playlist_songs(&self, playlist_id: i64, songs: Vec<(i64, u64)>) -> Result<()> {
+        self.execute_sync(move |db| db.restore_playlist_songs(playlist_id, &songs))
+    }
+
+    pub fn restore_playlist(
+        &self,
+        name: String,
+        rules: Option<String>,
+        folder: Option<i64>,
+        songs: Vec<u64>,
+    ) -> Result<i64> {
+        self.execute_sync(move |db| db.restore_playlist(&name, rules.as_deref(), folder, &songs))
+    }
+
     pub fn swap_position(&self, ps_id1: i64, ps_id2: i64, playlist_id: i64) -> Result<()> {
         self.execute_sync(move |db| db.swap_position(ps_id1, ps_id2, playlist_id))
     }
 
+    pub fn create_smart_playlist(&self, name: String, rules: String) -> Result<()> {
+        self.execute_sync(move |db| db.create_smart_playlist(&name, &rules))
+    }
+
+    pub fn set_playlist_rules(&self, id: i64, rules: String) -> Result<()> {
+        self.execute_sync(move |db| db.set_playlist_rules(id, &rules))
+    }
+
+    pub fn get_smart_rules(&self) -> Result<HashMap<i64, String>> {
+        self.execute_sync(move |db| db.get_smart_rules())
+    }
+
+    /// Songs that no longer exist in the library are dropped
+    pub fn run_smart_playlist(
+        &self,
+        rules: SmartRules,
+        library: &Library,
+    ) -> Result<Vec<Arc<SimpleSong>>> {
+        let ids = self.execute_sync(move |db| db.query_smart_playlist(&rules))?;
+        let songs = library.get_songs_map();
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| songs.get(id).map(Arc::clone))
+            .collect())
+    }
+
+    pub fn set_rating(&self, song_id: u64, rating: u8) {
+        self.execute(move |db| {
+            let _ = db.set_rating(song_id, rating);
+        });
+    }
+
+    pub fn set_loved(&self, song_id: u64, loved: bool) {
+        self.execute(move |db| {
+            let _ = db.set_loved(song_id, loved);
+        });
+    }
+
+    pub fn export_playlist_m3u(
+        &self,
+        name: String,
+        song_ids: Vec<u64>,
+        dest: PathBuf,
+    ) -> Result<()> {
+        self.execute_sync(move |db| db.export_playlist_m3u(&name, &song_ids, &dest))
+    }
+
+    pub fn import_playlist_m3u(&self, file: PathBuf) -> Result<M3uImport> {
+        self.execute_sync(move |db| db.import_playlist_m3u(&file))
+    }
+
+    pub fn export_history(&self, dest: PathBuf, format: HistoryFormat) -> Result<usize> {
+        self.execute_sync(move |db| db.export_history(&dest, format))
+    }
+
+    pub fn find_duplicates(&self) -> Result<Vec<DuplicateGroup>> {
+        self.execute_sync(move |db| db.find_duplicates())
+    }
+
+    pub fn ignore_duplicates(&self, ids: Vec<u64>) {
+        self.execute(move |db| {
+            let _ = db.ignore_duplicates(&ids);
+        });
+    }
+
     pub fn get_hashes(&self) -> Result<HashSet<u64>> {
         self.execute_sync(move |db| db.get_hashes())
     }
 
-    pub fn build_playlists(&mut self) -> Result<IndexMap<(i64, String), Vec<(i64, u64)>>> {
+    pub fn build_playlists(&mut self) -> Result<PlaylistMap> {
         self.execute_sync(move |db| db.build_playlists())
     }
 
+    pub fn get_playlist_folders(&self) -> Result<Vec<PlaylistFolder>> {
+        self.execute_sync(|db| db.get_playlist_folders())
+    }
+
+    pub fn create_playlist_folder(
+        &self,
+        name: String,
+        parent: Option<i64>,
+        move_to: PlaylistItem,
+    ) -> Result<i64> {
+        self.execute_sync(move |db| db.create_playlist_folder(&name, parent, move_to))
+    }
+
+    pub fn rename_playlist_folder(&self, id: i64, name: String) -> Result<()> {
+        self.execute_sync(move |db| db.rename_playlist_folder(id, &name))
+    }
+
+    pub fn move_playlist_item(&self, item: PlaylistItem, folder: Option<i64>) -> Result<()> {
+        self.execute_sync(move |db| db.move_playlist_item(item, folder))
+    }
+
     pub fn save_snapshot(&self, snapshot: Vec<(&'static str, String)>) -> Result<()> {
         self.execute_sync(move |db| db.save_snapshot(snapshot))
     }
@@ -136,6 +240,14 @@ impl DbWorker {
         self.execute_sync(move |db| db.load_snapshot(prefix))
     }
 
+    pub fn save_queue(&self, song_ids: Vec<u64>) -> Result<()> {
+        self.execute_sync(move |db| db.save_queue(&song_ids))
+    }
+
+    pub fn load_queue(&self) -> Result<Vec<u64>> {
+        self.execute_sync(move |db| db.load_queue())
+    }
+
     pub fn get_all_songs(&self) -> Result<SongMap> {
         self.execute_sync(move |db| db.get_all_songs())
     }
@@ -148,6 +260,42 @@ impl DbWorker {
         self.execute_sync(move |db| db.get_song_path(id))
     }
 
+    pub fn get_song_id_by_path(&self, path: PathBuf) -> Result<Option<u64>> {
+        self.execute_sync(move |db| db.get_song_id_by_path(&path))
+    }
+
+    pub fn get_sample_rate(&self, id: u64) -> Result<Option<u32>> {
+        self.execute_sync(move |db| db.get_sample_rate(id))
+    }
+
+    /// Linear gain from the song's measured loudness, for songs without
+    /// ReplayGain tags. Album mode falls back to the track on its own.
+    pub fn get_measured_gain(&self, id: u64, mode: ReplayGainMode) -> Result<Option<f32>> {
+        self.execute_sync(move |db| {
+            let loudness = match mode {
+                ReplayGainMode::Off => return Ok(None),
+                ReplayGainMode::Track => db.get_loudness(id)?,
+                ReplayGainMode::Album => match db.get_loudness(id)? {
+                    Some(track) => Some(db.get_album_loudness(id)?.unwrap_or(track)),
+                    None => None,
+                },
+            };
+            Ok(loudness.and_then(|l| l.linear_gain()))
+        })
+    }
+
+    pub fn get_genres(&self) -> Result<Vec<(Arc<String>, usize)>> {
+        self.execute_sync(|db| db.get_genres())
+    }
+
+    pub fn get_genre_songs(&self, genre: Arc<String>) -> Result<Vec<u64>> {
+        self.execute_sync(move |db| db.get_genre_songs(&genre))
+    }
+
+    pub fn get_song_genres(&self, id: u64) -> Result<Vec<String>> {
+        self.execute_sync(move |db| db.get_song_genres(id))
+    }
+
     pub fn insert_song_to_history(&self, song_id: u64) {
         self.execute(move |db| {
             let _ = db.insert_to_history(song_id);
@@ -166,6 +314,28 @@ impl DbWorker {
         });
     }
 
+    pub fn update_skip_count(&self, song_id: u64) {
+        self.execute(move |db| {
+            let _ = db.update_skip_count(song_id);
+        });
+    }
+
+    pub fn set_resume_point(&self, song_id: u64, position: f64) {
+        self.execute(move |db| {
+            let _ = db.set_resume_point(song_id, position);
+        });
+    }
+
+    pub fn clear_resume_point(&self, song_id: u64) {
+        self.execute(move |db| {
+            let _ = db.clear_resume_point(song_id);
+        });
+    }
+
+    pub fn get_resume_point(&self, song_id: u64) -> Result<Option<f64>> {
+        self.execute_sync(move |db| db.get_resume_point(song_id))
+    }
+
     pub fn set_waveform(&self, song_id: u64, waveform: Vec<f32>) {
         self.execute(move |db| {
             let _ = db.set_waveform(song_id, &waveform);
@@ -180,6 +350,30 @@ impl DbWorker {
         self.execute_sync(move |db| db.get_most_played(count))
     }
 
+    pub fn get_play_counts(&self) -> Result<HashMap<u64, u16>> {
+        self.execute_sync(move |db| db.get_play_counts())
+    }
+
+    pub fn get_most_played_since(&self, since: i64, count: u16) -> Result<Vec<(u64, u16)>> {
+        self.execute_sync(move |db| db.get_most_played_since(since, count))
+    }
+
+    pub fn get_recently_played(&self, count: u16) -> Result<Vec<u64>> {
+        self.execute_sync(move |db| db.get_recently_played(count))
+    }
+
+    pub fn get_plays_since(&self, since: i64) -> Result<(u32, u32)> {
+        self.execute_sync(move |db| db.get_plays_since(since))
+    }
+
+    pub fn get_local_period_start(&self, modifiers: &'static str) -> Result<i64> {
+        self.execute_sync(move |db| db.get_local_period_start(modifiers))
+    }
+
+    pub fn get_most_skipped(&self, count: u16) -> Result<Vec<(u64, u16)>> {
+        self.execute_sync(move |db| db.get_most_skipped(count))
+    }
+
     pub fn get_last_played(&self) -> Result<(u64, f32)> {
         self.execute_sync(move |db| db.get_last_played())
     }
@@ -0,0 +1,121 @@
+use anyhow::{Result, anyhow, bail};
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+const OP_CLOSE: u32 = 2;
+
+/// Largest reply worth reading; anything bigger means a broken stream
+const MAX_FRAME: usize = 64 * 1024;
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// A connection to the local Discord client's IPC socket. Each message is
+/// a little-endian opcode and length followed by a JSON payload.
+pub(super) struct DiscordIpc {
+    stream: Box<dyn Stream>,
+}
+
+impl DiscordIpc {
+    pub(super) fn connect(client_id: &str) -> Result<Self> {
+        let stream = (0..10)
+            .flat_map(socket_paths)
+            .find_map(|path| open(&path).ok())
+            .ok_or_else(|| anyhow!("Discord is not running"))?;
+
+        let mut ipc = DiscordIpc { stream };
+        ipc.send(
+            OP_HANDSHAKE,
+            &format!(r#"{{"v":1,"client_id":"{client_id}"}}"#),
+        )?;
+
+        Ok(ipc)
+    }
+
+    /// `None` clears the presence
+    pub(super) fn set_activity(&mut self, activity: Option<&str>) -> Result<()> {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let payload = format!(
+            r#"{{"cmd":"SET_ACTIVITY","args":{{"pid":{},"activity":{}}},"nonce":"{nonce}"}}"#,
+            std::process::id(),
+            activity.unwrap_or("null"),
+        );
+        self.send(OP_FRAME, &payload)
+    }
+
+    /// Every message gets a reply, which is read off so the socket never
+    /// fills. A close frame means Discord rejected the connection.
+    fn send(&mut self, op: u32, payload: &str) -> Result<()> {
+        let mut frame = Vec::with_capacity(8 + payload.len());
+        frame.extend_from_slice(&op.to_le_bytes());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(payload.as_bytes());
+        self.stream.write_all(&frame)?;
+
+        let mut header = [0u8; 8];
+        self.stream.read_exact(&mut header)?;
+        let reply_op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+        if len > MAX_FRAME {
+            bail!("Malformed reply from Discord");
+        }
+        let mut body = vec![0u8; len];
+        self.stream.read_exact(&mut body)?;
+
+        match reply_op {
+            OP_CLOSE => bail!("Discord closed the connection"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn socket_paths(idx: u8) -> Vec<PathBuf> {
+    let base = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(std::env::var_os)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+    let name = format!("discord-ipc-{idx}");
+    // Flatpak and Snap installs put the socket in their own subfolders
+    vec![
+        base.join(&name),
+        base.join("app/com.discordapp.Discord").join(&name),
+        base.join("snap.discord").join(&name),
+    ]
+}
+
+#[cfg(windows)]
+fn socket_paths(idx: u8) -> Vec<PathBuf> {
+    vec![PathBuf::from(format!(r"\\?\pipe\discord-ipc-{idx}"))]
+}
+
+#[cfg(unix)]
+fn open(path: &PathBuf) -> std::io::Result<Box<dyn Stream>> {
+    use std::{os::unix::net::UnixStream, time::Duration};
+
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(windows)]
+fn open(path: &PathBuf) -> std::io::Result<Box<dyn Stream>> {
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    Ok(Box::new(pipe))
+}
@@ -0,0 +1,201 @@
+mod ipc;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use ipc::DiscordIpc;
+use std::{
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Discord throttles presence to 5 updates per 20 seconds
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(4);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Clock drift tolerated before a playing track's timestamps are resent
+const DRIFT: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
+pub struct Presence {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: Duration,
+    /// When the track started, as wall-clock time. `None` while paused.
+    started_at: Option<Duration>,
+}
+
+impl Presence {
+    pub fn new(
+        title: &str,
+        artist: &str,
+        album: &str,
+        elapsed: Duration,
+        duration: Duration,
+        paused: bool,
+    ) -> Self {
+        let started_at = (!paused).then(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .saturating_sub(elapsed)
+        });
+
+        Presence {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            duration,
+            started_at,
+        }
+    }
+}
+
+/// Sent every tick, so ordinary playback must compare equal to what's shown
+impl PartialEq for Presence {
+    fn eq(&self, other: &Self) -> bool {
+        let same_clock = match (self.started_at, other.started_at) {
+            (Some(a), Some(b)) => a.abs_diff(b) < DRIFT,
+            (a, b) => a == b,
+        };
+
+        same_clock
+            && self.title == other.title
+            && self.artist == other.artist
+            && self.album == other.album
+            && self.duration == other.duration
+    }
+}
+
+enum PresenceMsg {
+    Set(Option<Presence>),
+    Shutdown,
+}
+
+/// Rich presence runs on its own thread so a slow or missing Discord client
+/// never stalls the UI. Only the latest state is kept; anything sent between
+/// updates is coalesced into one.
+pub struct DiscordPresence {
+    sender: Sender<PresenceMsg>,
+}
+
+impl DiscordPresence {
+    pub fn new(client_id: String) -> Self {
+        let (sender, receiver) = unbounded();
+        thread::spawn(move || presence_loop(client_id, receiver));
+
+        DiscordPresence { sender }
+    }
+
+    pub fn set(&self, presence: Presence) {
+        let _ = self.sender.send(PresenceMsg::Set(Some(presence)));
+    }
+
+    pub fn clear(&self) {
+        let _ = self.sender.send(PresenceMsg::Set(None));
+    }
+}
+
+/// Not joined: Discord drops the presence by itself once the socket closes
+impl Drop for DiscordPresence {
+    fn drop(&mut self) {
+        let _ = self.sender.send(PresenceMsg::Shutdown);
+    }
+}
+
+fn presence_loop(client_id: String, receiver: Receiver<PresenceMsg>) {
+    let mut ipc: Option<DiscordIpc> = None;
+    let mut last_attempt: Option<Instant> = None;
+    let mut last_update = Instant::now() - MIN_UPDATE_INTERVAL;
+
+    let mut wanted: Option<Presence> = None;
+    let mut shown: Option<Presence> = None;
+    let mut dirty = false;
+
+    loop {
+        let timeout = match dirty {
+            true => MIN_UPDATE_INTERVAL.saturating_sub(last_update.elapsed()),
+            false => RECONNECT_INTERVAL,
+        };
+
+        match receiver.recv_timeout(timeout) {
+            Ok(PresenceMsg::Set(p)) => {
+                dirty = p != shown;
+                wanted = p;
+            }
+            Ok(PresenceMsg::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => (),
+        }
+
+        if !dirty || last_update.elapsed() < MIN_UPDATE_INTERVAL {
+            continue;
+        }
+
+        // Silently no-op while Discord isn't running
+        if ipc.is_none() && last_attempt.is_none_or(|t| t.elapsed() >= RECONNECT_INTERVAL) {
+            last_attempt = Some(Instant::now());
+            ipc = DiscordIpc::connect(&client_id).ok();
+        }
+
+        last_update = Instant::now();
+        let Some(conn) = ipc.as_mut() else {
+            continue;
+        };
+
+        match conn.set_activity(wanted.as_ref().map(activity_json).as_deref()) {
+            Ok(()) => {
+                shown = wanted.clone();
+                dirty = false;
+            }
+            // Discord closed or restarted; reconnect on the next update
+            Err(_) => {
+                ipc = None;
+                last_attempt = None;
+            }
+        }
+    }
+
+    if let Some(mut conn) = ipc {
+        let _ = conn.set_activity(None);
+    }
+}
+
+fn activity_json(p: &Presence) -> String {
+    let mut activity = format!(
+        r#"{{"type":2,"details":"{}","state":"{}","assets":{{"large_text":"{}"}}"#,
+        escape(&fit(&p.title)),
+        escape(&fit(&p.artist)),
+        escape(&fit(&p.album)),
+    );
+
+    // Paused tracks show no clock; playing ones count down to the end
+    if let Some(started_at) = p.started_at {
+        let start = started_at.as_millis();
+        let end = start + p.duration.as_millis();
+        activity.push_str(&format!(r#","timestamps":{{"start":{start},"end":{end}}}"#));
+    }
+
+    activity.push('}');
+    activity
+}
+
+/// Discord rejects fields shorter than 2 or longer than 128 characters
+fn fit(s: &str) -> String {
+    let mut s = s.chars().take(128).collect::<String>();
+    while s.chars().count() < 2 {
+        s.push(' ');
+    }
+    s
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
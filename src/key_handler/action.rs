@@ -1,7 +1,10 @@
 use crate::{
     config::timing,
+    database::HistoryFormat,
     key_handler::*,
-    ui_state::{Mode, Pane, PlaylistAction, PopupType, Root, RowKind, SettingsMode, UiState},
+    ui_state::{
+        DuplicateMode, Mode, Pane, PlaylistAction, PopupType, Root, RowKind, SettingsMode, UiState,
+    },
 };
 use anyhow::Result;
 use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent};
@@ -11,7 +14,14 @@ use KeyCode::*;
 #[rustfmt::skip]
 pub fn handle_key_event(key_event: KeyEvent, state: &mut UiState) -> Option<Action> {
 
-    if !matches!(state.get_input_context(), InputContext::Search | InputContext::Popup(_)) {
+    if matches!(state.get_input_context(), InputContext::TrackList(_) | InputContext::Sidebar)
+        && let Some(action) = type_ahead(&key_event, state)
+    {
+        return action;
+    }
+
+    // Counts mean nothing in fullscreen, where the digits seek instead
+    if !matches!(state.get_input_context(), InputContext::Search | InputContext::Popup(_) | InputContext::Fullscreen) {
         if let KeyCode::Char(c) = key_event.code && key_event.modifiers == KeyModifiers::NONE {
             if state.key_buffer.push_digit(c) {
                 return None;
@@ -36,10 +46,33 @@ pub fn handle_key_event(key_event: KeyEvent, state: &mut UiState) -> Option<Acti
     }
 }
 
+/// `'` starts a type-ahead; characters typed soon enough after it build up
+/// a prefix to jump to. `Some(None)` consumes the key. Anything else ends
+/// the type-ahead and is handled as usual.
+fn type_ahead(key: &KeyEvent, state: &mut UiState) -> Option<Option<Action>> {
+    if state.key_buffer.is_jumping() {
+        if let (X | S, Char(c)) = (key.modifiers, key.code)
+            && let Some(prefix) = state.key_buffer.push_jump(c)
+        {
+            return Some(Some(Action::JumpTo(prefix)));
+        }
+        state.key_buffer.end_jump();
+    }
+
+    match (key.modifiers, key.code) {
+        (X, Char('\'')) => {
+            state.key_buffer.start_jump();
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
 fn global_commands(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Option<Action> {
     let in_search = state.get_pane() == Pane::Search;
     let fullscreen = matches!(state.get_mode(), Mode::Fullscreen);
     let popup_active = state.popup.is_open();
+    let raw_count = buf_count;
 
     if buf_count == 0 {
         buf_count = 1
@@ -55,6 +88,7 @@ fn global_commands(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Opt
         // Works on everything except search or popup
         _ if (!in_search && !popup_active && !fullscreen) => match (key.modifiers, key.code) {
             // PLAYBACK COMMANDS
+            (X, Esc) if !state.multi_select_empty() => Some(Action::ClearMultiSelect),
             (X, Esc) => Some(Action::SoftReset),
             (X, Backspace) => Some(Action::ClearKeyBuffer),
 
@@ -64,17 +98,39 @@ fn global_commands(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Opt
             (C, Char('t')) => Some(Action::GoTo(Root::Playlist)),
             (C, Char('q')) => Some(Action::ChangeMode(Mode::Queue)),
             (C, Char('z')) => Some(Action::ChangeMode(Mode::Power)),
-            (C, Char('r')) => Some(Action::ToggleRepeat),
+            (C, Char('f')) => Some(Action::ChangeMode(Mode::Loved)),
+            (C, Char('e')) => Some(Action::ChangeMode(Mode::Recent)),
+            (C, Char('o')) => Some(Action::LoveNowPlaying),
+            (C, Char('r')) => Some(Action::CycleRepeat),
+            (C, Char('x')) => Some(Action::CycleShuffleMode),
+            (A, Char('r')) => Some(Action::ToggleRadio),
+            (C, Char('g')) => Some(Action::CycleReplayGain),
+            (C, Char('d')) => Some(Action::ToggleDiscordPresence),
+            (C, Char('y')) => Some(Action::ToggleNotifications),
+            (C, Char('b')) => Some(Action::TogglePowerSave),
+            (A, Char('m')) => Some(Action::ToggleMouse),
+            (_, Char('Y')) => Some(Action::ToggleLyrics),
+            (A, Char('i')) => Some(Action::ToggleArtistPane),
+            (A, Char('j')) | (A, Down) => Some(Action::ScrollLyrics(buf_count as isize)),
+            (A, Char('k')) | (A, Up) => Some(Action::ScrollLyrics(-(buf_count as isize))),
+            (_, Char('T')) => Some(Action::SleepTimer(raw_count)),
+            (_, Char('A')) => Some(Action::MarkAbLoop),
+            (_, Char('R')) => Some(Action::RateNowPlaying(raw_count)),
 
             (_, Char('`') | Char('~')) => Some(Action::ViewSettings),
             (X, Char(' ')) => Some(Action::TogglePlayback),
             (C, Char('s')) => Some(Action::Stop),
+            (A, Char('u')) => Some(Action::Undo),
+            (A, Char('y')) => Some(Action::PlayAlbum),
 
             (X, Char('n')) => Some(Action::Seek(*SEEK_SMALL)),
             (_, Char('N')) => Some(Action::Seek(*SEEK_LARGE)),
 
             (X, Char('p')) => Some(Action::Seek(-*SEEK_SMALL)),
             (_, Char('P')) => Some(Action::Seek(-*SEEK_LARGE)),
+            (_, Char('%')) => Some(Action::SeekPercent(raw_count)),
+            (C, Char('k')) => Some(Action::SeekInput),
+            (_, Char(':')) => Some(Action::CommandLine),
 
             // NAVIGATION
             (X, Char('/')) => Some(Action::ChangeMode(Mode::Search)),
@@ -82,8 +138,11 @@ fn global_commands(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Opt
             (_, Char('\\')) => Some(Action::ShowStats),
             (_, Char('?')) => Some(Action::ShowKeymaps),
 
+            (A, Char('+')) => Some(Action::VolumeShift(VOLUME_STEP_FINE)),
+            (A, Char('-')) => Some(Action::VolumeShift(-VOLUME_STEP_FINE)),
             (_, Char('+')) => Some(Action::VolumeShift(VOLUME_STEP)),
             (_, Char('-')) => Some(Action::VolumeShift(-VOLUME_STEP)),
+            (_, Char('_')) => Some(Action::ToggleMute),
 
             (X, Char('m')) => Some(Action::SwapLayout),
 
@@ -108,10 +167,26 @@ fn global_commands(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Opt
             (X, Char('f')) | (_, Char('F')) => Some(Action::ChangeMode(Mode::Fullscreen)),
 
             (X, Char('w')) => Some(Action::NextProgressDisplay),
+            (C, Char('w')) => Some(Action::NextGradientSpeed),
             (_, Char('W')) => Some(Action::SetProgressDisplay(ProgressDisplay::Waveform)),
             (_, Char('O')) => Some(Action::SetProgressDisplay(ProgressDisplay::Oscilloscope)),
             (_, Char('S')) => Some(Action::SetProgressDisplay(ProgressDisplay::Spectrum)),
             (_, Char('B')) => Some(Action::SetProgressDisplay(ProgressDisplay::ProgressBar)),
+            (X, Char('z')) => Some(Action::NextFftSize),
+            (_, Char('Z')) => Some(Action::NextWindowFn),
+            (_, Char('#')) => Some(Action::ToggleAmplitudeScale),
+            (X, Char('o')) => Some(Action::ToggleStereoScope),
+            (A, Char('o')) => Some(Action::ToggleScopeTrigger),
+            (_, Char('H')) => Some(Action::ToggleSpectrumPeaks),
+            (_, Char('M')) => Some(Action::ToggleSpectrumMirror),
+            (_, Char('^')) => Some(Action::ToggleSpectrumColoring),
+            (_, Char('"')) => Some(Action::ToggleSpectrumAutoGain),
+            (_, Char('(')) => Some(Action::ShiftVizGain(Incrementor::Down)),
+            (_, Char(')')) => Some(Action::ShiftVizGain(Incrementor::Up)),
+            (_, Char('|')) => Some(Action::ToggleAutoGain),
+            (_, Char('!')) => Some(Action::ShiftSpectrumDecay(Incrementor::Down)),
+            (_, Char('@')) => Some(Action::ShiftSpectrumDecay(Incrementor::Up)),
+            (_, Char('&')) => Some(Action::VizSettings),
             (C, Char('u')) | (X, F(5)) => Some(Action::UpdateLibrary),
 
             _ => None,
@@ -125,10 +200,18 @@ fn handle_tracklist(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Op
         (X, Enter) => Some(Action::Play(buf_count)),
 
         (X, Char('a')) => Some(Action::AddToPlaylist),
+        (A, Char('a')) => Some(Action::CreatePlaylistWithSongs),
         (C, Char('a')) => Some(Action::GoToAlbum),
         (X, Char('q')) => Some(Action::QueueSong),
+        (X, Char('t')) => Some(Action::QueueNext(None)),
+        (X, Char('r')) => Some(Action::RateSong(buf_count)),
+        (_, Char('*')) => Some(Action::LoveSong),
+        (X, Char('e')) => Some(Action::TagEdit),
         (X, Char('v')) => Some(Action::MultiSelect(buf_count)),
         (C, Char('v')) => Some(Action::ClearMultiSelect),
+        (_, Char('V')) => Some(Action::MultiSelectAll),
+        (S, Up) => Some(Action::MultiSelectRange(Incrementor::Up)),
+        (S, Down) => Some(Action::MultiSelectRange(Incrementor::Down)),
         (X, Char('g')) => {
             if buf_count == 0 {
                 buf_count = 1
@@ -152,7 +235,6 @@ fn handle_tracklist(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Op
                 sel_type: SelectionType::Legal,
                 shuffle: false,
             }),
-            (_, Char('V')) => Some(Action::MultiSelectAll),
             (X, Char('x')) => Some(Action::RemoveSong),
             _ => None,
         },
@@ -160,26 +242,56 @@ fn handle_tracklist(key: &KeyEvent, state: &UiState, mut buf_count: usize) -> Op
         Mode::Queue => match (key.modifiers, key.code) {
             (X, Char('x')) => Some(Action::RemoveSong),
             (X, Char('s')) => Some(Action::ShuffleElements),
-            (_, Char('V')) => Some(Action::MultiSelectAll),
 
             (_, Char('K')) => Some(Action::ShiftPosition(Incrementor::Up)),
             (_, Char('J')) => Some(Action::ShiftPosition(Incrementor::Down)),
+            (_, Home) => Some(Action::ShiftToEdge(Incrementor::Up)),
+            (_, End) => Some(Action::ShiftToEdge(Incrementor::Down)),
             _ => None,
         },
 
-        Mode::Power | Mode::Search => match (key.modifiers, key.code) {
+        Mode::Search => match (key.modifiers, key.code) {
+            (A, Enter) => Some(Action::PlayMany(SelectionType::Legal)),
+            (_, Char('Q')) => Some(Action::QueueMany {
+                sel_type: SelectionType::Legal,
+                shuffle: false,
+            }),
             (C, Left) | (C, Char('h')) => Some(Action::SortColumnsPrev),
             (C, Right) | (C, Char('l')) => Some(Action::SortColumnsNext),
+            (C, Up) | (C, Down) | (_, Char('I')) => Some(Action::ReverseSort),
+            (_, Char('X')) => Some(Action::ColumnPicker),
+            _ => None,
+        },
+
+        Mode::Power | Mode::Loved => match (key.modifiers, key.code) {
+            (C, Left) | (C, Char('h')) => Some(Action::SortColumnsPrev),
+            (C, Right) | (C, Char('l')) => Some(Action::SortColumnsNext),
+            (C, Up) | (C, Down) | (_, Char('I')) => Some(Action::ReverseSort),
+            (_, Char('X')) => Some(Action::ColumnPicker),
             _ => None,
         },
         _ => None,
     }
 }
 
+/// Rows that fold open rather than showing their tracks
+pub(super) fn sidebar_header_selected(state: &UiState) -> bool {
+    matches!(
+        state.selected_row().map(|r| &r.kind),
+        Some(
+            RowKind::Category(_)
+                | RowKind::Artist { .. }
+                | RowKind::PlaylistFolder(_)
+                | RowKind::Folder { .. }
+        )
+    )
+}
+
 fn handle_sidebar(key: &KeyEvent, state: &UiState) -> Option<Action> {
     let kind = state.selected_row().map(|r| &r.kind);
-    let is_header = matches!(kind, Some(RowKind::Category(_) | RowKind::Artist { .. }));
+    let is_header = sidebar_header_selected(state);
     let is_pl_leaf = matches!(kind, Some(RowKind::Playlist(_)));
+    let is_pl_item = is_pl_leaf || matches!(kind, Some(RowKind::PlaylistFolder(_)));
 
     match (key.modifiers, key.code) {
         (X, Enter) => Some(match is_header {
@@ -202,6 +314,7 @@ fn handle_sidebar(key: &KeyEvent, state: &UiState) -> Option<Action> {
             sel_type: SelectionType::Legal,
             shuffle: false,
         }),
+        (X, Char('t')) => Some(Action::QueueNext(Some(SelectionType::Legal))),
 
         (X, Char('s')) => Some(Action::QueueMany {
             sel_type: SelectionType::Legal,
@@ -211,7 +324,11 @@ fn handle_sidebar(key: &KeyEvent, state: &UiState) -> Option<Action> {
         (X, Char('g')) => Some(Action::Scroll(Director::Top)),
 
         (X, Char('c')) => Some(Action::CreatePlaylist),
-        (X, Char('r')) if is_pl_leaf => Some(Action::RenamePlaylist),
+        (X, Char('e')) => Some(Action::SmartPlaylist),
+        (X, Char('i')) => Some(Action::ImportPlaylist),
+        (_, Char('E')) if is_pl_leaf => Some(Action::ExportPlaylist),
+        (X, Char('r')) if is_pl_item => Some(Action::RenamePlaylist),
+        (X, Char('b')) if is_pl_item => Some(Action::MovePlaylist),
         (X, Char('x')) if is_pl_leaf => Some(Action::DeletePlaylist),
 
         _ => None,
@@ -222,10 +339,14 @@ fn handle_search_pane(key: &KeyEvent) -> Option<Action> {
     match (key.modifiers, key.code) {
         (X, Esc) => Some(Action::ChangeMode(Mode::Library)),
         (X, Tab) | (X, Enter) => Some(Action::SendSearch),
+        (A, Enter) => Some(Action::PlayMany(SelectionType::Legal)),
         (C, Char('a')) => Some(Action::GoToAlbum),
+        (A, Char('y')) => Some(Action::PlayAlbum),
+        (C, Char('r')) => Some(Action::ToggleSearchRegex),
 
         (_, Left) | (C, Char('h')) => Some(Action::SortColumnsPrev),
         (_, Right) | (C, Char('l')) => Some(Action::SortColumnsNext),
+        (C, Up) | (C, Down) => Some(Action::ReverseSort),
         (C, Enter) | (S, Enter) => None,
         (_, Char(x)) if ILLEGAL_CHARS.contains(&x) => None,
 
@@ -243,13 +364,32 @@ fn handle_fullscreen(key: &KeyEvent) -> Option<Action> {
 
         (X, Char('p')) => Action::Seek(-*SEEK_SMALL),
         (_, Char('P')) => Action::Seek(-*SEEK_LARGE),
+        (_, Char('A')) => Action::MarkAbLoop,
+        (X, Char(d @ '0'..='9')) => Action::SeekPercent((d as usize - '0' as usize) * 10),
+        (C, Char('k')) => Action::SeekInput,
 
         (X, Char('w')) => Action::NextProgressDisplay,
+        (C, Char('w')) => Action::NextGradientSpeed,
 
         (_, Char('W')) => Action::SetProgressDisplay(ProgressDisplay::Waveform),
         (_, Char('O')) => Action::SetProgressDisplay(ProgressDisplay::Oscilloscope),
         (_, Char('S')) => Action::SetProgressDisplay(ProgressDisplay::Spectrum),
         (_, Char('B')) => Action::SetProgressDisplay(ProgressDisplay::ProgressBar),
+        (X, Char('z')) => Action::NextFftSize,
+        (_, Char('Z')) => Action::NextWindowFn,
+        (_, Char('#')) => Action::ToggleAmplitudeScale,
+        (X, Char('o')) => Action::ToggleStereoScope,
+        (A, Char('o')) => Action::ToggleScopeTrigger,
+        (C, Char('b')) => Action::TogglePowerSave,
+        (_, Char('H')) => Action::ToggleSpectrumPeaks,
+        (_, Char('M')) => Action::ToggleSpectrumMirror,
+        (_, Char('^')) => Action::ToggleSpectrumColoring,
+        (_, Char('"')) => Action::ToggleSpectrumAutoGain,
+        (_, Char('(')) => Action::ShiftVizGain(Incrementor::Down),
+        (_, Char(')')) => Action::ShiftVizGain(Incrementor::Up),
+        (_, Char('|')) => Action::ToggleAutoGain,
+        (_, Char('!')) => Action::ShiftSpectrumDecay(Incrementor::Down),
+        (_, Char('@')) => Action::ShiftSpectrumDecay(Incrementor::Up),
 
         (_, Char('<')) => Action::CycleTheme(Incrementor::Up),
         (_, Char('>')) => Action::CycleTheme(Incrementor::Down),
@@ -257,6 +397,7 @@ fn handle_fullscreen(key: &KeyEvent) -> Option<Action> {
         (_, Char('{')) => Action::IncrementWFSmoothness(Incrementor::Down),
         (_, Char('}')) => Action::IncrementWFSmoothness(Incrementor::Up),
 
+        // Anything else leaves too, `f` and `Esc` among them
         _ => Action::RevertFullscreen,
     };
 
@@ -266,9 +407,49 @@ fn handle_fullscreen(key: &KeyEvent) -> Option<Action> {
 fn handle_popup(key: &KeyEvent, popup: &PopupType) -> Option<Action> {
     match popup {
         PopupType::Settings(s) => root_manager(key, s),
+        PopupType::Duplicates(d) => duplicate_review(key, d),
         PopupType::Playlist(p) => handle_playlist(key, p),
         PopupType::ThemeManager => handle_themeing(key),
         PopupType::KeymapGuide => handle_themeing(key),
+        PopupType::Columns => match key.code {
+            Up | Char('k') => Some(Action::PopupScroll(Incrementor::Up)),
+            Down | Char('j') => Some(Action::PopupScroll(Incrementor::Down)),
+            Char(' ') | Enter => Some(Action::ToggleColumn),
+            Esc | Char('X') => Some(Action::ClosePopup),
+            _ => None,
+        },
+        PopupType::VizSettings => match key.code {
+            Up | Char('k') => Some(Action::PopupScroll(Incrementor::Up)),
+            Down | Char('j') => Some(Action::PopupScroll(Incrementor::Down)),
+            Left | Char('h') => Some(Action::ShiftVizSetting(Incrementor::Down)),
+            Right | Char('l') => Some(Action::ShiftVizSetting(Incrementor::Up)),
+            Esc | Enter | Char('&') => Some(Action::ClosePopup),
+            _ => None,
+        },
+        PopupType::Seek(_) => match key.code {
+            Esc => Some(Action::ClosePopup),
+            Enter => Some(Action::SeekInputConfirm),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        PopupType::Command(_) => match key.code {
+            Esc => Some(Action::ClosePopup),
+            Enter => Some(Action::CommandConfirm),
+            Tab => Some(Action::CommandComplete),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        PopupType::TagEdit => match key.code {
+            Esc => Some(Action::ClosePopup),
+            Enter => Some(Action::TagEditConfirm),
+            Up | BackTab => Some(Action::TagEditField(Incrementor::Up)),
+            Down | Tab => Some(Action::TagEditField(Incrementor::Down)),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        PopupType::Stats => match key.code {
+            Char('c') => Some(Action::ExportHistory(HistoryFormat::Csv)),
+            Char('j') => Some(Action::ExportHistory(HistoryFormat::Json)),
+            Tab | Char('w') => Some(Action::CycleStatsWindow),
+            _ => Some(Action::ClosePopup),
+        },
         _ => Some(Action::ClosePopup),
     }
 }
@@ -279,6 +460,7 @@ fn root_manager(key: &KeyEvent, variant: &SettingsMode) -> Option<Action> {
         ViewRoots => match key.code {
             Char('a') => Some(Action::RootAdd),
             Char('d') => Some(Action::RootRemove),
+            Char('f') => Some(Action::FindDuplicates),
             Up | Char('k') => Some(Action::PopupScroll(Incrementor::Up)),
             Down | Char('j') => Some(Action::PopupScroll(Incrementor::Down)),
             Char('~') | Char('`') => Some(Action::ClosePopup),
@@ -298,6 +480,26 @@ fn root_manager(key: &KeyEvent, variant: &SettingsMode) -> Option<Action> {
     }
 }
 
+fn duplicate_review(key: &KeyEvent, variant: &DuplicateMode) -> Option<Action> {
+    use DuplicateMode::*;
+    match variant {
+        Review => match key.code {
+            Char('d') => Some(Action::ReviewDuplicates(Delete)),
+            Char('D') => Some(Action::ReviewDuplicates(DeleteAll)),
+            Char('i') => Some(Action::IgnoreDuplicates),
+            Up | Char('k') => Some(Action::PopupScroll(Incrementor::Up)),
+            Down | Char('j') => Some(Action::PopupScroll(Incrementor::Down)),
+            Esc => Some(Action::CloseDuplicates),
+            _ => None,
+        },
+        Delete | DeleteAll => match key.code {
+            Esc => Some(Action::ReviewDuplicates(Review)),
+            Enter => Some(Action::ConfirmDuplicates),
+            _ => None,
+        },
+    }
+}
+
 fn handle_playlist(key: &KeyEvent, variant: &PlaylistAction) -> Option<Action> {
     use PlaylistAction::*;
 
@@ -329,6 +531,29 @@ fn handle_playlist(key: &KeyEvent, variant: &PlaylistAction) -> Option<Action> {
             Enter => Some(Action::RenamePlaylistConfirm),
             _ => Some(Action::PopupInput(*key)),
         },
+        CreateSmart => match key.code {
+            Enter => Some(Action::SmartPlaylistNameConfirm),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        SmartRules(_) => match key.code {
+            Enter => Some(Action::SmartPlaylistRulesConfirm),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        Import => match key.code {
+            Enter => Some(Action::ImportPlaylistConfirm),
+            _ => Some(Action::PopupInput(*key)),
+        },
+        Move => match key.code {
+            Up | Char('k') => Some(Action::PopupScroll(Incrementor::Up)),
+            Down | Char('j') => Some(Action::PopupScroll(Incrementor::Down)),
+            Enter | Char('b') => Some(Action::MovePlaylistConfirm),
+            Char('n') => Some(Action::NewPlaylistFolder),
+            _ => None,
+        },
+        NewFolder => match key.code {
+            Enter => Some(Action::NewPlaylistFolderConfirm),
+            _ => Some(Action::PopupInput(*key)),
+        },
     }
 }
 
@@ -1,6 +1,13 @@
+use std::time::{Duration, Instant};
+
+/// How long type-ahead waits for the next character before giving up
+pub const JUMP_TIMEOUT: Duration = Duration::from_millis(1000);
+
 #[derive(Default)]
 pub struct KeyBuffer {
     digits: String,
+    /// Type-ahead so far, kept with its leading `'` for display
+    jump: Option<(String, Instant)>,
 }
 
 impl KeyBuffer {
@@ -27,7 +34,8 @@ impl KeyBuffer {
     }
 
     pub fn clear(&mut self) {
-        self.digits.clear()
+        self.digits.clear();
+        self.jump = None;
     }
 
     pub fn get_count(&mut self) -> Option<usize> {
@@ -35,9 +43,43 @@ impl KeyBuffer {
     }
 
     pub fn pending(&self) -> Option<&str> {
+        if self.is_jumping() {
+            return self.jump.as_ref().map(|(typed, _)| typed.as_str());
+        }
+
         match !self.digits.is_empty() {
             true => Some(&self.digits),
             false => None,
         }
     }
+
+    pub fn start_jump(&mut self) {
+        self.digits.clear();
+        self.jump = Some((String::from("'"), Instant::now()));
+    }
+
+    pub fn is_jumping(&self) -> bool {
+        self.jump
+            .as_ref()
+            .is_some_and(|(_, last)| last.elapsed() < JUMP_TIMEOUT)
+    }
+
+    pub fn end_jump(&mut self) {
+        self.jump = None;
+    }
+
+    /// Adds to the type-ahead and returns everything typed so far, or
+    /// `None` if it has timed out
+    pub fn push_jump(&mut self, c: char) -> Option<String> {
+        if !self.is_jumping() {
+            self.jump = None;
+            return None;
+        }
+
+        let (typed, last) = self.jump.as_mut()?;
+        typed.push(c);
+        *last = Instant::now();
+
+        Some(typed[1..].to_string())
+    }
 }
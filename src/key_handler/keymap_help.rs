@@ -19,10 +19,34 @@ const HELP: &[HelpSection] = &[
         keys: &[
             key("<space>", "Play / pause"),
             key("<control+s>", "Stop"),
+            key("<alt+u>", "Undo (clear queue, playlist removals)"),
             key("<n>, <shift+N>", "Seek forward (small / large)"),
             key("<p>, <shift+P>", "Seek back (small / large)"),
+            key("<#><%>", "Seek to # percent (0-9 in fullscreen)"),
+            key("<control+k>", "Seek to a typed time (1:23:45, +90, -30)"),
+            key("<:>", "Command line (tab completes)"),
+            key("<shift+A>", "A-B loop (set A / set B / clear)"),
             key("<control+n>, <control+p>", "Play next / previous"),
-            key("<control+r>", "Toggle repeat"),
+            key("<control+r>", "Cycle repeat (off / track / queue)"),
+            key(
+                "<control+x>",
+                "Cycle shuffle mode (random / no repeats / album / weighted)",
+            ),
+            key("<alt+r>", "Toggle radio (queue more when it runs out)"),
+            key("<control+g>", "Cycle ReplayGain (off / track / album)"),
+            key("<control+d>", "Toggle Discord Rich Presence"),
+            key("<control+y>", "Toggle desktop notifications"),
+            key("<control+b>", "Toggle power save (visualizers paused)"),
+            key("<alt+m>", "Toggle mouse (click, double-click, wheel)"),
+            key("<shift+Y>", "Toggle lyrics pane"),
+            key(
+                "<alt+j>, <alt+k>",
+                "Scroll lyrics (follows the song again after 5s)",
+            ),
+            key("<alt+i>", "Toggle artist pane (artist.jpg / artist.txt)"),
+            key("<shift+T>", "Cycle sleep timer (off / 15 / 30 / 60 min)"),
+            key("<# + shift+T>", "Sleep after # minutes (e.g. 45T)"),
+            key("<# + shift+R>", "Rate playing track # stars (e.g. 4R)"),
             key("< / >", "Search"),
             key("<m>", "Toggle minimal mode"),
             key("<f>", "Fullscreen visualizer"),
@@ -30,23 +54,50 @@ const HELP: &[HelpSection] = &[
             key("<j>, <k>, <up>, <down>", "Scroll"),
             key("<d>, <u>", "Half-page down / up"),
             key("<control+a>", "Go to album view"),
+            key("<alt+y>", "Play the whole album, replacing the queue"),
             key("< ` >, < ~ >", "Access root settings"),
-            key("< + >, < - >", "Adjust volume up/down"),
-            key("<control+u>, <f5>", "Rescan library"),
+            key("<f>", "Find duplicates (in root settings)"),
+            key("< + >, < - >", "Adjust volume up/down by 5%"),
+            key("<alt+ + >, <alt+ - >", "Adjust volume up/down by 1%"),
+            key("< _ >", "Mute / unmute"),
+            key("<control+u>, <f5>", "Rescan library (again to cancel)"),
             key("<control+t>, <control+q>", "Playlists / Queue view"),
+            key("<control+f>", "Loved tracks view"),
+            key("<control+e>", "Recently played view"),
+            key("<control+o>", "Love / unlove playing track"),
             key("<shift+D>, <shift+U>", "Page down / up"),
             key("<shift+G>", "Jump to bottom"),
+            key("< ' > + text", "Jump to first row starting with text"),
             key("<[>, <]>", "Shrink / grow sidebar"),
             key("<{>, <}>", "Toggle waveform smoothness"),
             key("<w>", "Cycle progress display"),
+            key("<control+w>", "Cycle visualizer gradient speed"),
             key(
                 "<shift+W/O/S/B>",
                 "Waveform / Oscilloscope / Spectrum / Bar",
             ),
+            key("<z>", "Cycle spectrum FFT size"),
+            key("<shift+Z>", "Cycle spectrum window function"),
+            key("< # >", "Toggle spectrum scale (linear / dB)"),
+            key("<o>", "Toggle stereo-split oscilloscope"),
+            key(
+                "<alt+o>",
+                "Toggle oscilloscope trigger (steady / free-running)",
+            ),
+            key("<shift+H>", "Toggle spectrum peak-hold"),
+            key("<shift+M>", "Toggle spectrum mirror"),
+            key("< ^ >", "Toggle spectrum coloring (gradient / bands)"),
+            key("< \" >", "Toggle spectrum per-band auto-gain"),
+            key("< ( >, < ) >", "Lower / raise visualizer gain"),
+            key("< | >", "Toggle visualizer auto-gain"),
+            key("< ! >, < @ >", "Faster / slower spectrum falloff"),
+            key("< & >", "Spectrum bar settings (height / gap)"),
             key("<shift+C>", "Theme picker"),
             key("< , >, < . > ", "Cycle theme"),
             key("<f6>", "Reload themes from disk"),
             key("< \\ >", "Statistics"),
+            key("<w>", "Cycle stats period (in statistics)"),
+            key("<c>, <j>", "Export history as CSV / JSON (in statistics)"),
             key("<esc>", "Clear selection / reset"),
             key("<backspace>", "Clear key count"),
             key("<control+c>", "Quit"),
@@ -58,12 +109,19 @@ const HELP: &[HelpSection] = &[
         keys: &[
             key("<enter>", "Play Selected"),
             key("<q>", "Queue track"),
+            key("<t>", "Play next (after the current track)"),
             key("<a>", "Add to playlist"),
             key("<a+a>", "Add to last appended playlist"),
+            key("<alt+a>", "Add to a new playlist"),
+            key("<# + r>", "Rate track # stars (r alone clears)"),
+            key("<*>", "Love / unlove track"),
+            key("<e>", "Edit tags (FLAC / MP3)"),
             key("<control+a>", "Go to album"),
+            key("<alt+y>", "Play this track's album from the start"),
             key("<v>", "Toggle multi-select"),
             key("<control+v>", "Clear multi-select"),
-            key("<shift+V>", "Select all"),
+            key("<shift+V>", "Select all / none"),
+            key("<shift+up>, <shift+down>", "Select a range"),
             key("<g> / <G>", "Go to <top> / <bottom>"),
             key("<# + g>", "Go to track at line # (e.g. 7g)"),
             key("<h>, <left>, <tab>", "Back to sidebar"),
@@ -72,9 +130,13 @@ const HELP: &[HelpSection] = &[
                 "<shift+K>, <shift+J>",
                 "Move item up / down (playlist / queue)",
             ),
-            key("<shift+Q>", "Queue all"),
+            key("<home>, <end>", "Move item to top / bottom (queue)"),
+            key("<shift+Q>", "Queue all (library / search)"),
+            key("<alt+enter>", "Play all results (search)"),
             key("<s>", "Shuffle queue (queue mode)"),
             key("<control+h>, <control+l>", "Sort columns (search)"),
+            key("<I>, <control+up/down>", "Reverse sort (search)"),
+            key("<X>", "Choose columns (search)"),
         ],
     },
     HelpSection {
@@ -85,12 +147,17 @@ const HELP: &[HelpSection] = &[
             key("<h>, <left>", "Collapse (self or parent)"),
             key("<L>", "Expand everything"),
             key("<q>", "Queue selection"),
+            key("<t>", "Play selection next"),
             key("<s>", "Queue and shuffle selection"),
             key("<g>, <G>", "Jump to <top> / <bottom>"),
             key("<control+h>, <control+left>", "Sort albums (prev)"),
             key("<control+l>, <control+right>", "Sort albums (next)"),
             key("<c>", "Create playlist"),
-            key("<r>", "Rename playlist (playlist row)"),
+            key("<e>", "Create / edit smart playlist"),
+            key("<i>", "Import M3U / M3U8 playlist"),
+            key("<shift+E>", "Export playlist to M3U8 (playlist row)"),
+            key("<r>", "Rename playlist / folder"),
+            key("<b>", "Move playlist / folder into a folder"),
             key("<x>", "Delete playlist (playlist row)"),
         ],
     },
@@ -99,6 +166,8 @@ const HELP: &[HelpSection] = &[
         keys: &[
             key("(type)", "Filter results"),
             key("<enter>, <tab>", "Run search"),
+            key("<alt+enter>", "Play all results"),
+            key("<control+r>", "Toggle regex filter (e.g. artist:^The )"),
             key("<esc>", "Exit search"),
         ],
     },
@@ -1,6 +1,7 @@
 mod action;
 mod key_buffer;
 mod keymap_help;
+mod mouse;
 
 use std::cell::RefCell;
 use std::collections::HashSet;
@@ -12,9 +13,12 @@ pub use action::handle_key_event;
 pub use action::next_event;
 pub use key_buffer::KeyBuffer;
 pub use keymap_help::{HelpRow, help_rows};
+pub use mouse::handle_mouse_event;
 use ratatui::crossterm::event::KeyEvent;
 use ratatui::crossterm::event::KeyModifiers;
 
+use crate::database::HistoryFormat;
+use crate::ui_state::DuplicateMode;
 use crate::ui_state::Mode;
 use crate::ui_state::Pane;
 use crate::ui_state::PopupType;
@@ -27,11 +31,13 @@ static ILLEGAL_CHARS: LazyLock<HashSet<char>> = LazyLock::new(|| HashSet::from([
 const X: KeyModifiers = KeyModifiers::NONE;
 const S: KeyModifiers = KeyModifiers::SHIFT;
 const C: KeyModifiers = KeyModifiers::CONTROL;
+const A: KeyModifiers = KeyModifiers::ALT;
 
 pub static SEEK_SMALL: LazyLock<f64> = LazyLock::new(|| user_config().general.seek_small);
 pub static SEEK_LARGE: LazyLock<f64> = LazyLock::new(|| user_config().general.seek_large);
 
-const VOLUME_STEP: f32 = 0.1;
+const VOLUME_STEP: f32 = 0.05;
+const VOLUME_STEP_FINE: f32 = 0.01;
 
 const SCROLL_MID: usize = 5;
 const SCROLL_XTRA: usize = 50;
@@ -41,11 +47,20 @@ pub enum Action {
     // Player Controls
     Play(usize),
     Stop,
+    Undo,
     TogglePlayback,
     PlayNext,
     PlayPrev,
     Seek(f64),
+    SeekPercent(usize),
+    SeekFraction(f64),
+    SeekInput,
+    SeekInputConfirm,
+    CommandLine,
+    CommandConfirm,
+    CommandComplete,
     VolumeShift(f32),
+    ToggleMute,
 
     // Queue & Playlist Actions
     QueueSong,
@@ -53,9 +68,27 @@ pub enum Action {
         sel_type: SelectionType,
         shuffle: bool,
     },
+    QueueNext(Option<SelectionType>),
+    PlayMany(SelectionType),
     RemoveSong,
-
-    ToggleRepeat,
+    RateSong(usize),
+    LoveSong,
+
+    CycleRepeat,
+    CycleShuffleMode,
+    ToggleRadio,
+    CycleReplayGain,
+    SleepTimer(usize),
+    MarkAbLoop,
+    RateNowPlaying(usize),
+    LoveNowPlaying,
+    ToggleDiscordPresence,
+    ToggleNotifications,
+    ToggleMouse,
+    ToggleLyrics,
+    ToggleArtistPane,
+    ScrollLyrics(isize),
+    TogglePowerSave,
 
     AddToPlaylist,
     AddToPlaylistConfirm,
@@ -63,23 +96,45 @@ pub enum Action {
     CreatePlaylistWithSongs,
     CreatePlaylistWithSongsConfirm,
 
+    SmartPlaylist,
+    SmartPlaylistNameConfirm,
+    SmartPlaylistRulesConfirm,
+
+    ImportPlaylist,
+    ImportPlaylistConfirm,
+    ExportPlaylist,
+
+    // Tags
+    TagEdit,
+    TagEditField(Incrementor),
+    TagEditConfirm,
+
     // Updating App State
     UpdateLibrary,
     SendSearch,
     UpdateSearch(KeyEvent),
+    ToggleSearchRegex,
     SortColumnsNext,
     SortColumnsPrev,
+    ReverseSort,
+    ColumnPicker,
+    ToggleColumn,
+    VizSettings,
+    ShiftVizSetting(Incrementor),
     ToggleAlbumSort(bool),
     ChangeMode(Mode),
     ChangePane(Pane),
     GoToTrack(usize),
     GoToAlbum,
+    PlayAlbum,
     GoTo(Root),
     GoToNowPlaying,
+    JumpTo(String),
     Scroll(Director),
 
     MultiSelect(usize),
     MultiSelectAll,
+    MultiSelectRange(Incrementor),
     ClearMultiSelect,
     ClearKeyBuffer,
 
@@ -93,7 +148,13 @@ pub enum Action {
     RenamePlaylist,
     RenamePlaylistConfirm,
 
+    MovePlaylist,
+    MovePlaylistConfirm,
+    NewPlaylistFolder,
+    NewPlaylistFolderConfirm,
+
     ShiftPosition(Incrementor),
+    ShiftToEdge(Incrementor),
     ShuffleElements,
 
     SwapLayout,
@@ -113,12 +174,27 @@ pub enum Action {
 
     SetProgressDisplay(ProgressDisplay),
     NextProgressDisplay,
+    NextFftSize,
+    NextWindowFn,
+    ToggleAmplitudeScale,
+    ToggleStereoScope,
+    ToggleScopeTrigger,
+    ToggleSpectrumPeaks,
+    ToggleSpectrumMirror,
+    ToggleSpectrumColoring,
+    ToggleSpectrumAutoGain,
+    ShiftVizGain(Incrementor),
+    ShiftSpectrumDecay(Incrementor),
+    NextGradientSpeed,
+    ToggleAutoGain,
     ToggleProgressDisplay,
     RevertFullscreen,
 
     PopupScroll(Incrementor),
     PopupInput(KeyEvent),
     ShowStats,
+    ExportHistory(HistoryFormat),
+    CycleStatsWindow,
     ShowKeymaps,
 
     ClosePopup,
@@ -128,6 +204,11 @@ pub enum Action {
     RootAdd,
     RootRemove,
     RootConfirm,
+    FindDuplicates,
+    ReviewDuplicates(DuplicateMode),
+    IgnoreDuplicates,
+    ConfirmDuplicates,
+    CloseDuplicates,
 
     HandleErrors,
     SoftReset,
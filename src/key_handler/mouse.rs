@@ -0,0 +1,93 @@
+use crate::{
+    key_handler::{Action, InputContext, action::sidebar_header_selected},
+    ui_state::{ListArea, Mode, Pane, UiState},
+};
+use ratatui::{
+    crossterm::event::{MouseButton, MouseEvent, MouseEventKind},
+    layout::Position,
+};
+
+/// Rows moved per notch of the wheel
+const WHEEL_ROWS: usize = 3;
+
+/// Clicks and the wheel act on whatever was drawn under the cursor on the
+/// last frame. Nothing happens behind an open popup.
+pub fn handle_mouse_event(mouse: MouseEvent, state: &mut UiState) -> Option<Action> {
+    if matches!(state.get_input_context(), InputContext::Popup(_)) {
+        return None;
+    }
+
+    let (col, row) = (mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => click(state, col, row),
+        MouseEventKind::ScrollDown => wheel(state, col, row, WHEEL_ROWS as isize),
+        MouseEventKind::ScrollUp => wheel(state, col, row, -(WHEEL_ROWS as isize)),
+        _ => None,
+    }
+}
+
+fn click(state: &mut UiState, col: u16, row: u16) -> Option<Action> {
+    let double = state.mouse.is_double_click(col, row);
+
+    if let Some(bar) = state.mouse.seek_bar
+        && bar.contains(Position::new(col, row))
+    {
+        let fraction = (col - bar.x) as f64 / bar.width.saturating_sub(1).max(1) as f64;
+        return Some(Action::SeekFraction(fraction));
+    }
+
+    if let Some(idx) = list_row(state.mouse.tracklist, col, row) {
+        state.nav.table_pos.select(Some(idx));
+        state.set_pane(Pane::TrackList);
+        return double.then_some(Action::Play(0));
+    }
+
+    if let Some(idx) = list_row(state.mouse.sidebar, col, row)
+        && *state.get_mode() == Mode::Library
+    {
+        // Re-selecting the same row would throw away the multi-selection
+        if state.nav.sidebar.pos.selected() != Some(idx) {
+            state.select_sidebar_row(idx);
+        }
+        state.set_pane(Pane::SideBar);
+        return double.then(|| match sidebar_header_selected(state) {
+            true => Action::SidebarToggle,
+            false => Action::ChangePane(Pane::TrackList),
+        });
+    }
+
+    None
+}
+
+/// Moves the selection of the list under the cursor, stopping at either
+/// end rather than wrapping around as the keys do
+fn wheel(state: &mut UiState, col: u16, row: u16, rows: isize) -> Option<Action> {
+    let step = |selected: Option<usize>, len: usize| {
+        let selected = selected.unwrap_or(0) as isize;
+        (selected + rows).clamp(0, len.saturating_sub(1) as isize) as usize
+    };
+
+    if let Some(area) = state.mouse.lyrics
+        && area.contains(Position::new(col, row))
+    {
+        state.scroll_lyrics(rows);
+    } else if let Some(list) = state.mouse.tracklist
+        && list.rows.contains(Position::new(col, row))
+    {
+        let idx = step(state.nav.table_pos.selected(), list.len);
+        state.nav.table_pos.select(Some(idx));
+    } else if let Some(list) = state.mouse.sidebar
+        && list.rows.contains(Position::new(col, row))
+        && *state.get_mode() == Mode::Library
+    {
+        let idx = step(state.nav.sidebar.pos.selected(), list.len);
+        if state.nav.sidebar.pos.selected() != Some(idx) {
+            state.select_sidebar_row(idx);
+        }
+    }
+    None
+}
+
+fn list_row(list: Option<ListArea>, col: u16, row: u16) -> Option<usize> {
+    list?.row_at(col, row)
+}
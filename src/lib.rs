@@ -9,7 +9,7 @@ use ratatui::crossterm::{
 };
 use std::{
     fs,
-    io::{self, Write},
+    io::{self, Read, Seek, Write},
     path::{Path, PathBuf},
     sync::{Arc, LazyLock, OnceLock},
     time::{Duration, UNIX_EPOCH},
@@ -21,12 +21,19 @@ use xxhash_rust::xxh3::xxh3_64;
 pub mod addons;
 pub mod app_core;
 pub mod config;
+pub mod control;
 pub mod database;
+#[cfg(feature = "discord")]
+pub mod discord;
 pub mod key_handler;
 pub mod library;
 pub mod media_controls;
+#[cfg(feature = "notifications")]
+pub mod notify;
 pub mod playback;
 pub mod player;
+#[cfg(feature = "scrobble")]
+pub mod scrobble;
 pub mod theme;
 pub mod tui;
 pub mod ui_state;
@@ -62,7 +69,20 @@ static CONFIG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
 
 pub static THEME_DIR: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("themes"));
 pub static ADDON_DIR: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("addons"));
+pub static PLAYLIST_DIR: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("playlists"));
+pub static EXPORT_DIR: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("exports"));
+pub static COVER_DIR: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("covers"));
 pub static DB_PATH: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("noctavox.db"));
+#[cfg(feature = "scrobble")]
+pub static SCROBBLE_PATH: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("scrobble.toml"));
+/// Where a daemon listens, somewhere only this user can reach
+pub static SOCKET_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
+    dirs::runtime_dir()
+        .unwrap_or_else(|| CONFIG_DIR.clone())
+        .join("noctavox.sock")
+});
+pub static SCAN_REPORT_PATH: LazyLock<PathBuf> =
+    LazyLock::new(|| CONFIG_DIR.join("skipped_files.txt"));
 
 pub const ADDON_TRANSPOSE: &str = "nv-transpose";
 
@@ -103,6 +123,28 @@ pub fn calculate_signature<P: AsRef<Path>>(path: P) -> anyhow::Result<u64> {
     Ok(xxh3_64(&data))
 }
 
+const FINGERPRINT_SAMPLE: u64 = 64 * 1024;
+
+/// Create a hash of the file's contents, independent of where it lives.
+/// Only the size and the first and last 64KiB are read, which is enough to
+/// recognise a file that was moved or renamed without reading all of it.
+pub fn calculate_fingerprint<P: AsRef<Path>>(path: P) -> anyhow::Result<u64> {
+    let mut file = fs::File::open(&path)?;
+    let size = file.metadata()?.len();
+
+    let mut data = size.to_le_bytes().to_vec();
+    (&mut file)
+        .take(FINGERPRINT_SAMPLE)
+        .read_to_end(&mut data)?;
+
+    if size > FINGERPRINT_SAMPLE * 2 {
+        file.seek(io::SeekFrom::End(-(FINGERPRINT_SAMPLE as i64)))?;
+        file.read_to_end(&mut data)?;
+    }
+
+    Ok(xxh3_64(&data))
+}
+
 pub enum DurationStyle {
     Clean,
     CleanMillis,
@@ -174,6 +216,20 @@ fn truncate_at_last_space(s: &str, limit: usize) -> String {
     }
 }
 
+/// Escapes `s` for use inside a JSON string literal, quotes not included
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 pub fn normalize_metadata_str(s: &str) -> String {
     s.nfc()
         .filter(|c| match c {
@@ -0,0 +1,60 @@
+use crate::library::cover_art::CoverArt;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Pictures looked for in an artist's folder, in order
+const IMAGE_NAMES: [&str; 3] = ["artist.jpg", "artist.png", "artist.jpeg"];
+const NOTES_NAME: &str = "artist.txt";
+/// Plenty for a biography, and no more read from a stray large file
+const MAX_NOTES_BYTES: u64 = 64 * 1024;
+
+/// What sits beside an artist's music, read from disk and nowhere else
+#[derive(Default)]
+pub struct ArtistInfo {
+    pub image: Option<CoverArt>,
+    pub notes: Option<String>,
+}
+
+/// Sidecar files for an artist, given a song from each of their albums.
+/// An artist's folder usually holds their albums (`Artist/Album/01.flac`),
+/// so the folder above each song's comes first, then the song's own for
+/// albums kept loose in the artist's folder.
+pub fn load_artist_info(song_paths: &[PathBuf]) -> ArtistInfo {
+    let mut dirs: Vec<&Path> = Vec::new();
+    let parents = song_paths.iter().filter_map(|p| p.parent());
+    for dir in parents.clone().filter_map(Path::parent).chain(parents) {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    let image = dirs
+        .iter()
+        .flat_map(|dir| IMAGE_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|p| p.is_file())
+        .and_then(|p| CoverArt::new(fs::read(p).ok()?));
+
+    let notes = dirs
+        .iter()
+        .map(|dir| dir.join(NOTES_NAME))
+        .find(|p| p.is_file())
+        .and_then(|p| read_notes(&p));
+
+    ArtistInfo { image, notes }
+}
+
+fn read_notes(path: &Path) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > MAX_NOTES_BYTES {
+        return None;
+    }
+
+    let text = fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&text);
+    let text = text.trim_start_matches('\u{feff}').trim();
+    match text.is_empty() {
+        true => None,
+        false => Some(text.replace("\r\n", "\n")),
+    }
+}
@@ -0,0 +1,144 @@
+use crate::COVER_DIR;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use symphonia::{
+    core::{
+        formats::probe::Hint,
+        io::MediaSourceStream,
+        meta::{StandardVisualKey, Visual},
+    },
+    default::get_probe,
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Common names for cover art stored next to the audio files
+const COVER_NAMES: [&str; 6] = [
+    "cover.jpg",
+    "cover.png",
+    "folder.jpg",
+    "folder.png",
+    "front.jpg",
+    "front.png",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Other,
+}
+
+impl ImageFormat {
+    /// Tags can't be trusted to name the format, so read the magic bytes
+    fn sniff(data: &[u8]) -> Self {
+        match data {
+            [0x89, b'P', b'N', b'G', ..] => ImageFormat::Png,
+            [0xFF, 0xD8, 0xFF, ..] => ImageFormat::Jpeg,
+            _ => ImageFormat::Other,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CoverArt {
+    pub data: Arc<[u8]>,
+    pub format: ImageFormat,
+}
+
+impl CoverArt {
+    pub(crate) fn new(data: Vec<u8>) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+
+        Some(CoverArt {
+            format: ImageFormat::sniff(&data),
+            data: data.into(),
+        })
+    }
+}
+
+/// Covers are cached per album, not per song
+fn cache_path(album_artist: &str, album: &str) -> PathBuf {
+    let key = xxh3_64(format!("{album_artist}\0{album}").as_bytes());
+    COVER_DIR.join(format!("{key:016x}"))
+}
+
+/// Prefers the front cover when a file carries several pictures
+pub(crate) fn pick_visual(visuals: &[Visual]) -> Option<&Visual> {
+    visuals
+        .iter()
+        .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+        .or_else(|| visuals.first())
+}
+
+/// Called while scanning. The first song of an album to be parsed stores
+/// its picture; the rest find it already there.
+pub(crate) fn cache_embedded(album_artist: &str, album: &str, data: &[u8]) {
+    let path = cache_path(album_artist, album);
+    if path.exists() {
+        return;
+    }
+
+    let _ = fs::create_dir_all(&*COVER_DIR);
+    // Written under a temporary name so a reader never sees half a file
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    if fs::write(&tmp, data).is_ok() {
+        let _ = fs::rename(&tmp, &path);
+    }
+}
+
+/// Cover art for a song: the scan cache, then the file's own tags (for songs
+/// scanned before covers were cached), then an image in the song's folder.
+pub fn load_cover(song_path: &Path, album_artist: &str, album: &str) -> Option<CoverArt> {
+    let cached = cache_path(album_artist, album);
+    if let Ok(data) = fs::read(&cached) {
+        return CoverArt::new(data);
+    }
+
+    if let Some(data) = read_embedded(song_path) {
+        cache_embedded(album_artist, album, &data);
+        return CoverArt::new(data);
+    }
+
+    folder_cover(song_path).and_then(|p| CoverArt::new(fs::read(p).ok()?))
+}
+
+/// An image file sitting next to the song, e.g. `cover.jpg`
+pub fn folder_cover(song_path: &Path) -> Option<PathBuf> {
+    let dir = song_path.parent()?;
+    COVER_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|p| p.is_file())
+}
+
+fn read_embedded(path: &Path) -> Option<Vec<u8>> {
+    let src = File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let mut probed = get_probe()
+        .probe(&hint, mss, Default::default(), Default::default())
+        .ok()?;
+
+    let mut metadata = probed.metadata();
+    loop {
+        if let Some(md) = metadata.current()
+            && let Some(visual) = pick_visual(&md.media.visuals)
+        {
+            return Some(visual.data.to_vec());
+        }
+        if metadata.is_latest() {
+            return None;
+        }
+        metadata.pop();
+    }
+}
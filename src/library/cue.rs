@@ -0,0 +1,319 @@
+use crate::{calculate_signature, library::LEGAL_EXTENSION};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Cue timestamps count frames, at 75 to the second
+const FRAMES_PER_SEC: f64 = 75.0;
+
+/// Where a cue track sits in its audio file, in seconds. The last track of
+/// a file runs to the end of it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CueSpan {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+#[derive(Default, Debug)]
+pub struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub year: Option<u32>,
+    pub files: Vec<CueFile>,
+}
+
+#[derive(Debug)]
+pub struct CueFile {
+    pub path: PathBuf,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Debug)]
+pub struct CueTrack {
+    pub number: u32,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub span: CueSpan,
+}
+
+impl CueSheet {
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Ok(Self::parse(&decode(&bytes), dir))
+    }
+
+    /// Relative `FILE` entries are resolved against `dir`. Tracks without
+    /// an `INDEX 01` and files without any tracks are left out.
+    pub fn parse(text: &str, dir: &Path) -> Self {
+        let mut sheet = CueSheet::default();
+        // Starts, before the ends are known
+        let mut starts: Vec<Option<f64>> = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            let command = command.to_ascii_uppercase();
+
+            match command.as_str() {
+                "FILE" => {
+                    // A track whose pregap sits at the end of the previous
+                    // file only begins once its `INDEX 01` does, in this one
+                    let carried = match (starts.last(), sheet.files.last_mut()) {
+                        (Some(None), Some(file)) => starts.pop().zip(file.tracks.pop()),
+                        _ => None,
+                    };
+
+                    close_file(&mut sheet, &mut starts);
+                    sheet.files.push(CueFile {
+                        path: dir.join(unquote(rest)),
+                        tracks: Vec::new(),
+                    });
+
+                    if let (Some((start, track)), Some(file)) = (carried, sheet.files.last_mut()) {
+                        file.tracks.push(track);
+                        starts.push(start);
+                    }
+                }
+                "TRACK" => {
+                    let mut parts = rest.split_whitespace();
+                    let number = parts.next().and_then(|n| n.parse().ok());
+                    let audio = parts
+                        .next()
+                        .is_some_and(|t| t.eq_ignore_ascii_case("AUDIO"));
+
+                    if let (Some(number), true, Some(file)) =
+                        (number, audio, sheet.files.last_mut())
+                    {
+                        file.tracks.push(CueTrack {
+                            number,
+                            title: None,
+                            performer: None,
+                            span: CueSpan {
+                                start: 0.0,
+                                end: None,
+                            },
+                        });
+                        starts.push(None);
+                    }
+                }
+                "INDEX" => {
+                    let mut parts = rest.split_whitespace();
+                    if parts.next().and_then(|n| n.parse::<u32>().ok()) == Some(1)
+                        && let Some(last) = starts.last_mut()
+                    {
+                        *last = parts.next().and_then(parse_timestamp);
+                    }
+                }
+                "TITLE" | "PERFORMER" => {
+                    let value = Some(unquote(rest).to_string()).filter(|v| !v.is_empty());
+                    let track = sheet.files.last_mut().and_then(|f| f.tracks.last_mut());
+
+                    match (command.as_str(), track) {
+                        ("TITLE", Some(t)) => t.title = value,
+                        ("PERFORMER", Some(t)) => t.performer = value,
+                        ("TITLE", None) => sheet.title = value,
+                        _ => sheet.performer = value,
+                    }
+                }
+                "REM" => {
+                    let (key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    if key.eq_ignore_ascii_case("DATE") {
+                        sheet.year = unquote(value.trim()).get(..4).and_then(|y| y.parse().ok());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        close_file(&mut sheet, &mut starts);
+        sheet.files.retain(|f| !f.tracks.is_empty());
+        sheet
+    }
+}
+
+/// An audio file's place in the cue sheet that covers it
+pub struct CueSource {
+    pub sheet: Arc<CueSheet>,
+    file: usize,
+    signature: u64,
+}
+
+impl CueSource {
+    pub fn tracks(&self) -> &[CueTrack] {
+        &self.sheet.files[self.file].tracks
+    }
+
+    /// Keyed on the sheet as well as the audio file, so editing either one
+    /// has the tracks scanned again
+    pub fn track_id(&self, file_signature: u64, number: u32) -> u64 {
+        let mut data = file_signature.to_le_bytes().to_vec();
+        data.extend_from_slice(&self.signature.to_le_bytes());
+        data.extend_from_slice(&number.to_le_bytes());
+        xxh3_64(&data)
+    }
+}
+
+/// Every audio file the given sheets refer to, by canonical path. Sheets
+/// that can't be read, and files that don't exist, are passed over.
+pub fn index_sheets(sheets: &[PathBuf]) -> HashMap<PathBuf, CueSource> {
+    let mut index = HashMap::new();
+
+    for path in sheets {
+        let (Ok(signature), Ok(sheet)) = (calculate_signature(path), CueSheet::read(path)) else {
+            continue;
+        };
+        let sheet = Arc::new(sheet);
+
+        for (file, entry) in sheet.files.iter().enumerate() {
+            if let Ok(audio) = entry.path.canonicalize() {
+                index.insert(
+                    audio,
+                    CueSource {
+                        sheet: Arc::clone(&sheet),
+                        file,
+                        signature,
+                    },
+                );
+            }
+        }
+    }
+
+    index
+}
+
+/// Fills in the starts and ends of the file's tracks now that all of them
+/// are known, each ending where the next begins
+fn close_file(sheet: &mut CueSheet, starts: &mut Vec<Option<f64>>) {
+    let Some(file) = sheet.files.last_mut() else {
+        return;
+    };
+
+    let mut tracks = std::mem::take(&mut file.tracks)
+        .into_iter()
+        .zip(starts.drain(..))
+        .filter_map(|(mut track, start)| {
+            track.span.start = start?;
+            Some(track)
+        })
+        .collect::<Vec<_>>();
+
+    let next_starts = tracks
+        .iter()
+        .skip(1)
+        .map(|t| t.span.start)
+        .collect::<Vec<_>>();
+    for (track, end) in tracks.iter_mut().zip(next_starts) {
+        track.span.end = Some(end);
+    }
+
+    file.tracks = tracks;
+}
+
+/// `mm:ss:ff`, where minutes may run past 59
+fn parse_timestamp(stamp: &str) -> Option<f64> {
+    let mut parts = stamp.split(':').map(|p| p.parse::<u32>().ok());
+    let (m, s, f) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(m as f64 * 60.0 + s as f64 + f as f64 / FRAMES_PER_SEC)
+}
+
+/// `"Quoted value"` or a bare word
+fn unquote(value: &str) -> &str {
+    match value.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or(rest),
+        None => value.split_whitespace().next().unwrap_or(value),
+    }
+}
+
+/// Cue sheets predate UTF-8 being a safe bet; anything that isn't valid
+/// UTF-8 is read as Latin-1
+fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Tracks cut from one file would all share its path, which the song table
+/// keeps unique, so each is stored as `<file>#<track>`
+pub fn track_path(audio: &Path, number: u32) -> PathBuf {
+    let mut path = audio.as_os_str().to_owned();
+    path.push(format!("#{number}"));
+    PathBuf::from(path)
+}
+
+/// The audio file behind a stored path, and the cue track if it is one.
+/// Real files never match, as their extension would have to end in
+/// `#<digits>`.
+pub fn split_track_path(stored: &str) -> (&str, Option<u32>) {
+    if let Some((audio, number)) = stored.rsplit_once('#')
+        && let Ok(number) = number.parse()
+        && Path::new(audio)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| LEGAL_EXTENSION.contains(e.to_lowercase().as_str()))
+    {
+        return (audio, Some(number));
+    }
+    (stored, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tracks_across_files() {
+        let text = r#"
+REM DATE 1973
+PERFORMER "Pink Floyd"
+TITLE "Side Two"
+FILE "a.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "Speak to Me"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Breathe"
+    PERFORMER "Someone Else"
+    INDEX 00 01:05:00
+    INDEX 01 01:07:30
+FILE "b.flac" WAVE
+  TRACK 03 AUDIO
+    TITLE "Time"
+    INDEX 01 00:00:00
+"#;
+        let sheet = CueSheet::parse(text, Path::new("/music"));
+
+        assert_eq!(sheet.title.as_deref(), Some("Side Two"));
+        assert_eq!(sheet.performer.as_deref(), Some("Pink Floyd"));
+        assert_eq!(sheet.year, Some(1973));
+        assert_eq!(sheet.files.len(), 2);
+
+        let a = &sheet.files[0];
+        assert_eq!(a.path, Path::new("/music/a.flac"));
+        assert_eq!(a.tracks[0].span.end, Some(67.4));
+        assert_eq!(
+            a.tracks[1].span,
+            CueSpan {
+                start: 67.4,
+                end: None
+            }
+        );
+        assert_eq!(a.tracks[1].performer.as_deref(), Some("Someone Else"));
+        assert_eq!(sheet.files[1].tracks[0].title.as_deref(), Some("Time"));
+
+        let stored = track_path(&a.path, 2);
+        assert_eq!(
+            split_track_path(stored.to_str().unwrap()),
+            ("/music/a.flac", Some(2))
+        );
+        assert_eq!(split_track_path("/music/#1.flac"), ("/music/#1.flac", None));
+    }
+}
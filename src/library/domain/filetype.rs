@@ -2,15 +2,29 @@ use rusqlite::{
     Result as RusqliteResult, ToSql,
     types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef},
 };
-use std::fmt::Display;
+use std::{fmt::Display, path::Path};
 
 pub static LEGAL_EXTENSION: std::sync::LazyLock<std::collections::HashSet<&'static str>> =
     std::sync::LazyLock::new(|| {
         std::collections::HashSet::from([
-            "aiff", "alac", "flac", "m4a", "mp3", "ogg", "opus", "wav", "webm",
+            "aif", "aiff", "alac", "flac", "m4a", "mka", "mp3", "oga", "ogg", "opus", "wav", "webm",
         ])
     });
 
+/// Formats nothing in the decode path can play. Symphonia has no decoder
+/// for WavPack, Musepack, Monkey's Audio, TTA, WMA or DSD, so these are
+/// counted during a scan rather than added.
+pub static UNSUPPORTED_EXTENSION: std::sync::LazyLock<std::collections::HashSet<&'static str>> =
+    std::sync::LazyLock::new(|| {
+        std::collections::HashSet::from(["ape", "dff", "dsf", "mpc", "tta", "wma", "wv"])
+    });
+
+pub fn is_unsupported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| UNSUPPORTED_EXTENSION.contains(e.to_lowercase().as_str()))
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Default, PartialEq, Copy, Clone)]
 pub enum FileType {
@@ -32,12 +46,13 @@ impl From<&str> for FileType {
         match str.to_lowercase().as_ref() {
             "mp3" => Self::MP3,
             "aac" | "m4a" => Self::M4A,
-            "ogg" => Self::OGG,
+            "ogg" | "oga" => Self::OGG,
             "wav" => Self::WAV,
             "flac" => Self::FLAC,
             "opus" => Self::OPUS,
-            "webm" => Self::WEBM,
-            "aiff" => Self::AIFF,
+            // Matroska audio, which the same demuxer reads
+            "webm" | "mka" => Self::WEBM,
+            "aiff" | "aif" => Self::AIFF,
             "alac" => Self::ALAC,
             _ => Self::ERR,
         }
@@ -128,3 +143,32 @@ impl FileType {
         *self as i64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legal_extensions_are_known() {
+        for ext in LEGAL_EXTENSION.iter() {
+            assert!(
+                FileType::from(*ext) != FileType::ERR,
+                "{ext} has no file type"
+            );
+            assert!(
+                !UNSUPPORTED_EXTENSION.contains(ext),
+                "{ext} is listed twice"
+            );
+        }
+        for ext in ["opus", "OGG", "Flac", "m4a", "mka"] {
+            assert!(
+                FileType::from(ext) != FileType::ERR,
+                "{ext} should be playable"
+            );
+        }
+        for ext in ["wv", "mpc", "ape"] {
+            assert!(is_unsupported(Path::new(&format!("a.{ext}"))));
+            assert!(FileType::from(ext) == FileType::ERR);
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use super::{FileType, SongInfo};
 use crate::{
-    DurationStyle, calculate_signature, database::Database, get_readable_duration,
+    DurationStyle, calculate_fingerprint, calculate_signature,
+    database::Database,
+    get_readable_duration,
+    library::{
+        cover_art,
+        cue::{self, CueSource, CueSpan},
+    },
     normalize_metadata_str as nms,
 };
 use anyhow::{Result, anyhow, bail};
@@ -9,19 +15,27 @@ use std::{
     io::{BufReader, Read, Seek, SeekFrom},
     path::{Path, PathBuf},
     sync::{Arc, LazyLock},
-    time::Duration,
+    time::{Duration, UNIX_EPOCH},
 };
 use symphonia::{
     core::{
+        codecs::audio::{AudioCodecId, well_known::CODEC_ID_OPUS},
         formats::{TrackType, probe::Hint},
         io::MediaSourceStream,
-        meta::StandardTag,
+        meta::{RawValue, StandardTag},
         units::{Duration as SymphoniaDuration, TimeBase},
     },
-    default::get_probe,
+    default::{get_codecs, get_probe},
 };
 
 static NO_ARTIST: LazyLock<Arc<String>> = LazyLock::new(|| Arc::new(String::from("[NO ARTIST!]")));
+static VARIOUS_ARTISTS: LazyLock<Arc<String>> =
+    LazyLock::new(|| Arc::new(String::from("Various Artists")));
+
+/// Matches what the engine registers: Symphonia's own decoders, and libopus
+fn can_decode(codec: AudioCodecId) -> bool {
+    codec == CODEC_ID_OPUS || get_codecs().get_audio_decoder(codec).is_some()
+}
 
 #[derive(Default)]
 pub struct LongSong {
@@ -31,6 +45,7 @@ pub struct LongSong {
     pub(crate) artist: Arc<String>,
     pub(crate) album_artist: Arc<String>,
     pub(crate) album: Arc<String>,
+    pub(crate) genres: Vec<String>,
     pub(crate) track_no: Option<u32>,
     pub(crate) disc_no: Option<u32>,
     pub(crate) duration: Duration,
@@ -39,6 +54,10 @@ pub struct LongSong {
     pub(crate) sample_rate: Option<u32>,
     pub(crate) filetype: FileType,
     pub(crate) path: PathBuf,
+    pub(crate) size: u64,
+    pub(crate) mtime: i64,
+    pub(crate) fingerprint: Option<u64>,
+    pub(crate) cue: Option<CueSpan>,
 }
 
 impl LongSong {
@@ -51,6 +70,7 @@ impl LongSong {
 
     pub fn build_song_symphonia(path: PathBuf) -> Result<LongSong> {
         let src = File::open(&path)?;
+        let metadata = src.metadata()?;
 
         let mss = MediaSourceStream::new(Box::new(src), Default::default());
         let mut hint = Hint::new();
@@ -75,11 +95,23 @@ impl LongSong {
         let id = calculate_signature(&path)?;
         let mut song_info = LongSong::new(path);
         song_info.id = id;
+        song_info.size = metadata.len();
+        song_info.mtime = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        song_info.fingerprint = calculate_fingerprint(&song_info.path).ok();
 
         let track = probed
             .first_track_known_codec(TrackType::Audio)
             .ok_or_else(|| anyhow!("No audio tracks!"))?;
 
+        let codec = track
+            .codec_params
+            .as_ref()
+            .and_then(|cp| cp.audio())
+            .map(|audio| audio.codec);
+        if !codec.is_some_and(can_decode) {
+            bail!("No decoder for this file's audio codec");
+        }
+
         let duration = match (track.time_base, track.duration) {
             (Some(tb), Some(dur)) => get_duration(dur, tb),
             _ => {
@@ -114,11 +146,27 @@ impl LongSong {
 
         let mut artist: Option<(u8, Arc<String>)> = None;
         let mut alb_art: Option<(u8, Arc<String>)> = None;
+        let mut compilation = false;
+        let mut cover = None;
 
         let mut metadata = probed.metadata();
         loop {
             if let Some(md) = metadata.current() {
+                if cover.is_none() {
+                    cover = cover_art::pick_visual(&md.media.visuals).map(|v| v.data.clone());
+                }
+
                 for tag in &md.media.tags {
+                    // Vorbis' `DISC` only reads a bare number, so `1/2` is
+                    // left unparsed
+                    if tag.std.is_none()
+                        && song_info.disc_no.is_none()
+                        && ["disc", "disk"].contains(&tag.raw.key.to_lowercase().as_str())
+                        && let RawValue::String(v) = &tag.raw.value
+                    {
+                        song_info.disc_no = numerator(v);
+                    }
+
                     if let Some(std_tag) = &tag.std {
                         match std_tag {
                             StandardTag::TrackTitle(t) => song_info.title = nms(t),
@@ -132,8 +180,11 @@ impl LongSong {
 
                             StandardTag::Album(a) => song_info.album = Arc::new(nms(a)),
 
+                            StandardTag::Genre(g) => add_genres(&mut song_info.genres, g),
+
                             StandardTag::AlbumArtist(aa) => alb_art = best(alb_art, 0, aa),
                             StandardTag::SortAlbumArtist(s) => alb_art = best(alb_art, 1, s),
+                            StandardTag::CompilationFlag(c) => compilation = *c,
 
                             StandardTag::TrackNumber(t) => song_info.track_no = Some(*t as u32),
                             StandardTag::DiscNumber(d) => song_info.disc_no = Some(*d as u32),
@@ -170,6 +221,7 @@ impl LongSong {
                         release_year = release_year.or_else(|| val.get(..4)?.parse().ok());
                     }
                     b"ITRK" | b"IPRT" => song_info.track_no = val.parse().ok(),
+                    b"IGNR" => add_genres(&mut song_info.genres, &val),
                     _ => {}
                 }
             }
@@ -186,19 +238,103 @@ impl LongSong {
             None => song_info.artist = Arc::clone(&NO_ARTIST),
         }
 
+        // A compilation with no album artist would otherwise be cut up into
+        // one album per track artist
         match alb_art {
             Some((_, a)) => song_info.album_artist = Arc::new(nms(&a)),
-            _ => song_info.album_artist = Arc::clone(&song_info.artist),
+            None if compilation => song_info.album_artist = Arc::clone(&VARIOUS_ARTISTS),
+            None => song_info.album_artist = Arc::clone(&song_info.artist),
+        }
+
+        if let Some(data) = cover {
+            cover_art::cache_embedded(&song_info.album_artist, &song_info.album, &data);
         }
 
         Ok(song_info)
     }
 
+    /// Files the song under its own artist, whatever the album artist is
+    pub(crate) fn group_by_track_artist(&mut self) {
+        self.album_artist = Arc::clone(&self.artist);
+    }
+
+    /// Cuts the file up into the tracks its cue sheet lists, falling back on
+    /// the file's own tags for anything the sheet leaves out
+    pub fn split_cue(self, source: &CueSource) -> Vec<LongSong> {
+        let sheet = &source.sheet;
+        let album_artist = sheet
+            .performer
+            .as_ref()
+            .map(|p| Arc::new(nms(p)))
+            .unwrap_or_else(|| Arc::clone(&self.album_artist));
+        let album = sheet
+            .title
+            .as_ref()
+            .map(|t| Arc::new(nms(t)))
+            .unwrap_or_else(|| Arc::clone(&self.album));
+
+        source
+            .tracks()
+            .iter()
+            .map(|track| {
+                let end = track.span.end.unwrap_or(self.duration.as_secs_f64());
+
+                LongSong {
+                    id: source.track_id(self.id, track.number),
+                    title: match &track.title {
+                        Some(t) => nms(t),
+                        None => format!("Track {}", track.number),
+                    },
+                    year: sheet.year.or(self.year),
+                    artist: match &track.performer {
+                        Some(p) => Arc::new(nms(p)),
+                        None => Arc::clone(&album_artist),
+                    },
+                    album_artist: Arc::clone(&album_artist),
+                    album: Arc::clone(&album),
+                    genres: self.genres.clone(),
+                    track_no: Some(track.number),
+                    disc_no: self.disc_no,
+                    duration: Duration::from_secs_f64((end - track.span.start).max(0.0)),
+                    channels: self.channels,
+                    bitrate: self.bitrate,
+                    sample_rate: self.sample_rate,
+                    filetype: self.filetype,
+                    path: cue::track_path(&self.path, track.number),
+                    size: self.size,
+                    mtime: self.mtime,
+                    // Copies of the whole file still pair up track by track
+                    fingerprint: self.fingerprint.map(|fp| fp ^ track.number as u64),
+                    cue: Some(track.span),
+                }
+            })
+            .collect()
+    }
+
     pub fn get_path(&self, db: &mut Database) -> Result<String> {
         db.get_song_path(self.id)
     }
 }
 
+/// The `1` of `1/2`
+fn numerator(raw: &str) -> Option<u32> {
+    raw.split('/').next()?.trim().parse().ok()
+}
+
+/// A genre tag may hold several, as `Rock; Pop` or `Rock/Pop`. Each is
+/// trimmed, and repeats that only differ in case are dropped. `Unknown` is
+/// what untagged songs are listed under already, so it isn't kept.
+fn add_genres(genres: &mut Vec<String>, raw: &str) {
+    for genre in raw.split([';', '/']).map(nms) {
+        if !genre.is_empty()
+            && !genre.eq_ignore_ascii_case("unknown")
+            && !genres.iter().any(|g| g.eq_ignore_ascii_case(&genre))
+        {
+            genres.push(genre);
+        }
+    }
+}
+
 impl SongInfo for LongSong {
     fn get_id(&self) -> u64 {
         self.id
@@ -313,3 +449,24 @@ fn parse_info_body(buf: &[u8], tags: &mut Vec<([u8; 4], String)>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_genre_tags() {
+        let mut genres = Vec::new();
+        add_genres(&mut genres, " Rock; Pop /rock ");
+        add_genres(&mut genres, "Unknown;;Jazz");
+
+        assert_eq!(genres, ["Rock", "Pop", "Jazz"]);
+    }
+
+    #[test]
+    fn reads_disc_numerators() {
+        assert_eq!(numerator("2/3"), Some(2));
+        assert_eq!(numerator(" 1 "), Some(1));
+        assert_eq!(numerator("A"), None);
+    }
+}
@@ -4,13 +4,15 @@ mod long_song;
 mod playlist;
 mod refresh;
 mod simple_song;
+mod smart_rules;
 
 pub use album::Album;
-pub use filetype::{FileType, LEGAL_EXTENSION};
+pub use filetype::{FileType, LEGAL_EXTENSION, UNSUPPORTED_EXTENSION, is_unsupported};
 pub use long_song::LongSong;
-pub use playlist::{Playlist, PlaylistSong};
-pub use refresh::{RefreshProgress, RefreshStage};
+pub use playlist::{Playlist, PlaylistFolder, PlaylistSong};
+pub use refresh::{RefreshProgress, RefreshStage, ScanSummary};
 pub use simple_song::SimpleSong;
+pub use smart_rules::{Condition, RuleField, RuleOp, RuleValue, SmartRules};
 
 use crate::DurationStyle;
 use std::time::Duration;
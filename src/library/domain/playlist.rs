@@ -1,12 +1,16 @@
 use crate::{DurationStyle, library::SongInfo};
 
-use super::SimpleSong;
+use super::{SimpleSong, SmartRules};
 use std::{sync::Arc, time::Duration};
 
 pub struct Playlist {
     pub id: i64,
     pub name: String,
     pub tracklist: Vec<PlaylistSong>,
+    /// Set for smart playlists, whose tracklist is resolved from the rules
+    pub rules: Option<SmartRules>,
+    /// `None` for playlists at the top level
+    pub folder: Option<i64>,
     length: Duration,
 }
 
@@ -18,10 +22,16 @@ impl Playlist {
             id,
             name,
             tracklist,
+            rules: None,
+            folder: None,
             length,
         }
     }
 
+    pub fn is_smart(&self) -> bool {
+        self.rules.is_some()
+    }
+
     pub fn get_tracklist(&self) -> Vec<Arc<SimpleSong>> {
         self.tracklist
             .iter()
@@ -42,6 +52,13 @@ impl Playlist {
     }
 }
 
+/// Holds playlists and other folders, and goes once it holds neither
+pub struct PlaylistFolder {
+    pub id: i64,
+    pub name: String,
+    pub parent: Option<i64>,
+}
+
 pub struct PlaylistSong {
     pub id: i64,
     pub song: Arc<SimpleSong>,
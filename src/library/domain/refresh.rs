@@ -1,4 +1,8 @@
-use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering::Relaxed};
+use crate::SCAN_REPORT_PATH;
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering::Relaxed},
+};
 
 pub enum RefreshStage {
     Scanning = 0,
@@ -33,6 +37,7 @@ pub struct RefreshProgress {
     stage: AtomicU8,
     current: AtomicUsize,
     total: AtomicUsize,
+    cancelled: AtomicBool,
 }
 
 impl RefreshProgress {
@@ -57,4 +62,70 @@ impl RefreshProgress {
     pub fn counts(&self) -> (usize, usize) {
         (self.current.load(Relaxed), self.total.load(Relaxed))
     }
+
+    /// Asks the scan to stop after the batch it's working on
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Relaxed)
+    }
+}
+
+/// What a rescan changed, reported once it finishes
+#[derive(Default, Clone, Copy)]
+pub struct ScanSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub moved: usize,
+    pub removed: usize,
+    /// Files in formats there's no decoder for
+    pub unsupported: usize,
+    /// Files that couldn't be read, or held no audio that can be decoded
+    pub skipped: usize,
+    pub cancelled: bool,
+}
+
+impl ScanSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added + self.updated + self.moved + self.removed == 0
+    }
+
+    /// Nothing changed and nothing was left out, so there's only the one line
+    /// to report
+    pub fn is_up_to_date(&self) -> bool {
+        !self.cancelled && self.is_empty() && self.unsupported + self.skipped == 0
+    }
+}
+
+impl Display for ScanSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let heading = match self.cancelled {
+            true => "Scan cancelled!",
+            false if self.is_empty() => "Library is up to date!",
+            false => "Library updated!",
+        };
+
+        match !self.cancelled && self.is_empty() {
+            true => write!(f, "{heading}")?,
+            false => write!(
+                f,
+                "{heading}\n\nAdded: {}\nUpdated: {}\nMoved: {}\nRemoved: {}",
+                self.added, self.updated, self.moved, self.removed
+            )?,
+        }
+
+        let left_out = self.unsupported + self.skipped;
+        if left_out > 0 && !self.cancelled {
+            write!(
+                f,
+                "\n\nSkipped: {left_out} ({} unsupported, {} unreadable)\nSee {}",
+                self.unsupported,
+                self.skipped,
+                SCAN_REPORT_PATH.display()
+            )?;
+        }
+        Ok(())
+    }
 }
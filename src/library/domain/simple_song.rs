@@ -1,10 +1,10 @@
 use super::{FileType, SongInfo};
-use crate::{Database, DurationStyle, get_readable_duration};
+use crate::{Database, DurationStyle, get_readable_duration, library::cue::CueSpan};
 use anyhow::Result;
 use std::{
     sync::{
         Arc,
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
     },
     time::Duration,
 };
@@ -20,6 +20,46 @@ pub struct SimpleSong {
     pub(crate) disc_no: Option<u32>,
     pub(crate) duration: AtomicU64,
     pub(crate) filetype: FileType,
+    pub(crate) rating: AtomicU8,
+    pub(crate) loved: AtomicBool,
+    /// Set for tracks cut from a larger file by a cue sheet
+    pub(crate) cue: Option<CueSpan>,
+}
+
+impl SimpleSong {
+    pub const MAX_RATING: u8 = 5;
+
+    /// Stars out of `MAX_RATING`, 0 meaning unrated
+    pub fn get_rating(&self) -> u8 {
+        self.rating.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_rating(&self, rating: u8) {
+        self.rating
+            .store(rating.min(Self::MAX_RATING), Ordering::Relaxed);
+    }
+
+    pub fn is_loved(&self) -> bool {
+        self.loved.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_loved(&self, loved: bool) {
+        self.loved.store(loved, Ordering::Relaxed);
+    }
+
+    pub fn cue(&self) -> Option<CueSpan> {
+        self.cue
+    }
+
+    /// Untagged songs count as being on the first disc
+    pub fn disc(&self) -> u32 {
+        self.disc_no.filter(|&d| d > 0).unwrap_or(1)
+    }
+
+    /// Where the song falls in its album
+    pub fn album_order(&self) -> (u32, u32) {
+        (self.disc(), self.track_no.unwrap_or(0))
+    }
 }
 
 /// DATABASE RELATED METHODS
@@ -50,6 +90,11 @@ impl super::SongDatabase for SimpleSong {
     }
 
     fn update_duration_db(&self, cand: Duration) -> anyhow::Result<()> {
+        // The engine only knows the length of the whole file
+        if self.cue.is_some() {
+            return Ok(());
+        }
+
         let cand_f32 = cand.as_secs_f32();
         let current = self.get_duration_f32();
 
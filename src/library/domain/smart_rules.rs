@@ -0,0 +1,273 @@
+use super::FileType;
+use anyhow::{Result, anyhow, bail};
+use std::fmt::Display;
+
+/// Song attributes a smart playlist can filter on
+#[derive(Clone, Copy, PartialEq)]
+pub enum RuleField {
+    Title,
+    Artist,
+    Album,
+    Genre,
+    Year,
+    Plays,
+    Duration,
+    Format,
+    Rating,
+}
+
+impl RuleField {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "title" => RuleField::Title,
+            "artist" => RuleField::Artist,
+            "album" => RuleField::Album,
+            "genre" => RuleField::Genre,
+            "year" => RuleField::Year,
+            "plays" | "playcount" => RuleField::Plays,
+            "duration" | "length" => RuleField::Duration,
+            "format" | "filetype" => RuleField::Format,
+            "rating" | "stars" => RuleField::Rating,
+            _ => bail!(
+                "Unknown field '{s}'\n\nAvailable: title, artist, album, genre, year, plays, duration, format, rating"
+            ),
+        })
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            RuleField::Year | RuleField::Plays | RuleField::Duration | RuleField::Rating
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RuleOp {
+    Is,
+    IsNot,
+    Contains,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl RuleOp {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.to_lowercase().as_str() {
+            "is" | "=" | "==" => RuleOp::Is,
+            "isnt" | "not" | "!=" => RuleOp::IsNot,
+            "has" | "contains" | "~" => RuleOp::Contains,
+            ">" => RuleOp::Greater,
+            ">=" => RuleOp::GreaterEq,
+            "<" => RuleOp::Less,
+            "<=" => RuleOp::LessEq,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum RuleValue {
+    Text(String),
+    Number(f64),
+    Format(FileType),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Condition {
+    pub field: RuleField,
+    pub op: RuleOp,
+    pub value: RuleValue,
+}
+
+impl Condition {
+    fn parse(tokens: &[Token]) -> Result<Self> {
+        let [field, op_tokens @ ..] = tokens else {
+            bail!("Empty condition");
+        };
+        let field = RuleField::parse(&field.text)?;
+
+        // `is not` reads better than `isnt`, so accept it as one operator
+        let (op, value_tokens) = match op_tokens {
+            [is, not, rest @ ..]
+                if is.text.eq_ignore_ascii_case("is") && not.text.eq_ignore_ascii_case("not") =>
+            {
+                (RuleOp::IsNot, rest)
+            }
+            [op, rest @ ..] => (
+                RuleOp::parse(&op.text).ok_or_else(|| anyhow!("Unknown operator '{}'", op.text))?,
+                rest,
+            ),
+            [] => bail!("Condition is missing an operator"),
+        };
+
+        if value_tokens.is_empty() {
+            bail!("Condition is missing a value");
+        }
+        let raw = value_tokens
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let value = match field {
+            f if f.is_numeric() => {
+                if op == RuleOp::Contains {
+                    bail!("'has' only works on text fields");
+                }
+                RuleValue::Number(
+                    raw.parse()
+                        .map_err(|_| anyhow!("Expected a number, found '{raw}'"))?,
+                )
+            }
+            RuleField::Format => {
+                if !matches!(op, RuleOp::Is | RuleOp::IsNot) {
+                    bail!("Formats can only be compared with 'is' or 'is not'");
+                }
+                match FileType::from(raw.as_str()) {
+                    FileType::ERR => bail!("Unknown format '{raw}'"),
+                    ft => RuleValue::Format(ft),
+                }
+            }
+            _ => {
+                if !matches!(op, RuleOp::Is | RuleOp::IsNot | RuleOp::Contains) {
+                    bail!("Text fields can only be compared with 'is', 'is not' or 'has'");
+                }
+                RuleValue::Text(raw)
+            }
+        };
+
+        Ok(Condition { field, op, value })
+    }
+}
+
+/// A smart playlist's rule set: conditions that must all hold.
+///
+/// Only the source text is stored, so the playlist re-resolves against the
+/// library every time it's built.
+#[derive(Clone, PartialEq)]
+pub struct SmartRules {
+    source: String,
+    conditions: Vec<Condition>,
+}
+
+impl SmartRules {
+    /// Parses e.g. `artist has miles AND year >= 1960 AND plays > 5`.
+    /// Quote values that contain spaces or the word "and".
+    pub fn parse(source: &str) -> Result<Self> {
+        let source = source.trim();
+        if source.is_empty() {
+            bail!("Rules cannot be empty!");
+        }
+
+        let tokens = tokenize(source)?;
+        let conditions = tokens
+            .split(|t| !t.quoted && t.text.eq_ignore_ascii_case("and"))
+            .map(Condition::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SmartRules {
+            source: source.to_string(),
+            conditions,
+        })
+    }
+
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+}
+
+impl Display for SmartRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '"' | '\'' => {
+                chars.next();
+                let text: String = chars.by_ref().take_while(|&ch| ch != c).collect();
+                tokens.push(Token { text, quoted: true });
+            }
+            _ => {
+                let mut text = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() {
+                        break;
+                    }
+                    text.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token {
+                    text,
+                    quoted: false,
+                });
+            }
+        }
+    }
+
+    match tokens.is_empty() {
+        true => bail!("Rules cannot be empty!"),
+        false => Ok(tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_compound_rules() {
+        let rules =
+            SmartRules::parse("artist has 'simon and garfunkel' AND year >= 1960 AND stars >= 4")
+                .unwrap();
+        let conditions = rules.conditions();
+
+        assert_eq!(conditions.len(), 3);
+        assert!(conditions[0].op == RuleOp::Contains);
+        assert!(conditions[0].value == RuleValue::Text("simon and garfunkel".into()));
+        assert!(conditions[1].field == RuleField::Year);
+        assert!(conditions[1].value == RuleValue::Number(1960.0));
+        assert!(conditions[2].field == RuleField::Rating);
+    }
+
+    #[test]
+    fn parses_genre_as_text() {
+        let rules = SmartRules::parse("genre is Jazz AND year < 1970").unwrap();
+        assert!(rules.conditions()[0].field == RuleField::Genre);
+        assert!(rules.conditions()[0].op == RuleOp::Is);
+        assert!(rules.conditions()[0].value == RuleValue::Text("Jazz".into()));
+    }
+
+    #[test]
+    fn accepts_is_not() {
+        let rules = SmartRules::parse("format is not mp3").unwrap();
+        assert!(rules.conditions()[0].op == RuleOp::IsNot);
+        assert!(rules.conditions()[0].value == RuleValue::Format(FileType::MP3));
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert!(SmartRules::parse("").is_err());
+        assert!(SmartRules::parse("mood is calm").is_err());
+        assert!(SmartRules::parse("genre > 5").is_err());
+        assert!(SmartRules::parse("year >= nineteen").is_err());
+        assert!(SmartRules::parse("plays > 5 AND").is_err());
+        assert!(SmartRules::parse("title > 5").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::library::cue::split_track_path;
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Default)]
+pub struct Folder {
+    pub subfolders: Vec<Arc<PathBuf>>,
+    /// In filename order, cue tracks by number
+    pub songs: Vec<u64>,
+}
+
+/// The library's directories, as far down as there are songs in them.
+/// Built from the stored paths rather than the disk, so it can't loop.
+#[derive(Default)]
+pub struct FolderTree {
+    folders: BTreeMap<PathBuf, Folder>,
+    top: Vec<Arc<PathBuf>>,
+}
+
+impl FolderTree {
+    /// Songs outside every root, which a followed symlink can lead to, are
+    /// listed under their own folder at the top
+    pub fn build(roots: &HashSet<PathBuf>, paths: impl IntoIterator<Item = (u64, String)>) -> Self {
+        let mut files: BTreeMap<PathBuf, Vec<(String, Option<u32>, u64)>> = BTreeMap::new();
+        for (id, stored) in paths {
+            let (audio, track) = split_track_path(&stored);
+            let audio = Path::new(audio);
+            if let (Some(dir), Some(name)) = (audio.parent(), audio.file_name()) {
+                let name = name.to_string_lossy().to_lowercase();
+                files
+                    .entry(dir.to_path_buf())
+                    .or_default()
+                    .push((name, track, id));
+            }
+        }
+
+        let mut tree = FolderTree::default();
+        for (dir, mut songs) in files {
+            songs.sort();
+            tree.insert(&dir, roots);
+            if let Some(folder) = tree.folders.get_mut(&dir) {
+                folder.songs = songs.into_iter().map(|(_, _, id)| id).collect();
+            }
+        }
+
+        for folder in tree.folders.values_mut() {
+            folder.subfolders.sort_by_cached_key(|p| sort_key(p));
+        }
+        tree.top.sort_by_cached_key(|p| sort_key(p));
+        tree
+    }
+
+    /// Adds `dir` and whatever lies between it and its root
+    fn insert(&mut self, dir: &Path, roots: &HashSet<PathBuf>) {
+        if self.folders.contains_key(dir) {
+            return;
+        }
+        self.folders.insert(dir.to_path_buf(), Folder::default());
+
+        let under_root = roots.iter().any(|r| dir.starts_with(r) && dir != r);
+        match dir.parent().filter(|_| under_root) {
+            Some(parent) => {
+                self.insert(parent, roots);
+                if let Some(folder) = self.folders.get_mut(parent) {
+                    folder.subfolders.push(Arc::new(dir.to_path_buf()));
+                }
+            }
+            None => self.top.push(Arc::new(dir.to_path_buf())),
+        }
+    }
+
+    pub fn top(&self) -> &[Arc<PathBuf>] {
+        &self.top
+    }
+
+    pub fn get(&self, dir: &Path) -> Option<&Folder> {
+        self.folders.get(dir)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.folders.keys()
+    }
+
+    /// Everything in `dir` and below, each folder's own songs before its
+    /// subfolders'
+    pub fn songs_under(&self, dir: &Path) -> Vec<u64> {
+        let mut songs = Vec::new();
+        let mut stack = vec![dir];
+
+        while let Some(dir) = stack.pop() {
+            let Some(folder) = self.folders.get(dir) else {
+                continue;
+            };
+            songs.extend(&folder.songs);
+            stack.extend(folder.subfolders.iter().rev().map(|p| p.as_path()));
+        }
+        songs
+    }
+}
+
+fn sort_key(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nests_folders_under_roots() {
+        let roots = HashSet::from([PathBuf::from("/music")]);
+        let tree = FolderTree::build(
+            &roots,
+            [
+                (1, "/music/b/deep/x.flac".to_string()),
+                (2, "/music/a/2.flac".to_string()),
+                (3, "/music/a/1.flac#10".to_string()),
+                (4, "/music/a/1.flac#2".to_string()),
+                (5, "/elsewhere/y.mp3".to_string()),
+            ],
+        );
+
+        assert_eq!(
+            tree.top(),
+            [
+                Arc::new(PathBuf::from("/elsewhere")),
+                Arc::new(PathBuf::from("/music"))
+            ]
+        );
+        assert_eq!(tree.get(Path::new("/music/a")).unwrap().songs, [4, 3, 2]);
+        assert_eq!(tree.songs_under(Path::new("/music")), [4, 3, 2, 1]);
+    }
+}
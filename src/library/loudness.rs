@@ -0,0 +1,500 @@
+use crate::{Database, library::cue::split_track_path};
+use anyhow::{Result, anyhow};
+use std::{
+    collections::VecDeque,
+    f64::consts::PI,
+    fs::File,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+use symphonia::{
+    core::{
+        codecs::audio::AudioDecoderOptions,
+        errors::Error as SymphoniaErr,
+        formats::{FormatOptions, TrackType, probe::Hint},
+        io::MediaSourceStream,
+        meta::{MetadataOptions, StandardTag},
+    },
+    default::{get_codecs, get_probe},
+};
+
+/// The level ReplayGain 2.0 brings everything to, in LUFS
+const REFERENCE_LUFS: f64 = -18.0;
+
+/// Blocks quieter than this are left out entirely (BS.1770 absolute gate)
+const ABSOLUTE_GATE: f64 = -70.0;
+/// ...and so are blocks this far below the ungated loudness
+const RELATIVE_GATE: f64 = -10.0;
+/// Anything louder lands in the top bin; real masters don't get close
+const HISTOGRAM_TOP: f64 = 5.0;
+/// Bins are 0.1 LU wide, well below what anyone can hear
+const BINS_PER_LU: f64 = 10.0;
+const BINS: usize = ((HISTOGRAM_TOP - ABSOLUTE_GATE) * BINS_PER_LU) as usize;
+
+/// Gating blocks are 400ms, overlapping by 75%, so a new one closes every
+/// 100ms step
+const STEP_SECS: f64 = 0.1;
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Songs are measured in batches between database round trips
+const BATCH: usize = 32;
+
+/// How many gating blocks fell at each loudness. Keeping the distribution
+/// rather than a single figure is what lets an album be measured exactly
+/// from its tracks: the histograms just add up.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Histogram(Vec<u32>);
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram(vec![0; BINS])
+    }
+}
+
+impl Histogram {
+    fn add(&mut self, lufs: f64) {
+        if lufs >= ABSOLUTE_GATE {
+            let bin = ((lufs - ABSOLUTE_GATE) * BINS_PER_LU) as usize;
+            self.0[bin.min(BINS - 1)] += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a += b;
+        }
+    }
+
+    /// Gated integrated loudness, or `None` for silence
+    fn integrated(&self) -> Option<f64> {
+        let gated_mean = |gate: f64| {
+            let (energy, count) = self
+                .0
+                .iter()
+                .enumerate()
+                .filter(|&(bin, &n)| n > 0 && bin_lufs(bin) >= gate)
+                .fold((0.0, 0u64), |(e, c), (bin, &n)| {
+                    (e + n as f64 * energy(bin_lufs(bin)), c + n as u64)
+                });
+            (count > 0).then(|| lufs(energy / count as f64))
+        };
+
+        let ungated = gated_mean(ABSOLUTE_GATE)?;
+        gated_mean(ungated + RELATIVE_GATE)
+    }
+
+    /// Only the bins that were hit, as little-endian `(bin: u16, count: u32)`
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n > 0)
+            .flat_map(|(bin, n)| {
+                let mut entry = (bin as u16).to_le_bytes().to_vec();
+                entry.extend_from_slice(&n.to_le_bytes());
+                entry
+            })
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut histogram = Histogram::default();
+        for entry in bytes.chunks_exact(6) {
+            let bin = u16::from_le_bytes([entry[0], entry[1]]) as usize;
+            if let Some(slot) = histogram.0.get_mut(bin) {
+                *slot = u32::from_le_bytes([entry[2], entry[3], entry[4], entry[5]]);
+            }
+        }
+        histogram
+    }
+}
+
+fn bin_lufs(bin: usize) -> f64 {
+    ABSOLUTE_GATE + (bin as f64 + 0.5) / BINS_PER_LU
+}
+
+fn energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+fn lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// What analysis found out about a song
+#[derive(Clone, PartialEq, Debug)]
+pub enum Measurement {
+    /// Carries ReplayGain tags, which the engine applies on its own
+    Tagged,
+    Measured(Loudness),
+    /// Couldn't be decoded here; not tried again until the file changes
+    Failed,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Loudness {
+    histogram: Histogram,
+    /// Sample peak, linear
+    peak: f32,
+}
+
+impl Loudness {
+    pub fn from_parts(histogram: &[u8], peak: f32) -> Self {
+        Loudness {
+            histogram: Histogram::from_bytes(histogram),
+            peak,
+        }
+    }
+
+    pub fn histogram_bytes(&self) -> Vec<u8> {
+        self.histogram.to_bytes()
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    pub fn merge(&mut self, other: &Loudness) {
+        self.histogram.merge(&other.histogram);
+        self.peak = self.peak.max(other.peak);
+    }
+
+    pub fn integrated(&self) -> Option<f64> {
+        self.histogram.integrated()
+    }
+
+    /// Linear gain to the ReplayGain reference, held back where it would
+    /// push the peak past full scale, the same as with tagged gain
+    pub fn linear_gain(&self) -> Option<f32> {
+        let gain = 10f64.powf((REFERENCE_LUFS - self.integrated()?) / 20.0) as f32;
+        Some(match self.peak > 0.0 {
+            true => gain.min(1.0 / self.peak),
+            false => gain,
+        })
+    }
+}
+
+/// One stage of the K-weighting filter, in direct form I
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Coefficients derived for any sample rate, after libebur128, rather
+    /// than the 48kHz table in the standard
+    fn k_weighting(rate: f64) -> [Biquad; 2] {
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (PI * f0 / rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            ..Default::default()
+        };
+
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (PI * f0 / rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            ..Default::default()
+        };
+
+        [shelf, high_pass]
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// ITU-R BS.1770 loudness, fed interleaved samples as they're decoded
+pub struct Meter {
+    filters: Vec<[Biquad; 2]>,
+    weights: Vec<f64>,
+    step_frames: usize,
+    frames: usize,
+    sums: Vec<f64>,
+    steps: VecDeque<f64>,
+    loudness: Loudness,
+}
+
+impl Meter {
+    pub fn new(rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        // In a 5.1 layout the LFE is left out and the surrounds count
+        // for a little more
+        let weights = (0..channels)
+            .map(|ch| match (channels, ch) {
+                (6.., 3) => 0.0,
+                (6.., 4 | 5) => 1.41,
+                _ => 1.0,
+            })
+            .collect();
+
+        Meter {
+            filters: vec![Biquad::k_weighting(rate as f64); channels],
+            weights,
+            step_frames: ((rate as f64 * STEP_SECS) as usize).max(1),
+            frames: 0,
+            sums: vec![0.0; channels],
+            steps: VecDeque::with_capacity(STEPS_PER_BLOCK),
+            loudness: Loudness::default(),
+        }
+    }
+
+    pub fn feed(&mut self, samples: &[f32]) {
+        let channels = self.filters.len();
+        for frame in samples.chunks_exact(channels) {
+            for (ch, &s) in frame.iter().enumerate() {
+                self.loudness.peak = self.loudness.peak.max(s.abs());
+                let [shelf, high_pass] = &mut self.filters[ch];
+                let y = high_pass.process(shelf.process(s as f64));
+                self.sums[ch] += y * y;
+            }
+
+            self.frames += 1;
+            if self.frames == self.step_frames {
+                self.close_step();
+            }
+        }
+    }
+
+    fn close_step(&mut self) {
+        let step = self
+            .sums
+            .iter()
+            .zip(&self.weights)
+            .map(|(sum, w)| w * sum / self.frames as f64)
+            .sum::<f64>();
+        self.sums.fill(0.0);
+        self.frames = 0;
+
+        if self.steps.len() == STEPS_PER_BLOCK {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+
+        if self.steps.len() == STEPS_PER_BLOCK {
+            let block = self.steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64;
+            if block > 0.0 {
+                self.loudness.histogram.add(lufs(block));
+            }
+        }
+    }
+
+    pub fn finish(self) -> Loudness {
+        self.loudness
+    }
+}
+
+/// Measures a song, or only the span of it a cue track covers. Files that
+/// already carry ReplayGain tags aren't decoded.
+pub fn measure(stored_path: &str, cue: Option<(f64, Option<f64>)>) -> Result<Measurement> {
+    let (audio, _) = split_track_path(stored_path);
+    let path = Path::new(audio);
+
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut format = get_probe().probe(
+        &hint,
+        mss,
+        FormatOptions::default(),
+        MetadataOptions::default(),
+    )?;
+
+    let track = format
+        .default_track(TrackType::Audio)
+        .ok_or_else(|| anyhow!("No audio tracks!"))?;
+    let track_id = track.id;
+    let params = track
+        .codec_params
+        .as_ref()
+        .and_then(|cp| cp.audio())
+        .ok_or_else(|| anyhow!("No audio codec parameters"))?
+        .clone();
+
+    if let Some(revision) = format.metadata().skip_to_latest() {
+        let tagged = revision
+            .media
+            .tags
+            .iter()
+            .chain(
+                revision
+                    .per_track
+                    .iter()
+                    .flat_map(|t| t.metadata.tags.iter()),
+            )
+            .any(|tag| {
+                matches!(
+                    tag.std,
+                    Some(StandardTag::ReplayGainTrackGain(_) | StandardTag::ReplayGainAlbumGain(_))
+                )
+            });
+        if tagged {
+            return Ok(Measurement::Tagged);
+        }
+    }
+
+    let mut decoder = get_codecs().make_audio_decoder(&params, &AudioDecoderOptions::default())?;
+    let mut meter = None;
+    let mut samples = Vec::new();
+    let mut seen = 0u64;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(Some(p)) => p,
+            Ok(None) => break,
+            Err(SymphoniaErr::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+        if packet.track_id != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(d) => d,
+            Err(SymphoniaErr::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+
+        let (rate, channels) = (decoded.spec().rate(), decoded.spec().channels().count());
+        let frames = decoded.frames() as u64;
+        decoded.copy_to_vec_interleaved::<f32>(&mut samples);
+
+        // A cue track only counts the frames between its start and end
+        let (from, to) = match cue {
+            Some((start, end)) => (
+                (start * rate as f64) as u64,
+                end.map_or(u64::MAX, |e| (e * rate as f64) as u64),
+            ),
+            None => (0, u64::MAX),
+        };
+        let first = from.saturating_sub(seen).min(frames) as usize;
+        let last = to.saturating_sub(seen).min(frames) as usize;
+        seen += frames;
+
+        if first < last {
+            let meter = meter.get_or_insert_with(|| Meter::new(rate, channels));
+            meter.feed(&samples[first * channels..last * channels]);
+        }
+        if seen >= to {
+            break;
+        }
+    }
+
+    Ok(match meter {
+        Some(meter) => Measurement::Measured(meter.finish()),
+        None => Measurement::Failed,
+    })
+}
+
+/// Measures every song that hasn't been yet, one at a time on its own
+/// thread so playback keeps the disk and the cores it needs. Each result
+/// is stored as soon as it's known, so a pass cut short by quitting picks
+/// up where it left off on the next start.
+pub struct LoudnessScan {
+    cancel: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl LoudnessScan {
+    pub fn start() -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancel);
+
+        let handle = thread::spawn(move || {
+            let Ok(mut db) = Database::open() else {
+                return;
+            };
+
+            while !stop.load(Ordering::Relaxed) {
+                let pending = match db.get_unmeasured(BATCH) {
+                    Ok(pending) if !pending.is_empty() => pending,
+                    _ => break,
+                };
+
+                for (id, path, cue) in pending {
+                    if stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let measurement = measure(&path, cue).unwrap_or(Measurement::Failed);
+                    if db.set_loudness(id, &measurement).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        LoudnessScan { cancel, handle }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+impl Drop for LoudnessScan {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(rate: u32, secs: f64, amplitude: f32) -> Vec<f32> {
+        (0..(rate as f64 * secs) as usize)
+            .map(|i| amplitude * (2.0 * PI * 997.0 * i as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn measures_and_merges_loudness() {
+        // A full-scale 997Hz sine on one channel reads -3.01 LUFS
+        let mut meter = Meter::new(48000, 1);
+        meter.feed(&sine(48000, 5.0, 0.5));
+        let loud = meter.finish();
+        let measured = loud.integrated().unwrap();
+        assert!((measured - -9.03).abs() < 0.1, "{measured}");
+
+        let mut meter = Meter::new(44100, 1);
+        meter.feed(&sine(44100, 5.0, 0.05));
+        let quiet = meter.finish();
+
+        let stored = Loudness::from_parts(&loud.histogram_bytes(), loud.peak());
+        assert_eq!(stored, loud);
+
+        // The quiet half falls under the relative gate and doesn't drag
+        // the album down
+        let mut album = loud.clone();
+        album.merge(&quiet);
+        assert!((album.integrated().unwrap() - measured).abs() < 0.1);
+        assert_eq!(album.peak(), 0.5);
+
+        assert_eq!(Meter::new(48000, 2).finish().integrated(), None);
+    }
+}
@@ -0,0 +1,276 @@
+use std::{fs, fs::File, path::Path, time::Duration};
+use symphonia::{
+    core::{
+        formats::probe::Hint,
+        io::MediaSourceStream,
+        meta::{RawValue, StandardTag},
+    },
+    default::get_probe,
+};
+
+pub enum Lyrics {
+    /// Each line with the time it starts, in order
+    Synced(Vec<(Duration, String)>),
+    Plain(Vec<String>),
+}
+
+impl Lyrics {
+    pub fn len(&self) -> usize {
+        match self {
+            Lyrics::Synced(lines) => lines.len(),
+            Lyrics::Plain(lines) => lines.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn line(&self, idx: usize) -> &str {
+        match self {
+            Lyrics::Synced(lines) => &lines[idx].1,
+            Lyrics::Plain(lines) => &lines[idx],
+        }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        matches!(self, Lyrics::Synced(_))
+    }
+
+    /// The line being sung at `position`, none before the first one starts
+    pub fn active_line(&self, position: Duration) -> Option<usize> {
+        match self {
+            Lyrics::Synced(lines) => lines
+                .partition_point(|(start, _)| *start <= position)
+                .checked_sub(1),
+            Lyrics::Plain(_) => None,
+        }
+    }
+}
+
+/// Lyrics for a song: an `.lrc` file next to it, then a synced `SYLT` frame,
+/// then whatever unsynced lyrics are tagged (which may be LRC text too).
+pub fn load_lyrics(song_path: &Path) -> Option<Lyrics> {
+    let sidecar = song_path.with_extension("lrc");
+    if let Ok(text) = fs::read_to_string(&sidecar)
+        && let Some(lyrics) = parse_lrc(&text)
+    {
+        return Some(lyrics);
+    }
+
+    read_embedded(song_path)
+}
+
+fn read_embedded(path: &Path) -> Option<Lyrics> {
+    let src = File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let mut probed = get_probe()
+        .probe(&hint, mss, Default::default(), Default::default())
+        .ok()?;
+
+    let mut unsynced = None;
+    let mut metadata = probed.metadata();
+    loop {
+        if let Some(md) = metadata.current() {
+            for tag in &md.media.tags {
+                if tag.raw.key == "SYLT"
+                    && let RawValue::Binary(data) = &tag.raw.value
+                    && let Some(lyrics) = parse_sylt(data)
+                {
+                    return Some(lyrics);
+                }
+                if unsynced.is_none()
+                    && let Some(StandardTag::Lyrics(text)) = &tag.std
+                {
+                    unsynced = parse_lrc(text);
+                }
+            }
+        }
+        if metadata.is_latest() {
+            return unsynced;
+        }
+        metadata.pop();
+    }
+}
+
+/// Lines are led by one or more `[mm:ss.xx]` stamps. Text without any is
+/// taken as plain lyrics, unless some other line had one.
+pub(crate) fn parse_lrc(text: &str) -> Option<Lyrics> {
+    let mut synced = Vec::new();
+    let mut plain = Vec::new();
+    // Positive is earlier
+    let mut offset_ms = 0i64;
+
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let mut rest = line.trim();
+        let mut stamps = Vec::new();
+
+        while let Some(tag) = rest.strip_prefix('[')
+            && let Some((tag, after)) = tag.split_once(']')
+        {
+            if let Some(time) = parse_stamp(tag) {
+                stamps.push(time);
+            } else if let Some(ms) = tag.strip_prefix("offset:") {
+                offset_ms = ms.trim().parse().unwrap_or(0);
+            } else if !tag.contains(':') {
+                // Not a tag after all, just a bracket in the text
+                break;
+            }
+            rest = after.trim_start();
+        }
+
+        match stamps.is_empty() {
+            true if rest.is_empty() && line.trim_start().starts_with('[') => (),
+            true => plain.push(rest.to_string()),
+            false => synced.extend(stamps.into_iter().map(|t| (t, rest.to_string()))),
+        }
+    }
+
+    if !synced.is_empty() {
+        let shift = Duration::from_millis(offset_ms.unsigned_abs());
+        for (time, _) in &mut synced {
+            *time = match offset_ms >= 0 {
+                true => time.saturating_sub(shift),
+                false => *time + shift,
+            };
+        }
+        synced.sort_by_key(|(time, _)| *time);
+        return Some(Lyrics::Synced(synced));
+    }
+
+    // Blank lines at either end say nothing
+    let start = plain.iter().position(|l| !l.is_empty())?;
+    let end = plain.iter().rposition(|l| !l.is_empty())?;
+    Some(Lyrics::Plain(plain.drain(start..=end).collect()))
+}
+
+/// `mm:ss`, `mm:ss.xx` or `mm:ss:xx`, the minutes running past 59
+fn parse_stamp(tag: &str) -> Option<Duration> {
+    let (mins, secs) = tag.split_once(':')?;
+    let mins: u64 = mins.trim().parse().ok()?;
+
+    let (secs, fraction) = match secs.split_once(['.', ':']) {
+        Some((s, f)) => (s, f),
+        None => (secs, ""),
+    };
+    let secs: u64 = secs.trim().parse().ok()?;
+    let millis = match fraction.len() {
+        0 => 0,
+        n => {
+            let digits: u64 = fraction.parse().ok()?;
+            match n {
+                1 => digits * 100,
+                2 => digits * 10,
+                3 => digits,
+                _ => digits / 10u64.pow(n as u32 - 3),
+            }
+        }
+    };
+
+    Some(Duration::from_millis((mins * 60 + secs) * 1000 + millis))
+}
+
+/// An ID3v2 `SYLT` frame: encoding, language, timestamp format, content
+/// type and a description, then each piece of text followed by its time.
+/// Only millisecond stamps are read, MPEG frame counts mean nothing here.
+fn parse_sylt(data: &[u8]) -> Option<Lyrics> {
+    let (&encoding, rest) = data.split_first()?;
+    let rest = rest.get(3..)?;
+    let (&format, rest) = rest.split_first()?;
+    if format != 2 {
+        return None;
+    }
+    let rest = rest.get(1..)?;
+    let (_, mut rest) = split_string(rest, encoding)?;
+
+    let mut lines: Vec<(Duration, String)> = Vec::new();
+    while !rest.is_empty() {
+        let (text, after) = split_string(rest, encoding)?;
+        let stamp = after.get(..4)?;
+        let ms = u32::from_be_bytes([stamp[0], stamp[1], stamp[2], stamp[3]]);
+        rest = &after[4..];
+
+        // Text starting on a new line begins a new lyric line; anything
+        // else carries on the one before, a syllable at a time
+        let time = Duration::from_millis(ms as u64);
+        match lines.last_mut() {
+            Some((_, line)) if !text.starts_with(['\n', '\r']) => line.push_str(&text),
+            _ => lines.push((time, text.trim_start_matches(['\n', '\r']).to_string())),
+        }
+    }
+
+    match lines.is_empty() {
+        true => None,
+        false => Some(Lyrics::Synced(lines)),
+    }
+}
+
+/// One terminated string off the front of `data`, decoded
+fn split_string(data: &[u8], encoding: u8) -> Option<(String, &[u8])> {
+    match encoding {
+        // Latin-1 and UTF-8 end on a single zero
+        0 | 3 => {
+            let end = data.iter().position(|&b| b == 0)?;
+            let bytes = &data[..end];
+            let text = match encoding {
+                0 => bytes.iter().map(|&b| b as char).collect(),
+                _ => String::from_utf8_lossy(bytes).into_owned(),
+            };
+            Some((text, &data[end + 1..]))
+        }
+        // UTF-16 ends on a zero pair
+        1 | 2 => {
+            let end = data.chunks_exact(2).position(|c| c == [0, 0])? * 2;
+            let mut bytes = &data[..end];
+            let mut big_endian = encoding == 2;
+            match bytes {
+                [0xFE, 0xFF, rest @ ..] => (big_endian, bytes) = (true, rest),
+                [0xFF, 0xFE, rest @ ..] => (big_endian, bytes) = (false, rest),
+                _ => (),
+            }
+
+            let units = bytes.chunks_exact(2).map(|c| match big_endian {
+                true => u16::from_be_bytes([c[0], c[1]]),
+                false => u16::from_le_bytes([c[0], c[1]]),
+            });
+            let text = char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect();
+            Some((text, &data[end + 2..]))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_lrc_stamps_and_offset() {
+        let text = "[ar:Someone]\n[offset:500]\n[00:12.00]First\n[01:02.5][00:30.250]Twice\n\n";
+        let Some(Lyrics::Synced(lines)) = parse_lrc(text) else {
+            panic!("expected synced lyrics");
+        };
+
+        let ms = Duration::from_millis;
+        assert_eq!(lines[0], (ms(11_500), "First".to_string()));
+        assert_eq!(lines[1], (ms(29_750), "Twice".to_string()));
+        assert_eq!(lines[2], (ms(62_000), "Twice".to_string()));
+
+        let lyrics = Lyrics::Synced(lines);
+        assert_eq!(lyrics.active_line(ms(5_000)), None);
+        assert_eq!(lyrics.active_line(ms(30_000)), Some(1));
+
+        let Some(Lyrics::Plain(lines)) = parse_lrc("\nJust words\n[not a tag]\n") else {
+            panic!("expected plain lyrics");
+        };
+        assert_eq!(lines, vec!["Just words", "[not a tag]"]);
+    }
+}
@@ -1,9 +1,21 @@
+pub mod artist_info;
+pub mod cover_art;
+pub mod cue;
 mod domain;
+mod folders;
+mod loudness;
+pub mod lyrics;
+pub mod silence;
+mod tags;
 mod vox_library;
 
-pub use domain::LEGAL_EXTENSION;
 pub use domain::{
-    Album, FileType, LongSong, Playlist, PlaylistSong, RefreshProgress, RefreshStage, SimpleSong,
-    SongDatabase, SongInfo,
+    Album, Condition, FileType, LongSong, Playlist, PlaylistFolder, PlaylistSong, RefreshProgress,
+    RefreshStage, RuleField, RuleOp, RuleValue, ScanSummary, SimpleSong, SmartRules, SongDatabase,
+    SongInfo,
 };
+pub use domain::{LEGAL_EXTENSION, UNSUPPORTED_EXTENSION, is_unsupported};
+pub use folders::{Folder, FolderTree};
+pub use loudness::{Loudness, LoudnessScan, Measurement};
+pub use tags::{TagChange, TagField, write_tags};
 pub use vox_library::Library;
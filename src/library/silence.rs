@@ -0,0 +1,161 @@
+use anyhow::{Result, anyhow};
+use std::{fs::File, path::Path, time::Duration};
+use symphonia::{
+    core::{
+        codecs::audio::AudioDecoderOptions,
+        errors::Error as SymphoniaErr,
+        formats::{FormatOptions, TrackType, probe::Hint},
+        io::MediaSourceStream,
+        meta::MetadataOptions,
+    },
+    default::{get_codecs, get_probe},
+};
+
+/// Left either side of the sound, so a soft attack or a reverb tail that
+/// only just crosses the threshold isn't cut off
+const PAD_SECS: f64 = 0.1;
+
+/// The part of a song worth hearing. Neither end moves unless the silence
+/// there lasts long enough to be meant as silence.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Trim {
+    /// Seconds to skip at the top
+    pub start: f64,
+    /// Seconds in where it's done, `None` to play it out
+    pub end: Option<f64>,
+}
+
+/// Follows the first and last frames that rise above the threshold
+pub struct SilenceFinder {
+    threshold: f32,
+    rate: u32,
+    frames: u64,
+    first_loud: Option<u64>,
+    last_loud: u64,
+}
+
+impl SilenceFinder {
+    pub fn new(threshold_db: f32, rate: u32) -> Self {
+        SilenceFinder {
+            threshold: 10f32.powf(threshold_db / 20.0),
+            rate: rate.max(1),
+            frames: 0,
+            first_loud: None,
+            last_loud: 0,
+        }
+    }
+
+    pub fn feed(&mut self, samples: &[f32], channels: usize) {
+        for frame in samples.chunks_exact(channels.max(1)) {
+            if frame.iter().any(|s| s.abs() >= self.threshold) {
+                self.first_loud.get_or_insert(self.frames);
+                self.last_loud = self.frames;
+            }
+            self.frames += 1;
+        }
+    }
+
+    /// Nothing is trimmed from a song that never gets loud at all
+    pub fn finish(&self, min_silence: Duration) -> Trim {
+        let Some(first) = self.first_loud else {
+            return Trim::default();
+        };
+        let secs = |frames: u64| frames as f64 / self.rate as f64;
+        let min = min_silence.as_secs_f64();
+
+        let lead = secs(first);
+        let tail = secs(self.frames - self.last_loud - 1);
+        Trim {
+            start: match lead >= min {
+                true => (lead - PAD_SECS).max(0.0),
+                false => 0.0,
+            },
+            end: (tail >= min).then(|| secs(self.last_loud + 1) + PAD_SECS),
+        }
+    }
+}
+
+/// Decodes the whole file, as only the last loud frame says where the tail
+/// starts
+pub fn find_trim(path: &Path, threshold_db: f32, min_silence: Duration) -> Result<Trim> {
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut format = get_probe().probe(
+        &hint,
+        mss,
+        FormatOptions::default(),
+        MetadataOptions::default(),
+    )?;
+
+    let track = format
+        .default_track(TrackType::Audio)
+        .ok_or_else(|| anyhow!("No audio tracks!"))?;
+    let track_id = track.id;
+    let params = track
+        .codec_params
+        .as_ref()
+        .and_then(|cp| cp.audio())
+        .ok_or_else(|| anyhow!("No audio codec parameters"))?
+        .clone();
+
+    let mut decoder = get_codecs().make_audio_decoder(&params, &AudioDecoderOptions::default())?;
+    let mut finder = None;
+    let mut samples = Vec::new();
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(Some(p)) => p,
+            Ok(None) => break,
+            Err(SymphoniaErr::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+        if packet.track_id != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(d) => d,
+            Err(SymphoniaErr::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+
+        let (rate, channels) = (decoded.spec().rate(), decoded.spec().channels().count());
+        decoded.copy_to_vec_interleaved::<f32>(&mut samples);
+        finder
+            .get_or_insert_with(|| SilenceFinder::new(threshold_db, rate))
+            .feed(&samples, channels);
+    }
+
+    finder
+        .map(|f| f.finish(min_silence))
+        .ok_or_else(|| anyhow!("Nothing could be decoded"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_only_sustained_silence() {
+        let rate = 1000;
+        let mut finder = SilenceFinder::new(-60.0, rate);
+        // 3s of silence, 1s of sound, then 0.5s of silence
+        finder.feed(&vec![0.0; 3000], 1);
+        finder.feed(&vec![0.5; 1000], 1);
+        finder.feed(&vec![0.0; 500], 1);
+
+        let trim = finder.finish(Duration::from_secs(2));
+        assert!((trim.start - 2.9).abs() < 1e-9);
+        assert_eq!(trim.end, None);
+
+        let trim = finder.finish(Duration::from_millis(400));
+        assert!((trim.end.unwrap() - 4.1).abs() < 1e-9);
+
+        // A quiet intro still counts as sound
+        let mut finder = SilenceFinder::new(-60.0, rate);
+        finder.feed(&vec![0.01; 3000], 1);
+        assert_eq!(finder.finish(Duration::from_secs(2)).start, 0.0);
+    }
+}
@@ -0,0 +1,240 @@
+use super::{Retagged, TagChange, TagField, id3, read_at};
+use anyhow::{Result, bail};
+use std::io::{Read, Seek, SeekFrom};
+
+const STREAMINFO: u8 = 0;
+const PADDING: u8 = 1;
+const VORBIS_COMMENT: u8 = 4;
+
+/// Block lengths are 24 bits
+const MAX_BLOCK: usize = (1 << 24) - 1;
+/// Given when the tags outgrow the padding there was
+const NEW_PADDING: usize = 4096;
+
+impl TagField {
+    /// Every key the field is read from, the first being the one written
+    fn vorbis_keys(&self) -> &'static [&'static str] {
+        match self {
+            TagField::Title => &["TITLE"],
+            TagField::Artist => &["ARTIST"],
+            TagField::Album => &["ALBUM"],
+            TagField::AlbumArtist => &["ALBUMARTIST", "ALBUM ARTIST"],
+            TagField::Year => &["DATE", "YEAR"],
+            TagField::Genre => &["GENRE"],
+            TagField::Track => &["TRACKNUMBER", "TRACK"],
+        }
+    }
+}
+
+/// The file with its Vorbis comments changed. Pictures and any other
+/// blocks are carried over untouched.
+pub(super) fn retag<R: Read + Seek>(src: &mut R, changes: &[TagChange]) -> Result<Retagged> {
+    let file_len = src.seek(SeekFrom::End(0))?;
+    // Some taggers put an ID3v2 tag in front, which is kept as is
+    let start = (id3::tag_len(&read_at(src, 0, id3::HEADER as u64)?) as u64).min(file_len);
+    let id3_tag = read_at(src, 0, start)?;
+    if read_at(src, start, 4)? != b"fLaC" {
+        bail!("Not a FLAC file");
+    }
+
+    let mut blocks = Vec::new();
+    let mut pos = start + 4;
+    loop {
+        let Ok([flags, a, b, c]) = <[u8; 4]>::try_from(read_at(src, pos, 4)?) else {
+            bail!("Metadata is cut short");
+        };
+        let len = u32::from_be_bytes([0, a, b, c]) as usize;
+        let body = read_at(src, pos + 4, len as u64)?;
+        if body.len() < len {
+            bail!("Metadata is cut short");
+        }
+
+        blocks.push((flags & 0x7f, body));
+        pos += 4 + len as u64;
+        if flags & 0x80 != 0 {
+            break;
+        }
+    }
+    let metadata_len = (pos - start - 4) as usize;
+
+    if blocks.first().map(|(kind, _)| *kind) != Some(STREAMINFO) {
+        bail!("Stream info is missing");
+    }
+
+    let comments = match blocks.iter().find(|(kind, _)| *kind == VORBIS_COMMENT) {
+        Some((_, body)) => Comments::parse(body)?,
+        None => Comments::default(),
+    };
+    let comment_block = comments.apply(changes).write();
+    if comment_block.len() > MAX_BLOCK {
+        bail!("Tags are too large");
+    }
+
+    // Comments stay where they were, or go straight after the stream info
+    blocks.retain(|(kind, _)| *kind != PADDING);
+    match blocks.iter().position(|(kind, _)| *kind == VORBIS_COMMENT) {
+        Some(i) => blocks[i].1 = comment_block,
+        None => blocks.insert(1, (VORBIS_COMMENT, comment_block)),
+    }
+
+    // What's left of the old padding keeps the file the same size
+    let used = blocks.iter().map(|(_, body)| 4 + body.len()).sum::<usize>();
+    let padding = metadata_len.checked_sub(used + 4).unwrap_or(NEW_PADDING);
+    blocks.push((PADDING, vec![0; padding.min(MAX_BLOCK)]));
+
+    let mut head = Vec::with_capacity(id3_tag.len() + 4 + used + 4 + padding);
+    head.extend_from_slice(&id3_tag);
+    head.extend_from_slice(b"fLaC");
+    for (i, (kind, body)) in blocks.iter().enumerate() {
+        let last = if i + 1 == blocks.len() { 0x80 } else { 0 };
+        head.push(kind | last);
+        head.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        head.extend_from_slice(body);
+    }
+
+    Ok(Retagged {
+        head,
+        audio: pos..file_len,
+        tail: Vec::new(),
+    })
+}
+
+/// Each comment is a `KEY=value`, kept as stored in case it isn't UTF-8
+struct Comments {
+    vendor: Vec<u8>,
+    comments: Vec<Vec<u8>>,
+}
+
+impl Default for Comments {
+    fn default() -> Self {
+        Comments {
+            vendor: b"NoctaVox".to_vec(),
+            comments: Vec::new(),
+        }
+    }
+}
+
+impl Comments {
+    fn parse(body: &[u8]) -> Result<Self> {
+        let mut reader = Reader { body, pos: 0 };
+        let parsed = (|| {
+            let vendor = reader.field()?.to_vec();
+            let count = reader.len()?;
+            let comments = (0..count)
+                .map(|_| reader.field().map(<[u8]>::to_vec))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Comments { vendor, comments })
+        })();
+
+        match parsed {
+            Some(comments) => Ok(comments),
+            None => bail!("Vorbis comments are cut short"),
+        }
+    }
+
+    /// Whatever the field was read from goes, so an old value under another
+    /// key can't win out over the new one
+    fn apply(mut self, changes: &[TagChange]) -> Self {
+        for (field, value) in changes {
+            let keys = field.vorbis_keys();
+            self.comments.retain(|c| {
+                let key = c.split(|&b| b == b'=').next().unwrap_or_default();
+                !keys.iter().any(|k| key.eq_ignore_ascii_case(k.as_bytes()))
+            });
+
+            if !value.is_empty() {
+                self.comments
+                    .push(format!("{}={value}", keys[0]).into_bytes());
+            }
+        }
+        self
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_field(&mut out, &self.vendor);
+        out.extend_from_slice(&(self.comments.len() as u32).to_le_bytes());
+        for comment in &self.comments {
+            push_field(&mut out, comment);
+        }
+        out
+    }
+}
+
+fn push_field(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    body: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn len(&mut self) -> Option<usize> {
+        let bytes = self.body.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+    }
+
+    /// A length followed by that many bytes
+    fn field(&mut self) -> Option<&'a [u8]> {
+        let len = self.len()?;
+        let bytes = self.body.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retag(data: &[u8], changes: &[TagChange]) -> Vec<u8> {
+        super::super::retag_bytes(super::retag, data, changes)
+    }
+
+    fn comments(data: &[u8]) -> Vec<Vec<u8>> {
+        let body = &data[4 + 4 + 34 + 4..];
+        Comments::parse(body).unwrap().comments
+    }
+
+    fn file() -> Vec<u8> {
+        let mut file = b"fLaC\x80\x00\x00\x22".to_vec();
+        file.resize(file.len() + 34, 0);
+        file.extend_from_slice(b"audio");
+        file
+    }
+
+    #[test]
+    fn retags_in_place() {
+        let tagged = retag(&file(), &[(TagField::Title, "Intro".to_string())]);
+        assert!(tagged.ends_with(b"audio"));
+        assert_eq!(comments(&tagged), [b"TITLE=Intro".to_vec()]);
+
+        // Emptying a field takes it off, and the padding takes up the slack
+        let retagged = retag(
+            &tagged,
+            &[
+                (TagField::Track, "3".to_string()),
+                (TagField::Title, String::new()),
+            ],
+        );
+        assert_eq!(retagged.len(), tagged.len());
+        assert_eq!(comments(&retagged), [b"TRACKNUMBER=3".to_vec()]);
+    }
+
+    #[test]
+    fn keeps_an_id3_tag_in_front() {
+        let id3_tag = b"ID3\x04\x00\x00\x00\x00\x00\x04\x00\x00\x00\x00";
+        let file = [&id3_tag[..], &file()].concat();
+
+        let tagged = retag(&file, &[(TagField::Title, "Intro".to_string())]);
+
+        let (front, rest) = tagged.split_at(id3_tag.len());
+        assert_eq!(front, id3_tag);
+        assert!(rest.ends_with(b"audio"));
+        assert_eq!(comments(rest), [b"TITLE=Intro".to_vec()]);
+    }
+}
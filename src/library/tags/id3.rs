@@ -0,0 +1,357 @@
+use super::{Retagged, TagChange, TagField, read_at};
+use anyhow::{Result, bail};
+use std::io::{Read, Seek, SeekFrom};
+
+pub(super) const HEADER: usize = 10;
+/// Given when the tags outgrow the padding there was
+const NEW_PADDING: usize = 1024;
+/// Tag sizes are 28 bits
+const MAX_TAG: usize = (1 << 28) - 1;
+
+const V1_LEN: usize = 128;
+
+impl TagField {
+    /// Every frame the field is read from. The year can come from several,
+    /// and an older one left behind may be read over the new one.
+    fn id3_frames(&self) -> &'static [&'static [u8; 4]] {
+        match self {
+            TagField::Title => &[b"TIT2"],
+            TagField::Artist => &[b"TPE1"],
+            TagField::Album => &[b"TALB"],
+            TagField::AlbumArtist => &[b"TPE2"],
+            TagField::Year => &[b"TDRC", b"TYER", b"TDAT", b"TRDA", b"TDRL"],
+            TagField::Genre => &[b"TCON"],
+            TagField::Track => &[b"TRCK"],
+        }
+    }
+
+    /// Recording times became TDRC in v2.4
+    fn id3_written(&self, version: u8) -> &'static [u8; 4] {
+        match (self, version) {
+            (TagField::Year, 3) => b"TYER",
+            _ => self.id3_frames()[0],
+        }
+    }
+}
+
+/// How much of the start of a file is an ID3v2 tag, going by its header
+pub(super) fn tag_len(header: &[u8]) -> usize {
+    match header {
+        [b'I', b'D', b'3', _, _, flags, a, b, c, d, ..] => {
+            let footer = if flags & 0x10 != 0 { HEADER } else { 0 };
+            HEADER + syncsafe([*a, *b, *c, *d]) + footer
+        }
+        _ => 0,
+    }
+}
+
+/// The file with its ID3v2 frames changed, other frames being carried over
+/// untouched. A file without a tag gets a v2.4 one. An extended header or a
+/// footer isn't written back, as nothing in the tag needs them.
+pub(super) fn retag<R: Read + Seek>(src: &mut R, changes: &[TagChange]) -> Result<Retagged> {
+    let file_len = src.seek(SeekFrom::End(0))?;
+    let len = (tag_len(&read_at(src, 0, HEADER as u64)?) as u64).min(file_len);
+    let data = read_at(src, 0, len)?;
+
+    let (version, frames, room) = match data.len() {
+        0 => (4, Vec::new(), 0),
+        _ => {
+            let (version, flags) = (data[3], data[5]);
+            if !matches!(version, 3 | 4) {
+                bail!("ID3v2.{version} tags can't be edited");
+            }
+            if flags & 0x80 != 0 {
+                bail!("Unsynchronised tags can't be edited");
+            }
+
+            let end = (HEADER + syncsafe([data[6], data[7], data[8], data[9]])).min(data.len());
+            let mut start = HEADER;
+            // The extended header only holds checksums and such, and is dropped
+            if flags & 0x40 != 0 {
+                let Some(&[a, b, c, d]) = data.get(start..start + 4) else {
+                    bail!("Tag is cut short");
+                };
+                start += match version {
+                    4 => syncsafe([a, b, c, d]),
+                    _ => 4 + u32::from_be_bytes([a, b, c, d]) as usize,
+                };
+            }
+
+            let frames = read_frames(data.get(start..end).unwrap_or_default(), version)?;
+            (version, frames, end - HEADER)
+        }
+    };
+
+    let frames = apply(frames, changes, version);
+    let mut body = Vec::new();
+    for frame in &frames {
+        frame.write(&mut body, version);
+    }
+
+    let padding = room.checked_sub(body.len()).unwrap_or(NEW_PADDING);
+    let size = body.len() + padding;
+    if size > MAX_TAG {
+        bail!("Tags are too large");
+    }
+
+    let mut head = Vec::with_capacity(HEADER + size);
+    head.extend_from_slice(&[b'I', b'D', b'3', version, 0, 0]);
+    head.extend_from_slice(&to_syncsafe(size));
+    head.extend_from_slice(&body);
+    head.resize(head.len() + padding, 0);
+
+    // An ID3v1 tag at the end is read too, so it's kept in step as far as
+    // its fixed-width fields allow
+    let mut audio = len..file_len;
+    let mut tail = Vec::new();
+    if audio.end - audio.start >= V1_LEN as u64 {
+        let v1 = read_at(src, file_len - V1_LEN as u64, V1_LEN as u64)?;
+        if v1.starts_with(b"TAG") {
+            audio.end -= V1_LEN as u64;
+            tail = v1;
+            retag_v1(&mut tail, changes);
+        }
+    }
+
+    Ok(Retagged { head, audio, tail })
+}
+
+struct Frame {
+    id: [u8; 4],
+    flags: [u8; 2],
+    body: Vec<u8>,
+}
+
+impl Frame {
+    fn text(id: [u8; 4], value: &str, version: u8) -> Self {
+        // UTF-8 only arrived in v2.4, before it there's UTF-16
+        let body = match version {
+            4 => [&[3], value.as_bytes()].concat(),
+            _ => [1, 0xff, 0xfe]
+                .into_iter()
+                .chain(value.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+        };
+        Frame {
+            id,
+            flags: [0, 0],
+            body,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>, version: u8) {
+        out.extend_from_slice(&self.id);
+        match version {
+            4 => out.extend_from_slice(&to_syncsafe(self.body.len())),
+            _ => out.extend_from_slice(&(self.body.len() as u32).to_be_bytes()),
+        }
+        out.extend_from_slice(&self.flags);
+        out.extend_from_slice(&self.body);
+    }
+}
+
+fn read_frames(tag: &[u8], version: u8) -> Result<Vec<Frame>> {
+    let mut frames = Vec::new();
+    let mut pos = 0;
+
+    // Padding starts wherever a frame id would be a zero
+    while let Some(&[a, b, c, d, s1, s2, s3, s4, f1, f2]) = tag.get(pos..pos + HEADER) {
+        if a == 0 {
+            break;
+        }
+        let size = match version {
+            4 => syncsafe([s1, s2, s3, s4]),
+            _ => u32::from_be_bytes([s1, s2, s3, s4]) as usize,
+        };
+        let Some(body) = tag.get(pos + HEADER..pos + HEADER + size) else {
+            bail!("Tag is cut short");
+        };
+
+        frames.push(Frame {
+            id: [a, b, c, d],
+            flags: [f1, f2],
+            body: body.to_vec(),
+        });
+        pos += HEADER + size;
+    }
+    Ok(frames)
+}
+
+fn apply(mut frames: Vec<Frame>, changes: &[TagChange], version: u8) -> Vec<Frame> {
+    for (field, value) in changes {
+        let ids = field.id3_frames();
+        frames.retain(|f| !ids.contains(&&f.id));
+
+        if !value.is_empty() {
+            frames.push(Frame::text(*field.id3_written(version), value, version));
+        }
+    }
+    frames
+}
+
+/// `tag` being the 128 bytes of an ID3v1 tag
+fn retag_v1(tag: &mut [u8], changes: &[TagChange]) {
+    for (field, value) in changes {
+        match field {
+            TagField::Title => write_v1(&mut tag[3..33], value),
+            TagField::Artist => write_v1(&mut tag[33..63], value),
+            TagField::Album => write_v1(&mut tag[63..93], value),
+            TagField::Year => write_v1(&mut tag[93..97], value),
+            // Only v1.1 has room for a track, after a zero ending the comment
+            TagField::Track if tag[125] == 0 => {
+                let number = value.split('/').next().unwrap_or_default();
+                tag[126] = number.trim().parse().unwrap_or(0);
+            }
+            // Genres are numbered from a fixed list, so it's set to none
+            TagField::Genre => tag[127] = 0xff,
+            _ => (),
+        }
+    }
+}
+
+/// Latin-1, cut to fit and padded with zeros
+fn write_v1(field: &mut [u8], value: &str) {
+    let bytes = value
+        .chars()
+        .map(|c| u8::try_from(c).unwrap_or(b'?'))
+        .chain(std::iter::repeat(0));
+    for (slot, byte) in field.iter_mut().zip(bytes) {
+        *slot = byte;
+    }
+}
+
+fn syncsafe(bytes: [u8; 4]) -> usize {
+    bytes.iter().fold(0, |n, &b| (n << 7) | (b & 0x7f) as usize)
+}
+
+fn to_syncsafe(n: usize) -> [u8; 4] {
+    [21, 14, 7, 0].map(|shift| ((n >> shift) & 0x7f) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUDIO: [u8; 7] = [0xff, 0xfb, 0x90, 0x00, 1, 2, 3];
+
+    fn retag(data: &[u8], changes: &[TagChange]) -> Vec<u8> {
+        super::super::retag_bytes(super::retag, data, changes)
+    }
+
+    fn frames(data: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        let end = HEADER + syncsafe([data[6], data[7], data[8], data[9]]);
+        read_frames(&data[HEADER..end], data[3])
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.id, f.body))
+            .collect()
+    }
+
+    fn tag(version: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+        [
+            b"ID3",
+            &[version, 0, flags][..],
+            &to_syncsafe(body.len()),
+            body,
+        ]
+        .concat()
+    }
+
+    fn v3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [id, &(body.len() as u32).to_be_bytes()[..], &[0, 0], body].concat()
+    }
+
+    #[test]
+    fn retags_in_place() {
+        let tagged = retag(
+            &AUDIO,
+            &[
+                (TagField::Title, "Intro".to_string()),
+                (TagField::Year, "1999".to_string()),
+            ],
+        );
+
+        assert!(tagged.ends_with(&AUDIO));
+        assert_eq!(
+            frames(&tagged),
+            [
+                (*b"TIT2", b"\x03Intro".to_vec()),
+                (*b"TDRC", b"\x031999".to_vec())
+            ]
+        );
+
+        // Dropping a frame and changing another fits in the padding
+        let retagged = retag(
+            &tagged,
+            &[
+                (TagField::Year, String::new()),
+                (TagField::Title, "Outro".to_string()),
+            ],
+        );
+
+        assert_eq!(retagged.len(), tagged.len());
+        assert!(retagged.ends_with(&AUDIO));
+        assert_eq!(frames(&retagged), [(*b"TIT2", b"\x03Outro".to_vec())]);
+    }
+
+    #[test]
+    fn keeps_v3_and_drops_its_extended_header() {
+        let album = v3_frame(b"TALB", b"\x00Album");
+        // Six bytes of flags and padding size, after a length leaving itself out
+        let extended = [0, 0, 0, 6, 0, 0, 0, 0, 0, 0];
+        let file = [
+            tag(3, 0x40, &[&extended[..], &album].concat()),
+            AUDIO.to_vec(),
+        ]
+        .concat();
+
+        let tagged = retag(
+            &file,
+            &[
+                (TagField::Title, "Hi".to_string()),
+                (TagField::Year, "1999".to_string()),
+            ],
+        );
+
+        assert_eq!(&tagged[3..6], [3, 0, 0]);
+        assert!(tagged.ends_with(&AUDIO));
+        assert_eq!(
+            frames(&tagged),
+            [
+                (*b"TALB", b"\x00Album".to_vec()),
+                (*b"TIT2", b"\x01\xff\xfeH\x00i\x00".to_vec()),
+                (*b"TYER", b"\x01\xff\xfe1\x009\x009\x009\x00".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_a_v4_footer() {
+        let mut title = b"TIT2".to_vec();
+        title.extend_from_slice(&to_syncsafe(4));
+        title.extend_from_slice(b"\x00\x00\x03Old");
+        let mut file = tag(4, 0x10, &title);
+        file.extend_from_slice(&[b"3DI", &[4, 0, 0x10][..], &to_syncsafe(title.len())].concat());
+        file.extend_from_slice(&AUDIO);
+
+        let tagged = retag(&file, &[(TagField::Title, "New".to_string())]);
+
+        assert_eq!(tagged[5], 0);
+        assert!(tagged.ends_with(&AUDIO));
+        assert!(!tagged.windows(3).any(|w| w == b"3DI"));
+        assert_eq!(frames(&tagged), [(*b"TIT2", b"\x03New".to_vec())]);
+    }
+
+    #[test]
+    fn keeps_v1_in_step() {
+        let mut v1 = [0; V1_LEN];
+        v1[..6].copy_from_slice(b"TAGOld");
+        let file = [&AUDIO[..], &v1].concat();
+
+        let tagged = retag(&file, &[(TagField::Title, "New".to_string())]);
+
+        let (rest, v1) = tagged.split_at(tagged.len() - V1_LEN);
+        assert!(rest.ends_with(&AUDIO));
+        assert_eq!(&v1[..7], b"TAGNew\0");
+    }
+}
@@ -0,0 +1,144 @@
+mod flac;
+mod id3;
+
+use anyhow::{Context, Result, bail};
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::Path,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TagField {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Year,
+    Genre,
+    Track,
+}
+
+impl TagField {
+    /// Also the order they're edited in
+    pub const ALL: [TagField; 7] = [
+        TagField::Title,
+        TagField::Artist,
+        TagField::Album,
+        TagField::AlbumArtist,
+        TagField::Year,
+        TagField::Genre,
+        TagField::Track,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TagField::Title => "Title",
+            TagField::Artist => "Artist",
+            TagField::Album => "Album",
+            TagField::AlbumArtist => "Album Artist",
+            TagField::Year => "Year",
+            TagField::Genre => "Genre",
+            TagField::Track => "Track",
+        }
+    }
+}
+
+/// A field and what to set it to, an empty value taking the tag off
+pub type TagChange = (TagField, String);
+
+/// Writes `changes` into the file's tags, leaving everything else in them
+/// alone. FLAC and MP3 files are covered, and any other format is refused
+/// rather than risk writing a tag it can't read back.
+///
+/// The new file is written out next to the old one and renamed over it, so
+/// a failed write leaves the original as it was. Only the tags are read in,
+/// the audio being copied across as it is.
+pub fn write_tags(path: &Path, changes: &[TagChange]) -> Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let metadata = fs::metadata(path).with_context(|| format!("Couldn't open {name}"))?;
+    if metadata.permissions().readonly() {
+        bail!("{name} is read-only");
+    }
+
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut file = File::open(path).with_context(|| format!("Couldn't read {name}"))?;
+
+    let retagged = match ext.as_str() {
+        "flac" => flac::retag(&mut file, changes),
+        "mp3" => id3::retag(&mut file, changes),
+        _ => bail!("Tags can only be edited in FLAC and MP3 files"),
+    }
+    .with_context(|| format!("Couldn't tag {name}"))?;
+
+    replace(path, &retagged, &mut file, &metadata).with_context(|| format!("Couldn't write {name}"))
+}
+
+/// A file with new tags: `head` and `tail` go where the old ones were, with
+/// the audio between them copied over from the original
+struct Retagged {
+    head: Vec<u8>,
+    audio: Range<u64>,
+    tail: Vec<u8>,
+}
+
+impl Retagged {
+    fn write_to(&self, src: &mut (impl Read + Seek), out: &mut impl Write) -> io::Result<()> {
+        out.write_all(&self.head)?;
+
+        src.seek(SeekFrom::Start(self.audio.start))?;
+        let len = self.audio.end - self.audio.start;
+        if io::copy(&mut src.by_ref().take(len), out)? < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        out.write_all(&self.tail)
+    }
+}
+
+/// Up to `len` bytes from `offset`, fewer where the file ends first
+fn read_at(src: &mut (impl Read + Seek), offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    src.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    src.by_ref().take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn replace(path: &Path, retagged: &Retagged, src: &mut File, metadata: &Metadata) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".vox-tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let written = (|| -> Result<()> {
+        let mut file = File::create(&tmp)?;
+        retagged.write_to(src, &mut file)?;
+        file.sync_all()?;
+        fs::set_permissions(&tmp, metadata.permissions())?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+#[cfg(test)]
+fn retag_bytes(
+    retag: fn(&mut io::Cursor<Vec<u8>>, &[TagChange]) -> Result<Retagged>,
+    data: &[u8],
+    changes: &[TagChange],
+) -> Vec<u8> {
+    let mut src = io::Cursor::new(data.to_vec());
+    let mut out = Vec::new();
+    retag(&mut src, changes)
+        .unwrap()
+        .write_to(&mut src, &mut out)
+        .unwrap();
+    out
+}
@@ -1,16 +1,22 @@
-use super::LEGAL_EXTENSION;
+use super::{LEGAL_EXTENSION, UNSUPPORTED_EXTENSION, is_unsupported};
 use crate::{
-    SongMap, calculate_signature,
-    database::Database,
+    SCAN_REPORT_PATH, SongMap, calculate_fingerprint, calculate_signature,
+    database::{Database, Rekey, ScanEntry},
     expand_tilde,
-    library::{Album, LongSong, RefreshProgress, RefreshStage, SimpleSong, SongInfo},
+    library::{
+        Album, FolderTree, LongSong, RefreshProgress, RefreshStage, ScanSummary, SimpleSong,
+        SongInfo,
+        cue::{self, CueSource},
+    },
     user_config,
 };
 use anyhow::{Result, anyhow};
 use indexmap::IndexMap;
-use rayon::prelude::*;
+use rayon::{iter::Either, prelude::*};
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+    fs,
     path::{Path, PathBuf},
     sync::{
         Arc,
@@ -22,15 +28,18 @@ use walkdir::WalkDir;
 
 const SCANNING_PRE: u8 = 5;
 const PARSING_BASE: u8 = 20;
-const DB_OPEN: u8 = 70;
-const DB_INSERT_BASE: u8 = 72;
 const DB_BASE: u8 = 85;
 
+/// New files are parsed and committed this many at a time, so a cancelled
+/// scan keeps everything it got through
+const SCAN_BATCH: usize = 250;
+
 pub struct Library {
     db: Database,
     pub roots: HashSet<PathBuf>,
     pub songs: SongMap,
     pub albums: IndexMap<i64, Album>,
+    pub folders: FolderTree,
 }
 
 impl Library {
@@ -41,6 +50,7 @@ impl Library {
             roots: HashSet::new(),
             songs: SongMap::default(),
             albums: IndexMap::new(),
+            folders: FolderTree::default(),
         })
     }
 
@@ -74,11 +84,15 @@ impl Library {
     /// Collect valid files from a root directory
     ///
     /// Function collects valid files with vetted extensions
-    /// Currently, proper extensions are MP3, FLAC, and M4A
+    /// Currently, proper extensions are MP3, FLAC, and M4A, plus cue sheets.
+    /// Formats that can't be decoded are collected too, so they can be
+    /// reported.
     ///
-    /// Folders with a `.nomedia` file will be ignored
+    /// Folders with a `.nomedia` file will be ignored. Symlinks are
+    /// followed; one that loops back up the tree is skipped.
     fn collect_valid_files(dir: impl AsRef<Path>) -> impl ParallelIterator<Item = PathBuf> {
         WalkDir::new(dir)
+            .follow_links(true)
             .into_iter()
             .filter_entry(|e| {
                 !e.path().join(".nomedia").exists()
@@ -93,18 +107,48 @@ impl Library {
                     .path()
                     .extension()
                     .and_then(|ext| ext.to_str())
-                    .map(|ext| LEGAL_EXTENSION.contains(ext.to_lowercase().as_str()))
+                    .map(|ext| {
+                        let ext = ext.to_lowercase();
+                        ext == "cue"
+                            || LEGAL_EXTENSION.contains(ext.as_str())
+                            || UNSUPPORTED_EXTENSION.contains(ext.as_str())
+                    })
                     .unwrap_or(false)
             })
             .filter_map(|e| e.path().canonicalize().ok())
     }
 
+    /// Lists what the last full scan left out and why. The file is removed
+    /// once there's nothing left to report.
+    fn write_scan_report(unsupported: &[PathBuf], skipped: &[(PathBuf, String)]) -> Result<()> {
+        if unsupported.is_empty() && skipped.is_empty() {
+            if SCAN_REPORT_PATH.exists() {
+                fs::remove_file(&*SCAN_REPORT_PATH)?;
+            }
+            return Ok(());
+        }
+
+        let mut report = String::new();
+        if !unsupported.is_empty() {
+            writeln!(report, "Unsupported formats:")?;
+            for path in unsupported {
+                writeln!(report, "  {}", path.display())?;
+            }
+            writeln!(report)?;
+        }
+        if !skipped.is_empty() {
+            writeln!(report, "Could not be read:")?;
+            for (path, error) in skipped {
+                writeln!(report, "  {}\n    {error}", path.display())?;
+            }
+        }
+
+        fs::write(&*SCAN_REPORT_PATH, report)?;
+        Ok(())
+    }
+
     /// Push everything to db
-    fn commit_to_db(
-        db: &mut Database,
-        songs: &[LongSong],
-        progress: &RefreshProgress,
-    ) -> Result<()> {
+    fn commit_to_db(db: &mut Database, songs: &[LongSong], rekeys: &[Rekey]) -> Result<()> {
         let mut artist_cache = HashSet::new();
         let mut aa_binding = HashSet::new();
 
@@ -115,28 +159,20 @@ impl Library {
             aa_binding.insert((song.album_artist.as_str(), song.get_album()));
         }
 
-        progress.set(RefreshStage::Database, DB_OPEN);
-
         // ORDER IS IMPORTANT HERE
         db.insert_artists(&artist_cache)?;
         db.insert_albums(&aa_binding)?;
-
-        let total = songs.len();
-        let mut done = 0;
-        for chunk in songs.chunks(500) {
-            db.insert_songs(chunk)?;
-            done += chunk.len();
-            progress.set(
-                RefreshStage::Database,
-                DB_INSERT_BASE + (done * 13 / total.max(1)) as u8,
-            );
-        }
-
-        Ok(())
+        db.rekey_songs(rekeys)?;
+        db.insert_songs(songs)
     }
 
     fn collect_songs(&mut self) -> Result<()> {
         self.songs = self.db.get_all_songs()?;
+        let paths = self.db.get_scan_index()?.into_iter().map(|e| {
+            let path = e.path.to_string_lossy().into_owned();
+            (e.id, path)
+        });
+        self.folders = FolderTree::build(&self.roots, paths);
         Ok(())
     }
 
@@ -168,7 +204,7 @@ impl Library {
                     album.year = songs[0].year
                 }
 
-                songs.sort_by_key(|s| (s.disc_no.unwrap_or(0), s.track_no.unwrap_or(0)));
+                songs.sort_by_key(|s| s.album_order());
                 album.tracklist = songs.into()
             }
         }
@@ -240,7 +276,9 @@ impl Library {
                 self.collect_songs()?;
                 self.build_albums()?;
             }
-            false => self.rebuild_library(&RefreshProgress::default())?,
+            false => {
+                self.rebuild_library(&RefreshProgress::default())?;
+            }
         }
 
         Ok(())
@@ -264,47 +302,147 @@ impl Library {
 }
 
 impl Library {
-    pub fn rebuild_library(&mut self, progress: &RefreshProgress) -> Result<()> {
+    pub fn rebuild_library(&mut self, progress: &RefreshProgress) -> Result<ScanSummary> {
         if self.roots.is_empty() {
-            return Ok(());
+            return Ok(ScanSummary::default());
         }
 
         progress.set(RefreshStage::Scanning, 0);
 
-        let mut existing_hashes = self.db.get_hashes()?;
+        let mut existing = self
+            .db
+            .get_scan_index()?
+            .into_iter()
+            .map(|entry| (entry.id, entry))
+            .collect::<HashMap<u64, ScanEntry>>();
         let mut all_files = Vec::new();
 
         // First pass: collect all files from all roots
         for root in &self.roots {
             all_files.extend(Self::collect_valid_files(root).collect::<Vec<_>>());
         }
+        // A file reached through a symlink as well resolves to the same path
+        all_files.sort();
+        all_files.dedup();
+
+        let (sheets, all_files): (Vec<_>, Vec<_>) = all_files
+            .into_iter()
+            .partition(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("cue")));
+        let cues = cue::index_sheets(&sheets);
+        let (unsupported, all_files): (Vec<_>, Vec<_>) =
+            all_files.into_iter().partition(|p| is_unsupported(p));
+        let mut skipped = Vec::new();
 
         progress.set(RefreshStage::Scanning, SCANNING_PRE);
 
         // Second pass: Filter files
         let total_files = all_files.len();
         let mut new_files = Vec::new();
+        let mut unstamped = Vec::new();
+
+        // Songs scanned before genres were kept are read again, once, as
+        // are all of them when albums are to be grouped differently
+        let grouping = match user_config().general.group_by_album_artist {
+            true => "album_artist",
+            false => "artist",
+        };
+        let reread_all =
+            !self.db.genres_read()? || self.db.get_album_grouping()?.is_none_or(|g| g != grouping);
+        let mut reread = Vec::new();
 
         for (i, path) in all_files.into_iter().enumerate() {
+            if progress.is_cancelled() {
+                break;
+            }
             progress.set(
                 RefreshStage::Scanning,
                 SCANNING_PRE + ((i + 1) * 15 / total_files.max(1)) as u8,
             );
 
-            if let Ok(hash) = calculate_signature(&path)
-                && !existing_hashes.remove(&hash)
+            // A file split by a cue sheet is only up to date if all of
+            // its tracks are
+            if let Some(source) = cues.get(&path)
+                && let Ok(hash) = calculate_signature(&path)
             {
-                new_files.push(path);
+                let ids = source
+                    .tracks()
+                    .iter()
+                    .map(|t| source.track_id(hash, t.number))
+                    .collect::<Vec<_>>();
+
+                match ids.iter().all(|id| existing.contains_key(id)) {
+                    true => {
+                        for id in &ids {
+                            existing.remove(id);
+                        }
+                        if reread_all {
+                            reread.push(path);
+                        }
+                    }
+                    false => new_files.push(path),
+                }
+                continue;
+            }
+
+            if let Ok(hash) = calculate_signature(&path) {
+                match existing.remove(&hash) {
+                    Some(_) if reread_all => reread.push(path),
+                    Some(entry) if entry.fingerprint.is_none() => unstamped.push((hash, path)),
+                    Some(_) => (),
+                    None => new_files.push(path),
+                }
             }
         }
 
+        // Songs scanned before fingerprints were kept need one, or they
+        // can't be recognised if they're moved later
+        if !unstamped.is_empty() {
+            Self::stamp_unchanged(&mut self.db, unstamped, progress)?;
+        }
+
+        // Nothing is left to pair these with, so they're only rewritten
+        if !reread.is_empty() {
+            Self::process_new_files(
+                &mut self.db,
+                reread,
+                &cues,
+                &mut HashMap::new(),
+                &mut Vec::new(),
+                progress,
+            )?;
+        }
+
         // Phase 2: Processing song metadata
-        let removed_ids = existing_hashes.into_iter().collect::<Vec<u64>>();
+        // Whatever is left in `existing` was edited, moved, or deleted
+        let mut summary = ScanSummary::default();
 
         // 2.1 Inserting songs
         match new_files.is_empty() {
             true => progress.set(RefreshStage::Database, DB_BASE),
-            false => Self::process_new_files(&mut self.db, new_files, progress)?,
+            false => {
+                summary = Self::process_new_files(
+                    &mut self.db,
+                    new_files,
+                    &cues,
+                    &mut existing,
+                    &mut skipped,
+                    progress,
+                )?
+            }
+        }
+
+        // A cancelled scan can't tell a deleted file from one it never got to
+        summary.cancelled = progress.is_cancelled();
+        let removed_ids = match summary.cancelled {
+            true => Vec::new(),
+            false => existing.into_keys().collect::<Vec<u64>>(),
+        };
+        summary.removed = removed_ids.len();
+        summary.unsupported = unsupported.len();
+        summary.skipped = skipped.len();
+
+        if !summary.cancelled {
+            let _ = Self::write_scan_report(&unsupported, &skipped);
         }
 
         // 2.1 Deleting songs
@@ -327,35 +465,147 @@ impl Library {
         self.build_albums()?;
         progress.set(RefreshStage::Rebuilding, 100);
 
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        self.db.set_last_scan(timestamp)
+        // Leave the last scan as it was so the next one picks up the rest
+        if !summary.cancelled {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            self.db.set_last_scan(timestamp)?;
+            self.db.set_genres_read()?;
+            self.db.set_album_grouping(grouping)?;
+        }
+
+        Ok(summary)
     }
 
     fn process_new_files(
         db: &mut Database,
         new_files: Vec<PathBuf>,
+        cues: &HashMap<PathBuf, CueSource>,
+        existing: &mut HashMap<u64, ScanEntry>,
+        skipped: &mut Vec<(PathBuf, String)>,
         progress: &RefreshProgress,
-    ) -> Result<()> {
+    ) -> Result<ScanSummary> {
         let total = new_files.len();
         let processed = AtomicUsize::new(0);
+        let mut summary = ScanSummary::default();
+        let by_album_artist = user_config().general.group_by_album_artist;
 
-        let songs: Vec<LongSong> = new_files
-            .into_par_iter()
-            .filter_map(|path| {
-                let song = LongSong::build_song_symphonia(path).ok();
-                let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
+        for batch in new_files.chunks(SCAN_BATCH) {
+            if progress.is_cancelled() {
+                break;
+            }
+
+            let (songs, failed): (Vec<Vec<LongSong>>, Vec<_>) =
+                batch.par_iter().partition_map(|path| {
+                    if progress.is_cancelled() {
+                        return Either::Left(Vec::new());
+                    }
+
+                    let song = match LongSong::build_song_symphonia(path.clone()) {
+                        Ok(song) => Either::Left(match cues.get(path) {
+                            Some(source) => song.split_cue(source),
+                            None => vec![song],
+                        }),
+                        Err(e) => Either::Right((path.clone(), e.to_string())),
+                    };
+                    let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
 
-                progress.set(
-                    RefreshStage::Parsing,
-                    PARSING_BASE + (count * 50 / total.max(1)) as u8,
-                );
-                progress.set_counts(count, total);
+                    progress.set(
+                        RefreshStage::Parsing,
+                        PARSING_BASE + (count * 65 / total.max(1)) as u8,
+                    );
+                    progress.set_counts(count, total);
 
-                song
+                    song
+                });
+            let mut songs = songs.into_iter().flatten().collect::<Vec<_>>();
+            skipped.extend(failed);
+
+            if !by_album_artist {
+                songs.iter_mut().for_each(LongSong::group_by_track_artist);
+            }
+
+            let rekeys = Self::match_known_songs(&songs, existing);
+            Self::commit_to_db(db, &songs, &rekeys)?;
+
+            let moved = rekeys.iter().filter(|r| r.moved).count();
+            summary.added += songs.len() - rekeys.len();
+            summary.updated += rekeys.len() - moved;
+            summary.moved += moved;
+        }
+
+        Ok(summary)
+    }
+
+    /// Pairs new files with songs that dropped out of the library, so they
+    /// keep their history rather than showing up as new. A file at a known
+    /// path was edited; a file with a known fingerprint whose old path is
+    /// gone was moved. Matched songs are taken out of `existing`.
+    fn match_known_songs(songs: &[LongSong], existing: &mut HashMap<u64, ScanEntry>) -> Vec<Rekey> {
+        let by_path = existing
+            .values()
+            .map(|e| (e.path.as_path(), e.id))
+            .collect::<HashMap<_, _>>();
+
+        let by_fingerprint = existing
+            .values()
+            .filter(|e| !e.path.exists())
+            .filter_map(|e| Some((e.fingerprint?, e.id)))
+            .collect::<HashMap<_, _>>();
+
+        let mut rekeys = Vec::new();
+        let mut claimed = HashSet::new();
+
+        for song in songs {
+            if let Some(&old) = by_path.get(song.path.as_path()) {
+                claimed.insert(old);
+                rekeys.push(Rekey {
+                    old,
+                    new: song.id,
+                    moved: false,
+                });
+            }
+        }
+
+        for song in songs {
+            if by_path.contains_key(song.path.as_path()) {
+                continue;
+            }
+
+            // Copies share a fingerprint; only the first one takes over
+            if let Some(&old) = song.fingerprint.and_then(|fp| by_fingerprint.get(&fp))
+                && claimed.insert(old)
+            {
+                rekeys.push(Rekey {
+                    old,
+                    new: song.id,
+                    moved: true,
+                });
+            }
+        }
+
+        existing.retain(|id, _| !claimed.contains(id));
+        rekeys
+    }
+
+    fn stamp_unchanged(
+        db: &mut Database,
+        unstamped: Vec<(u64, PathBuf)>,
+        progress: &RefreshProgress,
+    ) -> Result<()> {
+        let stamps = unstamped
+            .into_par_iter()
+            .filter_map(|(id, path)| {
+                if progress.is_cancelled() {
+                    return None;
+                }
+                let metadata = fs::metadata(&path).ok()?;
+                let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+                let fingerprint = calculate_fingerprint(&path).ok()?;
+
+                Some((id, metadata.len(), mtime.as_secs() as i64, fingerprint))
             })
-            .collect();
+            .collect::<Vec<_>>();
 
-        Self::commit_to_db(db, &songs, progress)?;
-        Ok(())
+        db.set_fingerprints(&stamps)
     }
 }
@@ -3,10 +3,12 @@ use std::io::Write;
 
 fn main() {
     unsafe { std::env::set_var("RUST_BACKTRACE", "1"); }
-    let _ = write!(std::io::stdout(), "\x1b]0;NoctaVox\x07");
-    let _ = std::io::stdout().flush();
 
     if std::env::args().len() == 1 {
+        // Only the TUI names the window, so `--status` and `--send` print
+        // nothing a script would have to strip
+        let _ = write!(std::io::stdout(), "\x1b]0;NoctaVox\x07");
+        let _ = std::io::stdout().flush();
         match noctavox::app_core::NoctaVox::new() {
             Ok(mut app) => app.run(),
             Err(e) => {
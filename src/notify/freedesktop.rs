@@ -0,0 +1,67 @@
+use super::Notification;
+use anyhow::Result;
+use dbus::{
+    arg::{PropMap, Variant},
+    blocking::Connection,
+};
+use std::time::Duration;
+
+const DEST: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Let the daemon pick how long notifications stay up
+const EXPIRE_DEFAULT: i32 = -1;
+
+pub(super) struct NotifyDaemon {
+    conn: Connection,
+    replaces_id: u32,
+}
+
+impl NotifyDaemon {
+    pub(super) fn connect() -> Result<Self> {
+        Ok(NotifyDaemon {
+            conn: Connection::new_session()?,
+            replaces_id: 0,
+        })
+    }
+
+    pub(super) fn notify(&mut self, n: &Notification) -> Result<()> {
+        let body = match n.album.is_empty() {
+            true => n.artist.clone(),
+            false => format!("{}\n{}", n.artist, n.album),
+        };
+
+        let icon = n
+            .cover
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "audio-x-generic".to_string());
+
+        let mut hints = PropMap::new();
+        hints.insert(
+            "category".into(),
+            Variant(Box::new("x-gnome.music".to_string())),
+        );
+        hints.insert("transient".into(), Variant(Box::new(true)));
+
+        let proxy = self.conn.with_proxy(DEST, PATH, TIMEOUT);
+        let (id,): (u32,) = proxy.method_call(
+            DEST,
+            "Notify",
+            (
+                "NoctaVox",
+                self.replaces_id,
+                icon,
+                n.title.as_str(),
+                body,
+                Vec::<String>::new(),
+                hints,
+                EXPIRE_DEFAULT,
+            ),
+        )?;
+
+        self.replaces_id = id;
+        Ok(())
+    }
+}
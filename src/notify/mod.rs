@@ -0,0 +1,112 @@
+#[cfg(target_os = "linux")]
+mod freedesktop;
+
+use crate::library::cover_art::folder_cover;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+/// A track has to stay put this long before it's announced, so skipping
+/// through the queue only notifies for where it lands
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+pub struct Notification {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub cover: Option<PathBuf>,
+}
+
+impl Notification {
+    pub fn new(title: &str, artist: &str, album: &str, song_path: &Path) -> Self {
+        Notification {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            cover: folder_cover(song_path),
+        }
+    }
+}
+
+/// Sends desktop notifications from a background thread, as the daemon can
+/// be slow to answer. Each notification replaces the last one rather than
+/// stacking up.
+pub struct Notifier {
+    sender: Sender<Notification>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+        thread::spawn(move || notify_loop(receiver));
+
+        Notifier { sender }
+    }
+
+    pub fn send(&self, notification: Notification) {
+        let _ = self.sender.send(notification);
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn notify_loop(receiver: Receiver<Notification>) {
+    let mut backend = Backend::default();
+    let mut pending = None;
+
+    loop {
+        let msg = match pending {
+            Some(_) => receiver.recv_timeout(DEBOUNCE),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match msg {
+            Ok(n) => pending = Some(n),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(n) = pending.take() {
+                    backend.deliver(&n);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct Backend {
+    daemon: Option<freedesktop::NotifyDaemon>,
+}
+
+#[cfg(target_os = "linux")]
+impl Backend {
+    /// Without a session bus or notification daemon this silently does nothing
+    fn deliver(&mut self, n: &Notification) {
+        if self.daemon.is_none() {
+            self.daemon = freedesktop::NotifyDaemon::connect().ok();
+        }
+
+        if let Some(d) = self.daemon.as_mut()
+            && d.notify(n).is_err()
+        {
+            self.daemon = None;
+        }
+    }
+}
+
+/// Only the freedesktop notification spec is supported for now
+#[cfg(not(target_os = "linux"))]
+#[derive(Default)]
+struct Backend;
+
+#[cfg(not(target_os = "linux"))]
+impl Backend {
+    fn deliver(&mut self, _: &Notification) {}
+}
@@ -1,5 +1,11 @@
+mod radio;
+mod seek_target;
 mod session;
+mod shuffle;
 mod validated_song;
 
-pub use session::PlaybackSession;
+pub use radio::pick_radio_track;
+pub use seek_target::SeekTarget;
+pub use session::{PlaybackSession, RepeatMode};
+pub use shuffle::ShuffleMode;
 pub use validated_song::ValidatedSong;
@@ -0,0 +1,97 @@
+use crate::library::SimpleSong;
+use rand::seq::IndexedRandom;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// How often radio stays with the artist of the track before
+const SAME_ARTIST_CHANCE: f64 = 0.3;
+
+/// A track for radio to follow `last` with: one of the least played,
+/// passing over anything heard or queued lately. A library too small to
+/// have anything fresh falls back to whatever isn't `last` itself.
+pub fn pick_radio_track(
+    songs: &[Arc<SimpleSong>],
+    last: Option<&SimpleSong>,
+    recent: &HashSet<u64>,
+    plays: &HashMap<u64, u16>,
+) -> Option<Arc<SimpleSong>> {
+    let last_id = last.map(|s| s.id);
+    let others = songs
+        .iter()
+        .filter(|s| Some(s.id) != last_id)
+        .collect::<Vec<_>>();
+
+    let fresh = others
+        .iter()
+        .copied()
+        .filter(|s| !recent.contains(&s.id))
+        .collect::<Vec<_>>();
+    let pool = match fresh.is_empty() {
+        true => others,
+        false => fresh,
+    };
+
+    let same_artist = pool
+        .iter()
+        .copied()
+        .filter(|s| last.is_some_and(|l| l.artist == s.artist))
+        .collect::<Vec<_>>();
+    let pool = match !same_artist.is_empty() && rand::random::<f64>() < SAME_ARTIST_CHANCE {
+        true => same_artist,
+        false => pool,
+    };
+
+    let count = |s: &SimpleSong| plays.get(&s.id).copied().unwrap_or(0);
+    let fewest = pool.iter().map(|s| count(s)).min()?;
+    let least_played = pool
+        .into_iter()
+        .filter(|s| count(s) == fewest)
+        .collect::<Vec<_>>();
+
+    least_played.choose(&mut rand::rng()).map(|s| Arc::clone(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::FileType;
+
+    fn song(id: u64, artist: &str) -> Arc<SimpleSong> {
+        Arc::new(SimpleSong {
+            id,
+            title: String::new(),
+            artist: Arc::new(artist.to_string()),
+            year: None,
+            album: Arc::new(String::new()),
+            album_id: 0,
+            track_no: None,
+            disc_no: None,
+            duration: Default::default(),
+            filetype: FileType::FLAC,
+            rating: Default::default(),
+            loved: Default::default(),
+            cue: None,
+        })
+    }
+
+    #[test]
+    fn picks_least_played_and_never_repeats_a_lone_track() {
+        let songs = vec![song(1, "a"), song(2, "a"), song(3, "b"), song(4, "b")];
+        let plays = HashMap::from([(1, 4), (2, 1), (3, 2), (4, 1)]);
+        let recent = HashSet::from([4]);
+
+        for _ in 0..20 {
+            let pick = pick_radio_track(&songs, Some(&songs[0]), &recent, &plays);
+            assert_eq!(pick.map(|s| s.id), Some(2));
+        }
+
+        // Everything recent still beats stopping
+        let recent = HashSet::from([2, 3, 4]);
+        let pick = pick_radio_track(&songs[..2], Some(&songs[0]), &recent, &plays);
+        assert_eq!(pick.map(|s| s.id), Some(2));
+
+        assert!(pick_radio_track(&songs[..1], Some(&songs[0]), &recent, &plays).is_none());
+    }
+}
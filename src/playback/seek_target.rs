@@ -0,0 +1,88 @@
+use anyhow::{Result, anyhow, bail};
+
+/// A position typed into the seek popup
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SeekTarget {
+    /// Seconds from the start of the track
+    Absolute(f64),
+    /// Seconds from the current position, back when negative
+    Relative(f64),
+}
+
+impl SeekTarget {
+    /// `ss`, `mm:ss` or `hh:mm:ss`, optionally led by `+` or `-` to seek
+    /// relative to where playback is. Seconds may carry a fraction.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (sign, time) = match input.chars().next() {
+            Some('+') => (Some(1.0), &input[1..]),
+            Some('-') => (Some(-1.0), &input[1..]),
+            Some(_) => (None, input),
+            None => bail!("Enter a time, like 1:30 or +90"),
+        };
+
+        let parts = time.trim().split(':').collect::<Vec<_>>();
+        if parts.len() > 3 {
+            bail!("Too many fields, expected hh:mm:ss");
+        }
+
+        let (last, rest) = parts.split_last().expect("split always yields a part");
+        let secs = last
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .ok_or_else(|| anyhow!("\"{last}\" isn't a number of seconds"))?;
+
+        if !rest.is_empty() && secs >= 60.0 {
+            bail!("Seconds must be under 60");
+        }
+
+        let mut total = secs;
+        for (unit, field) in rest.iter().rev().enumerate() {
+            let value = field
+                .parse::<u32>()
+                .map_err(|_| anyhow!("\"{field}\" isn't a whole number"))?;
+            if unit == 0 && rest.len() == 2 && value >= 60 {
+                bail!("Minutes must be under 60");
+            }
+            total += value as f64 * 60f64.powi(unit as i32 + 1);
+        }
+
+        Ok(match sign {
+            Some(sign) => SeekTarget::Relative(sign * total),
+            None => SeekTarget::Absolute(total),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(SeekTarget::parse("90").unwrap(), SeekTarget::Absolute(90.0));
+        assert_eq!(
+            SeekTarget::parse("1:02:03").unwrap(),
+            SeekTarget::Absolute(3723.0)
+        );
+        assert_eq!(
+            SeekTarget::parse(" 75:30.5 ").unwrap(),
+            SeekTarget::Absolute(4530.5)
+        );
+        assert_eq!(
+            SeekTarget::parse("+90").unwrap(),
+            SeekTarget::Relative(90.0)
+        );
+        assert_eq!(
+            SeekTarget::parse("-1:30").unwrap(),
+            SeekTarget::Relative(-90.0)
+        );
+
+        for bad in [
+            "", "+", "1:75", "1:60:00", "a:10", "1::2", "1:2:3:4", "-5:-3",
+        ] {
+            assert!(SeekTarget::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
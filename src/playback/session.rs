@@ -1,13 +1,30 @@
-use crate::{Database, SongMap, library::SimpleSong, playback::ValidatedSong, user_config};
+use crate::{
+    Database, SongMap,
+    library::SimpleSong,
+    playback::{ShuffleMode, ValidatedSong},
+    user_config,
+};
 use anyhow::Result;
-use rand::seq::SliceRandom;
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
 };
+use voxio::ReplayGainMode;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum RepeatMode {
+    #[default]
+    Off,
+    /// The playing track starts over when it ends
+    One,
+    /// Finished tracks go to the back of the queue
+    All,
+}
 
 pub struct PlaybackSession {
-    repeat: bool,
+    repeat: RepeatMode,
+    replay_gain: ReplayGainMode,
+    gapless: bool,
 
     queue: VecDeque<Arc<ValidatedSong>>,
     history: VecDeque<Arc<SimpleSong>>,
@@ -19,7 +36,9 @@ pub struct PlaybackSession {
 impl PlaybackSession {
     pub fn init() -> Self {
         PlaybackSession {
-            repeat: false,
+            repeat: RepeatMode::Off,
+            replay_gain: user_config().general.replay_gain,
+            gapless: user_config().general.gapless,
 
             queue: VecDeque::new(),
             history: VecDeque::with_capacity(user_config().general.history_capacity as usize),
@@ -68,27 +87,26 @@ impl PlaybackSession {
         &mut self,
         songs: &[Arc<SimpleSong>],
     ) -> Result<Option<Arc<ValidatedSong>>> {
-        let mut db = Database::open()?;
-        let mut first_valid = None;
-        for song in songs {
-            let Ok(path) = db.get_song_path(song.id) else {
-                continue;
-            };
-            let Ok(_) = std::fs::metadata(&path) else {
-                continue;
-            };
-            self.queue_ids.insert(song.id);
-            let valid = Arc::new(ValidatedSong {
-                meta: Arc::clone(song),
-                path,
-            });
+        let valid = validate_multi(songs)?;
+        for song in &valid {
+            self.queue_ids.insert(song.id());
+            self.queue.push_back(Arc::clone(song));
+        }
+        Ok(valid.into_iter().next())
+    }
 
-            self.queue.push_back(Arc::clone(&valid));
-            if first_valid.is_none() {
-                first_valid = Some(Arc::clone(&valid))
-            }
+    /// Inserts `songs` ahead of everything else in the queue, so they play
+    /// straight after the current track in the order given
+    pub fn enqueue_next(
+        &mut self,
+        songs: &[Arc<SimpleSong>],
+    ) -> Result<Option<Arc<ValidatedSong>>> {
+        let valid = validate_multi(songs)?;
+        for song in valid.iter().rev() {
+            self.queue_ids.insert(song.id());
+            self.queue.push_front(Arc::clone(song));
         }
-        Ok(first_valid)
+        Ok(valid.into_iter().next())
     }
 
     /// Push song to front of queue
@@ -104,6 +122,15 @@ impl PlaybackSession {
     pub fn advance(&mut self) -> (Option<Arc<ValidatedSong>>, Option<Arc<SimpleSong>>) {
         let pushed = self.now_playing.take();
 
+        // Requeued before popping, so a lone track wraps around to itself
+        if self.repeat == RepeatMode::All
+            && let Some(song) = &pushed
+            && let Ok(validated) = ValidatedSong::new(song)
+        {
+            self.queue_ids.insert(validated.id());
+            self.queue.push_back(validated);
+        }
+
         let next = self
             .queue
             .pop_front()
@@ -118,9 +145,18 @@ impl PlaybackSession {
             .inspect(|s| self.remove_id_if_final(s.id()))
     }
 
-    pub fn clear_queue(&mut self) {
-        self.queue.clear();
+    /// Returns what was in the queue
+    pub fn clear_queue(&mut self) -> Vec<Arc<ValidatedSong>> {
         self.queue_ids.clear();
+        self.queue.drain(..).collect()
+    }
+
+    /// Puts cleared songs back ahead of anything queued since
+    pub fn restore_queue(&mut self, songs: Vec<Arc<ValidatedSong>>) {
+        for song in songs.into_iter().rev() {
+            self.queue_ids.insert(song.id());
+            self.queue.push_front(song);
+        }
     }
 
     pub fn swap(&mut self, a: usize, b: usize) {
@@ -131,8 +167,56 @@ impl PlaybackSession {
         self.queue.swap(a, b);
     }
 
-    pub fn shuffle_queue(&mut self) {
-        self.queue.make_contiguous().shuffle(&mut rand::rng());
+    /// Moves the songs at `indices` to the front or back of the queue, in
+    /// the order they were in. Returns where they ended up.
+    pub fn move_to_edge(&mut self, indices: &[usize], front: bool) -> Vec<usize> {
+        let mut indices = indices
+            .iter()
+            .copied()
+            .filter(|&i| i < self.queue.len())
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+
+        // Removed back to front so the remaining indices stay valid
+        let moved = indices
+            .iter()
+            .rev()
+            .filter_map(|&i| self.queue.remove(i))
+            .collect::<Vec<_>>();
+        let count = moved.len();
+
+        match front {
+            true => {
+                moved.into_iter().for_each(|s| self.queue.push_front(s));
+                (0..count).collect()
+            }
+            false => {
+                moved
+                    .into_iter()
+                    .rev()
+                    .for_each(|s| self.queue.push_back(s));
+                (self.queue.len() - count..self.queue.len()).collect()
+            }
+        }
+    }
+
+    /// Only what's still to come is reordered
+    pub fn shuffle_queue(&mut self, mode: ShuffleMode, plays: &HashMap<u64, u16>) {
+        let after = self.now_playing.as_deref();
+        mode.apply(self.queue.make_contiguous(), |s| &s.meta, after, plays);
+    }
+
+    /// The song that'll play right before anything appended to the queue
+    pub fn queue_tail(&self) -> Option<&Arc<SimpleSong>> {
+        self.queue
+            .back()
+            .map(|s| &s.meta)
+            .or(self.now_playing.as_ref())
+    }
+
+    pub fn queued_ids(&self) -> Vec<u64> {
+        self.queue.iter().map(|s| s.id()).collect()
     }
 
     pub fn is_queued(&self, id: u64) -> bool {
@@ -147,6 +231,13 @@ impl PlaybackSession {
         self.queue.is_empty()
     }
 
+    /// Everything queued or in the history, radio's songs to pass over
+    pub fn recent_ids(&self) -> HashSet<u64> {
+        let mut ids = self.queue_ids.clone();
+        ids.extend(self.history.iter().map(|s| s.id));
+        ids
+    }
+
     // ======================
     //    HISTORY METHODS
     // ======================
@@ -172,6 +263,14 @@ impl PlaybackSession {
             None => return Ok(None),
         };
 
+        // It was requeued when it finished, and is about to play again
+        if self.repeat == RepeatMode::All
+            && self.queue.back().is_some_and(|s| s.id() == last_played.id)
+            && let Some(requeued) = self.queue.pop_back()
+        {
+            self.remove_id_if_final(requeued.id());
+        }
+
         // If something is playing, place it back in the queue
         if let Some(current) = self.now_playing.take() {
             let validated = ValidatedSong::new(&current)?;
@@ -186,12 +285,40 @@ impl PlaybackSession {
         Ok(Some(validated_popped))
     }
 
-    pub fn repeat_is_enabled(&self) -> bool {
+    pub fn repeat_mode(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn repeats_track(&self) -> bool {
+        self.repeat == RepeatMode::One
+    }
+
+    /// Off -> One -> All -> Off
+    pub fn next_repeat(&mut self) -> RepeatMode {
+        self.repeat = match self.repeat {
+            RepeatMode::Off => RepeatMode::One,
+            RepeatMode::One => RepeatMode::All,
+            RepeatMode::All => RepeatMode::Off,
+        };
         self.repeat
     }
 
-    pub fn set_repeat(&mut self, status: bool) {
-        self.repeat = status
+    pub fn gapless_is_enabled(&self) -> bool {
+        self.gapless
+    }
+
+    pub fn replay_gain(&self) -> ReplayGainMode {
+        self.replay_gain
+    }
+
+    /// Off -> Track -> Album -> Off
+    pub fn next_replay_gain(&mut self) -> ReplayGainMode {
+        self.replay_gain = match self.replay_gain {
+            ReplayGainMode::Off => ReplayGainMode::Track,
+            ReplayGainMode::Track => ReplayGainMode::Album,
+            ReplayGainMode::Album => ReplayGainMode::Off,
+        };
+        self.replay_gain
     }
 
     fn remove_id_if_final(&mut self, id: u64) {
@@ -200,3 +327,21 @@ impl PlaybackSession {
         }
     }
 }
+
+/// Skips songs whose file has gone missing since the last scan
+fn validate_multi(songs: &[Arc<SimpleSong>]) -> Result<Vec<Arc<ValidatedSong>>> {
+    let mut db = Database::open()?;
+    let valid = songs
+        .iter()
+        .filter_map(|song| {
+            let path = db.get_song_path(song.id).ok()?;
+            std::fs::metadata(&path).ok()?;
+            Some(Arc::new(ValidatedSong {
+                meta: Arc::clone(song),
+                path,
+            }))
+        })
+        .collect();
+
+    Ok(valid)
+}
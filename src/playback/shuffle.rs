@@ -0,0 +1,153 @@
+use crate::library::SimpleSong;
+use indexmap::IndexMap;
+use rand::seq::SliceRandom;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Rating assumed for unrated songs, so they land midway in weighted shuffle
+const NEUTRAL_RATING: f64 = 3.0;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum ShuffleMode {
+    #[default]
+    Random,
+    /// Random, but never the same song or artist twice in a row
+    NoRepeat,
+    /// Whole albums in order, with the albums themselves shuffled
+    Album,
+    /// Favors highly rated and rarely played songs
+    Weighted,
+}
+
+impl ShuffleMode {
+    pub fn next(self) -> Self {
+        match self {
+            ShuffleMode::Random => ShuffleMode::NoRepeat,
+            ShuffleMode::NoRepeat => ShuffleMode::Album,
+            ShuffleMode::Album => ShuffleMode::Weighted,
+            ShuffleMode::Weighted => ShuffleMode::Random,
+        }
+    }
+
+    /// Short label for the buffer line
+    pub fn tag(self) -> &'static str {
+        match self {
+            ShuffleMode::Random => "sh:R",
+            ShuffleMode::NoRepeat => "sh:N",
+            ShuffleMode::Album => "sh:A",
+            ShuffleMode::Weighted => "sh:W",
+        }
+    }
+
+    /// Reorders `items` in place. `after` is what plays right before them,
+    /// and `plays` the play counts weighted shuffle draws on.
+    pub fn apply<T, F>(
+        self,
+        items: &mut [T],
+        song: F,
+        after: Option<&SimpleSong>,
+        plays: &HashMap<u64, u16>,
+    ) where
+        T: Clone,
+        F: Fn(&T) -> &SimpleSong,
+    {
+        match self {
+            ShuffleMode::Random => items.shuffle(&mut rand::rng()),
+            ShuffleMode::NoRepeat => spread(items, song, after),
+            ShuffleMode::Album => by_album(items, song),
+            ShuffleMode::Weighted => weighted(items, song, plays),
+        }
+    }
+}
+
+impl fmt::Display for ShuffleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShuffleMode::Random => write!(f, "Random"),
+            ShuffleMode::NoRepeat => write!(f, "No repeats"),
+            ShuffleMode::Album => write!(f, "Album"),
+            ShuffleMode::Weighted => write!(f, "Weighted"),
+        }
+    }
+}
+
+/// Shuffles, then pulls a later song forward wherever two neighbors clash.
+/// A queue that's mostly one artist can't always be fixed; what's left
+/// over stays where it fell.
+fn spread<T, F>(items: &mut [T], song: F, after: Option<&SimpleSong>)
+where
+    F: Fn(&T) -> &SimpleSong,
+{
+    items.shuffle(&mut rand::rng());
+
+    let key = |s: &SimpleSong| (s.id, Arc::clone(&s.artist));
+    let clashes = |(id, artist): &(u64, Arc<String>), s: &SimpleSong| {
+        *id == s.id || artist.eq_ignore_ascii_case(&s.artist)
+    };
+
+    let mut prev = after.map(key);
+    for i in 0..items.len() {
+        if let Some(p) = &prev
+            && clashes(p, song(&items[i]))
+            && let Some(j) = (i + 1..items.len()).find(|&j| !clashes(p, song(&items[j])))
+        {
+            items.swap(i, j);
+        }
+        prev = Some(key(song(&items[i])));
+    }
+}
+
+fn by_album<T, F>(items: &mut [T], song: F)
+where
+    T: Clone,
+    F: Fn(&T) -> &SimpleSong,
+{
+    let mut albums: IndexMap<i64, Vec<T>> = IndexMap::new();
+    for item in items.iter() {
+        albums
+            .entry(song(item).album_id)
+            .or_default()
+            .push(item.clone());
+    }
+
+    let mut albums = albums.into_values().collect::<Vec<_>>();
+    albums.shuffle(&mut rand::rng());
+
+    let ordered = albums
+        .into_iter()
+        .flat_map(|mut tracks| {
+            tracks.sort_by_key(|t| song(t).album_order());
+            tracks
+        })
+        .collect::<Vec<_>>();
+
+    items.clone_from_slice(&ordered);
+}
+
+/// Weighted sampling without replacement: each song draws `u^(1/w)` and
+/// the highest draws go first
+fn weighted<T, F>(items: &mut [T], song: F, plays: &HashMap<u64, u16>)
+where
+    T: Clone,
+    F: Fn(&T) -> &SimpleSong,
+{
+    let mut keyed = items
+        .iter()
+        .map(|item| {
+            let s = song(item);
+            let rating = match s.get_rating() {
+                0 => NEUTRAL_RATING,
+                r => r as f64,
+            };
+            let count = plays.get(&s.id).copied().unwrap_or(0) as f64;
+            let weight = rating / (1.0 + count).sqrt();
+
+            (rand::random::<f64>().powf(1.0 / weight), item.clone())
+        })
+        .collect::<Vec<_>>();
+
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (slot, (_, item)) in items.iter_mut().zip(keyed) {
+        *slot = item;
+    }
+}
@@ -0,0 +1,133 @@
+use super::Output;
+use std::{
+    cell::Cell,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// voxio clamps perceptual volume to this range
+const MAX_VOLUME: f32 = 1.5;
+/// How long the level stays on screen after being changed
+const LABEL_DURATION: Duration = Duration::from_millis(1500);
+
+/// Independent fade sources. Each owns one envelope and the output is the
+/// product of all of them, so overlapping fades never fight over the volume.
+#[derive(Clone, Copy)]
+pub enum Fade {
+    Crossfade,
+    SleepTimer,
+    Pause,
+}
+
+const FADE_COUNT: usize = 3;
+
+/// The user's volume and the fade envelopes layered on top of it, along
+/// with the gain measured for songs that lack ReplayGain tags.
+///
+/// The volume is on voxio's perceptual scale, which squares it on the way
+/// out, so 50% is about -12dB and sounds roughly half as loud. Muting keeps
+/// it for when sound comes back.
+///
+/// Fades only ever touch the envelopes, so the volume shown in the UI and
+/// saved to the session never dips mid-fade. Shared between the player handle
+/// and the UI on the main thread, hence `Rc` rather than `Arc`.
+pub struct OutputGain {
+    backend: Arc<Output>,
+    volume: Cell<f32>,
+    envelopes: [Cell<f32>; FADE_COUNT],
+    loudness: Cell<f32>,
+    muted: Cell<bool>,
+    changed_at: Cell<Option<Instant>>,
+}
+
+impl OutputGain {
+    pub fn new(backend: Arc<Output>) -> Self {
+        let volume = backend.volume();
+        OutputGain {
+            backend,
+            volume: Cell::new(volume),
+            envelopes: std::array::from_fn(|_| Cell::new(1.0)),
+            loudness: Cell::new(1.0),
+            muted: Cell::new(false),
+            changed_at: Cell::new(None),
+        }
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume.get()
+    }
+
+    /// Restoring a saved volume, without flashing it on screen
+    pub fn set_volume(&self, volume: f32) {
+        self.volume.set(volume.clamp(0.0, MAX_VOLUME));
+        self.apply();
+    }
+
+    /// A change asked for by the user, which also unmutes
+    pub fn adjust_volume(&self, delta: f32) {
+        let volume = (self.volume() + delta).clamp(0.0, MAX_VOLUME);
+        // Snapping to whole percents keeps coarse steps landing on round
+        // numbers after a few fine ones
+        self.volume.set((volume * 100.0).round() / 100.0);
+        self.muted.set(false);
+        self.changed_at.set(Some(Instant::now()));
+        self.apply();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.get()
+    }
+
+    pub fn toggle_mute(&self) {
+        self.muted.set(!self.muted.get());
+        self.changed_at.set(Some(Instant::now()));
+        self.apply();
+    }
+
+    /// What to show in place of the playing title, shortly after a change
+    pub fn label(&self) -> Option<String> {
+        self.changed_at
+            .get()
+            .filter(|t| t.elapsed() < LABEL_DURATION)
+            .map(|_| match self.is_muted() {
+                true => String::from("Muted"),
+                false => format!("Volume {}%", (self.volume() * 100.0).round()),
+            })
+    }
+
+    /// Combined attenuation of every active fade
+    pub fn envelope(&self) -> f32 {
+        self.envelopes.iter().map(Cell::get).product()
+    }
+
+    pub fn envelope_of(&self, fade: Fade) -> f32 {
+        self.envelopes[fade as usize].get()
+    }
+
+    pub fn set_envelope(&self, fade: Fade, envelope: f32) {
+        let envelope = envelope.clamp(0.0, 1.0);
+        let slot = &self.envelopes[fade as usize];
+        if envelope != slot.get() {
+            slot.set(envelope);
+            self.apply();
+        }
+    }
+
+    /// Takes linear gain, which the perceptual volume scale squares
+    pub fn set_loudness(&self, gain: f32) {
+        let factor = gain.max(0.0).sqrt();
+        if factor != self.loudness.get() {
+            self.loudness.set(factor);
+            self.apply();
+        }
+    }
+
+    fn apply(&self) {
+        let volume = match self.is_muted() {
+            true => 0.0,
+            false => self.volume(),
+        };
+        self.backend
+            .set_volume(volume * self.envelope() * self.loudness.get());
+    }
+}
@@ -1,16 +1,46 @@
+use super::{Fade, Output, OutputGain, prefetch::Prefetch};
+use crate::user_config;
 use anyhow::Result;
 use crossbeam_channel::Receiver;
-use std::{sync::Arc, time::Duration};
-use voxio::{Vox, VoxEvent, VoxEvents};
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use voxio::{ReplayGainMode, VoxEvent};
 
 pub struct PlayerHandle {
-    backend: Arc<Vox>,
-    events: VoxEvents,
+    backend: Arc<Output>,
+    gain: Rc<OutputGain>,
+    crossfade: f64,
+    fade_in: bool,
+    pause_fade: Duration,
+    pause_ramp: Cell<Option<PauseRamp>>,
+    prefetch: Prefetch,
+}
+
+/// A pause or resume in progress, from one envelope level towards 0 or 1
+#[derive(Clone, Copy)]
+struct PauseRamp {
+    from: f32,
+    to: f32,
+    started: Instant,
+    /// What a ramp across the whole range would take
+    length: Duration,
 }
 
 impl PlayerHandle {
-    pub fn new(backend: Arc<Vox>, events: VoxEvents) -> Result<Self> {
-        Ok(Self { backend, events })
+    pub fn new(backend: Arc<Output>, gain: Rc<OutputGain>) -> Result<Self> {
+        Ok(Self {
+            backend,
+            gain,
+            crossfade: user_config().general.crossfade_secs,
+            fade_in: false,
+            pause_fade: Duration::from_millis(user_config().general.pause_fade_ms as u64),
+            pause_ramp: Cell::new(None),
+            prefetch: Prefetch::default(),
+        })
     }
 }
 
@@ -18,11 +48,20 @@ impl PlayerHandle {
 //    COMMAND HANDLER
 // =====================
 impl PlayerHandle {
-    pub fn play(&self, s: &str) -> Result<()> {
+    pub fn play(&mut self, s: &str) -> Result<()> {
+        // Manual plays cut in at full volume rather than fading
+        self.reset_fade();
+        self.reset_pause_fade();
         self.backend.play(s)?;
         Ok(())
     }
 
+    /// Reads the head of `song` ahead of it being opened, or stops doing so
+    /// once it's `None`
+    pub fn prefetch(&mut self, song: Option<&str>) {
+        self.prefetch.warm(song);
+    }
+
     pub fn set_next(&self, song: Option<&str>) -> Result<()> {
         match song {
             Some(s) => self.backend.set_next(s)?,
@@ -31,22 +70,49 @@ impl PlayerHandle {
         Ok(())
     }
 
+    /// A pause still fading out counts as paused, so toggling again brings
+    /// the volume straight back up
     pub fn toggle_playback(&self) {
-        match self.backend.is_paused() {
-            true => self.backend.resume(),
-            false => self.backend.pause(),
+        match self.backend.is_paused() || self.is_pausing() {
+            true => self.resume(),
+            false => self.pause(),
         }
     }
 
     pub fn resume(&self) {
-        self.backend.resume();
+        if self.backend.is_paused() {
+            self.backend.resume();
+        }
+        self.ramp_pause_envelope(1.0);
     }
 
+    /// Fades out first, with `pause_fade_ms` set
     pub fn pause(&self) {
+        if self.backend.is_paused() || self.is_pausing() {
+            return;
+        }
+        match self.pause_fade.is_zero() {
+            true => self.pause_now(),
+            false => self.ramp_pause_envelope(0.0),
+        }
+    }
+
+    /// Pauses without a fade, for when nothing should be heard at all. The
+    /// envelope is held at silence so the resume can fade back in.
+    pub fn pause_now(&self) {
+        self.pause_ramp.set(None);
         self.backend.pause();
+
+        let held = match self.pause_fade.is_zero() {
+            true => 1.0,
+            false => 0.0,
+        };
+        self.gain.set_envelope(Fade::Pause, held);
     }
 
-    pub fn stop(&self) {
+    pub fn stop(&mut self) {
+        self.reset_fade();
+        self.reset_pause_fade();
         self.backend.stop();
     }
 
@@ -58,16 +124,154 @@ impl PlayerHandle {
         self.backend.seek_relative(dur);
     }
 
-    fn volume(&self) -> f32 {
-        self.backend.volume()
-    }
-
     pub fn set_volume(&self, vol: f32) {
-        self.backend.set_volume(vol);
+        self.gain.set_volume(vol);
     }
 
     pub fn adjust_volume(&self, delta: f32) {
-        self.backend.set_volume(self.volume() + delta);
+        self.gain.adjust_volume(delta);
+    }
+
+    pub fn toggle_mute(&self) {
+        self.gain.toggle_mute();
+    }
+
+    /// Takes effect on the current track immediately. Tags are read and
+    /// peak-limited by the engine; untagged tracks play at unity.
+    pub fn set_replay_gain(&self, mode: ReplayGainMode) {
+        self.backend.set_replaygain(mode);
+    }
+}
+
+// ===============
+//    CROSSFADE
+// ===============
+
+impl PlayerHandle {
+    pub fn crossfade(&self) -> f64 {
+        self.crossfade
+    }
+
+    pub fn set_crossfade(&mut self, secs: f64) {
+        self.crossfade = secs.clamp(0.0, 12.0);
+        if self.crossfade == 0.0 {
+            self.reset_fade();
+        }
+    }
+
+    /// Only tracks reached through a gapless hand-off fade in; anything the
+    /// user picked starts at full volume.
+    pub fn begin_track(&mut self, gapless: bool) {
+        self.fade_in = gapless && self.crossfade > 0.0;
+    }
+
+    pub fn reset_fade(&mut self) {
+        self.fade_in = false;
+        self.gain.set_envelope(Fade::Crossfade, 1.0);
+    }
+
+    /// voxio decodes a single stream, so rather than overlapping the two
+    /// tracks this fades the outgoing one down and the incoming one up around
+    /// the gapless boundary. Called once per frame.
+    ///
+    /// `handoff` is whether a next track is preloaded to take over. Without
+    /// one, the last track in the queue plays out at full volume.
+    pub fn update_crossfade(&self, handoff: bool) {
+        if self.crossfade == 0.0 || !self.backend.is_active() {
+            return;
+        }
+
+        let duration = self.backend.duration().as_secs_f64();
+        if duration <= 0.0 {
+            return;
+        }
+
+        // Tracks shorter than two fades split their length between them
+        let fade = self.crossfade.min(duration / 2.0);
+        let position = self.backend.position().as_secs_f64();
+
+        let fade_out = match handoff {
+            true => (duration - position) / fade,
+            false => 1.0,
+        };
+        let fade_in = match self.fade_in {
+            true => position / fade,
+            false => 1.0,
+        };
+
+        self.gain
+            .set_envelope(Fade::Crossfade, fade_out.min(fade_in) as f32);
+    }
+}
+
+// ================
+//    PAUSE FADE
+// ================
+
+impl PlayerHandle {
+    fn is_pausing(&self) -> bool {
+        self.pause_ramp.get().is_some_and(|r| r.to == 0.0)
+    }
+
+    /// Heads for `to` from wherever the envelope is now, so a pause cut short
+    /// by a resume turns around instead of jumping
+    fn ramp_pause_envelope(&self, to: f32) {
+        let from = self.gain.envelope_of(Fade::Pause);
+        match self.pause_fade.is_zero() || from == to {
+            true => self.pause_ramp.set(None),
+            false => self.pause_ramp.set(Some(PauseRamp {
+                from,
+                to,
+                started: Instant::now(),
+                length: self.pause_fade,
+            })),
+        }
+        if self.pause_fade.is_zero() {
+            self.gain.set_envelope(Fade::Pause, to);
+        }
+    }
+
+    /// Brings the volume up from silence over `length`, for a track picked
+    /// back up partway through rather than cutting in mid-phrase
+    pub fn fade_in(&self, length: Duration) {
+        match length.is_zero() {
+            true => self.reset_pause_fade(),
+            false => {
+                self.gain.set_envelope(Fade::Pause, 0.0);
+                self.pause_ramp.set(Some(PauseRamp {
+                    from: 0.0,
+                    to: 1.0,
+                    started: Instant::now(),
+                    length,
+                }));
+            }
+        }
+    }
+
+    fn reset_pause_fade(&self) {
+        self.pause_ramp.set(None);
+        self.gain.set_envelope(Fade::Pause, 1.0);
+    }
+
+    /// Called once per frame. The engine is only paused once the fade out
+    /// reaches silence.
+    pub fn update_pause_fade(&self) {
+        let Some(ramp) = self.pause_ramp.get() else {
+            return;
+        };
+
+        // A partial ramp takes its share of the full fade time
+        let span = ramp.length.as_secs_f32() * (ramp.to - ramp.from).abs();
+        let t = (ramp.started.elapsed().as_secs_f32() / span).min(1.0);
+        self.gain
+            .set_envelope(Fade::Pause, ramp.from + (ramp.to - ramp.from) * t);
+
+        if t >= 1.0 {
+            self.pause_ramp.set(None);
+            if ramp.to == 0.0 {
+                self.pause_now();
+            }
+        }
     }
 }
 
@@ -77,13 +281,21 @@ impl PlayerHandle {
 
 impl PlayerHandle {
     pub fn events(&self) -> &Receiver<VoxEvent> {
-        self.events.receiver()
+        self.backend.events()
     }
 
     pub fn elapsed(&self) -> Duration {
         self.backend.position()
     }
 
+    pub fn duration(&self) -> Duration {
+        self.backend.duration()
+    }
+
+    pub fn time_left(&self) -> Duration {
+        self.duration().saturating_sub(self.elapsed())
+    }
+
     pub fn is_paused(&self) -> bool {
         self.backend.is_paused()
     }
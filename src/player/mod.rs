@@ -1,3 +1,9 @@
+mod gain;
 mod handle;
+mod output;
+mod prefetch;
 
+pub use gain::{Fade, OutputGain};
 pub use handle::PlayerHandle;
+pub use output::Output;
+pub use prefetch::PREFETCH_LEAD;
@@ -0,0 +1,145 @@
+use anyhow::{Result, anyhow};
+use crossbeam_channel::{Receiver, never};
+use std::{
+    cell::{Cell, OnceCell},
+    time::Duration,
+};
+use voxio::{ReplayGainMode, TapHandle, Vox, VoxEvent, VoxEvents};
+
+/// The engine, once there is a device to run it on. Launching with no
+/// output leaves this empty so the library can still be browsed: reads
+/// answer as if nothing is playing, and only `play` and `set_next` fail.
+/// The volume and ReplayGain mode set in the meantime carry over to the
+/// engine when it [connects](Self::connect).
+pub struct Output {
+    vox: OnceCell<Vox>,
+    events: OnceCell<VoxEvents>,
+    idle: Receiver<VoxEvent>,
+    volume: Cell<f32>,
+    replay_gain: Cell<ReplayGainMode>,
+}
+
+impl Output {
+    pub fn new(replay_gain: ReplayGainMode) -> Self {
+        Output {
+            vox: OnceCell::new(),
+            events: OnceCell::new(),
+            idle: never(),
+            volume: Cell::new(1.0),
+            replay_gain: Cell::new(replay_gain),
+        }
+    }
+
+    /// Hands back the engine's tap for the visualizer. Only the first
+    /// engine is kept.
+    pub fn connect(&self, (mut vox, events): (Vox, VoxEvents)) -> Option<TapHandle> {
+        if self.is_connected() {
+            return None;
+        }
+        vox.set_volume(self.volume.get());
+        vox.set_replaygain(self.replay_gain.get());
+        let tap = vox.take_tap();
+
+        let _ = self.vox.set(vox);
+        let _ = self.events.set(events);
+        tap
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.vox.get().is_some()
+    }
+
+    fn engine(&self) -> Result<&Vox> {
+        self.vox
+            .get()
+            .ok_or_else(|| anyhow!("No audio output device is available yet"))
+    }
+
+    /// Never yields anything until the engine is connected
+    pub fn events(&self) -> &Receiver<VoxEvent> {
+        self.events.get().map_or(&self.idle, VoxEvents::receiver)
+    }
+}
+
+// =====================
+//    COMMAND HANDLER
+// =====================
+impl Output {
+    pub fn play(&self, s: &str) -> Result<()> {
+        self.engine()?.play(s)?;
+        Ok(())
+    }
+
+    pub fn set_next(&self, s: &str) -> Result<()> {
+        self.engine()?.set_next(s)?;
+        Ok(())
+    }
+
+    pub fn clear_next(&self) {
+        self.vox.get().inspect(|v| v.clear_next());
+    }
+
+    pub fn pause(&self) {
+        self.vox.get().inspect(|v| v.pause());
+    }
+
+    pub fn resume(&self) {
+        self.vox.get().inspect(|v| v.resume());
+    }
+
+    pub fn stop(&self) {
+        self.vox.get().inspect(|v| v.stop());
+    }
+
+    pub fn seek_to(&self, secs: f64) {
+        self.vox.get().inspect(|v| v.seek_to(secs));
+    }
+
+    pub fn seek_relative(&self, delta: f64) {
+        self.vox.get().inspect(|v| v.seek_relative(delta));
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.volume.set(volume);
+        self.vox.get().inspect(|v| v.set_volume(volume));
+    }
+
+    pub fn set_replaygain(&self, mode: ReplayGainMode) {
+        self.replay_gain.set(mode);
+        self.vox.get().inspect(|v| v.set_replaygain(mode));
+    }
+}
+
+// ===============
+//    ACCESSORS
+// ===============
+impl Output {
+    pub fn volume(&self) -> f32 {
+        self.vox.get().map_or(self.volume.get(), Vox::volume)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.vox.get().is_some_and(Vox::is_active)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.vox.get().is_some_and(Vox::is_paused)
+    }
+
+    pub fn position(&self) -> Duration {
+        self.vox.get().map_or(Duration::ZERO, Vox::position)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.vox.get().map_or(Duration::ZERO, Vox::duration)
+    }
+
+    /// Zero until connected
+    pub fn sample_rate(&self) -> u32 {
+        self.vox.get().map_or(0, Vox::sample_rate)
+    }
+
+    pub fn channels(&self) -> usize {
+        self.vox.get().map_or(2, Vox::channels)
+    }
+}
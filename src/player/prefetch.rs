@@ -0,0 +1,77 @@
+use std::{
+    fs::File,
+    io::Read,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long before the end of a track the next one is read ahead
+pub const PREFETCH_LEAD: Duration = Duration::from_secs(2);
+/// Plenty to get the decoder going; the rest streams in as it plays
+const HEAD_BYTES: u64 = 4 * 1024 * 1024;
+const CHUNK: usize = 64 * 1024;
+
+/// Reads the head of the next track into the OS cache shortly before the
+/// hand-off, so opening it doesn't stall on a cold disk or network share.
+/// Only one file is read at a time, through a fixed buffer, and a read for
+/// a track that's no longer next is called off.
+pub struct Prefetch {
+    path: Option<String>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Default for Prefetch {
+    fn default() -> Self {
+        Prefetch {
+            path: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Prefetch {
+    /// Called each frame with the track to read ahead, if any. Nothing is
+    /// done while it stays the same.
+    pub fn warm(&mut self, next: Option<&str>) {
+        if self.path.as_deref() == next {
+            return;
+        }
+
+        self.cancel.store(true, Ordering::Relaxed);
+        self.path = next.map(String::from);
+
+        if let Some(path) = next {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.cancel = Arc::clone(&cancel);
+
+            let path = path.to_string();
+            thread::spawn(move || read_head(&path, &cancel));
+        }
+    }
+}
+
+impl Drop for Prefetch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Errors don't matter here; the real open reports them
+fn read_head(path: &str, cancel: &AtomicBool) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+
+    let mut head = file.take(HEAD_BYTES);
+    let mut chunk = vec![0u8; CHUNK];
+    while !cancel.load(Ordering::Relaxed) {
+        match head.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Longest a request may take, connecting included
+const TIMEOUT_SECS: u32 = 15;
+
+pub struct Response {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// POSTs `body` with curl, which every desktop the player runs on already
+/// has, and which brings TLS along without the crate needing any. The
+/// request goes in on stdin, so tokens never show on a command line.
+pub fn post(url: &str, headers: &[(&str, &str)], body: &str) -> Result<Response> {
+    let mut config = format!(
+        "url = {}\nsilent\nshow-error\nmax-time = {TIMEOUT_SECS}\ndump-header = \"-\"\n",
+        quote(url)
+    );
+    for (name, value) in headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{name}: {value}"))
+        ));
+    }
+    // Unlike `data`, `data-raw` never reads a file for a body starting with @
+    config.push_str(&format!("data-raw = {}\n", quote(body)));
+
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Scrobbling needs curl, which could not be started")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The headers come first, once for every response on the way (an early
+/// `100 Continue` among them), and the last set is the one that counts
+fn parse(output: &str) -> Result<Response> {
+    let mut rest = output;
+    loop {
+        let Some((head, body)) = rest.split_once("\r\n\r\n") else {
+            bail!("Malformed response");
+        };
+
+        let mut lines = head.lines();
+        let status = lines
+            .next()
+            .and_then(|l| l.split_whitespace().nth(1))
+            .and_then(|s| s.parse::<u16>().ok())
+            .context("Malformed response")?;
+
+        if body.starts_with("HTTP/") {
+            rest = body;
+            continue;
+        }
+
+        let headers = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(n, v)| (n.trim().to_string(), v.trim().to_string()))
+            .collect();
+
+        return Ok(Response {
+            status,
+            headers,
+            body: body.to_string(),
+        });
+    }
+}
+
+/// A double-quoted value in curl's config syntax
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// For form bodies, every byte but the unreserved ones percent-encoded
+pub fn url_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// The text of a JSON string literal, quotes not included, with its
+/// escapes turned back into the characters they stand for
+pub fn json_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => {
+                let unit = |chars: &mut std::str::Chars| {
+                    let hex = chars.by_ref().take(4).collect::<String>();
+                    u32::from_str_radix(&hex, 16).ok()
+                };
+                let code = match unit(&mut chars) {
+                    // A surrogate pair, for anything past the first plane
+                    Some(high @ 0xd800..0xdc00) => match chars.as_str().strip_prefix("\\u") {
+                        Some(rest) => {
+                            chars = rest.chars();
+                            unit(&mut chars)
+                                .filter(|low| (0xdc00..0xe000).contains(low))
+                                .map(|low| 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                        }
+                        None => None,
+                    },
+                    code => code,
+                };
+                out.push(code.and_then(char::from_u32).unwrap_or('\u{fffd}'));
+            }
+            // `\"`, `\\` and `\/` stand for themselves
+            Some(c) => out.push(c),
+            None => (),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_past_an_early_continue() {
+        let output = "HTTP/1.1 100 Continue\r\n\r\nHTTP/2 429\r\nx-ratelimit-reset-in: 7\r\n\r\n{}";
+        let response = parse(output).unwrap();
+
+        assert_eq!(response.status, 429);
+        assert_eq!(response.header("X-RateLimit-Reset-In"), Some("7"));
+        assert_eq!(response.body, "{}");
+    }
+
+    #[test]
+    fn unescapes_json_strings() {
+        assert_eq!(
+            json_unescape(r#"Invalid \"track\" \\ A\/B\n\u00e9\ud83c\udfb5"#),
+            "Invalid \"track\" \\ A/B\né🎵"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use super::{Listen, Outcome, Service, http, md5::md5_hex};
+use anyhow::{Result, bail};
+use regex::Regex;
+use serde::Deserialize;
+use std::{sync::LazyLock, time::Duration};
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+/// The most scrobbles Last.fm takes in one request
+const MAX_BATCH: usize = 50;
+/// Last.fm doesn't say how long to back off for
+const WAIT: Duration = Duration::from_secs(60);
+/// Shorter tracks are never scrobbled
+const MIN_LENGTH_SECS: u32 = 30;
+
+static ERROR_CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""error"\s*:\s*(\d+)"#).unwrap());
+static ERROR_MESSAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""message"\s*:\s*"((?:[^"\\]|\\.)*)""#).unwrap());
+static SESSION_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""key"\s*:\s*"([0-9A-Za-z_-]+)""#).unwrap());
+
+/// An API account is made at last.fm/api. The session key can be given, or
+/// is asked for with the username and password, once a session.
+#[derive(Deserialize)]
+pub struct LastFmConfig {
+    pub api_key: String,
+    pub api_secret: String,
+    #[serde(default)]
+    pub session_key: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+pub struct LastFm {
+    config: LastFmConfig,
+}
+
+impl LastFm {
+    pub fn new(config: LastFmConfig) -> Self {
+        LastFm { config }
+    }
+
+    /// Every request is signed: each parameter's name and value, sorted by
+    /// name, then the secret, hashed together
+    fn call(&self, params: &mut Vec<(String, String)>) -> Result<(u16, String)> {
+        params.push(("api_key".to_string(), self.config.api_key.clone()));
+        params.sort();
+
+        let mut signed = params
+            .iter()
+            .map(|(k, v)| format!("{k}{v}"))
+            .collect::<String>();
+        signed.push_str(&self.config.api_secret);
+        params.push(("api_sig".to_string(), md5_hex(signed.as_bytes())));
+        params.push(("format".to_string(), "json".to_string()));
+
+        let body = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", http::url_encode(k), http::url_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let response = http::post(
+            API_URL,
+            &[("Content-Type", "application/x-www-form-urlencoded")],
+            &body,
+        )?;
+        Ok((response.status, response.body))
+    }
+
+    fn session_key(&mut self) -> Result<String> {
+        if let Some(key) = &self.config.session_key {
+            return Ok(key.clone());
+        }
+        let (Some(username), Some(password)) = (&self.config.username, &self.config.password)
+        else {
+            bail!("Last.fm needs a session_key, or a username and password");
+        };
+
+        let mut params = vec![
+            ("method".to_string(), "auth.getMobileSession".to_string()),
+            ("username".to_string(), username.clone()),
+            ("password".to_string(), password.clone()),
+        ];
+        let (_, body) = self.call(&mut params)?;
+        let Some(key) = SESSION_KEY.captures(&body).map(|c| c[1].to_string()) else {
+            bail!("Last.fm login failed: {}", error_message(&body));
+        };
+
+        self.config.session_key = Some(key.clone());
+        Ok(key)
+    }
+
+    fn send(&mut self, mut params: Vec<(String, String)>) -> Result<Outcome> {
+        let key = match self.session_key() {
+            Ok(key) => key,
+            Err(e) => return Ok(Outcome::Unauthorized(e.to_string())),
+        };
+        params.push(("sk".to_string(), key));
+
+        let (status, body) = self.call(&mut params)?;
+        let code = ERROR_CODE
+            .captures(&body)
+            .and_then(|c| c[1].parse::<u32>().ok());
+
+        let outcome = match code {
+            None if (200..300).contains(&status) => Outcome::Accepted,
+            // Invalid session, authentication failed, API key suspended
+            Some(4 | 9 | 10 | 26) => {
+                Outcome::Unauthorized(format!("Last.fm: {}", error_message(&body)))
+            }
+            // Service offline or temporarily unavailable, rate limit exceeded
+            Some(11 | 16 | 29) | None => Outcome::Wait(WAIT),
+            Some(_) => Outcome::Rejected(error_message(&body)),
+        };
+        Ok(outcome)
+    }
+}
+
+impl Service for LastFm {
+    fn name(&self) -> &'static str {
+        "lastfm"
+    }
+
+    fn max_batch(&self) -> usize {
+        MAX_BATCH
+    }
+
+    fn now_playing(&mut self, listen: &Listen) -> Result<Outcome> {
+        let mut params = vec![("method".to_string(), "track.updateNowPlaying".to_string())];
+        params.extend(track_params(listen, None));
+        self.send(params)
+    }
+
+    fn submit(&mut self, listens: &[Listen]) -> Result<Outcome> {
+        let listens = listens
+            .iter()
+            .filter(|l| l.duration_secs > MIN_LENGTH_SECS)
+            .collect::<Vec<_>>();
+        if listens.is_empty() {
+            return Ok(Outcome::Accepted);
+        }
+
+        let mut params = vec![("method".to_string(), "track.scrobble".to_string())];
+        for (i, listen) in listens.into_iter().enumerate() {
+            params.extend(track_params(listen, Some(i)));
+            params.push((format!("timestamp[{i}]"), listen.listened_at.to_string()));
+        }
+        self.send(params)
+    }
+}
+
+/// A scrobble's fields are numbered, as many go in one request
+fn track_params(listen: &Listen, index: Option<usize>) -> Vec<(String, String)> {
+    let name = |field: &str| match index {
+        Some(i) => format!("{field}[{i}]"),
+        None => field.to_string(),
+    };
+
+    let mut params = vec![
+        (name("artist"), listen.artist.clone()),
+        (name("track"), listen.title.clone()),
+        (name("duration"), listen.duration_secs.to_string()),
+    ];
+    if !listen.album.is_empty() {
+        params.push((name("album"), listen.album.clone()));
+    }
+    params
+}
+
+fn error_message(body: &str) -> String {
+    ERROR_MESSAGE
+        .captures(body)
+        .map(|c| http::json_unescape(&c[1]))
+        .unwrap_or_else(|| "no response".to_string())
+}
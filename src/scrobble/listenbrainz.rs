@@ -0,0 +1,132 @@
+use super::{Listen, Outcome, Service, http};
+use crate::json_string;
+use anyhow::Result;
+use regex::Regex;
+use serde::Deserialize;
+use std::{sync::LazyLock, time::Duration};
+
+/// Well under the server's limit of a thousand listens a request
+const MAX_BATCH: usize = 100;
+/// Waited when a 429 comes without saying for how long
+const DEFAULT_WAIT: Duration = Duration::from_secs(60);
+
+static ERROR_MESSAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""error"\s*:\s*"((?:[^"\\]|\\.)*)""#).unwrap());
+
+#[derive(Deserialize)]
+pub struct ListenBrainzConfig {
+    /// From the account's settings page
+    pub token: String,
+    /// Anything answering the same API, a self-hosted server say
+    #[serde(default = "default_url")]
+    pub url: String,
+}
+
+fn default_url() -> String {
+    "https://api.listenbrainz.org".to_string()
+}
+
+pub struct ListenBrainz {
+    config: ListenBrainzConfig,
+}
+
+impl ListenBrainz {
+    pub fn new(config: ListenBrainzConfig) -> Self {
+        ListenBrainz { config }
+    }
+
+    fn send(&self, listen_type: &str, listens: &[Listen]) -> Result<Outcome> {
+        let payload = listens
+            .iter()
+            .map(|l| track_json(l, listen_type != "playing_now"))
+            .collect::<Vec<_>>()
+            .join(",");
+        let body = format!("{{\"listen_type\":\"{listen_type}\",\"payload\":[{payload}]}}");
+
+        let url = format!("{}/1/submit-listens", self.config.url.trim_end_matches('/'));
+        let auth = format!("Token {}", self.config.token);
+        let response = http::post(
+            &url,
+            &[
+                ("Authorization", &auth),
+                ("Content-Type", "application/json"),
+            ],
+            &body,
+        )?;
+
+        let outcome = match response.status {
+            _ if response.is_success() => Outcome::Accepted,
+            401 => Outcome::Unauthorized("ListenBrainz token was not accepted".to_string()),
+            429 => Outcome::Wait(
+                response
+                    .header("X-RateLimit-Reset-In")
+                    .and_then(|s| s.parse().ok())
+                    .map_or(DEFAULT_WAIT, Duration::from_secs),
+            ),
+            400..500 => Outcome::Rejected(error_message(&response.body)),
+            // The server's troubles, which pass
+            _ => Outcome::Wait(DEFAULT_WAIT),
+        };
+
+        // Out of requests for now, though this one went through
+        if let Outcome::Accepted = outcome
+            && response.header("X-RateLimit-Remaining") == Some("0")
+            && let Some(secs) = response
+                .header("X-RateLimit-Reset-In")
+                .and_then(|s| s.parse().ok())
+        {
+            return Ok(Outcome::AcceptedThenWait(Duration::from_secs(secs)));
+        }
+        Ok(outcome)
+    }
+}
+
+impl Service for ListenBrainz {
+    fn name(&self) -> &'static str {
+        "listenbrainz"
+    }
+
+    fn max_batch(&self) -> usize {
+        MAX_BATCH
+    }
+
+    fn now_playing(&mut self, listen: &Listen) -> Result<Outcome> {
+        self.send("playing_now", std::slice::from_ref(listen))
+    }
+
+    fn submit(&mut self, listens: &[Listen]) -> Result<Outcome> {
+        match listens.len() {
+            1 => self.send("single", listens),
+            _ => self.send("import", listens),
+        }
+    }
+}
+
+fn track_json(listen: &Listen, with_time: bool) -> String {
+    let listened_at = match with_time {
+        true => format!("\"listened_at\":{},", listen.listened_at),
+        false => String::new(),
+    };
+    let release = match listen.album.is_empty() {
+        true => String::new(),
+        false => format!("\"release_name\":\"{}\",", json_string(&listen.album)),
+    };
+
+    format!(
+        "{{{listened_at}\"track_metadata\":{{\"artist_name\":\"{}\",\"track_name\":\"{}\",{release}\
+         \"additional_info\":{{\"duration_ms\":{},\"media_player\":\"NoctaVox\",\
+         \"submission_client\":\"NoctaVox\",\"submission_client_version\":\"{}\"}}}}}}",
+        json_string(&listen.artist),
+        json_string(&listen.title),
+        listen.duration_secs as u64 * 1000,
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// The reason given for a refusal, or the whole body when it gives none
+fn error_message(body: &str) -> String {
+    ERROR_MESSAGE
+        .captures(body)
+        .map(|c| http::json_unescape(&c[1]))
+        .unwrap_or_else(|| body.trim().to_string())
+}
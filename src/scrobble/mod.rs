@@ -0,0 +1,260 @@
+mod http;
+mod lastfm;
+mod listenbrainz;
+mod md5;
+
+use crate::{Database, SCROBBLE_PATH};
+use anyhow::{Context, Result};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use lastfm::{LastFm, LastFmConfig};
+use listenbrainz::{ListenBrainz, ListenBrainzConfig};
+use serde::Deserialize;
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+/// How often a queue that couldn't be sent is tried again
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Each failure in a row doubles the wait, up to this
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+
+/// `scrobble.toml`, next to `config.toml` so the main config can be shared
+/// without the tokens in it
+#[derive(Deserialize, Default)]
+pub struct ScrobbleConfig {
+    pub listenbrainz: Option<ListenBrainzConfig>,
+    pub lastfm: Option<LastFmConfig>,
+}
+
+impl ScrobbleConfig {
+    /// No file is no scrobbling, rather than an error
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(&*SCROBBLE_PATH) {
+            Ok(s) => toml::from_str(&s)
+                .with_context(|| format!("Could not read {}", SCROBBLE_PATH.display())),
+            Err(_) => Ok(ScrobbleConfig::default()),
+        }
+    }
+}
+
+/// Written as it was when played, so it survives the song leaving the
+/// library while it waits to be sent
+#[derive(Clone)]
+pub struct Listen {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration_secs: u32,
+    /// When the track started, in seconds since the epoch
+    pub listened_at: i64,
+}
+
+pub(crate) enum Outcome {
+    Accepted,
+    /// Sent, but that was the last request allowed for a while
+    AcceptedThenWait(Duration),
+    /// Refused for good, which trying again won't change
+    Rejected(String),
+    /// Rate limited or the service is down, so the listens stay queued
+    Wait(Duration),
+    /// The credentials are wrong, so nothing more is sent this session
+    Unauthorized(String),
+}
+
+/// `Err` is the request not getting there at all, offline say, and the
+/// listens stay queued
+pub(crate) trait Service: Send {
+    /// What its queued listens are filed under
+    fn name(&self) -> &'static str;
+    fn max_batch(&self) -> usize;
+    fn now_playing(&mut self, listen: &Listen) -> Result<Outcome>;
+    fn submit(&mut self, listens: &[Listen]) -> Result<Outcome>;
+}
+
+enum ScrobbleMsg {
+    NowPlaying(Listen),
+    Scrobble(Listen),
+}
+
+/// Submits listens from a background thread. Every scrobble is written to
+/// the database first and only cleared once a service has taken it, so
+/// anything played offline goes out once the connection is back, even after
+/// a restart.
+pub struct Scrobbler {
+    sender: Sender<ScrobbleMsg>,
+    errors: Receiver<String>,
+}
+
+impl Scrobbler {
+    /// `None` when no service is set up
+    pub fn new(config: ScrobbleConfig) -> Option<Self> {
+        let mut services: Vec<Box<dyn Service>> = Vec::new();
+        if let Some(lb) = config.listenbrainz {
+            services.push(Box::new(ListenBrainz::new(lb)));
+        }
+        if let Some(lfm) = config.lastfm {
+            services.push(Box::new(LastFm::new(lfm)));
+        }
+        if services.is_empty() {
+            return None;
+        }
+
+        let (sender, receiver) = unbounded();
+        let (error_tx, errors) = unbounded();
+        thread::spawn(move || scrobble_loop(services, receiver, error_tx));
+
+        Some(Scrobbler { sender, errors })
+    }
+
+    pub fn now_playing(&self, listen: Listen) {
+        let _ = self.sender.send(ScrobbleMsg::NowPlaying(listen));
+    }
+
+    pub fn scrobble(&self, listen: Listen) {
+        let _ = self.sender.send(ScrobbleMsg::Scrobble(listen));
+    }
+
+    /// Something the user should know about, like a token being refused
+    pub fn take_error(&self) -> Option<String> {
+        self.errors.try_recv().ok()
+    }
+}
+
+struct Backend {
+    service: Box<dyn Service>,
+    /// Nothing is sent before then
+    paused_until: Option<Instant>,
+    failures: u32,
+    unauthorized: bool,
+}
+
+impl Backend {
+    fn is_ready(&self) -> bool {
+        !self.unauthorized && self.paused_until.is_none_or(|t| Instant::now() >= t)
+    }
+
+    fn pause(&mut self, wait: Duration) {
+        self.paused_until = Some(Instant::now() + wait);
+    }
+
+    fn back_off(&mut self) {
+        self.failures += 1;
+        let wait = RETRY_INTERVAL.saturating_mul(1 << self.failures.min(5));
+        self.pause(wait.min(MAX_BACKOFF));
+    }
+
+    /// Sends the queue oldest first, until it's empty or the service can't
+    /// take any more
+    fn flush(&mut self, db: &mut Database, errors: &Sender<String>) {
+        let name = self.service.name();
+        let mut batch = self.service.max_batch();
+
+        while self.is_ready() {
+            let queued = match db.get_queued_scrobbles(name, batch) {
+                Ok(queued) if !queued.is_empty() => queued,
+                _ => return,
+            };
+            let (ids, listens): (Vec<_>, Vec<_>) = queued.into_iter().unzip();
+
+            let cleared = match self.service.submit(&listens) {
+                Ok(Outcome::Accepted) => {
+                    self.failures = 0;
+                    db.remove_queued_scrobbles(&ids)
+                }
+                Ok(Outcome::AcceptedThenWait(wait)) => {
+                    self.failures = 0;
+                    self.pause(wait);
+                    db.remove_queued_scrobbles(&ids)
+                }
+                // One bad listen shouldn't take the rest of its batch with it
+                Ok(Outcome::Rejected(_)) if batch > 1 => {
+                    batch = 1;
+                    Ok(())
+                }
+                Ok(Outcome::Rejected(reason)) => {
+                    let title = &listens[0].title;
+                    let _ = errors.send(format!("{name} refused {title}: {reason}"));
+                    db.remove_queued_scrobbles(&ids)
+                }
+                Ok(Outcome::Wait(wait)) => {
+                    self.pause(wait);
+                    Ok(())
+                }
+                Ok(Outcome::Unauthorized(reason)) => {
+                    self.refuse(reason, errors);
+                    Ok(())
+                }
+                Err(_) => {
+                    self.back_off();
+                    Ok(())
+                }
+            };
+
+            // Still queued, so going on would send the same listens again
+            if cleared.is_err() {
+                self.back_off();
+                return;
+            }
+        }
+    }
+
+    fn refuse(&mut self, reason: String, errors: &Sender<String>) {
+        self.unauthorized = true;
+        let _ = errors.send(format!("{reason}. Scrobbles are kept until it's fixed."));
+    }
+}
+
+fn scrobble_loop(
+    services: Vec<Box<dyn Service>>,
+    receiver: Receiver<ScrobbleMsg>,
+    errors: Sender<String>,
+) {
+    let Ok(mut db) = Database::open() else {
+        let _ = errors.send("Scrobbling stopped: the database could not be opened".to_string());
+        return;
+    };
+
+    let mut backends = services
+        .into_iter()
+        .map(|service| Backend {
+            service,
+            paused_until: None,
+            failures: 0,
+            unauthorized: false,
+        })
+        .collect::<Vec<_>>();
+
+    // Whatever was left over from last time
+    for backend in &mut backends {
+        backend.flush(&mut db, &errors);
+    }
+
+    loop {
+        match receiver.recv_timeout(RETRY_INTERVAL) {
+            Ok(ScrobbleMsg::Scrobble(listen)) => {
+                for backend in &mut backends {
+                    let _ = db.queue_scrobble(backend.service.name(), &listen);
+                }
+            }
+            // Never queued, as it's only worth sending while it's true
+            Ok(ScrobbleMsg::NowPlaying(listen)) => {
+                for backend in backends.iter_mut().filter(|b| b.is_ready()) {
+                    match backend.service.now_playing(&listen) {
+                        Ok(Outcome::Unauthorized(reason)) => backend.refuse(reason, &errors),
+                        Ok(Outcome::Wait(wait) | Outcome::AcceptedThenWait(wait)) => {
+                            backend.pause(wait)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        for backend in &mut backends {
+            backend.flush(&mut db, &errors);
+        }
+    }
+}
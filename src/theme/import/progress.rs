@@ -27,12 +27,16 @@ pub struct SpectrumScheme {
     pub mirror: Option<bool>,
     pub decay: Option<f32>,
     pub speed: Option<f32>,
+    pub style: Option<String>,
+    pub coloring: Option<String>,
+    pub bands: Option<ProgressGradientRaw>,
 }
 
 #[derive(Deserialize)]
 pub struct OscilloScheme {
     pub color: Option<ProgressGradientRaw>,
     pub speed: Option<f32>,
+    pub style: Option<String>,
 }
 
 #[derive(Deserialize)]
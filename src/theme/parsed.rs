@@ -2,8 +2,10 @@ use crate::theme::{
     BAR_SYMBOL_PLAYED, BAR_SYMBOL_UNPLAYED, InactiveGradient, NONWAVEFORM_SPEED, ProgressGradient,
     SPECTRUM_DECAY, SPECTRUM_MIRROR, WAVEFORM_SPEED,
     import::{OscilloScheme, ProgressBarScheme, SpectrumScheme, WaveformScheme},
+    theme_utils::parse_display,
 };
 use anyhow::Result;
+use ratatui::{style::Color, symbols::Marker};
 
 #[derive(Clone)]
 pub struct ParsedBar {
@@ -58,13 +60,16 @@ impl ParsedBar {
 pub struct ParsedOscillo {
     pub color: ProgressGradient,
     pub speed: f32,
+    pub style: Marker,
 }
 
 impl ParsedOscillo {
+    /// `m` is the `[progress]` style, used unless the widget sets its own
     pub(crate) fn parse(
         p: Option<&OscilloScheme>,
         c: &ProgressGradient,
         s: Option<f32>,
+        m: Marker,
     ) -> Result<Self> {
         match p {
             Some(oscillo) => Ok(ParsedOscillo {
@@ -73,21 +78,70 @@ impl ParsedOscillo {
                     None => c.clone(),
                 },
                 speed: p.and_then(|o| o.speed).or(s).unwrap_or(NONWAVEFORM_SPEED) / 10.0,
+                style: oscillo
+                    .style
+                    .as_deref()
+                    .map_or(m, |s| parse_display(Some(s))),
             }),
             None => Ok(ParsedOscillo {
                 color: c.clone(),
                 speed: s.unwrap_or(NONWAVEFORM_SPEED) / 10.0,
+                style: m,
             }),
         }
     }
 }
 
+/// How the spectrum picks each bar's color: from the scrolling gradient by
+/// position, or from the theme's bands by the frequency the bar stands for
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum SpectrumColoring {
+    #[default]
+    Gradient,
+    Bands,
+}
+
+impl SpectrumColoring {
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::Gradient => Self::Bands,
+            Self::Bands => Self::Gradient,
+        }
+    }
+}
+
+impl From<&str> for SpectrumColoring {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().trim() {
+            "bands" | "frequency" => Self::Bands,
+            _ => Self::Gradient,
+        }
+    }
+}
+
+impl std::fmt::Display for SpectrumColoring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpectrumColoring::Gradient => write!(f, "gradient"),
+            SpectrumColoring::Bands => write!(f, "bands"),
+        }
+    }
+}
+
+/// Lowest and highest frequencies the bands are spread over
+const BAND_MIN_HZ: f32 = 20.0;
+const BAND_MAX_HZ: f32 = 20000.0;
+
 #[derive(Clone)]
 pub struct ParsedSpectrum {
     pub colors: ProgressGradient,
     pub mirror: bool,
     pub decay: f32,
     pub speed: f32,
+    pub style: Marker,
+    pub coloring: SpectrumColoring,
+    /// Bass first, each color given an even share of the octaves
+    pub bands: ProgressGradient,
 }
 
 impl ParsedSpectrum {
@@ -95,16 +149,30 @@ impl ParsedSpectrum {
         p: Option<&SpectrumScheme>,
         c: &ProgressGradient,
         s: Option<f32>,
+        m: Marker,
     ) -> Result<Self> {
         match p {
             Some(spectrum) => Ok(ParsedSpectrum {
+                bands: match (spectrum.bands.as_ref(), spectrum.color.as_ref()) {
+                    (Some(raw), _) | (None, Some(raw)) => ProgressGradient::from_raw(raw)?,
+                    (None, None) => c.clone(),
+                },
                 colors: match spectrum.color.as_ref() {
                     Some(raw) => ProgressGradient::from_raw(raw)?,
                     None => c.clone(),
                 },
+                coloring: spectrum
+                    .coloring
+                    .as_deref()
+                    .map(SpectrumColoring::from)
+                    .unwrap_or_default(),
                 mirror: spectrum.mirror.unwrap_or(SPECTRUM_MIRROR),
                 decay: spectrum.decay.unwrap_or(SPECTRUM_DECAY).clamp(0.7, 0.97),
                 speed: p.and_then(|w| w.speed).or(s).unwrap_or(NONWAVEFORM_SPEED) / 10.0,
+                style: spectrum
+                    .style
+                    .as_deref()
+                    .map_or(m, |s| parse_display(Some(s))),
             }),
 
             None => Ok(ParsedSpectrum {
@@ -112,9 +180,25 @@ impl ParsedSpectrum {
                 mirror: SPECTRUM_MIRROR,
                 decay: SPECTRUM_DECAY,
                 speed: s.unwrap_or(NONWAVEFORM_SPEED) / 10.0,
+                style: m,
+                coloring: SpectrumColoring::default(),
+                bands: c.clone(),
             }),
         }
     }
+
+    /// The band `hz` falls in. Without a list of bands there's only the one.
+    pub fn band_color(&self, hz: f32) -> Color {
+        match &self.bands {
+            ProgressGradient::Static(c) => *c,
+            ProgressGradient::Gradient(bands) => {
+                let octaves = (hz.clamp(BAND_MIN_HZ, BAND_MAX_HZ) / BAND_MIN_HZ).log2();
+                let span = (BAND_MAX_HZ / BAND_MIN_HZ).log2();
+                let idx = (octaves / span * bands.len() as f32) as usize;
+                bands[idx.min(bands.len() - 1)]
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -14,6 +14,9 @@ use ratatui::{
 };
 use std::{path::Path, rc::Rc, sync::Arc};
 
+/// Built in next to the default, for terminals with a light background
+const LIGHT_THEME: &str = include_str!("light.toml");
+
 #[derive(Clone)]
 pub struct ThemeConfig {
     pub name: String,
@@ -72,15 +75,23 @@ pub struct ThemeIcons {
 impl ThemeConfig {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let file_str = std::fs::read_to_string(path.as_ref())?;
-        let config = toml::from_str::<ThemeImport>(&file_str)?;
+        let stem = path.as_ref().file_stem().and_then(|s| s.to_str());
+        Self::from_toml(&file_str, stem)
+    }
+
+    /// The light counterpart to the default theme
+    pub fn default_light() -> Self {
+        Self::from_toml(LIGHT_THEME, None).expect("The built-in light theme parses")
+    }
+
+    /// Named by `[meta].name`, or else by `stem`
+    fn from_toml(text: &str, stem: Option<&str>) -> Result<Self> {
+        let config = toml::from_str::<ThemeImport>(text)?;
         let mut theme = Self::try_from(&config)?;
 
         theme.name = match config.meta.name.as_deref().map(str::trim) {
             Some(name) if !name.is_empty() => name.to_string(),
-            _ => path
-                .as_ref()
-                .file_stem()
-                .and_then(|s| s.to_str())
+            _ => stem
                 .ok_or(anyhow!("Could not identify theme name"))?
                 .to_string(),
         };
@@ -104,6 +115,7 @@ impl TryFrom<&ThemeImport> for ThemeConfig {
             .unwrap_or(ProgressGradient::Static(accent));
 
         let speed = progress.and_then(|p| p.speed);
+        let progress_style = parse_display(progress.and_then(|p| p.style.as_deref()));
 
         let fallback_icons = &user_config().icons;
         let config_icons = config.icons.as_ref();
@@ -142,7 +154,7 @@ impl TryFrom<&ThemeImport> for ThemeConfig {
                 .and_then(|b| b.style)
                 .unwrap_or(BorderType::Rounded),
 
-            progress_style: parse_display(progress.and_then(|p| p.style.as_deref())),
+            progress_style,
 
             bar: ParsedBar::parse(progress.and_then(|p| p.bar.as_ref()), &pcolor, speed)?,
 
@@ -150,12 +162,14 @@ impl TryFrom<&ThemeImport> for ThemeConfig {
                 progress.and_then(|p| p.oscilloscope.as_ref()),
                 &pcolor,
                 speed,
+                progress_style,
             )?,
 
             spectrum: ParsedSpectrum::parse(
                 progress.and_then(|p| p.spectrum.as_ref()),
                 &pcolor,
                 speed,
+                progress_style,
             )?,
 
             waveform: ParsedWaveform::parse(
@@ -273,6 +287,7 @@ impl Default for ThemeConfig {
                     DARK_GRAY,
                 ])),
                 speed: NONWAVEFORM_SPEED,
+                style: Marker::Braille,
             },
 
             spectrum: ParsedSpectrum {
@@ -284,6 +299,9 @@ impl Default for ThemeConfig {
                 mirror: SPECTRUM_MIRROR,
                 decay: SPECTRUM_DECAY,
                 speed: NONWAVEFORM_SPEED,
+                style: Marker::Braille,
+                coloring: SpectrumColoring::Gradient,
+                bands: ProgressGradient::Gradient(Arc::from([GOOD_RED_DARK, GOLD, DARK_WHITE])),
             },
 
             waveform: ParsedWaveform {
@@ -351,4 +369,12 @@ mod tests {
             "expected at least one [Light] theme to verify [meta].dark"
         );
     }
+
+    /// The light theme picked for light terminals is built in, so it has to
+    /// parse and come out light
+    #[test]
+    fn built_in_light_theme_parses() {
+        let _ = USER_CONFIG.set(UserConfig::default());
+        assert!(!ThemeConfig::default_light().is_dark);
+    }
 }
@@ -2,6 +2,20 @@ use crate::{
     THEME_DIR,
     theme::{DisplayTheme, ThemeConfig, ThemeIcons, fade_color},
 };
+use anyhow::{Error, anyhow};
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+/// Written on first launch, as a starting point for custom themes
+const EXAMPLE_THEME: &str = include_str!("example.toml");
+
+/// How often the theme folder is checked for edits when hot reload is on
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a failed reload is reported in the buffer line
+const NOTICE_DURATION: Duration = Duration::from_secs(4);
 
 pub struct ThemeManager {
     pub active: ThemeConfig,
@@ -9,11 +23,24 @@ pub struct ThemeManager {
     pub cached_unfocused: DisplayTheme,
 
     pub theme_lib: Vec<ThemeConfig>,
+    /// Files from the last load that aren't valid themes, and why
+    pub skipped: Vec<String>,
+
+    /// Summary of the theme folder as of the last load, to tell when it
+    /// has changed
+    stamp: u64,
+    last_poll: Instant,
+    notice: Option<(String, Instant)>,
+    /// Set once a theme is picked by hand, after which the terminal's
+    /// background no longer decides it
+    pub manual: bool,
 }
 
 impl ThemeManager {
     pub fn new() -> Self {
-        let theme_lib = Self::collect_themes();
+        Self::write_example();
+
+        let (theme_lib, skipped) = Self::collect_themes();
         let active = theme_lib.first().cloned().unwrap_or_default();
 
         let cached_focused = Self::set_display_theme(&active, true);
@@ -24,9 +51,36 @@ impl ThemeManager {
             theme_lib,
             cached_focused,
             cached_unfocused,
+            skipped,
+            stamp: Self::folder_stamp(),
+            last_poll: Instant::now(),
+            notice: None,
+            manual: false,
         }
     }
 
+    fn write_example() {
+        if THEME_DIR.exists() {
+            return;
+        }
+        if fs::create_dir_all(&*THEME_DIR).is_ok() {
+            let _ = fs::write(THEME_DIR.join("example.toml"), EXAMPLE_THEME);
+        }
+    }
+
+    /// A warning naming the theme files that couldn't be loaded
+    pub fn skipped_warning(&self) -> Option<Error> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+
+        Some(anyhow!(
+            "Skipped {} invalid theme file(s):\n\n{}",
+            self.skipped.len(),
+            self.skipped.join("\n")
+        ))
+    }
+
     pub fn get_display_theme(&self, focus: bool) -> &DisplayTheme {
         match focus {
             true => &self.cached_focused,
@@ -43,8 +97,58 @@ impl ThemeManager {
     }
 
     pub fn update_themes(&mut self) {
-        let themes = Self::collect_themes();
-        self.theme_lib = themes
+        let (themes, skipped) = Self::collect_themes();
+        self.theme_lib = themes;
+        self.skipped = skipped;
+        self.stamp = Self::folder_stamp();
+    }
+
+    /// Whether any theme file was added, removed or written to since the
+    /// last load. Checks at most every `POLL_INTERVAL`.
+    pub fn poll_changes(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        Self::folder_stamp() != self.stamp
+    }
+
+    /// Shown in the buffer line for a few seconds, in place of a popup
+    pub fn set_notice(&mut self, notice: String) {
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, t)| t.elapsed() < NOTICE_DURATION)
+            .map(|(n, _)| n.as_str())
+    }
+
+    /// Hashes the name, size and modification time of every theme file.
+    /// Cheap enough to run on a timer, which saves watching the folder.
+    fn folder_stamp() -> u64 {
+        let mut files = THEME_DIR
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("toml"))
+            .map(|e| {
+                let meta = e.metadata().ok();
+                (
+                    e.file_name(),
+                    meta.as_ref().map(|m| m.len()),
+                    meta.and_then(|m| m.modified().ok()),
+                )
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let mut hasher = DefaultHasher::new();
+        files.hash(&mut hasher);
+        hasher.finish()
     }
 
     pub fn find_theme_by_name(&self, name: &str) -> Option<&ThemeConfig> {
@@ -61,23 +165,42 @@ impl ThemeManager {
         self.theme_lib.get(idx).cloned()
     }
 
-    fn collect_themes() -> Vec<ThemeConfig> {
+    /// Themes from the theme folder, plus the built-in ones unless a file
+    /// takes their name
+    fn collect_themes() -> (Vec<ThemeConfig>, Vec<String>) {
         let mut themes = vec![];
+        let mut skipped = vec![];
         let theme_path = &*THEME_DIR;
 
         if let Ok(entries) = theme_path.read_dir() {
             for entry in entries.flatten() {
                 let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("toml") {
+                    continue;
+                }
 
-                if path.extension().and_then(|s| s.to_str()) == Some("toml")
-                    && let Ok(theme) = ThemeConfig::load_from_file(&path)
-                {
-                    themes.push(theme);
+                match ThemeConfig::load_from_file(&path) {
+                    Ok(theme) => themes.push(theme),
+                    Err(e) => {
+                        let file = path.file_name().unwrap_or_default().to_string_lossy();
+                        skipped.push(format!(
+                            "{file}: {}",
+                            e.to_string().lines().next().unwrap_or_default()
+                        ));
+                    }
                 }
             }
         }
+
+        for built_in in [ThemeConfig::default(), ThemeConfig::default_light()] {
+            if !themes.iter().any(|t| t.name == built_in.name) {
+                themes.push(built_in);
+            }
+        }
+
         themes.sort_by_key(|t| t.name.to_lowercase());
-        themes
+        skipped.sort();
+        (themes, skipped)
     }
 
     pub(crate) fn set_display_theme(theme: &ThemeConfig, focused: bool) -> DisplayTheme {
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+/// Terminals that answer at all do so within a frame or two
+#[cfg(unix)]
+const REPLY_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// Whether the terminal's background is light, as reported to an OSC 11
+/// query. `None` when the terminal doesn't answer in time.
+///
+/// Has to run in raw mode and before the key reader starts, or the reply
+/// would be read as keypresses. A device attributes query goes out right
+/// after: every terminal answers that one, so one that ignores OSC 11 is
+/// known about without waiting out the timeout.
+#[cfg(unix)]
+pub fn query_is_light() -> Option<bool> {
+    use std::{io::Write, time::Instant};
+
+    let mut out = std::io::stdout();
+    out.write_all(b"\x1b]11;?\x07\x1b[c").ok()?;
+    out.flush().ok()?;
+
+    let deadline = Instant::now() + REPLY_TIMEOUT;
+    let mut reply = Vec::new();
+
+    while !device_attributes_seen(&reply) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        let mut chunk = [0u8; 64];
+        // SAFETY: one valid pollfd, and a buffer that outlives the read
+        let read = unsafe {
+            if libc::poll(&mut fd, 1, left.as_millis() as libc::c_int) <= 0 {
+                break;
+            }
+            libc::read(libc::STDIN_FILENO, chunk.as_mut_ptr().cast(), chunk.len())
+        };
+        if read <= 0 {
+            break;
+        }
+        reply.extend_from_slice(&chunk[..read as usize]);
+    }
+
+    parse_reply(&String::from_utf8_lossy(&reply)).map(|lum| lum > 0.5)
+}
+
+#[cfg(not(unix))]
+pub fn query_is_light() -> Option<bool> {
+    None
+}
+
+/// `ESC [ ? ... c`, which closes the reply
+#[cfg(unix)]
+fn device_attributes_seen(reply: &[u8]) -> bool {
+    reply
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+/// Relative luminance of `rgb:RRRR/GGGG/BBBB`, where each channel has one
+/// to four hex digits
+fn parse_reply(reply: &str) -> Option<f32> {
+    let (_, rest) = reply.split_once("rgb:")?;
+    let mut channels = rest.splitn(3, '/').map(|c| {
+        let digits = c
+            .chars()
+            .take_while(char::is_ascii_hexdigit)
+            .collect::<String>();
+        let max = 16u32.checked_pow(digits.len() as u32)? - 1;
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        Some(value as f32 / max as f32)
+    });
+
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_background_replies() {
+        let light = parse_reply("\x1b]11;rgb:ffff/fafa/f0f0\x07\x1b[?62;c").unwrap();
+        let dark = parse_reply("\x1b]11;rgb:1e/1e/2e\x1b\\").unwrap();
+
+        assert!(light > 0.9);
+        assert!(dark < 0.2);
+        assert_eq!(parse_reply("\x1b[?62;c"), None);
+    }
+}
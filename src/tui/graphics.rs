@@ -0,0 +1,137 @@
+use crate::library::cover_art::{CoverArt, ImageFormat};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ratatui::layout::Rect;
+use std::env;
+
+/// Kitty wants its payload split into chunks of at most this many bytes
+const KITTY_CHUNK: usize = 4096;
+/// Fixed ids, so each new cover replaces the last one instead of piling up
+const KITTY_COVER_ID: u32 = 7223;
+const KITTY_ARTIST_ID: u32 = 7224;
+
+/// The places an image can be on screen, each holding one at a time
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageSlot {
+    Cover,
+    Artist,
+}
+
+impl ImageSlot {
+    fn kitty_id(self) -> u32 {
+        match self {
+            ImageSlot::Cover => KITTY_COVER_ID,
+            ImageSlot::Artist => KITTY_ARTIST_ID,
+        }
+    }
+}
+
+/// Rows for an image as wide as `area` that comes out roughly square, as
+/// cells are about twice as tall as they are wide. Never more than half of
+/// `area`, so whatever shares it keeps some room.
+pub fn square_image_height(area: Rect) -> u16 {
+    (area.width / 2).min(area.height / 2)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GraphicsProtocol {
+    /// Kitty's graphics protocol, also spoken by Ghostty and WezTerm
+    Kitty,
+    /// iTerm2's inline images, also spoken by WezTerm
+    Iterm2,
+    None,
+}
+
+impl GraphicsProtocol {
+    /// Guessed from the environment, as querying the terminal means racing
+    /// the key reader for the reply.
+    ///
+    /// Multiplexers are left out: they don't pass the sequences through
+    /// unless configured to, and don't know to clear them when switching
+    /// panes.
+    pub fn detect() -> Self {
+        let var = |key| env::var(key).unwrap_or_default();
+        let term = var("TERM");
+
+        if env::var_os("TMUX").is_some() || env::var_os("ZELLIJ").is_some() {
+            return GraphicsProtocol::None;
+        }
+        if term.starts_with("screen") || term.starts_with("tmux") {
+            return GraphicsProtocol::None;
+        }
+
+        match var("TERM_PROGRAM").as_str() {
+            "iTerm.app" | "WezTerm" => return GraphicsProtocol::Iterm2,
+            "ghostty" => return GraphicsProtocol::Kitty,
+            _ => {}
+        }
+
+        if var("LC_TERMINAL") == "iTerm2" {
+            return GraphicsProtocol::Iterm2;
+        }
+
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+        {
+            return GraphicsProtocol::Kitty;
+        }
+
+        GraphicsProtocol::None
+    }
+
+    /// Nothing here decodes images, so the terminal has to accept the file
+    /// as stored
+    pub fn supports(&self, format: ImageFormat) -> bool {
+        match self {
+            GraphicsProtocol::Kitty => format == ImageFormat::Png,
+            GraphicsProtocol::Iterm2 => format != ImageFormat::Other,
+            GraphicsProtocol::None => false,
+        }
+    }
+
+    /// Draws the cover stretched over `area`, leaving the cursor where the
+    /// next frame expects it to be redrawn from
+    pub fn draw(&self, cover: &CoverArt, area: Rect, slot: ImageSlot) -> String {
+        let payload = STANDARD.encode(&cover.data);
+        let mut out = format!("\x1b7\x1b[{};{}H", area.y + 1, area.x + 1);
+
+        match self {
+            GraphicsProtocol::Kitty => {
+                let chunks = payload.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<_>>();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = (i + 1 < chunks.len()) as u8;
+                    match i {
+                        0 => out.push_str(&format!(
+                            "\x1b_Gf=100,a=T,t=d,i={},q=2,C=1,c={},r={},m={more};",
+                            slot.kitty_id(),
+                            area.width,
+                            area.height
+                        )),
+                        _ => out.push_str(&format!("\x1b_Gm={more};")),
+                    }
+                    out.push_str(&String::from_utf8_lossy(chunk));
+                    out.push_str("\x1b\\");
+                }
+            }
+            GraphicsProtocol::Iterm2 => out.push_str(&format!(
+                "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{payload}\x07",
+                cover.data.len(),
+                area.width,
+                area.height
+            )),
+            GraphicsProtocol::None => return String::new(),
+        }
+
+        out.push_str("\x1b8");
+        out
+    }
+
+    /// iTerm2 images live in the cells themselves and go away once the
+    /// screen is repainted
+    pub fn clear(&self, slot: ImageSlot) -> String {
+        match self {
+            GraphicsProtocol::Kitty => format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", slot.kitty_id()),
+            _ => String::new(),
+        }
+    }
+}
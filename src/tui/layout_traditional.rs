@@ -1,13 +1,21 @@
+use super::graphics::square_image_height;
 use crate::{
     ui_state::{Mode, UiState},
     visualization::ProgressDisplay,
 };
 use ratatui::layout::{Constraint, Layout, Rect};
 
+const MIN_ART_WIDTH: u16 = 12;
+/// Share of the width beside the tracklist the lyrics and artist panes take
+const LYRICS_PERCENTAGE: u16 = 40;
+
 pub struct LayoutTraditional {
     pub sidebar: Rect,
+    pub art: Rect,
     pub search_bar: Rect,
     pub song_window: Rect,
+    pub lyrics: Rect,
+    pub artist: Rect,
     pub widget: Rect,
 }
 
@@ -48,14 +56,41 @@ impl LayoutTraditional {
         ])
         .areas(upper_block);
 
+        let art_height = match state.art.is_enabled() && state.get_now_playing().is_some() {
+            true if sidebar.width >= MIN_ART_WIDTH => square_image_height(sidebar),
+            _ => 0,
+        };
+
+        let [sidebar, art] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(art_height)]).areas(sidebar);
+
+        let show_artist = state.artist_pane.is_shown();
+        let side_width = match state.lyrics.visible || show_artist {
+            true => Constraint::Percentage(LYRICS_PERCENTAGE),
+            false => Constraint::Length(0),
+        };
+        let [upper_block, side] =
+            Layout::horizontal([Constraint::Fill(1), side_width]).areas(upper_block);
+
+        // The artist sits above the lyrics when both are open
+        let artist_height = match (show_artist, state.lyrics.visible) {
+            (true, true) => Constraint::Percentage(50),
+            (true, false) => Constraint::Fill(1),
+            (false, _) => Constraint::Length(0),
+        };
+        let [artist, lyrics] = Layout::vertical([artist_height, Constraint::Fill(1)]).areas(side);
+
         let [search_bar, song_window] =
             Layout::vertical([Constraint::Length(search_height), Constraint::Fill(100)])
                 .areas(upper_block);
 
         LayoutTraditional {
             sidebar,
+            art,
             search_bar,
             song_window,
+            lyrics,
+            artist,
             widget,
         }
     }
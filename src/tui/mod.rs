@@ -1,3 +1,5 @@
+mod background;
+mod graphics;
 mod layout_minimal;
 mod layout_traditional;
 mod renderer;
@@ -9,6 +11,8 @@ use ratatui::{
     widgets::{Block, Widget},
 };
 
+pub use background::query_is_light;
+pub use graphics::{GraphicsProtocol, ImageSlot};
 pub use layout_traditional::LayoutTraditional;
 pub use renderer::render;
 pub use widgets::{ErrorMsg, Progress, SearchBar, SideBarHandler as SideBar, SongTable};
@@ -4,7 +4,7 @@ use crate::{
     tui::{
         layout_minimal::LayoutMinimal,
         render_bg,
-        widgets::{BreadCrumbs, BufferLine, PopupManager},
+        widgets::{AlbumArtPane, ArtistPane, BreadCrumbs, BufferLine, LyricsPane, PopupManager},
     },
     ui_state::{LayoutStyle, Mode, Pane},
 };
@@ -12,12 +12,24 @@ use ratatui::{Frame, layout::Rect, widgets::StatefulWidget};
 
 pub fn render(f: &mut Frame, state: &mut UiState) {
     let area = f.area();
+    state.art.area = None;
+    state.art.artist_area = None;
+    state.mouse.clear_areas();
+
+    if state.idle.is_resting() {
+        Progress.render(area, f.buffer_mut(), state);
+        return;
+    }
 
     if matches!(state.get_mode(), Mode::Fullscreen) {
         let bf_area = get_bufferline_area(area);
         Progress.render(area, f.buffer_mut(), state);
         BufferLine.render(bf_area, f.buffer_mut(), state);
 
+        // Only the seek popup opens from here
+        if state.popup.is_open() {
+            PopupManager.render(f.area(), f.buffer_mut(), state);
+        }
         return;
     }
 
@@ -55,6 +67,9 @@ fn render_minimal(area: Rect, f: &mut Frame, state: &mut UiState) {
 }
 
 fn render_traditional(area: Rect, f: &mut Frame, state: &mut UiState) {
+    state.sync_album_art();
+    state.sync_lyrics();
+    state.sync_artist_info();
     let layout = LayoutTraditional::new(area, state);
 
     let bf_area = get_bufferline_area(area);
@@ -62,7 +77,16 @@ fn render_traditional(area: Rect, f: &mut Frame, state: &mut UiState) {
 
     SearchBar.render(layout.search_bar, f.buffer_mut(), state);
     SideBar.render(layout.sidebar, f.buffer_mut(), state);
+    if !layout.art.is_empty() {
+        AlbumArtPane.render(layout.art, f.buffer_mut(), state);
+    }
     SongTable.render(layout.song_window, f.buffer_mut(), state);
+    if !layout.artist.is_empty() {
+        ArtistPane.render(layout.artist, f.buffer_mut(), state);
+    }
+    if !layout.lyrics.is_empty() {
+        LyricsPane.render(layout.lyrics, f.buffer_mut(), state);
+    }
 
     if state.metrics.is_active() || state.library_refresh.is_some() {
         Progress.render(layout.widget, f.buffer_mut(), state);
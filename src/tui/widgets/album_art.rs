@@ -0,0 +1,60 @@
+use crate::ui_state::UiState;
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    text::{Line, Text},
+    widgets::{Block, Paragraph, StatefulWidget, Widget},
+};
+
+pub struct AlbumArtPane;
+impl StatefulWidget for AlbumArtPane {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(false);
+
+        let block = Block::bordered()
+            .borders(theme.border_display)
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.is_empty() {
+            return;
+        }
+
+        // The image itself is sent once the frame is drawn. Popups would
+        // end up underneath it, so it's taken down while one is open.
+        if state.art.drawable().is_some() {
+            if !state.popup.is_open() {
+                state.art.area = Some(inner);
+            }
+            return;
+        }
+
+        let album = state
+            .get_now_playing()
+            .map(|s| s.album.to_string())
+            .unwrap_or_default();
+
+        let [placeholder] = Layout::vertical([Constraint::Length(3)])
+            .flex(Flex::Center)
+            .areas(inner);
+
+        Paragraph::new(Text::from(vec![
+            Line::from("♪").fg(theme.accent),
+            Line::default(),
+            Line::from(album).fg(theme.text_muted),
+        ]))
+        .centered()
+        .render(placeholder, buf);
+    }
+}
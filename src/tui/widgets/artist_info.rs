@@ -0,0 +1,77 @@
+use crate::{tui::graphics::square_image_height, ui_state::UiState};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    text::{Line, Text},
+    widgets::{Block, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+pub struct ArtistPane;
+impl StatefulWidget for ArtistPane {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(false);
+        let name = state.artist_pane.artist().unwrap_or_default().to_string();
+
+        let block = Block::bordered()
+            .borders(theme.border_display)
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(format!(" {name} "))
+            .padding(Padding::horizontal(1))
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.is_empty() {
+            return;
+        }
+
+        let image_height = match state.art.artist_drawable() {
+            Some(_) => square_image_height(inner),
+            None => 0,
+        };
+        let [image, _, notes] = Layout::vertical([
+            Constraint::Length(image_height),
+            Constraint::Length((image_height > 0) as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        // Sent once the frame is drawn, like the cover, and kept out from
+        // under popups
+        if image_height > 0 && !state.popup.is_open() {
+            state.art.artist_area = Some(image);
+        }
+
+        match state.artist_pane.notes() {
+            Some(text) => Paragraph::new(text.to_string())
+                .wrap(Wrap { trim: false })
+                .fg(theme.text_secondary)
+                .render(notes, buf),
+            None if image_height == 0 => {
+                let [placeholder] = Layout::vertical([Constraint::Length(4)])
+                    .flex(Flex::Center)
+                    .areas(inner);
+
+                Paragraph::new(Text::from(vec![
+                    Line::from("♪").fg(theme.accent),
+                    Line::default(),
+                    Line::from("Add artist.jpg or artist.txt to the artist's folder")
+                        .fg(theme.text_muted),
+                ]))
+                .centered()
+                .wrap(Wrap { trim: true })
+                .render(placeholder, buf);
+            }
+            None => (),
+        }
+    }
+}
@@ -32,6 +32,8 @@ impl StatefulWidget for BreadCrumbs {
         let right_label = match top_level {
             Root::Library => state.nav.get_album_sort().to_string(),
             Root::Playlist => format!("{} {}", state.playlists.len(), state.theme.icons().upcoming),
+            Root::Genre => state.genres.len().to_string(),
+            Root::Folder => state.library().folders.paths().count().to_string(),
         };
 
         let spans = match state.get_pane() {
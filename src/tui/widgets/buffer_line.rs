@@ -1,15 +1,18 @@
 use crate::{
     library::{RefreshStage, SongInfo},
+    playback::RepeatMode,
     theme::DisplayTheme,
     truncate_at_last_space,
-    ui_state::{LayoutStyle, UiState},
+    ui_state::{LayoutStyle, Marquee, UiState},
 };
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::Stylize,
+    style::{Style, Stylize},
     text::{Line, Span},
     widgets::{Block, Borders, Gauge, StatefulWidget, Widget},
 };
+use unicode_width::UnicodeWidthStr;
+use voxio::ReplayGainMode;
 
 pub struct BufferLine;
 
@@ -22,17 +25,21 @@ impl StatefulWidget for BufferLine {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
+        let playing = state.get_now_playing().map(|s| s.id);
+        state.marquee.tick(playing);
+
         let theme = state.theme.get_display_theme(true);
 
         if let Some(refresh) = &state.library_refresh {
             let percent = refresh.percent();
 
-            let label = match refresh.stage() {
-                RefreshStage::Parsing => {
+            let label = match (refresh.is_cancelled(), refresh.stage()) {
+                (true, _) => format!("Cancelling... | {percent}%"),
+                (false, RefreshStage::Parsing) => {
                     let (c, t) = refresh.counts();
-                    format!("Processing {c}/{t} | {percent}%")
+                    format!("Processing {c}/{t} | {percent}% | F5 to cancel")
                 }
-                stage => format!("{} | {percent}%", stage.label()),
+                (false, stage) => format!("{} | {percent}% | F5 to cancel", stage.label()),
             }
             .fg(theme.text_muted);
 
@@ -68,20 +75,34 @@ impl StatefulWidget for BufferLine {
             }
             vol.render(left, buf);
         }
-        playing_title(state, theme, center.width as usize).render(center, buf);
+        match state.theme.notice() {
+            Some(notice) => {
+                let notice = truncate_at_last_space(notice, center.width as usize);
+                Line::from(notice)
+                    .fg(theme.bg_error)
+                    .centered()
+                    .render(center, buf);
+            }
+            None => match state.notice().map(String::from).or(state.gain.label()) {
+                Some(label) => Line::from(label)
+                    .fg(theme.accent)
+                    .centered()
+                    .render(center, buf),
+                None => playing_title(state, theme, center.width as usize).render(center, buf),
+            },
+        }
         queue_display(state, theme, right.width as usize).render(right, buf);
     }
 }
 
-const SEPARATOR_LEN: usize = 3;
-const MIN_TITLE_LEN: usize = 20;
-const MIN_ARTIST_LEN: usize = 15;
+/// Between the end of the line and its start coming round again
+const MARQUEE_GAP: &str = "     ";
 
 fn playing_title(state: &UiState, theme: &DisplayTheme, width: usize) -> Option<Line<'static>> {
     let song = state.get_now_playing()?;
-    let decorator = match state.playback.repeat_is_enabled() {
-        true => &state.theme.icons().repeat,
-        false => &state.theme.icons().decorator,
+    let decorator = match state.playback.repeat_mode() {
+        RepeatMode::Off => &state.theme.icons().decorator,
+        _ => &state.theme.icons().repeat,
     };
 
     let paused = &state.theme.icons().paused;
@@ -96,58 +117,53 @@ fn playing_title(state: &UiState, theme: &DisplayTheme, width: usize) -> Option<
     let title = song.get_title();
     let artist = song.get_artist();
 
-    let title_len = title.chars().count();
-    let artist_len = artist.chars().count();
+    // Flashes to the accent on each detected beat
+    let title_color = match state.viz.beat().is_pulsing() {
+        true => theme.accent,
+        false => theme.text_secondary,
+    };
 
-    if width >= title_len + SEPARATOR_LEN + artist_len {
-        Some(
-            Line::from_iter([
-                " ".into(),
-                Span::from(title.to_string()).fg(theme.text_secondary),
-                separator,
-                Span::from(artist.to_string()).fg(theme.text_muted),
-                " ".into(),
-            ])
-            .centered(),
-        )
-    } else if width >= MIN_TITLE_LEN + SEPARATOR_LEN + MIN_ARTIST_LEN {
-        let available_space = width.saturating_sub(SEPARATOR_LEN);
-        let title_space = (available_space * 2) / 3;
-        let artist_space = available_space.saturating_sub(title_space);
+    let spans = [
+        Span::from(title.to_string()).fg(title_color),
+        separator,
+        Span::from(artist.to_string()).fg(theme.text_muted),
+    ];
+
+    // The spaces either side are only kept when there's room
+    let text_width = spans.iter().map(Span::width).sum::<usize>();
+    if text_width + 2 <= width {
+        let line = [" ".into()].into_iter().chain(spans).chain([" ".into()]);
+        return Some(Line::from_iter(line).centered());
+    }
+    if text_width <= width {
+        return Some(Line::from_iter(spans).centered());
+    }
 
-        let truncated_title = truncate_at_last_space(title, title_space);
-        let truncated_artist = truncate_at_last_space(artist, artist_space);
+    Some(marquee(&spans, &state.marquee, width))
+}
 
-        Some(
-            Line::from_iter([
-                " ".into(),
-                Span::from(truncated_title).fg(theme.text_secondary),
-                separator,
-                Span::from(truncated_artist).fg(theme.text_muted),
-                " ".into(),
-            ])
-            .centered(),
-        )
-    } else {
-        match state.metrics.is_paused() {
-            true => {
-                let truncated_title = truncate_at_last_space(title, title_len - SEPARATOR_LEN);
-                Some(
-                    Line::from_iter([
-                        " ".into(),
-                        separator,
-                        Span::from(truncated_title).fg(theme.text_secondary),
-                        " ".into(),
-                    ])
-                    .centered(),
-                )
-            }
-            false => {
-                let truncated_title = truncate_at_last_space(title, width);
-                Some(Line::from(Span::from(truncated_title).fg(theme.text_secondary)).centered())
-            }
+/// The line scrolled round by however far the marquee has got, as much of
+/// it as fits. Whole graphemes are moved, so none is ever cut in two.
+fn marquee(spans: &[Span<'static>], marquee: &Marquee, width: usize) -> Line<'static> {
+    let gap = Span::from(MARQUEE_GAP);
+    let graphemes = spans
+        .iter()
+        .chain([&gap])
+        .flat_map(|span| span.styled_graphemes(Style::new()))
+        .collect::<Vec<_>>();
+
+    let mut line = Line::default();
+    let mut used = 0;
+    let start = marquee.offset(graphemes.len());
+    // The line is wider than the space, so one time round is always enough
+    for g in graphemes.iter().cycle().skip(start).take(graphemes.len()) {
+        used += g.symbol.width();
+        if used > width {
+            break;
         }
+        line.push_span(Span::styled(g.symbol.to_string(), g.style));
     }
+    line
 }
 
 fn volume_slider(state: &UiState, theme: &DisplayTheme, area: Rect) -> Option<Line<'static>> {
@@ -156,12 +172,47 @@ fn volume_slider(state: &UiState, theme: &DisplayTheme, area: Rect) -> Option<Li
     }
 
     let width = (area.width / 10).clamp(4, 11) as usize;
-    let ratio = (state.metrics.volume() / 1.0).clamp(0.0, 1.0);
+    let ratio = (state.gain.volume() / 1.0).clamp(0.0, 1.0);
     let pos = (ratio * (width - 1) as f32).round() as usize;
-    let pct = (state.metrics.volume() * 100.0).round() as usize;
-    let percent = match area.width >= 80 {
-        true => format!(" {pct}%"),
-        false => String::default(),
+    let pct = (state.gain.volume() * 100.0).round() as usize;
+    let percent = match (area.width >= 80, state.gain.is_muted()) {
+        (true, true) => String::from(" mute"),
+        (true, false) => format!(" {pct}%"),
+        _ => String::default(),
+    };
+
+    let replay_gain = match (area.width >= 100, state.playback.replay_gain()) {
+        (true, ReplayGainMode::Track) => " rg:T",
+        (true, ReplayGainMode::Album) => " rg:A",
+        _ => "",
+    };
+
+    let repeat = match (area.width >= 100, state.playback.repeat_mode()) {
+        (true, RepeatMode::One) => " rp:1",
+        (true, RepeatMode::All) => " rp:A",
+        _ => "",
+    };
+
+    let shuffle = match area.width >= 100 {
+        true => format!(" {}", state.shuffle_mode.tag()),
+        false => String::new(),
+    };
+
+    let radio = match area.width >= 100 && state.radio {
+        true => " radio",
+        false => "",
+    };
+
+    // The stream runs at the device's rate; anything else is resampled
+    let output_rate = state.metrics.sample_rate();
+    let (rate, resampled) = match (area.width >= 120, state.metrics.is_active()) {
+        (true, true) if output_rate != 0 => match state.source_rate {
+            Some(src) if src != output_rate => {
+                (format!(" {}→{}", khz(src), khz(output_rate)), true)
+            }
+            _ => (format!(" {}", khz(output_rate)), false),
+        },
+        _ => (String::new(), false),
     };
 
     let left_track = "─".repeat(pos);
@@ -170,10 +221,26 @@ fn volume_slider(state: &UiState, theme: &DisplayTheme, area: Rect) -> Option<Li
     Some(Line::from_iter([
         Span::from(format!(" {left_track}")).fg(theme.text_muted),
         Span::from("○").fg(theme.accent),
-        Span::from(format!("{right_track}{percent} ")).fg(theme.text_muted),
+        Span::from(format!("{right_track}{percent}")).fg(theme.text_muted),
+        Span::from(replay_gain).fg(theme.accent),
+        Span::from(repeat).fg(theme.accent),
+        Span::from(shuffle).fg(theme.text_muted),
+        Span::from(radio).fg(theme.accent),
+        Span::from(format!("{rate} ")).fg(match resampled {
+            true => theme.accent,
+            false => theme.text_muted,
+        }),
     ]))
 }
 
+/// `44100` reads as `44.1k`, `48000` as `48k`
+fn khz(rate: u32) -> String {
+    match rate % 1000 {
+        0 => format!("{}k", rate / 1000),
+        _ => format!("{:.1}k", rate as f32 / 1000.0),
+    }
+}
+
 fn _volume_meter(state: &UiState, theme: &DisplayTheme) -> Line<'static> {
     const MAX: f32 = 1.5; // voxio clamps perceptual volume to 0.0..=1.5
     const CELLS: usize = 12; // one bar cell per 12.5%
@@ -181,7 +248,7 @@ fn _volume_meter(state: &UiState, theme: &DisplayTheme) -> Line<'static> {
     const BLOCKS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
     const TRACK: char = '░';
 
-    let vol = state.metrics.volume().clamp(0.0, MAX);
+    let vol = state.gain.volume().clamp(0.0, MAX);
     let eighths = (vol / MAX * (CELLS * 8) as f32).round() as usize; // filled 1/8ths, 0..=96
 
     let mut spans: Vec<Span<'static>> = (0..CELLS)
@@ -247,3 +314,23 @@ fn queue_display(state: &UiState, theme: &DisplayTheme, width: usize) -> Option<
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marquee_keeps_graphemes_whole() {
+        // An accent made of two chars, and a character two columns wide
+        let spans = [Span::from("Cafe\u{301} 日本")];
+        let line = marquee(&spans, &Marquee::default(), 6);
+
+        let symbols = line
+            .spans
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(symbols, ["C", "a", "f", "e\u{301}", " "]);
+        assert_eq!(line.width(), 5);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::ui_state::UiState;
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Padding, Paragraph, StatefulWidget, Widget},
+};
+use std::mem;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+pub struct LyricsPane;
+impl StatefulWidget for LyricsPane {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(false);
+
+        let block = Block::bordered()
+            .borders(theme.border_display)
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .padding(Padding::horizontal(1))
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.is_empty() {
+            return;
+        }
+        state.mouse.lyrics = Some(inner);
+
+        let active = state.active_lyric();
+        let Some(lyrics) = state.lyrics.lyrics().filter(|l| !l.is_empty()) else {
+            let hint = match state.get_now_playing() {
+                Some(_) => "Drop an .lrc file next to the track",
+                None => "Nothing playing",
+            };
+            return placeholder(inner, buf, theme.accent, theme.text_muted, hint);
+        };
+
+        // Every line wrapped to the pane, remembering where each one starts
+        let width = inner.width as usize;
+        let mut rows = Vec::new();
+        let mut starts = Vec::with_capacity(lyrics.len());
+        for idx in 0..lyrics.len() {
+            starts.push(rows.len());
+            rows.extend(wrap(lyrics.line(idx), width).into_iter().map(|r| (idx, r)));
+        }
+
+        // The focused line sits in the middle until either end is reached
+        let focus = state.lyrics.focus(active).min(lyrics.len() - 1);
+        let focus_rows = starts.get(focus + 1).unwrap_or(&rows.len()) - starts[focus];
+        let height = inner.height as usize;
+        let top = (starts[focus] + focus_rows / 2)
+            .saturating_sub(height / 2)
+            .min(rows.len().saturating_sub(height));
+
+        let synced = lyrics.is_synced();
+        let lines = rows
+            .into_iter()
+            .skip(top)
+            .take(height)
+            .map(|(idx, row)| {
+                let style = match (synced, Some(idx) == active) {
+                    (true, true) => Style::new().fg(theme.accent).add_modifier(Modifier::BOLD),
+                    (true, false) => Style::new().fg(theme.text_muted),
+                    (false, _) => Style::new().fg(theme.text_primary),
+                };
+                Line::styled(row, style)
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).centered().render(inner, buf);
+    }
+}
+
+fn placeholder(
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    accent: ratatui::style::Color,
+    muted: ratatui::style::Color,
+    hint: &str,
+) {
+    let [area] = Layout::vertical([Constraint::Length(3)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    Paragraph::new(Text::from(vec![
+        Line::from("No lyrics").fg(accent),
+        Line::default(),
+        Line::from(hint.to_string()).fg(muted),
+    ]))
+    .centered()
+    .render(area, buf);
+}
+
+/// The rows a lyric line takes at `width`, broken between words where it
+/// can and anywhere in a word wider than the pane
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if text.width() <= width {
+        return vec![text.to_string()];
+    }
+
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    for word in text.split_whitespace() {
+        if !row.is_empty() && row.width() + 1 + word.width() > width {
+            rows.push(mem::take(&mut row));
+        }
+        if !row.is_empty() {
+            row.push(' ');
+        }
+        for c in word.chars() {
+            if !row.is_empty() && row.width() + c.width().unwrap_or(0) > width {
+                rows.push(mem::take(&mut row));
+            }
+            row.push(c);
+        }
+    }
+    rows.push(row);
+    rows
+}
@@ -1,5 +1,8 @@
+mod album_art;
+mod artist_info;
 mod bread_crumbs;
 mod buffer_line;
+mod lyrics;
 mod popup;
 mod popups;
 mod progress;
@@ -7,10 +10,16 @@ mod search;
 mod sidebar;
 mod tracklist;
 
+pub use album_art::AlbumArtPane;
+pub use artist_info::ArtistPane;
 pub use bread_crumbs::BreadCrumbs;
 pub use buffer_line::BufferLine;
+pub use lyrics::LyricsPane;
 pub use popup::PopupManager;
-pub use popups::{ErrorMsg, KeymapGuide, PlaylistPopup, RootManager, ThemeManager, UserStats};
+pub use popups::{
+    ColumnPicker, CommandLine, DuplicateManager, ErrorMsg, InfoMsg, KeymapGuide, PlaylistPopup,
+    RootManager, SeekPopup, TagPopup, ThemeManager, UserStats, VizSettings,
+};
 pub use progress::Progress;
 pub use search::SearchBar;
 pub use sidebar::SideBarHandler;
@@ -6,9 +6,12 @@ use ratatui::{
 use crate::{
     tui::{
         ErrorMsg,
-        widgets::{KeymapGuide, PlaylistPopup, RootManager, ThemeManager, UserStats},
+        widgets::{
+            ColumnPicker, CommandLine, DuplicateManager, InfoMsg, KeymapGuide, PlaylistPopup,
+            RootManager, SeekPopup, TagPopup, ThemeManager, UserStats, VizSettings,
+        },
     },
-    ui_state::{PopupType, UiState},
+    ui_state::{PlaylistAction, PopupType, UiState},
 };
 
 pub struct PopupManager;
@@ -23,11 +26,26 @@ impl StatefulWidget for PopupManager {
     ) {
         let popup_rect = match &state.popup.current {
             PopupType::Stats => centered_rect(60, 80, area),
+            PopupType::Playlist(PlaylistAction::SmartRules(_)) => centered_rect(60, 45, area),
             PopupType::Playlist(_) => centered_rect(35, 40, area),
             PopupType::Settings(_) => centered_rect(40, 40, area),
+            PopupType::Duplicates(_) => centered_rect(70, 70, area),
             PopupType::ThemeManager => centered_rect(40, 40, area),
             PopupType::KeymapGuide => centered_rect(65, 70, area),
-            PopupType::Error(_) => centered_rect(50, 40, area),
+            PopupType::Columns => centered_rect(30, 40, area),
+            PopupType::VizSettings => centered_rect(35, 25, area),
+            PopupType::Error(_) | PopupType::Info(_) => centered_rect(50, 40, area),
+            PopupType::Seek(_) => centered_rect(35, 30, area),
+            PopupType::TagEdit => centered_rect(45, 50, area),
+            // Along the bottom, with a line above it for anything to say
+            PopupType::Command(hint) => {
+                let height = if hint.is_some() { 2 } else { 1 }.min(area.height);
+                Rect {
+                    y: area.bottom() - height,
+                    height,
+                    ..area
+                }
+            }
             _ => return,
         };
 
@@ -36,9 +54,16 @@ impl StatefulWidget for PopupManager {
             PopupType::Stats => UserStats.render(popup_rect, buf, state),
             PopupType::Playlist(_) => PlaylistPopup.render(popup_rect, buf, state),
             PopupType::Settings(_) => RootManager.render(popup_rect, buf, state),
+            PopupType::Duplicates(_) => DuplicateManager.render(popup_rect, buf, state),
             PopupType::ThemeManager => ThemeManager.render(popup_rect, buf, state),
             PopupType::KeymapGuide => KeymapGuide.render(popup_rect, buf, state),
+            PopupType::Columns => ColumnPicker.render(popup_rect, buf, state),
+            PopupType::VizSettings => VizSettings.render(popup_rect, buf, state),
             PopupType::Error(_) => ErrorMsg.render(popup_rect, buf, state),
+            PopupType::Info(_) => InfoMsg.render(popup_rect, buf, state),
+            PopupType::Seek(_) => SeekPopup.render(popup_rect, buf, state),
+            PopupType::TagEdit => TagPopup.render(popup_rect, buf, state),
+            PopupType::Command(_) => CommandLine.render(popup_rect, buf, state),
             _ => unreachable!(),
         }
     }
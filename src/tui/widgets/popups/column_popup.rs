@@ -0,0 +1,54 @@
+use ratatui::{
+    layout::Alignment,
+    style::Stylize,
+    widgets::{Block, List, StatefulWidget},
+};
+
+use crate::{
+    tui::widgets::POPUP_PADDING,
+    ui_state::{TableColumn, UiState},
+};
+
+pub struct ColumnPicker;
+impl StatefulWidget for ColumnPicker {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = &state.theme.get_display_theme(true);
+
+        let selector = state.theme.icons().selector.to_string();
+
+        let columns = TableColumn::ALL
+            .iter()
+            .map(|c| {
+                let mark = match state.nav.columns.contains(c) {
+                    true => "[x]",
+                    false => "[ ]",
+                };
+                format!("{mark} {}", c.name())
+            })
+            .collect::<Vec<String>>();
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(" Columns ")
+            .title_bottom(" [Space] / [Esc] ")
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let list = List::new(columns)
+            .block(block)
+            .fg(theme.text_muted)
+            .highlight_symbol(selector)
+            .highlight_style(theme.accent);
+
+        StatefulWidget::render(list, area, buf, &mut state.popup.selection);
+    }
+}
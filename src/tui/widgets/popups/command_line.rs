@@ -0,0 +1,48 @@
+use crate::ui_state::{CommandHint, PopupType, UiState};
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, StatefulWidget, Widget},
+};
+
+pub struct CommandLine;
+impl StatefulWidget for CommandLine {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let PopupType::Command(hint) = state.popup.current.clone() else {
+            return;
+        };
+
+        let theme = state.theme.get_display_theme(true);
+        Block::new().bg(theme.bg).render(area, buf);
+
+        let [hint_area, line_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let hint = match hint {
+            Some(CommandHint::Error(err)) => Line::from(err).fg(theme.bg_error),
+            Some(CommandHint::Options(options)) => {
+                Line::from(options.join("  ")).fg(theme.text_muted)
+            }
+            None => Line::default(),
+        };
+        hint.render(hint_area, buf);
+
+        let [prompt_area, input_area] =
+            Layout::horizontal([Constraint::Length(1), Constraint::Fill(1)]).areas(line_area);
+        Span::from(":").fg(theme.accent).render(prompt_area, buf);
+
+        state.popup.input.set_block(Block::default());
+        state
+            .popup
+            .input
+            .set_style(Style::new().fg(theme.text_primary));
+        state.popup.input.render(input_area, buf);
+    }
+}
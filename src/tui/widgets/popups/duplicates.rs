@@ -0,0 +1,178 @@
+use crate::{
+    DurationStyle, get_readable_duration, strip_win_prefix,
+    ui_state::{DuplicateMode, UiState},
+};
+use ratatui::{
+    layout::Alignment,
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, HighlightSpacing, List, ListItem, Padding, Paragraph, StatefulWidget, Widget, Wrap,
+    },
+};
+use std::time::Duration;
+
+pub struct DuplicateManager;
+impl StatefulWidget for DuplicateManager {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let Some(mode) = state.get_duplicate_mode().cloned() else {
+            return;
+        };
+        let theme = state.theme.get_display_theme(true);
+
+        let title = match mode {
+            DuplicateMode::Review => {
+                format!(" Duplicates - {} Found ", state.duplicates.groups.len())
+            }
+            DuplicateMode::Delete => " Delete Duplicates ".to_string(),
+            DuplicateMode::DeleteAll => " Delete All Duplicates ".to_string(),
+        };
+
+        let keymaps = match mode {
+            DuplicateMode::Review => " [d]elete extras / [D]elete all / [i]gnore / [Esc] close ",
+            _ => " [Enter] confirm / [Esc] cancel ",
+        };
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(title)
+            .title_bottom(keymaps)
+            .title_alignment(Alignment::Center)
+            .padding(Padding::uniform(1))
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        match mode {
+            DuplicateMode::Review => render_groups(inner, buf, state),
+            _ => render_confirm(inner, buf, state, &mode),
+        }
+    }
+}
+
+fn render_groups(
+    area: ratatui::prelude::Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    state: &mut UiState,
+) {
+    let theme = state.theme.get_display_theme(true);
+
+    if state.duplicates.groups.is_empty() {
+        Paragraph::new("No duplicates left!")
+            .fg(theme.text_muted)
+            .centered()
+            .render(area, buf);
+        return;
+    }
+
+    let items = state
+        .duplicates
+        .groups
+        .iter()
+        .map(|group| {
+            let best = group.best();
+            let mut lines = vec![Line::from(format!("{} - {}", best.artist, best.title))];
+
+            for (i, song) in group.songs.iter().enumerate() {
+                let (label, color) = match i {
+                    0 => ("keep", theme.accent),
+                    _ => ("del ", theme.text_muted),
+                };
+                let duration = Duration::from_secs_f32(song.duration);
+
+                lines.push(Line::from(vec![
+                    Span::from(format!("  {label}  ")).fg(color),
+                    Span::from(format!(
+                        "{:<4} {:>5} kbps  {:>6}  ",
+                        song.format.to_str(),
+                        song.bitrate,
+                        get_readable_duration(duration, DurationStyle::Clean),
+                    ))
+                    .fg(theme.text_secondary),
+                    Span::from(strip_win_prefix(&song.path)).fg(theme.text_muted),
+                ]));
+            }
+            lines.push(Line::default());
+
+            ListItem::new(Text::from(lines))
+        })
+        .collect::<Vec<_>>();
+
+    let selector = state.theme.icons().selector.to_string();
+
+    let list = List::new(items)
+        .fg(theme.text_primary)
+        .highlight_symbol(selector)
+        .highlight_style(Style::new().fg(theme.accent))
+        .highlight_spacing(HighlightSpacing::Always);
+
+    StatefulWidget::render(list, area, buf, &mut state.popup.selection);
+}
+
+fn render_confirm(
+    area: ratatui::prelude::Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    state: &UiState,
+    mode: &DuplicateMode,
+) {
+    let theme = state.theme.get_display_theme(true);
+    let groups = &state.duplicates.groups;
+
+    let mut lines = match mode {
+        DuplicateMode::DeleteAll => {
+            let count = groups.iter().map(|g| g.extras().len()).sum::<usize>();
+            vec![
+                Line::from(format!(
+                    "Delete {count} extra copies across {} songs?",
+                    groups.len()
+                )),
+                Line::default(),
+                "The highest bitrate copy of each song is kept."
+                    .fg(theme.text_muted)
+                    .into(),
+            ]
+        }
+        _ => {
+            let Some(group) = state.get_selected_duplicates() else {
+                return;
+            };
+
+            let mut lines = vec![Line::from("Delete these files?"), Line::default()];
+            lines.extend(
+                group
+                    .extras()
+                    .iter()
+                    .map(|s| Line::from(strip_win_prefix(&s.path)).fg(theme.accent)),
+            );
+            lines.push(Line::default());
+            lines.push(
+                format!("Keeping {}", strip_win_prefix(&group.best().path))
+                    .fg(theme.text_muted)
+                    .into(),
+            );
+            lines
+        }
+    };
+
+    lines.push(Line::default());
+    lines.push(
+        "Files are removed from disk. This cannot be undone."
+            .fg(theme.text_muted)
+            .into(),
+    );
+
+    Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: true })
+        .centered()
+        .fg(theme.text_secondary)
+        .render(area, buf);
+}
@@ -0,0 +1,49 @@
+use crate::ui_state::{PopupType, UiState};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout},
+    style::Stylize,
+    widgets::{Block, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+static PADDING: Padding = Padding {
+    left: 4,
+    right: 4,
+    top: 1,
+    bottom: 1,
+};
+
+/// Like [`ErrorMsg`](super::ErrorMsg), for news too long for the buffer line
+pub struct InfoMsg;
+impl StatefulWidget for InfoMsg {
+    type State = UiState;
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let PopupType::Info(msg) = &state.popup.current else {
+            return;
+        };
+        let theme = &state.theme.get_display_theme(true);
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title_bottom(" Press <Esc> to clear ")
+            .title_alignment(Alignment::Center)
+            .padding(PADDING)
+            .fg(theme.text_primary)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let chunks =
+            Layout::vertical([Constraint::Percentage(15), Constraint::Fill(1)]).split(inner);
+
+        Paragraph::new(msg.as_str())
+            .wrap(Wrap { trim: false })
+            .centered()
+            .render(chunks[1], buf);
+    }
+}
@@ -1,13 +1,27 @@
+mod column_popup;
+mod command_line;
+mod duplicates;
 mod error;
+mod info;
 mod keymap_guide;
 mod playlist_popup;
 mod root_manager;
+mod seek_popup;
 mod stats;
+mod tag_popup;
 mod theme_popup;
+mod viz_settings;
 
+pub use column_popup::ColumnPicker;
+pub use command_line::CommandLine;
+pub use duplicates::DuplicateManager;
 pub use error::ErrorMsg;
+pub use info::InfoMsg;
 pub use keymap_guide::KeymapGuide;
 pub use playlist_popup::PlaylistPopup;
 pub use root_manager::RootManager;
+pub use seek_popup::SeekPopup;
 pub use stats::UserStats;
+pub use tag_popup::TagPopup;
 pub use theme_popup::ThemeManager;
+pub use viz_settings::VizSettings;
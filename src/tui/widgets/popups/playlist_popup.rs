@@ -1,4 +1,5 @@
 use crate::{
+    database::PlaylistItem,
     theme::fade_color,
     tui::widgets::POPUP_PADDING,
     ui_state::{Pane, PlaylistAction, PopupType, UiState},
@@ -22,12 +23,35 @@ impl StatefulWidget for PlaylistPopup {
     ) {
         if let PopupType::Playlist(action) = &state.popup.current {
             match action {
-                PlaylistAction::Create | PlaylistAction::CreateWithSongs => {
-                    render_create_popup(area, buf, state)
-                }
+                PlaylistAction::Create | PlaylistAction::CreateWithSongs => render_create_popup(
+                    area,
+                    buf,
+                    state,
+                    " Create New Playlist ",
+                    "Enter playlist title: ",
+                ),
+                PlaylistAction::CreateSmart => render_create_popup(
+                    area,
+                    buf,
+                    state,
+                    " Create Smart Playlist ",
+                    "Enter playlist title: ",
+                ),
+                PlaylistAction::Import => render_create_popup(
+                    area,
+                    buf,
+                    state,
+                    " Import Playlist ",
+                    "Enter path to M3U / M3U8 file: ",
+                ),
+                PlaylistAction::SmartRules(_) => render_smart_rules_popup(area, buf, state),
                 PlaylistAction::AddSong => render_add_song_popup(area, buf, state),
                 PlaylistAction::Delete => render_delete_popup(area, buf, state),
                 PlaylistAction::Rename => render_rename_popup(area, buf, state),
+                PlaylistAction::Move => render_move_popup(area, buf, state),
+                PlaylistAction::NewFolder => {
+                    render_create_popup(area, buf, state, " New Folder ", "Enter folder name: ")
+                }
             }
         }
     }
@@ -37,6 +61,8 @@ fn render_create_popup(
     area: ratatui::prelude::Rect,
     buf: &mut ratatui::prelude::Buffer,
     state: &mut UiState,
+    title: &str,
+    prompt: &str,
 ) {
     let focus = matches!(state.get_pane(), Pane::Popup);
     let theme = state.theme.get_display_theme(focus);
@@ -46,7 +72,7 @@ fn render_create_popup(
     let block = Block::bordered()
         .border_type(theme.border_type)
         .border_style(theme.border)
-        .title(" Create New Playlist ")
+        .title(title)
         .title_bottom(" [Enter] confirm / [Esc] cancel ")
         .title_alignment(ratatui::layout::Alignment::Center)
         .padding(Padding {
@@ -63,9 +89,7 @@ fn render_create_popup(
 
     let chunks = Layout::vertical([Constraint::Max(2), Constraint::Length(3)]).split(inner);
 
-    Paragraph::new("Enter playlist title: ")
-        .centered()
-        .render(chunks[0], buf);
+    Paragraph::new(prompt).centered().render(chunks[0], buf);
 
     state.popup.input.set_block(
         Block::bordered()
@@ -87,8 +111,7 @@ fn render_add_song_popup(
     let focus = matches!(state.get_pane(), Pane::Popup);
     let theme = state.theme.get_display_theme(focus);
     let list_items = state
-        .playlists
-        .values()
+        .manual_playlists()
         .map(|p| {
             let playlist_name = p.name.to_string();
             Line::from(playlist_name)
@@ -124,6 +147,106 @@ fn render_add_song_popup(
     StatefulWidget::render(list, area, buf, &mut state.popup.selection);
 }
 
+fn render_move_popup(
+    area: ratatui::prelude::Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    state: &mut UiState,
+) {
+    let focus = matches!(state.get_pane(), Pane::Popup);
+    let theme = state.theme.get_display_theme(focus);
+    let list_items =
+        state
+            .folder_targets()
+            .into_iter()
+            .map(|(folder, depth)| {
+                let name = match folder.and_then(|id| state.playlist_folders.get(&id)) {
+                    Some(f) => f.name.clone(),
+                    None => "(top level)".to_string(),
+                };
+                Line::from(format!("{}{name}", "  ".repeat(depth.saturating_sub(1))))
+                    .fg(fade_color(theme.dark, theme.text_muted, 0.85))
+            })
+            .collect::<Vec<Line>>();
+
+    let block = Block::bordered()
+        .border_type(theme.border_type)
+        .border_style(theme.border)
+        .title(" Move To Folder ")
+        .title_bottom(" [Enter] / [n]ew folder here / [Esc] ")
+        .title_alignment(Alignment::Center)
+        .padding(POPUP_PADDING)
+        .bg(theme.bg);
+
+    let list = List::new(list_items)
+        .block(block)
+        .scroll_padding(area.height as usize - 5)
+        .highlight_style(theme.accent);
+
+    StatefulWidget::render(list, area, buf, &mut state.popup.selection);
+}
+
+fn render_smart_rules_popup(
+    area: ratatui::prelude::Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    state: &mut UiState,
+) {
+    let focus = matches!(state.get_pane(), Pane::Popup);
+    let theme = state.theme.get_display_theme(focus);
+    let padding_h = (area.height as f32 * 0.15) as u16;
+    let padding_w = (area.width as f32 * 0.08) as u16;
+
+    let block = Block::bordered()
+        .border_type(theme.border_type)
+        .border_style(theme.border)
+        .title(" Smart Playlist Rules ")
+        .title_bottom(" [Enter] confirm / [Esc] cancel ")
+        .title_alignment(Alignment::Center)
+        .padding(Padding {
+            left: padding_w,
+            right: padding_w,
+            top: padding_h,
+            bottom: 0,
+        })
+        .fg(theme.accent)
+        .bg(theme.bg);
+
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let chunks = Layout::vertical([
+        Constraint::Max(2),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ])
+    .split(inner);
+
+    Paragraph::new("Songs matching every condition (joined by AND):")
+        .centered()
+        .render(chunks[0], buf);
+
+    state.popup.input.set_block(
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2)),
+    );
+    state
+        .popup
+        .input
+        .set_style(Style::new().fg(theme.text_primary));
+    state.popup.input.render(chunks[1], buf);
+
+    Paragraph::new(Text::from_iter([
+        "",
+        "fields: title  artist  album  year  plays  duration  format",
+        "operators: is  is not  has  >  >=  <  <=",
+        "quote values containing spaces or \"and\"",
+    ]))
+    .fg(theme.text_muted)
+    .centered()
+    .wrap(Wrap { trim: true })
+    .render(chunks[2], buf);
+}
+
 fn render_delete_popup(
     area: ratatui::prelude::Rect,
     buf: &mut ratatui::prelude::Buffer,
@@ -169,8 +292,12 @@ fn render_rename_popup(
     let padding_h = (area.height as f32 * 0.25) as u16;
     let padding_w = (area.width as f32 * 0.2) as u16;
 
+    let title = match state.selected_playlist_item() {
+        Some(PlaylistItem::Folder(_)) => " Rename Folder ",
+        _ => " Rename Playlist ",
+    };
     let block = Block::bordered()
-        .title(" Rename Playlist ")
+        .title(title)
         .title_bottom(" [Enter] confirm / [Esc] cancel ")
         .title_alignment(Alignment::Center)
         .border_type(theme.border_type)
@@ -189,8 +316,13 @@ fn render_rename_popup(
 
     let chunks = Layout::vertical([Constraint::Max(3), Constraint::Length(3)]).split(inner);
 
-    if let Some(playlist) = state.get_selected_playlist() {
-        let p_name = Span::from(playlist.name.as_str());
+    let name = match state.selected_playlist_item() {
+        Some(PlaylistItem::Playlist(id)) => state.playlists.get(&id).map(|p| p.name.clone()),
+        Some(PlaylistItem::Folder(id)) => state.playlist_folders.get(&id).map(|f| f.name.clone()),
+        None => None,
+    };
+    if let Some(name) = name {
+        let p_name = Span::from(name);
         Paragraph::new(Text::from_iter(["Enter a new name for\n".into(), p_name]))
             .centered()
             .render(chunks[0], buf);
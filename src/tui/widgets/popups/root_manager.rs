@@ -63,7 +63,7 @@ impl StatefulWidget for RootManager {
 fn get_keymaps(mode: Option<&SettingsMode>) -> &'static str {
     if let Some(m) = mode {
         match m {
-            SettingsMode::ViewRoots => " [a]dd / [d]elete / [Esc] close ",
+            SettingsMode::ViewRoots => " [a]dd / [d]elete / [f]ind duplicates / [Esc] close ",
             SettingsMode::AddRoot => " [Enter] confirm / [Esc] cancel ",
             SettingsMode::RemoveRoot => " [Enter] confirm / [Esc] cancel ",
         }
@@ -11,7 +11,7 @@ use crate::{
     SimpleSong,
     library::SongInfo,
     theme::{DisplayTheme, fade_color},
-    ui_state::{LayoutStyle, LibraryStats, UiState},
+    ui_state::{LayoutStyle, LibraryStats, StatsWindow, UiState},
 };
 
 pub struct UserStats;
@@ -26,15 +26,19 @@ impl StatefulWidget for UserStats {
         let theme = state.theme.get_display_theme(true);
         let stats = state.stats.get_lib_stats();
         let most_played = state.stats.get_most_played();
+        let most_skipped = state.stats.get_most_skipped();
 
         let bg = match state.layout {
             LayoutStyle::Traditional => theme.bg,
             LayoutStyle::Minimal => theme.bg_global,
         };
 
+        let window = state.stats.window;
         let block = Block::bordered()
-            .title(" Library Stats ")
-            .title_bottom(" Press anything to close ")
+            .title(format!(" Library Stats · {} ", window.label()))
+            .title_bottom(
+                " [w] Period | [c] Export CSV | [j] Export JSON | Any other key to close ",
+            )
             .title_alignment(HorizontalAlignment::Center)
             .border_style(theme.border)
             .bg(bg);
@@ -65,7 +69,7 @@ impl StatefulWidget for UserStats {
         .areas(lib_stats);
 
         let library_stats = library_column(stats, theme);
-        let playback_stats = listening_column(stats, theme);
+        let playback_stats = listening_column(stats, window, theme);
 
         let horiz_padding = area.width / 10;
 
@@ -86,17 +90,51 @@ impl StatefulWidget for UserStats {
             .centered()
             .render(top_play_title, buf);
 
+        // Skips only get a section once there are some to show
+        let (top_played_buf, skipped_title, skipped_buf) = match most_skipped.is_empty() {
+            true => (top_played_buf, Rect::default(), Rect::default()),
+            false => {
+                let [played, _, title, skipped] = Layout::vertical([
+                    Constraint::Fill(3),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                    Constraint::Fill(2),
+                ])
+                .areas(top_played_buf);
+                (played, title, skipped)
+            }
+        };
+
+        let padding = Padding {
+            left: horiz_padding,
+            right: horiz_padding,
+            top: 1,
+            bottom: 0,
+        };
+
         if !most_played.is_empty() {
             let most_played_vec = get_most_played(most_played, theme, &area);
+            let bottom = if most_skipped.is_empty() { 3 } else { 0 };
             Paragraph::new(most_played_vec)
+                .block(Block::default().padding(Padding { bottom, ..padding }))
+                .centered()
+                .render(top_played_buf, buf);
+        }
+
+        if !most_skipped.is_empty() {
+            Line::from("Most Skipped Songs")
+                .fg(theme.text_secondary)
+                .centered()
+                .render(skipped_title, buf);
+
+            let most_skipped_vec = get_most_played(most_skipped, theme, &area);
+            Paragraph::new(most_skipped_vec)
                 .block(Block::default().padding(Padding {
-                    left: horiz_padding,
-                    right: horiz_padding,
-                    top: 1,
-                    bottom: 3,
+                    bottom: 2,
+                    ..padding
                 }))
                 .centered()
-                .render(top_played_buf, buf);
+                .render(skipped_buf, buf);
         }
     }
 }
@@ -112,14 +150,24 @@ fn library_column<'a>(stats: &LibraryStats, theme: &'a DisplayTheme) -> Vec<Line
     ]
 }
 
-fn listening_column(stats: &LibraryStats, theme: &DisplayTheme) -> Vec<Line<'static>> {
+fn listening_column(
+    stats: &LibraryStats,
+    window: StatsWindow,
+    theme: &DisplayTheme,
+) -> Vec<Line<'static>> {
     let play_percent = format!("Played ({:.2}%)", stats.play_percentage);
+    let header = match window {
+        StatsWindow::AllTime => "Listening",
+        w => w.label(),
+    };
 
     vec![
-        Line::from("    Listening").fg(theme.text_secondary),
+        Line::from(format!("    {header}")).fg(theme.text_secondary),
         Line::from("=".repeat(21)),
         stat_line_right(print_commas(stats.total_plays), "Combined plays", theme),
         stat_line_right(print_commas(stats.unique_plays), &play_percent, theme),
+        stat_line_right(print_commas(stats.loved), "Loved", theme),
+        stat_line_right(print_commas(stats.total_skips), "Skips", theme),
     ]
 }
 
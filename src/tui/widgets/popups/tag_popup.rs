@@ -0,0 +1,88 @@
+use crate::{tui::widgets::POPUP_PADDING, ui_state::UiState};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, StatefulWidget, Widget},
+};
+
+/// Wide enough for the longest label, `Album Artist`
+const LABEL_WIDTH: u16 = 14;
+
+pub struct TagPopup;
+impl StatefulWidget for TagPopup {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(true);
+        let selected = state.popup.selection.selected();
+
+        let title = match state.tag_editor.songs.len() {
+            1 => " Edit Tags ".to_string(),
+            n => format!(" Edit Tags: {n} tracks "),
+        };
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(title)
+            .title_bottom(" [Tab] next / [Enter] save / [Esc] cancel ")
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let rows = Layout::vertical(
+            state
+                .tag_editor
+                .entries
+                .iter()
+                .map(|_| Constraint::Length(2)),
+        )
+        .split(inner);
+
+        for (i, (entry, row)) in state.tag_editor.entries.iter().zip(rows.iter()).enumerate() {
+            let [label_area, value_area] = split_row(*row);
+
+            let is_selected = selected == Some(i);
+            let label = match is_selected {
+                true => Span::from(entry.field.label()).fg(theme.accent),
+                false => Span::from(entry.field.label()).fg(theme.text_muted),
+            };
+            label.render(label_area, buf);
+
+            if is_selected {
+                continue;
+            }
+            let value = match (&entry.shared, entry.value.is_empty()) {
+                (None, true) => Line::from("(various)").fg(theme.text_muted),
+                _ => Line::from(entry.value.as_str()).fg(theme.text_secondary),
+            };
+            value.render(value_area, buf);
+        }
+
+        // The field being edited is typed into in place
+        if let Some(row) = selected.and_then(|i| rows.get(i)) {
+            let [_, value_area] = split_row(*row);
+
+            state.popup.input.set_block(Block::default());
+            state
+                .popup
+                .input
+                .set_style(Style::new().fg(theme.text_primary));
+            state.popup.input.render(value_area, buf);
+        }
+    }
+}
+
+/// The label and value, on the first line of a field's row
+fn split_row(row: Rect) -> [Rect; 2] {
+    Layout::horizontal([Constraint::Length(LABEL_WIDTH), Constraint::Fill(1)])
+        .areas(Rect { height: 1, ..row })
+}
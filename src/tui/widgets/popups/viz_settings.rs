@@ -0,0 +1,46 @@
+use ratatui::{
+    layout::Alignment,
+    style::Stylize,
+    widgets::{Block, List, StatefulWidget},
+};
+
+use crate::{tui::widgets::POPUP_PADDING, ui_state::UiState, visualization::LookSetting};
+
+pub struct VizSettings;
+impl StatefulWidget for VizSettings {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = &state.theme.get_display_theme(true);
+
+        let selector = state.theme.icons().selector.to_string();
+        let look = state.viz.spectrum_look();
+
+        let rows = LookSetting::ALL
+            .iter()
+            .map(|&s| format!("{:<20}< {} >", s.name(), look.value(s)))
+            .collect::<Vec<String>>();
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(" Visualizer ")
+            .title_bottom(" [←/→] / [Esc] ")
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let list = List::new(rows)
+            .block(block)
+            .fg(theme.text_muted)
+            .highlight_symbol(selector)
+            .highlight_style(theme.accent);
+
+        StatefulWidget::render(list, area, buf, &mut state.popup.selection);
+    }
+}
@@ -1,22 +1,39 @@
 mod oscilloscope;
 mod progress_bar;
+mod radial;
+mod spectrogram;
 mod spectrum;
 mod timer;
+mod vu_meter;
 mod waveform;
 
 pub use oscilloscope::Oscilloscope;
 pub use progress_bar::ProgressBar;
+pub use radial::RadialSpectrum;
+pub use spectrogram::Spectrogram;
 pub use spectrum::SpectrumAnalyzer;
 pub use timer::Timer;
+pub use vu_meter::VuMeter;
 pub use waveform::Waveform;
 
 use crate::{
+    gen_config,
+    theme::DisplayTheme,
     ui_state::{LayoutStyle, UiState},
     visualization::ProgressDisplay,
 };
-use ratatui::widgets::StatefulWidget;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::Line,
+    widgets::{StatefulWidget, Widget},
+};
 
 const DEFAULT_AMP: f32 = 1.0;
+/// Gradient scroll rate for the spectrum and spectrogram, before the
+/// user's gradient speed
+const SPECTRUM_SCROLL: f32 = 1.0;
 
 pub struct Progress;
 impl StatefulWidget for Progress {
@@ -27,6 +44,15 @@ impl StatefulWidget for Progress {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
+        if state.power_save() {
+            ProgressBar.render(area, buf, state);
+            if state.layout == LayoutStyle::Traditional {
+                Timer.render(area, buf, state);
+            }
+            state.beat_detected = false;
+            return;
+        }
+
         state.viz.fill_tap();
         match &state.viz.get_progress_display() {
             ProgressDisplay::ProgressBar => ProgressBar.render(area, buf, state),
@@ -36,9 +62,47 @@ impl StatefulWidget for Progress {
             },
             ProgressDisplay::Oscilloscope => Oscilloscope.render(area, buf, state),
             ProgressDisplay::Spectrum => SpectrumAnalyzer.render(area, buf, state),
+            ProgressDisplay::RadialSpectrum => RadialSpectrum.render(area, buf, state),
+            ProgressDisplay::Spectrogram => Spectrogram.render(area, buf, state),
+            ProgressDisplay::VuMeter => VuMeter.render(area, buf, state),
         }
         if state.layout == LayoutStyle::Traditional {
             Timer.render(area, buf, state);
         }
+
+        let playing = state.metrics.is_active() && !state.metrics.is_paused();
+        state.beat_detected = gen_config().beat_pulse && playing && state.viz.detect_beat();
+
+        // The gain only applies to the oscilloscope and waveform, the decay
+        // and auto-gain to everything drawn from the spectrum
+        let label = match state.viz.get_progress_display() {
+            ProgressDisplay::Oscilloscope => state.viz.gain().label(),
+            ProgressDisplay::Waveform if state.viz.waveform_is_valid() => state.viz.gain().label(),
+            ProgressDisplay::ProgressBar | ProgressDisplay::VuMeter => None,
+            _ => state.viz.spectrum_label(),
+        };
+        if let Some(label) = label {
+            let theme = state.theme.get_display_theme(true);
+            render_label(area, buf, theme, &label);
+        }
     }
 }
+
+/// Top right corner, clear of the timestamps
+fn render_label(area: Rect, buf: &mut Buffer, theme: &DisplayTheme, label: &str) {
+    let width = label.chars().count() as u16 + 2;
+    if area.height == 0 || area.width < width + 2 {
+        return;
+    }
+
+    let at = Rect {
+        x: area.right() - width - 1,
+        y: area.y,
+        width,
+        height: 1,
+    };
+    Line::from(format!(" {label} "))
+        .fg(theme.accent)
+        .bg(theme.bg_global)
+        .render(at, buf);
+}
@@ -1,6 +1,12 @@
-use crate::{theme::DisplayTheme, ui_state::UiState};
+use crate::{
+    config::ScopeTrace,
+    gen_config,
+    theme::{DisplayTheme, fade_color},
+    ui_state::UiState,
+};
 use ratatui::{
     style::Stylize,
+    symbols::Marker,
     widgets::{
         Block, Padding, StatefulWidget, Widget,
         canvas::{Canvas, Context, Line},
@@ -8,6 +14,13 @@ use ratatui::{
 };
 
 const OSCILLO_LIMITER: usize = 512;
+/// A crossing only triggers after the trace has dipped this far below zero,
+/// as a share of the peak, so noise around zero doesn't
+const TRIGGER_HYSTERESIS: f32 = 0.05;
+/// The trace scrolls its gradient far slower than the spectrum does
+const OSCILLO_SCROLL: f32 = 0.25;
+/// How bright the shading under a filled trace is, next to the trace itself
+const FILL_BRIGHTNESS: f32 = 0.45;
 
 pub struct Oscilloscope;
 impl StatefulWidget for Oscilloscope {
@@ -20,28 +33,57 @@ impl StatefulWidget for Oscilloscope {
         state: &mut Self::State,
     ) {
         let theme = state.theme.get_display_theme(true);
-        let elapsed = state.metrics.position().as_secs_f32();
+        let elapsed = state.viz.gradient_time(OSCILLO_SCROLL);
+        let gain = state.viz.gain().factor();
+
+        // Mono, or anything beyond stereo, collapses to the single trace
+        let stereo = state.viz.stereo_scope_enabled() && state.metrics.channels() == 2;
+        let trigger = state.viz.scope_trigger_enabled();
+        let frames = match stereo {
+            true => {
+                let frames = state.viz.stereo_tap();
+                let mono = frames.iter().map(|f| f[0] + f[1]).collect::<Vec<_>>();
+                let (start, n) = window(&mono, trigger);
+                frames[start..start + n].to_vec()
+            }
+            false => Vec::new(),
+        };
 
         let samples = state.viz.display_tap();
 
-        let n = OSCILLO_LIMITER.min(samples.len());
-        let samples = &samples[samples.len() - n..];
+        let (start, n) = window(samples, trigger);
+        let samples = &samples[start..start + n];
 
         if samples.is_empty() {
             return;
         }
 
+        let len = match frames.is_empty() {
+            true => samples.len(),
+            false => frames.len(),
+        };
+
         let v_marg = match area.height > 20 {
             true => ((area.height as f32) * 0.25) as u16,
             false => 0,
         };
 
+        let rows = area.height.saturating_sub(v_marg * 2).max(1);
+        let marker = theme.oscilloscope.style;
+        let pen = Pen {
+            theme,
+            time: elapsed,
+            style: gen_config().oscilloscope_trace,
+            pixel: 2.0 / (rows as f64 * dots_per_row(marker)),
+        };
+
         Canvas::default()
-            .x_bounds([0.0, samples.len() as f64])
+            .x_bounds([0.0, len as f64])
             .y_bounds([-1.0, 1.0])
-            .marker(theme.progress_style)
-            .paint(|ctx| {
-                draw_oscilloscope(ctx, samples, elapsed, theme);
+            .marker(marker)
+            .paint(|ctx| match frames.is_empty() {
+                true => draw_oscilloscope(ctx, samples, gain, &pen),
+                false => draw_stereo_split(ctx, &frames, gain, &pen),
             })
             .background_color(theme.bg_global)
             .block(Block::new().bg(theme.bg_global).padding(Padding {
@@ -54,35 +96,159 @@ impl StatefulWidget for Oscilloscope {
     }
 }
 
-fn draw_oscilloscope(ctx: &mut Context, samples: &[f32], time: f32, theme: &DisplayTheme) {
-    let peak = samples
+/// Where the drawn stretch starts, and how long it is. Triggered, it opens
+/// on a rising zero crossing so a steady tone holds still, and runs free
+/// from the latest samples whenever there isn't one.
+fn window(samples: &[f32], trigger: bool) -> (usize, usize) {
+    let n = OSCILLO_LIMITER.min(samples.len());
+    let latest = samples.len() - n;
+    match trigger {
+        true => (trigger_point(samples, n).unwrap_or(latest), n),
+        false => (latest, n),
+    }
+}
+
+/// The latest rising crossing that still leaves `window` samples after it,
+/// looking back at most one window so the trace never lags far behind
+fn trigger_point(samples: &[f32], window: usize) -> Option<usize> {
+    let last = samples.len().checked_sub(window)?;
+    let first = last.saturating_sub(window).max(1);
+    if first > last {
+        return None;
+    }
+
+    let peak = samples[first - 1..]
         .iter()
+        .fold(0.0_f32, |p, s| p.max(s.abs()));
+    let low = -peak * TRIGGER_HYSTERESIS;
+
+    let mut armed = false;
+    let mut found = None;
+    for i in first..=last {
+        let (prev, cur) = (samples[i - 1], samples[i]);
+        if prev < low {
+            armed = true;
+        }
+        if armed && prev < 0.0 && cur >= 0.0 {
+            found = Some(i);
+            armed = false;
+        }
+    }
+    found
+}
+
+/// What a trace is drawn with
+struct Pen<'a> {
+    theme: &'a DisplayTheme,
+    time: f32,
+    style: ScopeTrace,
+    /// The height of one dot of the marker, in canvas units
+    pixel: f64,
+}
+
+/// Rows of dots a terminal cell holds for each marker
+fn dots_per_row(marker: Marker) -> f64 {
+    match marker {
+        Marker::Braille | Marker::Octant => 4.0,
+        Marker::Sextant => 3.0,
+        Marker::Quadrant | Marker::HalfBlock => 2.0,
+        _ => 1.0,
+    }
+}
+
+fn draw_oscilloscope(ctx: &mut Context, samples: &[f32], gain: f32, pen: &Pen) {
+    let scale = peak_scale(samples.iter().copied()) * gain;
+    draw_trace(ctx, samples, scale, 0.0, 1.0, pen);
+}
+
+/// Left channel on the top half, right channel mirrored on the bottom half
+fn draw_stereo_split(ctx: &mut Context, frames: &[[f32; 2]], gain: f32, pen: &Pen) {
+    let left = frames.iter().map(|f| f[0]).collect::<Vec<_>>();
+    let right = frames.iter().map(|f| f[1]).collect::<Vec<_>>();
+
+    // Shared scale so a louder channel still reads as louder
+    let scale = peak_scale(left.iter().chain(right.iter()).copied()) * gain * 0.5;
+
+    draw_trace(ctx, &left, scale, 0.5, 0.5, pen);
+    draw_trace(ctx, &right, -scale, -0.5, 0.5, pen);
+}
+
+fn peak_scale(samples: impl Iterator<Item = f32>) -> f32 {
+    let peak = samples
         .map(|s| s.abs())
         .max_by(|a, b| a.total_cmp(b))
         .unwrap_or(1.0);
 
-    let scale = if peak > 1.0 { 1.0 / peak } else { 1.0 };
+    if peak > 1.0 { 1.0 / peak } else { 1.0 }
+}
+
+/// Samples are scaled, then held within `limit` of `center` so a raised
+/// gain flattens out at the edge rather than leaving the canvas
+fn draw_trace(ctx: &mut Context, samples: &[f32], scale: f32, center: f64, limit: f32, pen: &Pen) {
+    let theme = pen.theme;
+    let offsets: &[f64] = match pen.style {
+        ScopeTrace::Thick => &[-1.0, 0.0, 1.0],
+        ScopeTrace::Line | ScopeTrace::Filled => &[0.0],
+    };
 
     for (i, window) in samples.windows(2).enumerate() {
         let x1 = i as f64;
-        let y1 = (window[0] * scale) as f64;
+        let y1 = center + (window[0] * scale).clamp(-limit, limit) as f64;
         let x2 = (i + 1) as f64;
-        let y2 = (window[1] * scale) as f64;
+        let y2 = center + (window[1] * scale).clamp(-limit, limit) as f64;
 
         let progress = i as f32 / samples.len() as f32;
 
-        let time = time / 4.0; // Slow down gradient scroll substantially
         let color = theme
             .oscilloscope
             .color
-            .color_at(progress, time, theme.oscilloscope.speed);
-
-        ctx.draw(&Line {
-            x1,
-            y1,
-            x2,
-            y2,
-            color,
-        });
+            .color_at(progress, pen.time, theme.oscilloscope.speed);
+
+        // Drawn first, so the trace stays on top of its own shading
+        if pen.style == ScopeTrace::Filled {
+            ctx.draw(&Line {
+                x1,
+                y1: center,
+                x2: x1,
+                y2: y1,
+                color: fade_color(theme.dark, color, FILL_BRIGHTNESS),
+            });
+        }
+
+        for offset in offsets {
+            let dy = offset * pen.pixel;
+            ctx.draw(&Line {
+                x1,
+                y1: y1 + dy,
+                x2,
+                y2: y2 + dy,
+                color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggers_on_a_rising_crossing() {
+        // A sine with a period of 100 samples, starting partway through one
+        let samples = (0..1200)
+            .map(|i| ((i as f32 + 37.0) / 100.0 * std::f32::consts::TAU).sin())
+            .collect::<Vec<_>>();
+
+        let start = trigger_point(&samples, OSCILLO_LIMITER).unwrap();
+        assert!(samples[start - 1] < 0.0 && samples[start] >= 0.0);
+        assert!(start + OSCILLO_LIMITER <= samples.len());
+        assert!(start > samples.len() - OSCILLO_LIMITER - 100);
+
+        // Silence never crosses, so the trace runs free
+        assert_eq!(trigger_point(&[0.0; 1200], OSCILLO_LIMITER), None);
+        assert_eq!(
+            window(&[0.0; 1200], true),
+            (1200 - OSCILLO_LIMITER, OSCILLO_LIMITER)
+        );
     }
 }
@@ -1,5 +1,6 @@
 use crate::ui_state::{LayoutStyle, UiState};
 use ratatui::{
+    layout::Rect,
     style::Stylize,
     widgets::{Block, LineGauge, Padding, StatefulWidget, Widget},
 };
@@ -14,10 +15,21 @@ impl StatefulWidget for ProgressBar {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
+        let (x_pad, y_pad) = match state.layout {
+            LayoutStyle::Traditional => (9, 1),
+            LayoutStyle::Minimal => (0, 0),
+        };
+        let bar_y = area.y + y_pad;
+        let bar_x = area.x + x_pad;
+        let bar_w = area.width.saturating_sub(x_pad * 2);
+        if bar_y < area.bottom() {
+            state.mouse.seek_bar = Some(Rect::new(bar_x, bar_y, bar_w, 1));
+        }
+
         let theme = state.theme.get_display_theme(true);
 
-        let elapsed = state.metrics.position().as_secs_f32();
-        let duration = state.metrics.duration().as_secs_f32();
+        let (elapsed, duration) = state.track_progress();
+        let (elapsed, duration) = (elapsed.as_secs_f32(), duration.as_secs_f32());
 
         // Prevent crash
         let ratio = (elapsed / duration).min(0.9999);
@@ -41,11 +53,6 @@ impl StatefulWidget for ProgressBar {
             &theme.progress_bar.active_color,
         );
 
-        let (x_pad, y_pad) = match state.layout {
-            LayoutStyle::Traditional => (9, 1),
-            LayoutStyle::Minimal => (0, 0),
-        };
-
         let guage = LineGauge::default()
             .block(Block::new().bg(theme.bg_global).padding(Padding {
                 left: x_pad,
@@ -61,5 +68,17 @@ impl StatefulWidget for ProgressBar {
             .ratio(ratio as f64);
 
         guage.render(area, buf);
+
+        // A-B loop markers, drawn over the bar itself
+        if bar_w == 0 || bar_y >= area.bottom() || duration <= 0.0 {
+            return;
+        }
+
+        let markers = [(state.ab_loop.start(), "["), (state.ab_loop.end(), "]")];
+        for (point, symbol) in markers {
+            let Some(secs) = point else { continue };
+            let col = ((secs as f32 / duration) * (bar_w - 1) as f32).round() as u16;
+            buf.set_string(bar_x + col.min(bar_w - 1), bar_y, symbol, theme.accent);
+        }
     }
 }
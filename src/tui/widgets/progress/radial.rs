@@ -0,0 +1,86 @@
+use crate::{theme::fade_color, ui_state::UiState};
+use ratatui::{
+    style::Stylize,
+    widgets::{
+        Block, StatefulWidget, Widget,
+        canvas::{Canvas, Line},
+    },
+};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Where the bars start, as a share of the largest radius that fits
+const INNER_RADIUS: f64 = 0.3;
+/// Fewer spokes than this and the circle reads as a handful of sticks
+const MIN_SPOKES: usize = 24;
+const MAX_SPOKES: usize = 180;
+
+/// The spectrum wrapped around a circle, low frequencies starting at the
+/// top and rising clockwise
+pub struct RadialSpectrum;
+
+impl StatefulWidget for RadialSpectrum {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        if state.metrics.is_active() && !state.metrics.is_paused() {
+            state.viz.update_spectrum();
+        }
+
+        let theme = state.theme.get_display_theme(true);
+        let elapsed = state.viz.gradient_time(super::SPECTRUM_SCROLL);
+
+        // Measured in cell widths, where a cell is about twice as tall as
+        // it is wide, so a circle comes out round however the pane is shaped
+        let half_w = area.width.max(1) as f64 / 2.0;
+        let half_h = area.height.max(1) as f64;
+        let outer = half_w.min(half_h) * 0.95;
+        let inner = outer * INNER_RADIUS;
+
+        // About one spoke per braille dot around the inner edge
+        let spokes = ((TAU * inner * 2.0) as usize).clamp(MIN_SPOKES, MAX_SPOKES);
+
+        let spectrum = state.viz.spectrum_mut();
+        spectrum.remap_display(spokes);
+        let display = spectrum.get_display_bins();
+
+        if display.is_empty() {
+            return;
+        }
+
+        Canvas::default()
+            .x_bounds([-half_w, half_w])
+            .y_bounds([-half_h, half_h])
+            .marker(theme.spectrum.style)
+            .paint(|ctx| {
+                for (i, &mag) in display.iter().enumerate() {
+                    let progress = i as f32 / spokes as f32;
+                    let base =
+                        theme
+                            .spectrum
+                            .colors
+                            .color_at(progress, elapsed, theme.spectrum.speed);
+                    let color = fade_color(theme.dark, base, mag.clamp(0.25, 1.0));
+
+                    let angle = FRAC_PI_2 - progress as f64 * TAU;
+                    let (sin, cos) = angle.sin_cos();
+                    let reach = inner + mag.clamp(0.0, 1.0) as f64 * (outer - inner);
+
+                    ctx.draw(&Line {
+                        x1: inner * cos,
+                        y1: inner * sin,
+                        x2: reach * cos,
+                        y2: reach * sin,
+                        color,
+                    });
+                }
+            })
+            .background_color(theme.bg_global)
+            .block(Block::new().bg(theme.bg_global))
+            .render(area, buf)
+    }
+}
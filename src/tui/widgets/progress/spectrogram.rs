@@ -0,0 +1,83 @@
+use crate::{
+    theme::fade_color,
+    ui_state::{LayoutStyle, UiState},
+};
+use ratatui::{
+    style::Stylize,
+    widgets::{Block, Padding, StatefulWidget, Widget},
+};
+
+/// Magnitudes below this are drawn as background to keep silence clean
+const SILENCE: f32 = 0.05;
+
+pub struct Spectrogram;
+
+impl StatefulWidget for Spectrogram {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(true);
+        let elapsed = state.viz.gradient_time(super::SPECTRUM_SCROLL);
+
+        let padding = match state.layout {
+            LayoutStyle::Traditional => Padding {
+                left: 10,
+                right: 10,
+                top: 1,
+                bottom: 1,
+            },
+            LayoutStyle::Minimal => Padding::default(),
+        };
+
+        let block = Block::new().bg(theme.bg_global).padding(padding);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.width == 0 || inner.height == 0 {
+            return;
+        }
+
+        // Each cell holds two history rows via the upper half block
+        let width = inner.width as usize;
+        let playing = state.metrics.is_active() && !state.metrics.is_paused();
+        state
+            .viz
+            .update_spectrogram(width, inner.height as usize * 2, playing);
+
+        let rows = state.viz.spectrogram().rows();
+
+        let color_for = |x: usize, mag: f32| match mag < SILENCE {
+            true => theme.bg_global,
+            false => {
+                let progress = x as f32 / width as f32;
+                let base = theme
+                    .spectrum
+                    .colors
+                    .color_at(progress, elapsed, theme.spectrum.speed);
+                fade_color(theme.dark, base, mag.clamp(0.0, 1.0))
+            }
+        };
+
+        for y in 0..inner.height {
+            let upper = rows.get(y as usize * 2);
+            let lower = rows.get(y as usize * 2 + 1);
+
+            for x in 0..inner.width {
+                let idx = x as usize;
+                let top = upper.and_then(|r| r.get(idx)).copied().unwrap_or(0.0);
+                let bottom = lower.and_then(|r| r.get(idx)).copied().unwrap_or(0.0);
+
+                if let Some(c) = buf.cell_mut((inner.x + x, inner.y + y)) {
+                    c.set_char('▀')
+                        .set_fg(color_for(idx, top))
+                        .set_bg(color_for(idx, bottom));
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
-    theme::fade_color,
+    gen_config,
+    theme::{SpectrumColoring, fade_color},
     ui_state::{LayoutStyle, UiState},
+    visualization::BarGap,
 };
 use ratatui::{
     style::{Color, Stylize},
@@ -26,19 +28,49 @@ impl StatefulWidget for SpectrumAnalyzer {
         }
 
         let theme = state.theme.get_display_theme(true);
-        let elapsed = state.metrics.position().as_secs_f32();
+        let is_mirrored = state.viz.spectrum_mirrored(theme.spectrum.mirror);
+        let elapsed = state.viz.gradient_time(super::SPECTRUM_SCROLL);
+        let coloring = state.viz.spectrum_coloring(theme.spectrum.coloring);
+        let min_height = state.viz.spectrum_look().min_height();
+        let bar_gap = state.viz.spectrum_look().gap();
 
         let canvas_width = area.width.max(1) as usize;
         let pixel_width = canvas_width * 2;
 
+        // A forced count wider than the pane has its neighbors merged, so
+        // each bar still gets a column of its own
+        let forced = gen_config().spectrum_bars.map(usize::from);
+        let group = forced.map_or(1, |n| n.div_ceil(canvas_width));
+
         let spectrum = state.viz.spectrum_mut();
-        spectrum.remap_display(canvas_width);
-        let display = spectrum.get_display_bins();
+        spectrum.remap_display(forced.unwrap_or(canvas_width));
+        let display = grouped(spectrum.get_display_bins(), group);
+        let peaks = spectrum.get_display_peaks().map(|p| grouped(p, group));
+        let band_colors = match coloring {
+            SpectrumColoring::Bands => display
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    let center = (i as f32 + 0.5) / display.len() as f32;
+                    theme.spectrum.band_color(spectrum.frequency_at(center))
+                })
+                .collect(),
+            SpectrumColoring::Gradient => Vec::new(),
+        };
 
         if display.is_empty() {
             return;
         }
 
+        let bars = display.len();
+        let bar_width = pixel_width as f64 / bars as f64;
+        // Wide bars are set apart, as they'd otherwise read as one block
+        let gap = match bar_gap {
+            BarGap::Auto if forced.is_some() && bar_width >= 3.0 => 1.0,
+            BarGap::Auto => 0.0,
+            BarGap::Dots(n) => n as f64,
+        };
+
         let padding = if let LayoutStyle::Traditional = state.layout {
             Padding {
                 left: 10,
@@ -50,8 +82,6 @@ impl StatefulWidget for SpectrumAnalyzer {
             Padding::default()
         };
 
-        let is_mirrored = theme.spectrum.mirror;
-
         let y_min = match is_mirrored {
             true => -1.05,
             false => 0.05,
@@ -60,19 +90,38 @@ impl StatefulWidget for SpectrumAnalyzer {
         Canvas::default()
             .x_bounds([0.00, pixel_width as f64])
             .y_bounds([y_min, 1.05]) // The 0.05 prevents overflow
-            .marker(theme.progress_style)
+            .marker(theme.spectrum.style)
             .paint(|ctx| {
                 for (i, &mag) in display.iter().enumerate() {
-                    let progress = i as f32 / canvas_width as f32;
-                    let base =
-                        theme
-                            .spectrum
-                            .colors
-                            .color_at(progress, elapsed, theme.spectrum.speed);
+                    let mag = mag.max(min_height);
+                    let progress = i as f32 / bars as f32;
+                    let base = match band_colors.get(i) {
+                        Some(&color) => color,
+                        None => {
+                            theme
+                                .spectrum
+                                .colors
+                                .color_at(progress, elapsed, theme.spectrum.speed)
+                        }
+                    };
                     let color = fade_color(theme.dark, base, mag.clamp(0.25, 1.0));
 
-                    for x in [i * 2, i * 2 + 1] {
-                        ctx.draw(&spectrum_line(x as f64, mag as f64, is_mirrored, color))
+                    let start = (i as f64 * bar_width).round();
+                    let end = ((i + 1) as f64 * bar_width).round() - gap;
+                    let mut x = start;
+                    while x < end.max(start + 1.0) {
+                        ctx.draw(&spectrum_line(x, mag as f64, is_mirrored, color));
+                        x += 1.0;
+                    }
+
+                    if let Some(&peak) = peaks.as_ref().and_then(|p| p.get(i))
+                        && peak > mag
+                    {
+                        let cap = (end - start - 1.0).max(0.0);
+                        ctx.draw(&peak_cap(start, cap, peak as f64, base));
+                        if is_mirrored {
+                            ctx.draw(&peak_cap(start, cap, -peak as f64, base));
+                        }
                     }
                 }
             })
@@ -95,3 +144,21 @@ fn spectrum_line(x: f64, mag: f64, mirrored: bool, color: Color) -> Line {
         color,
     }
 }
+
+#[inline]
+fn peak_cap(x: f64, width: f64, y: f64, color: Color) -> Line {
+    Line {
+        x1: x,
+        y1: y,
+        x2: x + width,
+        y2: y,
+        color,
+    }
+}
+
+/// The loudest of every `size` neighboring bars
+fn grouped(bars: &[f32], size: usize) -> Vec<f32> {
+    bars.chunks(size.max(1))
+        .map(|c| c.iter().copied().fold(0.0, f32::max))
+        .collect()
+}
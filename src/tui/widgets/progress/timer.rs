@@ -1,6 +1,8 @@
 use crate::{
     DurationStyle, get_readable_duration, ui_state::UiState, visualization::ProgressDisplay,
 };
+use std::time::Duration;
+
 use ratatui::{
     layout::Rect,
     style::Stylize,
@@ -25,11 +27,10 @@ impl StatefulWidget for Timer {
 
         let text_color = state.theme.active.text_muted;
 
-        let elapsed = state.metrics.position();
+        let (elapsed, dur_str) = state.track_progress();
         let elapsed_str = get_readable_duration(elapsed, crate::DurationStyle::Compact);
         let elapsed_str_len = elapsed_str.len() as u16;
 
-        let dur_str = state.metrics.duration();
         let duration_str = get_readable_duration(dur_str, DurationStyle::Compact);
 
         let dur_str_len = duration_str.len() as u16;
@@ -45,5 +46,50 @@ impl StatefulWidget for Timer {
         Text::from(duration_str)
             .fg(text_color)
             .render(Rect::new(x_dur_pos, y_pos, dur_str_len, 1), buf);
+
+        // Indicators sit beneath the timestamps, or above them when the
+        // timestamps are already on the bottom row
+        let y_extra = match y_pos + 1 < area.bottom() {
+            true => y_pos + 1,
+            false => y_pos.saturating_sub(1).max(area.y),
+        };
+        if y_extra == y_pos {
+            return;
+        }
+
+        let accent = state.theme.active.accent;
+
+        let loop_str = match (state.ab_loop.start(), state.ab_loop.end()) {
+            (Some(_), Some(_)) => Some("A-B".to_string()),
+            (Some(a), None) => Some(format!(
+                "A {}",
+                get_readable_duration(Duration::from_secs_f64(a), DurationStyle::Compact)
+            )),
+            _ => None,
+        };
+
+        if let Some(loop_str) = loop_str {
+            let loop_str_len = loop_str.chars().count() as u16;
+            let x_loop_pos = 2 + (8_u16.saturating_sub(loop_str_len) / 2);
+
+            Text::from(loop_str)
+                .fg(accent)
+                .render(Rect::new(x_loop_pos, y_extra, loop_str_len, 1), buf);
+        }
+
+        if let Some(remaining) = state.sleep_timer.remaining() {
+            let sleep_str = format!(
+                "z {}",
+                get_readable_duration(remaining, DurationStyle::Compact)
+            );
+            let sleep_str_len = sleep_str.chars().count() as u16;
+
+            let x_sleep_pos = (area.x + area.width.saturating_sub(10))
+                + (10_u16.saturating_sub(sleep_str_len) / 2);
+
+            Text::from(sleep_str)
+                .fg(accent)
+                .render(Rect::new(x_sleep_pos, y_extra, sleep_str_len, 1), buf);
+        }
     }
 }
@@ -0,0 +1,112 @@
+use crate::{
+    ui_state::{LayoutStyle, UiState},
+    visualization,
+};
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    widgets::{Block, Padding, StatefulWidget, Widget},
+};
+
+const FILLED: char = '█';
+const UNFILLED: char = '─';
+const PEAK: char = '▌';
+
+pub struct VuMeter;
+
+impl StatefulWidget for VuMeter {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme.get_display_theme(true);
+        let elapsed = state.metrics.position().as_secs_f32();
+
+        let padding = match state.layout {
+            LayoutStyle::Traditional => Padding {
+                left: 10,
+                right: 10,
+                top: 1,
+                bottom: 1,
+            },
+            LayoutStyle::Minimal => Padding::default(),
+        };
+
+        let block = Block::new().bg(theme.bg_global).padding(padding);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // Leave room for the channel label
+        if inner.width < 4 || inner.height == 0 {
+            return;
+        }
+
+        let meter = state.viz.vu_meter();
+        let levels = meter.levels();
+        let peaks = meter.peaks();
+
+        let rows = match inner.height {
+            1 => vec![inner],
+            h => {
+                let half = h / 2;
+                vec![
+                    Rect::new(inner.x, inner.y, inner.width, half),
+                    Rect::new(inner.x, inner.y + h - half, inner.width, half),
+                ]
+            }
+        };
+
+        let width = inner.width - 2;
+        let zero_mark = (visualization::VuMeter::zero_mark() * width as f32) as u16;
+
+        for (ch, row) in rows.iter().enumerate() {
+            let label = match (rows.len(), ch) {
+                (1, _) => 'M',
+                (_, 0) => 'L',
+                _ => 'R',
+            };
+
+            // A single row shows the louder channel
+            let (level, peak) = match rows.len() {
+                1 => (levels[0].max(levels[1]), peaks[0].max(peaks[1])),
+                _ => (levels[ch], peaks[ch]),
+            };
+
+            let filled = (level * width as f32).round() as u16;
+            let peak_x = ((peak * width as f32) as u16).min(width - 1);
+
+            for y in row.y..row.y + row.height {
+                if let Some(c) = buf.cell_mut((row.x, y)) {
+                    c.set_char(label).set_fg(theme.text_muted);
+                }
+
+                for x in 0..width {
+                    let progress = x as f32 / width as f32;
+                    let hot = x >= zero_mark;
+
+                    let (symbol, color) = match x < filled {
+                        true if hot => (FILLED, theme.bg_error),
+                        true => (
+                            FILLED,
+                            theme.progress_bar.active_color.color_at(
+                                progress,
+                                elapsed,
+                                theme.progress_bar.speed,
+                            ),
+                        ),
+                        false if x == peak_x && peak > 0.0 => (PEAK, theme.text_primary),
+                        false => (UNFILLED, theme.text_muted),
+                    };
+
+                    if let Some(c) = buf.cell_mut((row.x + 2 + x, y)) {
+                        c.set_char(symbol).set_fg(color);
+                    }
+                }
+            }
+        }
+    }
+}
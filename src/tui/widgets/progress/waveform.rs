@@ -19,8 +19,6 @@ impl StatefulWidget for Waveform {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
-        let theme = state.theme.get_display_theme(true);
-
         let padding_vertical = match area.height {
             0..=6 => 0,
             7..=20 => (area.height as f32 * 0.15) as u16 - 1,
@@ -43,20 +41,33 @@ impl StatefulWidget for Waveform {
             },
         };
 
+        // The whole track is laid out along the width, so a click anywhere
+        // on it seeks there
+        let block = Block::new().padding(padding);
+        let inner = block.inner(area);
+        if !inner.is_empty() {
+            state.mouse.seek_bar = Some(inner);
+        }
+
+        let theme = state.theme.get_display_theme(true);
         let waveform = state.viz.get_waveform_as_slice();
         let wf_len = waveform.len();
+        // Bins are already normalized to the track's peak, so auto-gain
+        // following the live signal has nothing to add here
+        let gain = state.viz.gain().manual_factor();
 
         Canvas::default()
             .x_bounds([0.0, wf_len as f64])
             .y_bounds([-WAVEFORM_WIDGET_HEIGHT, WAVEFORM_WIDGET_HEIGHT])
             .marker(theme.progress_style)
             .paint(|ctx| {
-                let elapsed = state.metrics.position().as_secs_f32();
-                let duration = state.metrics.duration().as_secs_f32();
-                let progress = elapsed / duration;
+                let (elapsed, duration) = state.track_progress();
+                let elapsed = elapsed.as_secs_f32();
+                let progress = elapsed / duration.as_secs_f32();
 
                 for (idx, amp) in waveform.iter().enumerate() {
-                    let hgt = (*amp as f64 * WAVEFORM_WIDGET_HEIGHT).round();
+                    let amp = (amp * gain).min(1.0);
+                    let hgt = (amp as f64 * WAVEFORM_WIDGET_HEIGHT).round();
                     let position = idx as f32 / wf_len as f32;
 
                     let color = match position < progress {
@@ -70,7 +81,7 @@ impl StatefulWidget for Waveform {
                             elapsed,
                             theme.waveform.speed,
                             theme.dark,
-                            *amp,
+                            amp,
                             &theme.waveform.active_color,
                         ),
                     };
@@ -80,9 +91,13 @@ impl StatefulWidget for Waveform {
                         false => draw_waveform_rect(ctx, idx as f64, hgt, color),
                     }
                 }
+
+                if progress.is_finite() && wf_len > 0 {
+                    draw_playhead(ctx, progress as f64 * wf_len as f64, theme.text_primary);
+                }
             })
             .background_color(theme.bg_global)
-            .block(Block::new().bg(theme.bg_global).padding(padding))
+            .block(block.bg(theme.bg_global))
             .render(area, buf)
     }
 }
@@ -99,6 +114,17 @@ fn draw_waveform_line(ctx: &mut Context, idx: f64, hgt: f64, color: Color) {
     })
 }
 
+/// Marks the playing position across the full height
+fn draw_playhead(ctx: &mut Context, x: f64, color: Color) {
+    ctx.draw(&Line {
+        x1: x,
+        x2: x,
+        y1: WAVEFORM_WIDGET_HEIGHT,
+        y2: -WAVEFORM_WIDGET_HEIGHT,
+        color,
+    })
+}
+
 /// Rectangles cleanly extend the waveform when in
 /// full-screen view
 fn draw_waveform_rect(ctx: &mut Context, idx: f64, hgt: f64, color: Color) {
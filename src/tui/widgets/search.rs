@@ -1,6 +1,7 @@
 use crate::ui_state::{LayoutStyle, Pane, UiState};
 use ratatui::{
     style::Stylize,
+    text::Line,
     widgets::{Block, Borders, Padding, StatefulWidget, Widget},
 };
 
@@ -41,9 +42,16 @@ impl StatefulWidget for SearchBar {
             _ => (1, 2),
         };
 
+        let mode = match state.search.regex_error() {
+            Some(error) => Line::from(format!(" {error} ")).fg(theme.bg_error),
+            None if state.search.is_regex() => Line::from(" regex ").fg(theme.text_muted),
+            None => Line::default(),
+        };
+
         let search = state.search.get_widget_mut();
         search.set_block(
             Block::bordered()
+                .title_top(mode.right_aligned())
                 .borders(border_display)
                 .border_type(border_type)
                 .border_style(border_style)
@@ -4,13 +4,13 @@ use crate::{
     tui::widgets::sidebar::{
         KILL_WIDTH_ALBUM, KILL_WIDTH_PLAYLIST, create_standard_list, get_padding,
     },
-    ui_state::{AlbumSort, LayoutStyle, Pane, RowKind, SidebarRow, UiState},
+    ui_state::{AlbumSort, LayoutStyle, ListArea, Pane, RowKind, SidebarRow, UiState},
 };
 use ratatui::{
     layout::Rect,
     style::Stylize,
     text::{Line, Span},
-    widgets::{ListItem, StatefulWidget},
+    widgets::{Block, ListItem, StatefulWidget},
 };
 use unicode_width::UnicodeWidthStr;
 
@@ -32,8 +32,20 @@ impl StatefulWidget for SideBarHandler {
             items.push(render_row(row, state, area, theme));
         }
 
+        let inner = Block::bordered()
+            .borders(theme.border_display)
+            .padding(get_padding(&state.layout, theme.border_display))
+            .inner(area);
+
         let list = create_standard_list(items, state, area);
         StatefulWidget::render(list, area, buf, &mut state.nav.sidebar.pos);
+
+        state.mouse.sidebar = Some(ListArea {
+            rows: inner,
+            stride: 1,
+            offset: state.nav.sidebar.pos.offset(),
+            len: state.nav.sidebar.rows.len(),
+        });
     }
 }
 
@@ -46,7 +58,7 @@ fn render_row(
     let indent = " ".repeat((row.depth as usize * 2).saturating_sub(1));
 
     let glyph = match row.collapse_key() {
-        Some(k) => match state.nav.sidebar.collapsed.contains(&k) {
+        Some(k) => match state.is_collapsed(&k) {
             true => state.theme.icons().collapsed.to_string(),
             false => state.theme.icons().expanded.to_string(),
         },
@@ -133,6 +145,74 @@ fn render_row(
             }
         }
 
+        RowKind::Folder { label, .. } => {
+            let max_width = area.width.saturating_sub(padding + prefix_w) as usize;
+            ListItem::new(Line::from_iter([
+                Span::from(prefix).fg(theme.text_muted),
+                Span::from(truncate_at_last_space(label, max_width)).fg(theme.text_secondary),
+            ]))
+        }
+
+        RowKind::PlaylistFolder(id) => {
+            let Some(folder) = state.playlist_folders.get(id) else {
+                return ListItem::new("");
+            };
+
+            let count = state.playlists_under(*id).len();
+            let count_str = match area.width > KILL_WIDTH_PLAYLIST {
+                false => String::new(),
+                true => match state.layout {
+                    LayoutStyle::Traditional => format!("({count})"),
+                    LayoutStyle::Minimal => format!("{count}"),
+                },
+            };
+            let count_w = count_str.width() as u16;
+
+            let max_name_width =
+                area.width.saturating_sub(count_w + padding + prefix_w + 1) as usize;
+            let name = truncate_at_last_space(&folder.name, max_name_width);
+
+            let gap = area
+                .width
+                .saturating_sub(padding + prefix_w + count_w)
+                .saturating_sub(name.width() as u16) as usize;
+
+            ListItem::new(Line::from_iter([
+                Span::from(prefix).fg(theme.text_muted),
+                Span::from(name).fg(theme.text_secondary).italic(),
+                Span::from(" ".repeat(gap)),
+                Span::from(count_str).fg(theme.text_muted),
+            ]))
+        }
+
+        RowKind::Genre(name) => {
+            let count = state.genres.get(name).copied().unwrap_or_default();
+            let count_str = match area.width > KILL_WIDTH_PLAYLIST {
+                false => String::new(),
+                true => match state.layout {
+                    LayoutStyle::Traditional => format!("({count})"),
+                    LayoutStyle::Minimal => format!("{count}"),
+                },
+            };
+            let count_w = count_str.width() as u16;
+
+            let max_name_width =
+                area.width.saturating_sub(count_w + padding + prefix_w + 1) as usize;
+            let label = truncate_at_last_space(name, max_name_width);
+
+            let gap = area
+                .width
+                .saturating_sub(padding + prefix_w + count_w)
+                .saturating_sub(label.width() as u16) as usize;
+
+            ListItem::new(Line::from_iter([
+                Span::from(prefix),
+                Span::from(label).fg(theme.text_secondary),
+                Span::from(" ".repeat(gap)),
+                Span::from(count_str).fg(theme.text_muted),
+            ]))
+        }
+
         RowKind::Playlist(id) => {
             let Some(playlist) = state.playlists.get(id) else {
                 return ListItem::new("");
@@ -162,9 +242,14 @@ fn render_row(
                 .saturating_sub(name.width() as u16)
                 .saturating_sub(count_w) as usize;
 
+            let name_color = match playlist.is_smart() {
+                true => theme.accent,
+                false => theme.text_secondary,
+            };
+
             ListItem::new(Line::from_iter([
                 Span::from(prefix),
-                Span::from(name).fg(theme.text_secondary),
+                Span::from(name).fg(name_color),
                 Span::from(" ".repeat(gap)),
                 Span::from(count_str).fg(theme.text_muted),
             ]))
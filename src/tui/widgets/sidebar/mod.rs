@@ -31,6 +31,8 @@ pub fn create_standard_list<'a>(
             let count = match root {
                 Root::Library => state.albums.len(),
                 Root::Playlist => state.playlists.len(),
+                Root::Genre => state.genres.len(),
+                Root::Folder => state.library().folders.paths().count(),
             };
             Line::from(format!(" ⟪ {} {} ⟫ ", count, root.label()))
         })
@@ -47,7 +49,7 @@ pub fn create_standard_list<'a>(
 
     let keymaps = if focus {
         match state.get_selected_root() {
-            Root::Library => Line::from(" [q]ueue "),
+            Root::Library | Root::Genre | Root::Folder => Line::from(" [q]ueue "),
             Root::Playlist => {
                 let decorator = &state.theme.icons().decorator;
                 let playlist_keymaps =
@@ -4,16 +4,16 @@ mod song_table;
 pub use song_table::SongTable;
 
 use crate::{
-    DurationStyle, get_readable_duration,
+    DurationStyle, SimpleSong, get_readable_duration,
     theme::DisplayTheme,
     truncate_at_last_space,
-    ui_state::{LayoutStyle, Mode, Pane, UiState},
+    ui_state::{LayoutStyle, Mode, Pane, TableColumn, UiState},
 };
 use ratatui::{
     layout::{Constraint, Flex, HorizontalAlignment, Rect},
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Padding, Row, Table},
+    widgets::{Block, Cell, Padding, Row, Table},
 };
 
 // max len of `XXm XXs` or `XXh XXm` format
@@ -24,25 +24,22 @@ const SCROLL_PAD: f32 = 0.25;
 pub(super) const TRAD_ROW_HEIGHT: u16 = 2;
 pub(super) const TRAD_ROW_MARGIN: u16 = 1;
 pub(super) const TRAD_ROW_STRIDE: u16 = TRAD_ROW_HEIGHT + TRAD_ROW_MARGIN;
+const HEADER_MARGIN: u16 = 1;
+/// Lines the header takes out of the table, margin included
+pub(super) const HEADER_HEIGHT: u16 = 1 + HEADER_MARGIN;
 
 pub(super) fn get_widths(state: &UiState) -> Vec<Constraint> {
     match state.get_mode() {
-        Mode::Power | Mode::Search => match state.layout {
-            LayoutStyle::Traditional => vec![
-                Constraint::Ratio(3, 9),
-                Constraint::Ratio(2, 9),
-                Constraint::Ratio(2, 9),
-                Constraint::Length(1),
-                Constraint::Length(8),
-            ],
-            LayoutStyle::Minimal => {
-                vec![
-                    Constraint::Percentage(40),
-                    Constraint::Percentage(30),
-                    Constraint::Percentage(30),
-                ]
+        Mode::Power | Mode::Search | Mode::Loved | Mode::Recent => {
+            let columns = state.nav.columns.iter().map(|c| column_width(*c));
+            match state.layout {
+                // status icon first
+                LayoutStyle::Traditional => std::iter::once(Constraint::Length(1))
+                    .chain(columns)
+                    .collect(),
+                LayoutStyle::Minimal => columns.collect(),
             }
-        },
+        }
         Mode::Library | Mode::Queue => match state.layout {
             LayoutStyle::Traditional => {
                 vec![Constraint::Fill(1), Constraint::Length(DURATION_SPACING)]
@@ -62,6 +59,46 @@ pub(super) fn get_widths(state: &UiState) -> Vec<Constraint> {
     }
 }
 
+fn column_width(column: TableColumn) -> Constraint {
+    match column {
+        TableColumn::Title => Constraint::Fill(3),
+        TableColumn::Artist | TableColumn::Album => Constraint::Fill(2),
+        TableColumn::Track | TableColumn::Year => Constraint::Length(4),
+        TableColumn::Duration => Constraint::Length(8),
+        // heart, space and stars
+        TableColumn::Rating => Constraint::Length(SimpleSong::MAX_RATING as u16 + 2),
+        TableColumn::Plays => Constraint::Length(5),
+    }
+}
+
+/// Column labels, with the sorted one marked by its direction
+fn table_header(state: &UiState, theme: &DisplayTheme) -> Row<'static> {
+    let sort = state.nav.table_sort;
+    let labels = state.nav.columns.iter().map(|c| {
+        let label = match sort.column == *c {
+            true if sort.descending => format!("{} ▼", c.label()),
+            true => format!("{} ▲", c.label()),
+            false => c.label().to_string(),
+        };
+        let cell = Cell::from(label);
+        match sort.column == *c {
+            true => cell.fg(theme.accent),
+            false => cell.fg(theme.text_muted),
+        }
+    });
+
+    Row::new(std::iter::once(Cell::default()).chain(labels)).bottom_margin(HEADER_MARGIN)
+}
+
+/// Whether the table gets a header row, which only a sortable table needs
+pub(super) fn has_header(state: &UiState) -> bool {
+    state.layout == LayoutStyle::Traditional
+        && matches!(
+            state.get_mode(),
+            Mode::Power | Mode::Search | Mode::Loved | Mode::Recent
+        )
+}
+
 pub fn get_keymaps(mode: &Mode, decorator: &str) -> String {
     match mode {
         Mode::Library | Mode::Queue => {
@@ -121,7 +158,12 @@ pub fn create_standard_table<'a>(
         _ => Style::new(),
     };
 
-    Table::new(rows, widths)
+    let table = match has_header(state) {
+        true => Table::new(rows, widths).header(table_header(state, theme)),
+        false => Table::new(rows, widths),
+    };
+
+    table
         .block(block)
         .column_spacing(COLUMN_SPACING)
         .flex(Flex::SpaceBetween)
@@ -151,7 +193,10 @@ fn get_title(state: &UiState, area: Rect) -> Line<'static> {
     let total = state.get_legal_songs().len();
     let third = (area.width / 3) as usize;
 
-    if matches!(mode, Mode::Queue | Mode::Search) {
+    if matches!(
+        mode,
+        Mode::Queue | Mode::Search | Mode::Loved | Mode::Recent
+    ) {
         let count_str = match total {
             1 => "[1 Song] ".to_string(),
             _ => format!("[{total} Songs] "),
@@ -159,6 +204,8 @@ fn get_title(state: &UiState, area: Rect) -> Line<'static> {
         Line::from_iter([
             Span::from(match mode {
                 Mode::Queue => " Queue ",
+                Mode::Loved => " Loved ",
+                Mode::Recent => " Recently Played ",
                 _ => " Total: ",
             })
             .fg(theme.accent),
@@ -5,7 +5,7 @@ use crate::{
     library::{Album, SongInfo},
     theme::{DisplayTheme, ThemeIcons, fade_color},
     tui::widgets::tracklist::{TRAD_ROW_HEIGHT, TRAD_ROW_MARGIN},
-    ui_state::{LayoutStyle, MatchField, Mode, Pane, UiState},
+    ui_state::{LayoutStyle, MatchField, Mode, Pane, TableColumn, UiState},
 };
 use ratatui::{
     style::{Color, Stylize},
@@ -91,6 +91,10 @@ impl CellFactory {
         title_line.push(Span::raw(song.get_title().to_string()).fg(p.primary).bold());
         title_line.push(Span::raw("  "));
         title_line.push(Span::raw(song.filetype.as_str_label()).fg(p.muted));
+        if let Some(badges) = CellFactory::badges(song) {
+            title_line.push(Span::raw("  "));
+            title_line.push(Span::raw(badges).fg(p.accent));
+        }
 
         let number = CellFactory::track_disc_super(ctx, song, idx, ctx.selected_album.is_some());
         let artist_line = Line::from(vec![
@@ -120,6 +124,25 @@ impl CellFactory {
         Cell::from(Self::status_icon(ctx, song).unwrap_or_else(|| "".into()))
     }
 
+    /// Loved heart followed by the star rating, if either is set
+    pub fn badges(song: &Arc<SimpleSong>) -> Option<String> {
+        let heart = match song.is_loved() {
+            true => LOVED,
+            false => "",
+        };
+        let stars = STAR.repeat(song.get_rating() as usize);
+
+        match (heart.is_empty(), stars.is_empty()) {
+            (true, true) => None,
+            (false, false) => Some(format!("{heart} {stars}")),
+            _ => Some(format!("{heart}{stars}")),
+        }
+    }
+
+    pub fn badge_cell(song: &Arc<SimpleSong>) -> Cell<'static> {
+        Cell::from(Self::badges(song).unwrap_or_default())
+    }
+
     pub fn duration_cell(s: &Arc<SimpleSong>, style: DurationStyle) -> Cell<'static> {
         let duration_str = s.get_duration_str(style);
         Cell::from(Text::from(duration_str).right_aligned())
@@ -145,6 +168,9 @@ impl CellFactory {
     }
 }
 
+const STAR: &str = "★";
+const LOVED: &str = "♥";
+
 const SUPERSCRIPT: [&str; 10] = ["⁰", "¹", "²", "³", "⁴", "⁵", "⁶", "⁷", "⁸", "⁹"];
 fn superscript(n: u32, width: usize) -> String {
     format!("{n:0width$}")
@@ -161,7 +187,7 @@ pub(crate) fn build_row(ctx: &RowCtx, song: &Arc<SimpleSong>, idx: usize) -> Row
         &ctx.palette_base
     };
     let row = match (&ctx.mode, ctx.layout) {
-        (Mode::Search | Mode::Power, _) => search(&ctx, song, p),
+        (Mode::Search | Mode::Power | Mode::Loved | Mode::Recent, _) => search(&ctx, song, p),
         (Mode::Library | Mode::Queue, LayoutStyle::Traditional) => {
             standard_tracklist(ctx, song, idx, p)
         }
@@ -203,29 +229,80 @@ fn minimal_tracklist(
     )
     .fg(p.accent);
     let symbol = CellFactory::status_cell(&ctx, &s).fg(p.secondary);
-    let title = Cell::from(s.get_title().to_string()).fg(p.primary);
+    let mut title = vec![Span::raw(s.get_title().to_string()).fg(p.primary)];
+    if let Some(badges) = CellFactory::badges(s) {
+        title.push(Span::raw(format!("  {badges}")).fg(p.accent));
+    }
+    let title = Cell::from(Line::from(title));
     let duration = CellFactory::duration_cell(&s, DurationStyle::Clean).fg(p.muted);
 
     Row::new([idx, title, symbol, duration])
 }
 
-fn search(ctx: &RowCtx, s: &Arc<SimpleSong>, p: &RowPalette) -> Row<'static> {
-    let symbol = CellFactory::status_cell(&ctx, &s);
-    let mut title_col = Cell::from(s.get_title().to_string()).fg(p.muted);
-    let mut artist_col = Cell::from(s.get_artist().to_string()).fg(p.muted);
-    let mut album_col = Cell::from(s.get_album().to_string()).fg(p.muted);
-    let dur_col = CellFactory::duration_cell(&s, DurationStyle::Clean).fg(p.muted);
-
-    if let Some(field) = ctx.state.get_match_fields(s.id) {
-        match field {
-            MatchField::Title => title_col = title_col.fg(p.secondary),
-            MatchField::Artist => artist_col = artist_col.fg(p.secondary),
-            MatchField::Album => album_col = album_col.fg(p.secondary),
+/// Matched characters are bolded as well as colored, so they still stand
+/// out on the selected row where the palette is a single color
+fn highlight_matches(text: &str, indices: &[usize], p: &RowPalette) -> Cell<'static> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_hit = false;
+
+    for (i, c) in text.chars().enumerate() {
+        let hit = indices.binary_search(&i).is_ok();
+        if hit != run_hit && !run.is_empty() {
+            spans.push(match_span(std::mem::take(&mut run), run_hit, p));
         }
+        run_hit = hit;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(match_span(run, run_hit, p));
     }
 
+    Cell::from(Line::from(spans))
+}
+
+fn match_span(text: String, hit: bool, p: &RowPalette) -> Span<'static> {
+    match hit {
+        true => Span::from(text).fg(p.accent).bold(),
+        false => Span::from(text).fg(p.secondary),
+    }
+}
+
+fn search(ctx: &RowCtx, s: &Arc<SimpleSong>, p: &RowPalette) -> Row<'static> {
+    let found = ctx.state.get_search_match(s.id);
+    let text_col = |field: MatchField, text: &str| match found {
+        Some(m) if m.field == field => highlight_matches(text, &m.indices, p),
+        _ => Cell::from(text.to_string()).fg(p.muted),
+    };
+
+    let columns = ctx.state.nav.columns.iter().map(|column| match column {
+        TableColumn::Track => {
+            let track = s.track_no.map_or(String::new(), |t| t.to_string());
+            Cell::from(Line::from(track).right_aligned()).fg(p.muted)
+        }
+        TableColumn::Title => text_col(MatchField::Title, s.get_title()),
+        TableColumn::Artist => text_col(MatchField::Artist, s.get_artist()),
+        TableColumn::Album => text_col(MatchField::Album, s.get_album()),
+        TableColumn::Year => {
+            let year = s
+                .year
+                .filter(|y| *y != 0)
+                .map_or(String::new(), |y| y.to_string());
+            Cell::from(year).fg(p.muted)
+        }
+        TableColumn::Duration => CellFactory::duration_cell(s, DurationStyle::Clean).fg(p.muted),
+        TableColumn::Rating => CellFactory::badge_cell(s).fg(p.accent),
+        TableColumn::Plays => {
+            let plays = ctx.state.play_counts.get(&s.id).copied().unwrap_or(0);
+            Cell::from(Line::from(plays.to_string()).right_aligned()).fg(p.muted)
+        }
+    });
+
     match ctx.layout {
-        LayoutStyle::Traditional => Row::new([title_col, artist_col, album_col, symbol, dur_col]),
-        LayoutStyle::Minimal => Row::new([title_col, artist_col, album_col]),
+        LayoutStyle::Traditional => {
+            let symbol = CellFactory::status_cell(ctx, s);
+            Row::new(std::iter::once(symbol).chain(columns))
+        }
+        LayoutStyle::Minimal => Row::new(columns),
     }
 }
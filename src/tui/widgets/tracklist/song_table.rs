@@ -1,12 +1,19 @@
 use crate::{
+    gen_config,
     tui::widgets::tracklist::{
-        TRAD_ROW_STRIDE, create_empty_block, create_standard_table, get_padding,
+        HEADER_HEIGHT, TRAD_ROW_MARGIN, TRAD_ROW_STRIDE, create_empty_block, create_standard_table,
+        get_padding, has_header,
         row::{RowCtx, build_row},
         scroll_offset,
     },
-    ui_state::{LayoutStyle, Mode, Pane, UiState},
+    ui_state::{LayoutStyle, ListArea, Mode, Pane, UiState},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    widgets::{Block, Row, StatefulWidget, TableState, Widget},
 };
-use ratatui::widgets::{Row, StatefulWidget, TableState, Widget};
 
 pub struct SongTable;
 impl StatefulWidget for SongTable {
@@ -29,6 +36,7 @@ impl StatefulWidget for SongTable {
         let total = songs.len();
         let padding = get_padding(state, theme, area);
         let borders = if theme.has_borders() { 2 } else { 0 };
+        let header = if has_header(state) { HEADER_HEIGHT } else { 0 };
         let row_height = match (ctx.layout, &ctx.mode) {
             (LayoutStyle::Traditional, Mode::Library | Mode::Queue) => TRAD_ROW_STRIDE,
             _ => 1,
@@ -36,7 +44,7 @@ impl StatefulWidget for SongTable {
 
         let capacity = (area
             .height
-            .saturating_sub(borders + padding.top + padding.bottom)
+            .saturating_sub(borders + header + padding.top + padding.bottom)
             .max(1) as usize)
             .div_ceil(row_height)
             .max(1);
@@ -44,6 +52,11 @@ impl StatefulWidget for SongTable {
         let offset = scroll_offset(total, capacity, sel, state.nav.table_pos.offset());
         let end = (offset + capacity).min(total);
 
+        let inner = Block::bordered()
+            .borders(theme.border_display)
+            .padding(padding)
+            .inner(area);
+
         let rows: Vec<Row> = songs[offset..end]
             .iter()
             .enumerate()
@@ -55,5 +68,65 @@ impl StatefulWidget for SongTable {
         *state.nav.table_pos.offset_mut() = offset;
         let mut local = TableState::default().with_selected(Some(sel.saturating_sub(offset)));
         StatefulWidget::render(table, area, buf, &mut local);
+
+        state.mouse.tracklist = Some(ListArea {
+            rows: Rect {
+                y: inner.y + header,
+                height: inner.height.saturating_sub(header),
+                ..inner
+            },
+            stride: row_height as u16,
+            offset,
+            len: total,
+        });
+
+        if gen_config().disc_separators {
+            render_disc_separators(state, area, buf, offset, end);
+        }
+    }
+}
+
+/// Labels each disc of a multi-disc album in the blank line above its first
+/// track, which the traditional layout leaves between rows anyway
+fn render_disc_separators(
+    state: &UiState,
+    area: Rect,
+    buf: &mut Buffer,
+    offset: usize,
+    end: usize,
+) {
+    let songs = state.get_legal_songs();
+    if state.layout != LayoutStyle::Traditional
+        || *state.get_mode() != Mode::Library
+        || state.get_selected_album().is_none()
+        || songs.iter().all(|s| s.disc() == 1)
+    {
+        return;
+    }
+
+    let focus = matches!(state.get_pane(), Pane::TrackList);
+    let theme = state.theme.get_display_theme(focus);
+    let inner = Block::bordered()
+        .borders(theme.border_display)
+        .padding(get_padding(state, theme, area))
+        .inner(area);
+
+    for i in offset..end {
+        let disc = songs[i].disc();
+        if i > 0 && songs[i - 1].disc() == disc {
+            continue;
+        }
+
+        // The top padding is always at least a line, so the first row has
+        // room above it too
+        let row_y = inner.y + ((i - offset) as u16) * TRAD_ROW_STRIDE;
+        let Some(y) = row_y.checked_sub(TRAD_ROW_MARGIN).filter(|&y| y >= area.y) else {
+            continue;
+        };
+
+        let label = format!("── Disc {disc} ")
+            .fg(theme.text_muted)
+            .into_left_aligned_line();
+        label.render(Rect::new(inner.x, y, inner.width, 1), buf);
     }
 }
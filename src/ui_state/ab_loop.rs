@@ -0,0 +1,50 @@
+/// Shortest region worth looping, in seconds
+const MIN_LOOP: f64 = 0.25;
+
+/// An A-B repeat region within the current track, in seconds.
+#[derive(Default)]
+pub struct AbLoop {
+    loop_start: Option<f64>,
+    loop_end: Option<f64>,
+}
+
+impl AbLoop {
+    /// First press sets A, second sets B, third clears. A B placed before A
+    /// swaps the two.
+    pub fn mark(&mut self, position: f64) {
+        match (self.loop_start, self.loop_end) {
+            (None, _) => self.loop_start = Some(position),
+            (Some(a), None) => {
+                if (position - a).abs() < MIN_LOOP {
+                    return;
+                }
+                self.loop_start = Some(a.min(position));
+                self.loop_end = Some(a.max(position));
+            }
+            (Some(_), Some(_)) => self.clear(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.loop_start = None;
+        self.loop_end = None;
+    }
+
+    pub fn start(&self) -> Option<f64> {
+        self.loop_start
+    }
+
+    pub fn end(&self) -> Option<f64> {
+        self.loop_end
+    }
+
+    /// Both points, once the loop is complete
+    pub fn region(&self) -> Option<(f64, f64)> {
+        self.loop_start.zip(self.loop_end)
+    }
+
+    pub fn contains(&self, position: f64) -> bool {
+        self.region()
+            .is_some_and(|(a, b)| (a..=b).contains(&position))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    config::AlbumArtMode,
+    gen_config,
+    library::cover_art::{self, CoverArt},
+    tui::{GraphicsProtocol, ImageSlot},
+    ui_state::UiState,
+};
+use ratatui::layout::Rect;
+use std::{
+    io::{Write, stdout},
+    path::Path,
+};
+
+/// Cover art for the playing song, and the picture of the artist being
+/// browsed. Images are written straight to the terminal after each frame,
+/// since ratatui only deals in text cells.
+pub struct AlbumArt {
+    protocol: GraphicsProtocol,
+    song_id: Option<u64>,
+    cover: Option<CoverArt>,
+    /// Where the image belongs this frame, if anywhere
+    pub(crate) area: Option<Rect>,
+    /// What's currently on screen
+    shown: Option<(u64, Rect)>,
+    /// Keyed by the artist's name
+    artist: Option<(u64, CoverArt)>,
+    pub(crate) artist_area: Option<Rect>,
+    artist_shown: Option<(u64, Rect)>,
+}
+
+impl AlbumArt {
+    pub fn new() -> Self {
+        AlbumArt {
+            protocol: GraphicsProtocol::detect(),
+            song_id: None,
+            cover: None,
+            area: None,
+            shown: None,
+            artist: None,
+            artist_area: None,
+            artist_shown: None,
+        }
+    }
+
+    /// Whether the layout should make room for the pane at all
+    pub fn is_enabled(&self) -> bool {
+        match gen_config().album_art {
+            AlbumArtMode::Auto => self.protocol != GraphicsProtocol::None,
+            AlbumArtMode::On => true,
+            AlbumArtMode::Off => false,
+        }
+    }
+
+    /// The cover, provided the terminal can draw it
+    pub fn drawable(&self) -> Option<&CoverArt> {
+        self.cover
+            .as_ref()
+            .filter(|c| self.protocol.supports(c.format))
+    }
+
+    /// The artist's picture, on the same terms as the cover
+    pub fn artist_drawable(&self) -> Option<&CoverArt> {
+        self.artist
+            .as_ref()
+            .map(|(_, image)| image)
+            .filter(|image| self.is_enabled() && self.protocol.supports(image.format))
+    }
+
+    pub(crate) fn set_artist(&mut self, artist: Option<(u64, CoverArt)>) {
+        self.artist = artist;
+    }
+
+    fn target(&self) -> Option<(u64, Rect)> {
+        self.drawable()?;
+        Some((self.song_id?, self.area?))
+    }
+
+    fn artist_target(&self) -> Option<(u64, Rect)> {
+        self.artist_drawable()?;
+        Some((self.artist.as_ref()?.0, self.artist_area?))
+    }
+
+    /// iTerm2 images are overwritten by whatever is drawn in their cells,
+    /// but ratatui won't redraw cells it thinks are unchanged
+    pub fn needs_repaint(&self) -> bool {
+        let moved = |shown: Option<(u64, Rect)>, target| shown.is_some() && shown != target;
+        self.protocol == GraphicsProtocol::Iterm2
+            && (moved(self.shown, self.target()) || moved(self.artist_shown, self.artist_target()))
+    }
+
+    /// Sends the images, or takes them down, when they no longer match the
+    /// last frame
+    pub fn flush(&mut self) {
+        let target = self.target();
+        let artist_target = self.artist_target();
+        if target == self.shown && artist_target == self.artist_shown {
+            return;
+        }
+
+        // A repaint takes down every iTerm2 image, not just the one that moved
+        let all = self.protocol == GraphicsProtocol::Iterm2;
+        let mut out = String::new();
+        if all || target != self.shown {
+            out.push_str(&self.redraw(self.shown, target, self.drawable(), ImageSlot::Cover));
+        }
+        if all || artist_target != self.artist_shown {
+            out.push_str(&self.redraw(
+                self.artist_shown,
+                artist_target,
+                self.artist_drawable(),
+                ImageSlot::Artist,
+            ));
+        }
+
+        let mut stdout = stdout();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+
+        self.shown = target;
+        self.artist_shown = artist_target;
+    }
+
+    fn redraw(
+        &self,
+        shown: Option<(u64, Rect)>,
+        target: Option<(u64, Rect)>,
+        image: Option<&CoverArt>,
+        slot: ImageSlot,
+    ) -> String {
+        let mut out = String::new();
+        if shown.is_some() {
+            out.push_str(&self.protocol.clear(slot));
+        }
+        if let (Some((_, area)), Some(image)) = (target, image) {
+            out.push_str(&self.protocol.draw(image, area, slot));
+        }
+        out
+    }
+}
+
+impl Default for AlbumArt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UiState {
+    /// Loads the cover when the playing song changes. Songs sharing an
+    /// album read it from the same cache file.
+    pub(crate) fn sync_album_art(&mut self) {
+        let song = self.get_now_playing().cloned();
+        let id = song.as_ref().map(|s| s.id);
+        if id == self.art.song_id || !self.art.is_enabled() {
+            return;
+        }
+
+        self.art.song_id = id;
+        self.art.cover = song.and_then(|song| {
+            let path = self.db_worker.get_song_path(song.id).ok()?;
+            let album_artist = match self.library.albums.get(&song.album_id) {
+                Some(album) => album.get_album_artist(),
+                None => song.artist.as_str(),
+            };
+            cover_art::load_cover(Path::new(&path), album_artist, &song.album)
+        });
+    }
+}
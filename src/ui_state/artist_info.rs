@@ -0,0 +1,88 @@
+use crate::{
+    library::artist_info::{self, ArtistInfo},
+    ui_state::{LayoutStyle, Mode, UiState, domain::RowKind},
+};
+use anyhow::{Result, bail};
+use std::{path::PathBuf, sync::Arc};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// The artist pane, and what was found on disk for the artist being
+/// browsed while it's open
+#[derive(Default)]
+pub struct ArtistState {
+    pub(crate) visible: bool,
+    artist: Option<Arc<String>>,
+    notes: Option<String>,
+}
+
+impl ArtistState {
+    /// The artist shown, none when nothing selected belongs to one
+    pub fn artist(&self) -> Option<&str> {
+        self.artist.as_deref().map(String::as_str)
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// Whether the layout should make room for the pane
+    pub fn is_shown(&self) -> bool {
+        self.visible && self.artist.is_some()
+    }
+}
+
+impl UiState {
+    pub fn toggle_artist_pane(&mut self) -> Result<()> {
+        self.artist_pane.visible = !self.artist_pane.visible;
+        if self.artist_pane.visible && self.layout == LayoutStyle::Minimal {
+            bail!("The artist pane shows in the traditional layout, switch with <m>");
+        }
+        Ok(())
+    }
+
+    /// The artist selected in the library sidebar, or the one whose album is
+    fn browsed_artist(&self) -> Option<Arc<String>> {
+        if self.get_mode() != Mode::Library {
+            return None;
+        }
+        match &self.selected_row()?.kind {
+            RowKind::Artist { name, .. } => Some(Arc::clone(name)),
+            RowKind::Album(id) => Some(Arc::clone(&self.library.albums.get(id)?.artist)),
+            _ => None,
+        }
+    }
+
+    /// Reads the sidecar files when the browsed artist changes, only while
+    /// the pane is open to show them
+    pub(crate) fn sync_artist_info(&mut self) {
+        let artist = match self.artist_pane.visible {
+            true => self.browsed_artist(),
+            false => None,
+        };
+        if artist == self.artist_pane.artist {
+            return;
+        }
+
+        // A song from each album, as the albums may sit apart on disk
+        let info = match &artist {
+            Some(name) => {
+                let paths = self
+                    .library
+                    .albums
+                    .values()
+                    .filter(|album| album.artist == *name)
+                    .filter_map(|album| album.tracklist.first())
+                    .filter_map(|song| self.db_worker.get_song_path(song.id).ok())
+                    .map(PathBuf::from)
+                    .collect::<Vec<_>>();
+                artist_info::load_artist_info(&paths)
+            }
+            None => ArtistInfo::default(),
+        };
+
+        let key = artist.as_ref().map(|name| xxh3_64(name.as_bytes()));
+        self.art.set_artist(key.zip(info.image));
+        self.artist_pane.notes = info.notes;
+        self.artist_pane.artist = artist;
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{
+    app_core::NoctaVox,
+    database::PlaylistItem,
+    key_handler::Action,
+    ui_state::{Mode, PopupType, UiState},
+};
+use anyhow::{Result, bail};
+
+/// Also the order they're offered in when completing
+const COMMANDS: &[&str] = &[
+    "help", "next", "playlist", "prev", "q", "quit", "scan", "seek", "stop", "theme", "undo",
+];
+const PLAYLIST_COMMANDS: &[&str] = &["add", "new", "open"];
+
+/// Shown above the command line: why the last command didn't run, or what
+/// a completion could have meant
+#[derive(PartialEq, Clone)]
+pub enum CommandHint {
+    Error(String),
+    Options(Vec<String>),
+}
+
+/// A line typed at the `:` prompt, with its arguments
+enum Command {
+    Run(Action),
+    Seek(String),
+    Theme(String),
+    NewPlaylist(String),
+    AddToPlaylist(String),
+    OpenPlaylist(String),
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Command> {
+        let (name, args) = split_word(line);
+        let command = match name {
+            "q" | "quit" => Command::Run(Action::QUIT),
+            "scan" => Command::Run(Action::UpdateLibrary),
+            "stop" => Command::Run(Action::Stop),
+            "next" => Command::Run(Action::PlayNext),
+            "prev" => Command::Run(Action::PlayPrev),
+            "undo" => Command::Run(Action::Undo),
+            "help" => Command::Run(Action::ShowKeymaps),
+            "seek" => Command::Seek(required(args, "seek 1:30")?),
+            "theme" => Command::Theme(required(args, "theme <name>")?),
+            "playlist" => {
+                let (action, name) = split_word(args);
+                match action {
+                    "new" => Command::NewPlaylist(required(name, "playlist new <name>")?),
+                    "add" => Command::AddToPlaylist(required(name, "playlist add <name>")?),
+                    "open" => Command::OpenPlaylist(required(name, "playlist open <name>")?),
+                    "" => bail!("Usage: playlist new|add|open <name>"),
+                    other => bail!("Unknown playlist command: {other}"),
+                }
+            }
+            other => bail!("Unknown command: {other}"),
+        };
+        Ok(command)
+    }
+}
+
+/// The first word, and everything after it
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (s, ""),
+    }
+}
+
+fn required(arg: &str, usage: &str) -> Result<String> {
+    match arg.is_empty() {
+        true => bail!("Usage: {usage}"),
+        false => Ok(arg.to_string()),
+    }
+}
+
+/// An exact match, ignoring case, or else the only one starting with `name`
+fn find_named<'a, T>(
+    items: impl Iterator<Item = (&'a str, T)>,
+    name: &str,
+    kind: &str,
+) -> Result<T> {
+    let wanted = name.to_lowercase();
+    let mut starts_with = Vec::new();
+    for (item, value) in items {
+        let item = item.to_lowercase();
+        if item == wanted {
+            return Ok(value);
+        }
+        if item.starts_with(&wanted) {
+            starts_with.push(value);
+        }
+    }
+
+    match starts_with.len() {
+        1 => Ok(starts_with.remove(0)),
+        0 => bail!("No {kind} named {name}"),
+        _ => bail!("More than one {kind} starts with {name}"),
+    }
+}
+
+impl UiState {
+    pub fn open_command_line(&mut self) {
+        self.show_popup(PopupType::Command(None));
+    }
+
+    /// Completes the word being typed as far as the options agree, listing
+    /// them when more than one fits
+    pub fn complete_command(&mut self) {
+        let line = self.popup.input.lines()[0].trim_start().to_string();
+        let (name, args) = split_word(&line);
+
+        let (typed, arg, options) = match (name, args) {
+            (_, "") if !line.ends_with(char::is_whitespace) => ("", name, strings(COMMANDS)),
+            ("theme", arg) => (
+                "theme ",
+                arg,
+                self.theme
+                    .theme_lib
+                    .iter()
+                    .map(|t| t.name.clone())
+                    .collect(),
+            ),
+            ("playlist", args) => match split_word(args) {
+                (action, "") if !line.ends_with(char::is_whitespace) || args.is_empty() => {
+                    ("playlist ", action, strings(PLAYLIST_COMMANDS))
+                }
+                (action @ ("add" | "open"), name) => {
+                    let names = match action {
+                        "add" => self.manual_playlists().map(|p| p.name.clone()).collect(),
+                        _ => self.playlists.values().map(|p| p.name.clone()).collect(),
+                    };
+                    let typed = match action {
+                        "add" => "playlist add ",
+                        _ => "playlist open ",
+                    };
+                    (typed, name, names)
+                }
+                _ => return,
+            },
+            _ => return,
+        };
+
+        let fits = options
+            .into_iter()
+            .filter(|o| o.to_lowercase().starts_with(&arg.to_lowercase()))
+            .collect::<Vec<_>>();
+
+        let completed = match fits.as_slice() {
+            [] => {
+                self.popup.current =
+                    PopupType::Command(Some(CommandHint::Error("Nothing to complete".to_string())));
+                return;
+            }
+            // Commands and sub-commands are followed by something
+            [only] if typed.is_empty() || typed == "playlist " => format!("{typed}{only} "),
+            [only] => format!("{typed}{only}"),
+            _ => format!("{typed}{}", common_prefix(&fits)),
+        };
+
+        let hint = (fits.len() > 1).then_some(CommandHint::Options(fits));
+        self.popup.current = PopupType::Command(hint);
+        self.popup.input.clear();
+        self.popup.input.insert_str(completed);
+    }
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// How much the options share from the start, ignoring case, as spelt in the
+/// first of them
+fn common_prefix(options: &[String]) -> String {
+    let first = &options[0];
+    let len = options[1..].iter().fold(first.len(), |len, option| {
+        first
+            .char_indices()
+            .zip(option.chars())
+            .take_while(|((i, a), b)| *i < len && a.to_lowercase().eq(b.to_lowercase()))
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+    });
+    first[..len].to_string()
+}
+
+impl NoctaVox {
+    /// Runs the typed command. If it can't be, the line stays open with the
+    /// reason.
+    pub(crate) fn confirm_command(&mut self) -> Result<()> {
+        let line = self.ui.get_popup_string();
+        if line.is_empty() {
+            self.ui.close_popup();
+            return Ok(());
+        }
+
+        let result = Command::parse(&line).and_then(|command| self.run_command(command));
+        match result {
+            Err(e) if matches!(self.ui.popup.current, PopupType::Command(_)) => {
+                self.ui.popup.current = PopupType::Command(Some(CommandHint::Error(e.to_string())));
+                Ok(())
+            }
+            // Anything going wrong once the line is closed is shown as usual
+            result => result,
+        }
+    }
+
+    /// The line is closed once the command is known to make sense, so that
+    /// whatever it opens isn't closed along with it
+    fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Run(action) => {
+                self.ui.close_popup();
+                self.handle_action(action)?;
+            }
+            Command::Seek(time) => {
+                self.seek_to_input(&time)?;
+                self.ui.close_popup();
+            }
+            Command::Theme(name) => {
+                let themes = self.ui.theme.theme_lib.iter().map(|t| (t.name.as_str(), t));
+                let theme = find_named(themes, &name, "theme")?.clone();
+                self.ui.close_popup();
+                self.ui.theme.manual = true;
+                self.ui.set_theme(theme);
+            }
+            // Closes the line itself, once the name turns out to be free
+            Command::NewPlaylist(name) => self.ui.create_named_playlist(name)?,
+            Command::AddToPlaylist(name) => {
+                if self.ui.legal_songs.is_empty() {
+                    bail!("No songs to add");
+                }
+                let playlists = self.ui.manual_playlists().map(|p| (p.name.as_str(), p.id));
+                let id = find_named(playlists, &name, "playlist")?;
+                self.ui.close_popup();
+                self.ui.add_selection_to_playlist(id)?;
+            }
+            Command::OpenPlaylist(name) => {
+                let playlists = self
+                    .ui
+                    .playlists
+                    .values()
+                    .map(|p| (p.name.as_str(), (p.id, p.folder)));
+                let (id, folder) = find_named(playlists, &name, "playlist")?;
+                self.ui.close_popup();
+                self.ui.set_mode(Mode::Library);
+                self.ui
+                    .reveal_playlist_item(PlaylistItem::Playlist(id), folder)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_keep_their_spaces() {
+        let Ok(Command::NewPlaylist(name)) = Command::parse("playlist new  Road Trip ") else {
+            panic!("not parsed as a new playlist");
+        };
+        assert_eq!(name, "Road Trip");
+
+        assert!(Command::parse("playlist").is_err());
+        assert!(Command::parse("seek").is_err());
+        assert!(Command::parse("dance").is_err());
+    }
+
+    #[test]
+    fn completes_as_far_as_options_agree() {
+        let options = strings(&["Late Night", "late nights", "Later"]);
+        assert_eq!(common_prefix(&options), "Late");
+        assert_eq!(common_prefix(&options[..2]), "Late Night");
+    }
+}
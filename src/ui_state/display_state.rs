@@ -1,26 +1,35 @@
-use super::{AlbumSort, Mode, Pane, TableSort, UiState};
+use super::{AlbumSort, Mode, Pane, TableColumn, TableSort, UiState};
 use crate::{
     key_handler::{Director, Incrementor},
     library::{Album, Playlist, SimpleSong, SongInfo},
-    ui_state::{NodeKey, PopupType, Root, Sidebar, domain::RowKind},
+    strip_diacritics,
+    ui_state::{NodeKey, PlaylistAction, PopupType, Root, Sidebar, domain::RowKind},
+    visualization::LookSetting,
 };
 use anyhow::{Context, Result, anyhow, bail};
 use indexmap::IndexSet;
 use ratatui::widgets::TableState;
 use std::{sync::Arc, time::Duration};
 
+/// Tracks shown in the recently played view
+const RECENT_LIMIT: u16 = 100;
+
 pub struct DisplayState {
     mode: Mode,
     mode_cached: Option<Mode>,
     pane: Pane,
 
-    table_sort: TableSort,
+    pub table_sort: TableSort,
+    /// Shown in the sortable views, in `TableColumn::ALL` order
+    pub columns: Vec<TableColumn>,
     pub table_pos: TableState,
     table_pos_cached: usize,
 
     pub sidebar: Sidebar,
 
     pub multi_select: IndexSet<usize>,
+    /// Where a shift-select range started, and where it last ended
+    pub(super) select_range: Option<(usize, usize)>,
 }
 
 impl DisplayState {
@@ -30,7 +39,8 @@ impl DisplayState {
             mode_cached: None,
             pane: Pane::TrackList,
 
-            table_sort: TableSort::Title,
+            table_sort: TableSort::default(),
+            columns: TableColumn::DEFAULT.to_vec(),
 
             sidebar: Sidebar::new(),
 
@@ -38,6 +48,7 @@ impl DisplayState {
             table_pos_cached: 0,
 
             multi_select: IndexSet::default(),
+            select_range: None,
         }
     }
 
@@ -70,7 +81,10 @@ impl UiState {
     }
 
     pub fn set_mode(&mut self, mode: Mode) {
-        self.clear_multi_select();
+        // Fullscreen only covers the view, which is left as it is beneath
+        if mode != Mode::Fullscreen {
+            self.clear_multi_select();
+        }
         if let Mode::Power = self.nav.mode {
             self.nav.table_pos_cached = self
                 .nav
@@ -83,7 +97,6 @@ impl UiState {
             Mode::Power => {
                 self.nav.pane = Pane::TrackList;
                 self.nav.mode = Mode::Power;
-                self.nav.table_sort = TableSort::Title;
                 self.set_legal_songs();
                 self.nav.table_pos.select(Some(self.nav.table_pos_cached));
             }
@@ -110,8 +123,29 @@ impl UiState {
                     self.set_error(anyhow!("Queue is empty!"));
                 }
             }
+            Mode::Loved => {
+                if self.library.get_songs_map().values().any(|s| s.is_loved()) {
+                    *self.nav.table_pos.offset_mut() = 0;
+                    self.nav.table_pos.select(Some(0));
+                    self.nav.mode = Mode::Loved;
+                    self.nav.pane = Pane::TrackList;
+                    self.set_legal_songs()
+                } else {
+                    self.set_error(anyhow!("No loved tracks yet!"));
+                }
+            }
+            Mode::Recent => {
+                if self.recently_played().is_empty() {
+                    self.set_error(anyhow!("Nothing played yet!"));
+                    return;
+                }
+                *self.nav.table_pos.offset_mut() = 0;
+                self.nav.table_pos.select(Some(0));
+                self.nav.mode = Mode::Recent;
+                self.nav.pane = Pane::TrackList;
+                self.set_legal_songs()
+            }
             Mode::Search => {
-                self.nav.table_sort = TableSort::Title;
                 self.search.input.clear();
                 self.nav.mode = Mode::Search;
                 self.nav.pane = Pane::Search;
@@ -130,7 +164,12 @@ impl UiState {
         }
 
         match self.nav.mode {
-            Mode::Power | Mode::Library | Mode::Search | Mode::Queue => {
+            Mode::Power
+            | Mode::Library
+            | Mode::Search
+            | Mode::Queue
+            | Mode::Loved
+            | Mode::Recent => {
                 let idx = self.nav.get_table_idx()?;
 
                 self.legal_songs
@@ -152,6 +191,20 @@ impl UiState {
         }
     }
 
+    /// Every track of the selected song's album, or of the album row
+    /// selected in the sidebar, in disc and track order
+    pub fn album_tracks(&mut self) -> Result<Vec<Arc<SimpleSong>>> {
+        let album = match self.get_pane() {
+            Pane::SideBar => self.get_selected_album(),
+            _ => {
+                let album_id = self.get_selected_song()?.album_id;
+                self.library.albums.get(&album_id)
+            }
+        };
+
+        Ok(album.context("No album selected")?.tracklist.to_vec())
+    }
+
     pub fn get_selected_playlist(&self) -> Option<&Playlist> {
         match &self.selected_row()?.kind {
             RowKind::Playlist(id) => self.playlists.get(id),
@@ -200,32 +253,95 @@ impl UiState {
         self.rebuild_rows();
     }
 
+    /// Fuzzy results stay ranked by score; regex results have no ranking
+    /// to lose
     pub(crate) fn next_song_column(&mut self) {
-        if self.search.len() < 1 {
-            self.nav.table_sort = self.nav.table_sort.next();
+        self.step_sort_column(true);
+    }
+
+    pub(crate) fn prev_song_column(&mut self) {
+        self.step_sort_column(false);
+    }
+
+    fn step_sort_column(&mut self, forward: bool) {
+        if self.search.len() < 1 || self.search.is_regex() {
+            self.nav.table_sort = self.nav.table_sort.step(&self.nav.columns, forward);
             self.set_legal_songs();
         }
     }
 
-    pub(crate) fn prev_song_column(&mut self) {
-        if self.search.len() < 1 {
-            self.nav.table_sort = self.nav.table_sort.prev();
+    pub(crate) fn reverse_sort(&mut self) {
+        if self.search.len() < 1 || self.search.is_regex() {
+            self.nav.table_sort = self.nav.table_sort.reversed();
             self.set_legal_songs();
         }
     }
 
-    fn sort_by_table_column(&mut self) {
-        match self.nav.table_sort {
-            TableSort::Title => {
-                self.legal_songs.sort_by(|a, b| a.title.cmp(&b.title));
+    pub(crate) fn open_column_picker(&mut self) {
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::Columns);
+    }
+
+    pub(crate) fn toggle_selected_column(&mut self) {
+        let idx = self.popup.selection.selected().unwrap_or(0);
+        if let Some(&column) = TableColumn::ALL.get(idx) {
+            self.toggle_column(column);
+        }
+    }
+
+    pub(crate) fn open_viz_settings(&mut self) {
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::VizSettings);
+    }
+
+    pub(crate) fn shift_viz_setting(&mut self, direction: Incrementor) {
+        let idx = self.popup.selection.selected().unwrap_or(0);
+        if let Some(&setting) = LookSetting::ALL.get(idx) {
+            self.viz.spectrum_look_mut().shift(setting, direction);
+        }
+    }
+
+    /// The last visible column can't be hidden
+    fn toggle_column(&mut self, column: TableColumn) {
+        let columns = &mut self.nav.columns;
+        match columns.iter().position(|&c| c == column) {
+            Some(_) if columns.len() == 1 => return,
+            Some(i) => {
+                columns.remove(i);
+            }
+            None => {
+                columns.push(column);
+                columns.sort_by_key(|c| TableColumn::ALL.iter().position(|a| a == c));
+            }
+        }
+        self.set_legal_songs();
+    }
+
+    /// Stable both ways, so songs that tie keep the order they came in
+    pub(super) fn sort_by_table_column(&mut self) {
+        fn sort<K: Ord>(songs: &mut [Arc<SimpleSong>], desc: bool, key: impl Fn(&SimpleSong) -> K) {
+            match desc {
+                true => songs.sort_by_cached_key(|s| std::cmp::Reverse(key(s))),
+                false => songs.sort_by_cached_key(|s| key(s)),
+            }
+        }
+
+        let TableSort { column, descending } = self.nav.table_sort;
+        let songs = &mut self.legal_songs;
+        match column {
+            TableColumn::Track => sort(songs, descending, |s| s.album_order()),
+            TableColumn::Title => sort(songs, descending, |s| s.title.clone()),
+            TableColumn::Artist => sort(songs, descending, |s| s.get_artist().to_lowercase()),
+            TableColumn::Album => sort(songs, descending, |s| s.get_album().to_lowercase()),
+            TableColumn::Year => sort(songs, descending, |s| s.year),
+            TableColumn::Duration => sort(songs, descending, |s| s.get_duration()),
+            TableColumn::Rating => sort(songs, descending, |s| s.get_rating()),
+            TableColumn::Plays => {
+                let plays = &self.play_counts;
+                sort(songs, descending, |s| {
+                    plays.get(&s.id).copied().unwrap_or(0)
+                })
             }
-            TableSort::Artist => self
-                .legal_songs
-                .sort_by_cached_key(|s| s.get_artist().to_lowercase()),
-            TableSort::Album => self
-                .legal_songs
-                .sort_by_cached_key(|s| s.get_album().to_lowercase()),
-            TableSort::Duration => self.legal_songs.sort_by_key(|s| s.get_duration()),
         };
     }
 
@@ -289,13 +405,9 @@ impl UiState {
     }
 
     pub(crate) fn go_to(&mut self, root: Root) {
-        let not = match root {
-            Root::Library => Root::Playlist,
-            Root::Playlist => Root::Library,
-        };
-
-        self.nav.sidebar.collapsed.insert(NodeKey::Root(not));
-        self.nav.sidebar.collapsed.remove(&NodeKey::Root(root));
+        for other in [Root::Library, Root::Playlist, Root::Genre, Root::Folder] {
+            self.set_collapsed(NodeKey::Root(other), other != root);
+        }
         self.nav.sidebar.prev_folds.clear();
         self.rebuild_rows();
         if let Some(row) = self.selected_row()
@@ -305,6 +417,16 @@ impl UiState {
         }
     }
 
+    /// Back-to-back plays of the same track show up once
+    pub(super) fn recently_played(&self) -> Vec<Arc<SimpleSong>> {
+        self.db_worker
+            .get_recently_played(RECENT_LIMIT)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| self.library.get_song_by_id(id).cloned())
+            .collect()
+    }
+
     pub fn get_legal_songs(&self) -> &[Arc<SimpleSong>] {
         self.legal_songs.as_slice()
     }
@@ -314,6 +436,15 @@ impl UiState {
     }
 
     pub(crate) fn set_legal_songs(&mut self) {
+        let sortable = matches!(
+            self.nav.mode,
+            Mode::Power | Mode::Search | Mode::Loved | Mode::Recent
+        );
+        let plays = TableColumn::Plays;
+        if sortable && (self.nav.columns.contains(&plays) || self.nav.table_sort.column == plays) {
+            self.play_counts = self.db_worker.get_play_counts().unwrap_or_default();
+        }
+
         match &self.nav.mode {
             Mode::Power => {
                 self.legal_songs = self.library.get_all_songs().to_vec();
@@ -326,6 +457,18 @@ impl UiState {
                 }
             }
             Mode::Queue => self.legal_songs = self.playback.get_queue(),
+            Mode::Loved => {
+                self.legal_songs = self
+                    .library
+                    .get_songs_map()
+                    .values()
+                    .filter(|s| s.is_loved())
+                    .cloned()
+                    .collect();
+                self.sort_by_table_column();
+            }
+            // Kept in the order they were played, newest first
+            Mode::Recent => self.legal_songs = self.recently_played(),
 
             Mode::Search => match self.search.len() > 1 {
                 true => self.filter_songs_by_search(),
@@ -349,12 +492,15 @@ impl UiState {
         }
     }
 
+    /// Back to the view fullscreen was opened from, just as it was left.
+    /// Going through `set_mode` would reset its pane and scroll, so only
+    /// the songs are brought up to date, the queue having moved on say.
     pub fn revert_fullscreen(&mut self) {
         if matches!(self.get_mode(), Mode::Fullscreen)
-            && let Some(mode) = &self.nav.mode_cached
+            && let Some(mode) = self.nav.mode_cached.take()
         {
-            self.set_mode(mode.to_owned());
-            self.nav.mode_cached = None;
+            self.nav.mode = mode;
+            self.set_legal_songs();
         }
     }
 }
@@ -372,6 +518,64 @@ impl UiState {
         }
     }
 
+    /// Moves to the first row, in display order, whose sort key starts with
+    /// `prefix`. Case and accents are ignored; nothing moves without a match.
+    pub fn jump_to(&mut self, prefix: &str) {
+        let prefix = strip_diacritics(prefix);
+        let hit = |key: &str| strip_diacritics(key).starts_with(&prefix);
+
+        match self.nav.pane {
+            Pane::SideBar => {
+                let pos = self
+                    .nav
+                    .sidebar
+                    .rows
+                    .iter()
+                    .position(|row| self.row_jump_key(&row.kind).is_some_and(|k| hit(&k)));
+
+                if let Some(idx) = pos {
+                    self.nav.sidebar.pos.select(Some(idx));
+                    self.clear_multi_select();
+                    *self.nav.table_pos.offset_mut() = 0;
+                    self.set_legal_songs();
+                }
+            }
+            Pane::TrackList => {
+                let sorted = matches!(self.nav.mode, Mode::Power | Mode::Search | Mode::Loved);
+                let pos = self.legal_songs.iter().position(|s| {
+                    hit(match (sorted, self.nav.table_sort.column) {
+                        (true, TableColumn::Artist) => s.get_artist(),
+                        (true, TableColumn::Album) => s.get_album(),
+                        _ => s.get_title(),
+                    })
+                });
+
+                if let Some(idx) = pos {
+                    self.nav.table_pos.select(Some(idx));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn row_jump_key(&self, kind: &RowKind) -> Option<String> {
+        match kind {
+            RowKind::Category(root) => Some(root.label().to_string()),
+            RowKind::Artist { name, .. } => Some(name.to_string()),
+            RowKind::Album(id) => {
+                let album = self.library.albums.get(id)?;
+                match (self.nav.sidebar.album_sort, album.year) {
+                    (AlbumSort::Year, Some(year)) => Some(year.to_string()),
+                    _ => Some(album.title.to_string()),
+                }
+            }
+            RowKind::Playlist(id) => self.playlists.get(id).map(|p| p.name.clone()),
+            RowKind::PlaylistFolder(id) => self.playlist_folders.get(id).map(|f| f.name.clone()),
+            RowKind::Genre(name) => Some(name.to_string()),
+            RowKind::Folder { label, .. } => Some(label.clone()),
+        }
+    }
+
     fn scroll_tracklist(&mut self, director: &Director) {
         if !self.legal_songs.is_empty() {
             let len = self.legal_songs.len();
@@ -399,12 +603,17 @@ impl UiState {
                 Director::Bottom => len - 1,
             };
 
-            self.nav.sidebar.pos.select(Some(new_pos));
-            *self.nav.table_pos.offset_mut() = 0;
-            self.set_legal_songs();
+            self.select_sidebar_row(new_pos);
         }
     }
 
+    pub(crate) fn select_sidebar_row(&mut self, idx: usize) {
+        self.nav.sidebar.pos.select(Some(idx));
+        self.clear_multi_select();
+        *self.nav.table_pos.offset_mut() = 0;
+        self.set_legal_songs();
+    }
+
     fn scroll_to_top(&mut self) {
         if let Pane::TrackList = &self.nav.pane {
             self.nav.table_pos.select_first()
@@ -438,8 +647,12 @@ impl UiState {
 
         let list_len = match popup_type {
             PopupType::Settings(_) => self.get_roots().len(),
-            PopupType::Playlist(_) => self.playlists.len(),
+            PopupType::Duplicates(_) => self.duplicates.groups.len(),
+            PopupType::Playlist(PlaylistAction::Move) => self.folder_targets().len(),
+            PopupType::Playlist(_) => self.manual_playlists().count(),
             PopupType::ThemeManager => self.theme.theme_lib.len(),
+            PopupType::Columns => TableColumn::ALL.len(),
+            PopupType::VizSettings => LookSetting::ALL.len(),
             _ => return,
         };
 
@@ -470,8 +683,12 @@ impl UiState {
 
         let list_len = match popup_type {
             PopupType::Settings(_) => self.get_roots().len(),
-            PopupType::Playlist(_) => self.playlists.len(),
+            PopupType::Duplicates(_) => self.duplicates.groups.len(),
+            PopupType::Playlist(PlaylistAction::Move) => self.folder_targets().len(),
+            PopupType::Playlist(_) => self.manual_playlists().count(),
             PopupType::ThemeManager => self.theme.theme_lib.len(),
+            PopupType::Columns => TableColumn::ALL.len(),
+            PopupType::VizSettings => LookSetting::ALL.len(),
             _ => return,
         };
 
@@ -490,6 +707,7 @@ impl UiState {
         if let Some(idx) = self.popup.selection.selected()
             && let Some(theme) = self.theme.theme_lib.get(idx)
         {
+            self.theme.manual = true;
             self.set_theme(theme.clone());
         }
     }
@@ -5,7 +5,7 @@ mod sidebar;
 mod table_sort;
 
 pub use album_sort::AlbumSort;
-pub use mode::{Mode};
+pub use mode::Mode;
 pub use pane::Pane;
 pub use sidebar::{NodeKey, Root, RowKind, SidebarRow};
-pub use table_sort::TableSort;
+pub use table_sort::{TableColumn, TableSort};
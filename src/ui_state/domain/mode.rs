@@ -6,6 +6,8 @@ pub enum Mode {
     Fullscreen,
     Queue,
     Search,
+    Loved,
+    Recent,
     QUIT,
 }
 
@@ -23,6 +25,8 @@ impl Mode {
             Mode::Fullscreen => "Fullscreen",
             Mode::Queue => "Queue",
             Mode::Search => "Search",
+            Mode::Loved => "Loved",
+            Mode::Recent => "Recent",
             Mode::QUIT => "Quit",
         }
         .to_string()
@@ -33,6 +37,8 @@ impl Mode {
             "library" => Mode::Library,
             "queue" => Mode::Queue,
             "search" => Mode::Search,
+            "loved" => Mode::Loved,
+            "recent" => Mode::Recent,
             "quit" => Mode::QUIT,
             _ => Mode::Library,
         }
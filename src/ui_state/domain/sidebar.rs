@@ -1,9 +1,11 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Root {
     Library,
     Playlist,
+    Genre,
+    Folder,
 }
 
 impl Root {
@@ -11,6 +13,8 @@ impl Root {
         match self {
             Root::Library => "Albums",
             Root::Playlist => "Playlists",
+            Root::Genre => "Genres",
+            Root::Folder => "Folders",
         }
     }
 }
@@ -21,16 +25,29 @@ pub enum NodeKey {
     Artist(Arc<String>),
     Album(i64),
     Playlist(i64),
+    PlaylistFolder(i64),
+    Genre(Arc<String>),
+    Folder(Arc<PathBuf>),
 }
 
 impl NodeKey {
+    /// Folders start out folded, so the tree only opens as far as asked
+    pub fn folded_by_default(&self) -> bool {
+        matches!(self, Self::Folder(_) | Self::PlaylistFolder(_))
+    }
+
     pub fn serialize(&self) -> String {
         match self {
             Self::Root(Root::Library) => "root:music".into(),
             Self::Root(Root::Playlist) => "root:playlists".into(),
+            Self::Root(Root::Genre) => "root:genres".into(),
+            Self::Root(Root::Folder) => "root:folders".into(),
             Self::Artist(n) => format!("artist:{n}"),
             Self::Album(id) => format!("album:{id}"),
             Self::Playlist(id) => format!("playlist:{id}"),
+            Self::PlaylistFolder(id) => format!("playlist_folder:{id}"),
+            Self::Genre(n) => format!("genre:{n}"),
+            Self::Folder(p) => format!("folder:{}", p.display()),
         }
     }
 
@@ -39,9 +56,14 @@ impl NodeKey {
         match tag {
             "root" if rest == "music" => Some(Self::Root(Root::Library)),
             "root" if rest == "playlists" => Some(Self::Root(Root::Playlist)),
+            "root" if rest == "genres" => Some(Self::Root(Root::Genre)),
+            "root" if rest == "folders" => Some(Self::Root(Root::Folder)),
             "artist" => Some(Self::Artist(Arc::new(rest.to_string()))),
             "album" => rest.parse().ok().map(Self::Album),
             "playlist" => rest.parse().ok().map(Self::Playlist),
+            "playlist_folder" => rest.parse().ok().map(Self::PlaylistFolder),
+            "genre" => Some(Self::Genre(Arc::new(rest.to_string()))),
+            "folder" => Some(Self::Folder(Arc::new(PathBuf::from(rest)))),
             _ => None,
         }
     }
@@ -56,6 +78,14 @@ pub enum RowKind {
     },
     Album(i64),
     Playlist(i64),
+    PlaylistFolder(i64),
+    Genre(Arc<String>),
+    /// `label` covers every folder in a chain that holds nothing but the
+    /// next one, which are shown as a single row
+    Folder {
+        path: Arc<PathBuf>,
+        label: String,
+    },
 }
 
 #[derive(Clone)]
@@ -75,6 +105,9 @@ impl SidebarRow {
             RowKind::Artist { name, .. } => NodeKey::Artist(Arc::clone(name)),
             RowKind::Album(id) => NodeKey::Album(*id),
             RowKind::Playlist(id) => NodeKey::Playlist(*id),
+            RowKind::PlaylistFolder(id) => NodeKey::PlaylistFolder(*id),
+            RowKind::Genre(name) => NodeKey::Genre(Arc::clone(name)),
+            RowKind::Folder { path, .. } => NodeKey::Folder(Arc::clone(path)),
         }
     }
 
@@ -82,13 +115,18 @@ impl SidebarRow {
         match &self.kind {
             RowKind::Category(r) => *r,
             RowKind::Artist { .. } | RowKind::Album(_) => Root::Library,
-            RowKind::Playlist(_) => Root::Playlist,
+            RowKind::Playlist(_) | RowKind::PlaylistFolder(_) => Root::Playlist,
+            RowKind::Genre(_) => Root::Genre,
+            RowKind::Folder { .. } => Root::Folder,
         }
     }
 
     pub fn collapse_key(&self) -> Option<NodeKey> {
         match &self.kind {
-            RowKind::Category(_) | RowKind::Artist { .. } => Some(self.key()),
+            RowKind::Category(_)
+            | RowKind::Artist { .. }
+            | RowKind::PlaylistFolder(_)
+            | RowKind::Folder { .. } => Some(self.key()),
             _ => None,
         }
     }
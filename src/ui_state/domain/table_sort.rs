@@ -1,36 +1,167 @@
-pub enum TableSort {
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TableColumn {
+    Track,
     Title,
     Artist,
     Album,
+    Year,
     Duration,
+    Rating,
+    Plays,
+}
+
+impl TableColumn {
+    /// Also the order columns are shown in
+    pub const ALL: [TableColumn; 8] = [
+        TableColumn::Track,
+        TableColumn::Title,
+        TableColumn::Artist,
+        TableColumn::Album,
+        TableColumn::Year,
+        TableColumn::Duration,
+        TableColumn::Rating,
+        TableColumn::Plays,
+    ];
+
+    pub const DEFAULT: [TableColumn; 5] = [
+        TableColumn::Title,
+        TableColumn::Artist,
+        TableColumn::Album,
+        TableColumn::Rating,
+        TableColumn::Duration,
+    ];
+
+    /// Spelled out, where the header's `#` would be unclear
+    pub fn name(&self) -> &'static str {
+        match self {
+            TableColumn::Track => "Track number",
+            _ => self.label(),
+        }
+    }
+
+    /// For the table header
+    pub fn label(&self) -> &'static str {
+        match self {
+            TableColumn::Track => "#",
+            TableColumn::Title => "Title",
+            TableColumn::Artist => "Artist",
+            TableColumn::Album => "Album",
+            TableColumn::Year => "Year",
+            TableColumn::Duration => "Duration",
+            TableColumn::Rating => "Rating",
+            TableColumn::Plays => "Plays",
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            TableColumn::Track => "track",
+            TableColumn::Title => "title",
+            TableColumn::Artist => "artist",
+            TableColumn::Album => "album",
+            TableColumn::Year => "year",
+            TableColumn::Duration => "duration",
+            TableColumn::Rating => "rating",
+            TableColumn::Plays => "plays",
+        }
+    }
+
+    fn from_key(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == s)
+    }
+
+    /// Counts read best from the top down
+    fn descending_first(&self) -> bool {
+        matches!(self, TableColumn::Rating | TableColumn::Plays)
+    }
+
+    /// `title,artist,...`, for the session snapshot
+    pub fn join(columns: &[TableColumn]) -> String {
+        columns
+            .iter()
+            .map(|c| c.key())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Unknown names are dropped; an empty list falls back to the default
+    pub fn split(s: &str) -> Vec<TableColumn> {
+        let columns = Self::ALL
+            .into_iter()
+            .filter(|c| s.split(',').any(|k| k.trim() == c.key()))
+            .collect::<Vec<_>>();
+
+        match columns.is_empty() {
+            true => Self::DEFAULT.to_vec(),
+            false => columns,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: TableColumn,
+    pub descending: bool,
+}
+
+impl Default for TableSort {
+    fn default() -> Self {
+        TableSort::by(TableColumn::Title)
+    }
 }
 
 impl ToString for TableSort {
     fn to_string(&self) -> String {
-        match self {
-            TableSort::Title => "Title".into(),
-            TableSort::Artist => "Artist".into(),
-            TableSort::Album => "Album".into(),
-            TableSort::Duration => "Duration".into(),
+        match self.descending {
+            true => format!("{}:desc", self.column.key()),
+            false => self.column.key().to_string(),
         }
     }
 }
 
 impl TableSort {
-    pub fn next(&self) -> Self {
-        match self {
-            TableSort::Title => TableSort::Artist,
-            TableSort::Artist => TableSort::Album,
-            TableSort::Album => TableSort::Duration,
-            TableSort::Duration => TableSort::Title,
+    /// Sorting by a new column starts out in its natural direction
+    pub fn by(column: TableColumn) -> Self {
+        TableSort {
+            column,
+            descending: column.descending_first(),
         }
     }
-    pub fn prev(&self) -> Self {
-        match self {
-            TableSort::Title => TableSort::Duration,
-            TableSort::Artist => TableSort::Title,
-            TableSort::Album => TableSort::Artist,
-            TableSort::Duration => TableSort::Album,
+
+    pub fn reversed(self) -> Self {
+        TableSort {
+            descending: !self.descending,
+            ..self
+        }
+    }
+
+    /// The next of `columns` over from the sorted one, wrapping around
+    pub fn step(self, columns: &[TableColumn], forward: bool) -> Self {
+        let len = columns.len();
+        let next = match columns.iter().position(|&c| c == self.column) {
+            Some(i) if forward => columns[(i + 1) % len],
+            Some(i) => columns[(i + len - 1) % len],
+            None => match columns.first() {
+                Some(&c) => c,
+                None => return self,
+            },
+        };
+        TableSort::by(next)
+    }
+
+    /// The other way from `to_string`, anything unknown sorting by title
+    pub fn parse_key(s: &str) -> Self {
+        let (key, desc) = match s.strip_suffix(":desc") {
+            Some(key) => (key, true),
+            None => (s, false),
+        };
+
+        match TableColumn::from_key(key) {
+            Some(column) => TableSort {
+                column,
+                descending: desc,
+            },
+            None => TableSort::default(),
         }
     }
 }
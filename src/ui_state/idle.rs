@@ -0,0 +1,44 @@
+use crate::{gen_config, ui_state::UiState};
+use std::time::{Duration, Instant};
+
+/// The screensaver: the visualizer over everything once no key or click
+/// has come in for `idle_timeout_secs`. Playback never notices.
+pub struct IdleScreen {
+    last_input: Instant,
+    resting: bool,
+}
+
+impl Default for IdleScreen {
+    fn default() -> Self {
+        IdleScreen {
+            last_input: Instant::now(),
+            resting: false,
+        }
+    }
+}
+
+impl IdleScreen {
+    pub fn is_resting(&self) -> bool {
+        self.resting
+    }
+
+    /// Notes a key or click. True when it woke the screen, which is then
+    /// all it should do.
+    pub fn wake(&mut self) -> bool {
+        self.last_input = Instant::now();
+        std::mem::take(&mut self.resting)
+    }
+}
+
+impl UiState {
+    /// Called once per loop. Only something playing is worth resting on,
+    /// and never with a popup waiting on an answer.
+    pub fn update_idle(&mut self) {
+        let timeout = gen_config().idle_timeout_secs;
+        let can_rest = timeout > 0 && self.metrics.is_active() && !self.popup.is_open();
+
+        self.idle.resting = can_rest
+            && (self.idle.resting
+                || self.idle.last_input.elapsed() >= Duration::from_secs(timeout as u64));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    library::lyrics::{self, Lyrics},
+    ui_state::{LayoutStyle, UiState},
+};
+use anyhow::{Result, bail};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// How long a scroll by hand holds before the pane follows the song again
+const MANUAL_HOLD: Duration = Duration::from_secs(5);
+
+/// The lyrics pane, and the lyrics of the playing song while it's open
+#[derive(Default)]
+pub struct LyricsState {
+    pub(crate) visible: bool,
+    song_id: Option<u64>,
+    lyrics: Option<Lyrics>,
+    /// The line scrolled to by hand, and when
+    manual: Option<(usize, Instant)>,
+}
+
+impl LyricsState {
+    pub fn lyrics(&self) -> Option<&Lyrics> {
+        self.lyrics.as_ref()
+    }
+
+    /// The line to keep in the middle of the pane. Synced lyrics follow
+    /// the song unless scrolled by hand a moment ago; plain lyrics stay
+    /// wherever they were scrolled to.
+    pub fn focus(&self, active: Option<usize>) -> usize {
+        let synced = self.lyrics.as_ref().is_some_and(Lyrics::is_synced);
+        match self.manual {
+            Some((line, at)) if !synced || at.elapsed() < MANUAL_HOLD => line,
+            _ => active.unwrap_or(0),
+        }
+    }
+
+    fn scroll(&mut self, lines: isize, active: Option<usize>) {
+        let Some(len) = self.lyrics.as_ref().map(Lyrics::len) else {
+            return;
+        };
+        let line = (self.focus(active) as isize + lines).clamp(0, len.saturating_sub(1) as isize);
+        self.manual = Some((line as usize, Instant::now()));
+    }
+}
+
+impl UiState {
+    pub fn toggle_lyrics(&mut self) -> Result<()> {
+        self.lyrics.visible = !self.lyrics.visible;
+        if self.lyrics.visible && self.layout == LayoutStyle::Minimal {
+            bail!("Lyrics show in the traditional layout, switch with <m>");
+        }
+        Ok(())
+    }
+
+    /// The synced line being sung now
+    pub fn active_lyric(&self) -> Option<usize> {
+        self.lyrics.lyrics()?.active_line(self.displayed_position())
+    }
+
+    pub fn scroll_lyrics(&mut self, lines: isize) {
+        let active = self.active_lyric();
+        self.lyrics.scroll(lines, active);
+    }
+
+    /// Loads the lyrics when the playing song changes, only while the pane
+    /// is open to show them
+    pub(crate) fn sync_lyrics(&mut self) {
+        let song = self.get_now_playing().cloned();
+        let id = song.as_ref().map(|s| s.id);
+        if id == self.lyrics.song_id || !self.lyrics.visible {
+            return;
+        }
+
+        self.lyrics.song_id = id;
+        self.lyrics.manual = None;
+        self.lyrics.lyrics = song.and_then(|song| {
+            let path = self.db_worker.get_song_path(song.id).ok()?;
+            lyrics::load_lyrics(Path::new(&path))
+        });
+    }
+}
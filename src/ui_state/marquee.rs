@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+/// How long the text stays put when a song starts, and after a key press or
+/// click, before it carries on scrolling
+const HOLD: Duration = Duration::from_secs(3);
+/// Time for each grapheme scrolled, however often frames are drawn
+const STEP: Duration = Duration::from_millis(150);
+
+/// How far a now-playing line too long for its space has scrolled. It moves
+/// one grapheme every `STEP`.
+#[derive(Default)]
+pub struct Marquee {
+    song: Option<u64>,
+    offset: usize,
+    stepped_at: Option<Instant>,
+    held_until: Option<Instant>,
+}
+
+impl Marquee {
+    /// Called once a frame with the playing song, which starts the text over
+    /// whenever it changes
+    pub fn tick(&mut self, song: Option<u64>) {
+        if song != self.song {
+            self.song = song;
+            self.offset = 0;
+            self.hold();
+            return;
+        }
+
+        let now = Instant::now();
+        if self.held_until.is_some_and(|t| now < t) {
+            return;
+        }
+        let Some(stepped_at) = self.stepped_at else {
+            self.stepped_at = Some(now);
+            return;
+        };
+
+        let steps = (now - stepped_at).as_millis() / STEP.as_millis();
+        if steps > 0 {
+            self.offset = self.offset.wrapping_add(steps as usize);
+            self.stepped_at = Some(stepped_at + STEP * steps as u32);
+        }
+    }
+
+    /// Keeps the text still for a while, so it can be read
+    pub fn hold(&mut self) {
+        self.held_until = Some(Instant::now() + HOLD);
+        self.stepped_at = None;
+    }
+
+    /// Graphemes scrolled past, for text `len` graphemes long going round
+    pub fn offset(&self, len: usize) -> usize {
+        self.offset % len.max(1)
+    }
+}
@@ -1,38 +1,76 @@
+mod ab_loop;
+mod album_art;
+mod artist_info;
+mod command_line;
 mod display_state;
 mod domain;
+mod idle;
+mod lyrics;
+mod marquee;
+mod mouse;
 mod multi_select;
+mod play_tally;
 mod player_snapshot;
 mod playlist;
 mod popup;
+mod power_save;
+mod resume;
 mod search_state;
 mod settings;
 mod sidebar;
+mod sleep_timer;
 mod stats;
+mod tag_editor;
 mod ui_snapshot;
 mod ui_state;
+mod undo;
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+pub use ab_loop::AbLoop;
+pub use artist_info::ArtistState;
+pub use command_line::CommandHint;
 pub use display_state::DisplayState;
-pub use domain::{AlbumSort, Mode, NodeKey, Pane, Root, RowKind, SidebarRow, TableSort};
+pub use domain::{
+    AlbumSort, Mode, NodeKey, Pane, Root, RowKind, SidebarRow, TableColumn, TableSort,
+};
+pub use idle::IdleScreen;
 use indexmap::IndexMap;
+pub use lyrics::LyricsState;
+pub use marquee::Marquee;
+pub use mouse::{ListArea, MouseState};
+pub use play_tally::PlayTally;
 pub use player_snapshot::PlayerSnapshot;
 pub use playlist::PlaylistAction;
 pub use popup::PopupType;
-pub use search_state::MatchField;
-pub use settings::SettingsMode;
+pub use power_save::PowerSave;
+pub use resume::ResumePoint;
+pub use search_state::{MatchField, SearchMatch};
+pub use settings::{DuplicateMode, SettingsMode};
 pub use sidebar::Sidebar;
-pub use stats::LibraryStats;
+pub use sleep_timer::SleepTimer;
+pub use stats::{LibraryStats, StatsWindow};
+pub use tag_editor::{TagEditor, TagEntry};
 pub use ui_snapshot::UiSnapshot;
-use voxio::Vox;
+pub use undo::{UndoEntry, UndoStack};
 
 use crate::{
     Library, PlaybackSession,
     database::DbWorker,
     key_handler::KeyBuffer,
-    library::{Album, Playlist, RefreshProgress, SimpleSong},
+    library::{Album, Playlist, PlaylistFolder, RefreshProgress, SimpleSong},
+    playback::ShuffleMode,
+    player::{Output, OutputGain},
     theme::ThemeManager,
-    ui_state::{popup::PopupState, search_state::SearchState, stats::VoxStats},
+    ui_state::{
+        album_art::AlbumArt, popup::PopupState, search_state::SearchState,
+        settings::DuplicateReview, stats::VoxStats,
+    },
     visualization::Visualizer,
 };
 
@@ -64,8 +102,11 @@ pub struct UiState {
     library: Arc<Library>,
     db_worker: DbWorker,
 
-    pub(crate) metrics: Arc<Vox>,
+    pub(crate) metrics: Arc<Output>,
+    pub(crate) gain: Rc<OutputGain>,
     pub(crate) playback: PlaybackSession,
+    pub(crate) sleep_timer: SleepTimer,
+    pub(crate) ab_loop: AbLoop,
     pub(crate) nav: DisplayState,
 
     pub(crate) search: SearchState,
@@ -74,13 +115,41 @@ pub struct UiState {
 
     pub(crate) layout: LayoutStyle,
     pub(crate) stats: VoxStats,
+    pub(crate) duplicates: DuplicateReview,
+    pub(crate) tag_editor: TagEditor,
+    pub(crate) undo: UndoStack,
     pub(crate) viz: Visualizer,
+    pub(crate) art: AlbumArt,
+    pub(crate) lyrics: LyricsState,
+    pub(crate) artist_pane: ArtistState,
+    pub(crate) mouse: MouseState,
+    pub(crate) marquee: Marquee,
+    pub(crate) play_tally: PlayTally,
+    pub(crate) resume: ResumePoint,
+    pub(crate) power_save: PowerSave,
+    pub(crate) idle: IdleScreen,
+    /// Set on the frame a beat is detected, with `beat_pulse` on
+    pub(crate) beat_detected: bool,
+    /// Where the last seek is headed, and when it was asked for
+    seek_hold: Option<(Duration, Instant)>,
+    /// Shown on the buffer line for a moment, and when it was set
+    notice: Option<(String, Instant)>,
 
     pub(crate) albums: Vec<Album>,
     pub(crate) playlists: IndexMap<i64, Playlist>,
+    pub(crate) playlist_folders: IndexMap<i64, PlaylistFolder>,
+    /// Track count for each genre
+    pub(crate) genres: IndexMap<Arc<String>, usize>,
+    /// For the plays column, refreshed whenever the songs shown are
+    pub(crate) play_counts: HashMap<u64, u16>,
     legal_songs: Vec<Arc<SimpleSong>>,
     legal_songs_dur: Duration,
     shuffle_seed: u64,
+    pub(crate) shuffle_mode: ShuffleMode,
+    /// Keeps playback going past the end of the queue
+    pub(crate) radio: bool,
+    /// Native rate of the playing file, as recorded when it was scanned
+    pub(crate) source_rate: Option<u32>,
 
     pub library_refresh: Option<Arc<RefreshProgress>>,
     pub key_buffer: KeyBuffer,
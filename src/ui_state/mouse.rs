@@ -0,0 +1,125 @@
+use crate::{gen_config, ui_state::UiState};
+use anyhow::Result;
+use ratatui::{
+    crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture},
+        execute,
+    },
+    layout::{Position, Rect},
+};
+use std::time::{Duration, Instant};
+
+/// A second click on the same row within this long counts as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Rows of a list as last drawn: where the first visible row starts, how far
+/// apart they are, and which entry the first one is
+#[derive(Clone, Copy)]
+pub struct ListArea {
+    pub rows: Rect,
+    pub stride: u16,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl ListArea {
+    /// The entry drawn under the cursor, if any. The gaps between rows of
+    /// the traditional tracklist belong to the row above.
+    pub fn row_at(&self, col: u16, row: u16) -> Option<usize> {
+        if !self.rows.contains(Position::new(col, row)) {
+            return None;
+        }
+        let idx = self.offset + ((row - self.rows.y) / self.stride.max(1)) as usize;
+        (idx < self.len).then_some(idx)
+    }
+}
+
+/// Whether the mouse is captured, and what was clickable on the last frame.
+/// The areas are recorded on every render, so they follow the terminal
+/// through a resize.
+pub struct MouseState {
+    pub enabled: bool,
+    pub sidebar: Option<ListArea>,
+    pub tracklist: Option<ListArea>,
+    pub seek_bar: Option<Rect>,
+    pub lyrics: Option<Rect>,
+    last_click: Option<(Instant, Position)>,
+}
+
+impl Default for MouseState {
+    fn default() -> Self {
+        MouseState {
+            enabled: gen_config().mouse,
+            sidebar: None,
+            tracklist: None,
+            seek_bar: None,
+            lyrics: None,
+            last_click: None,
+        }
+    }
+}
+
+impl MouseState {
+    /// Forgets the areas, before a frame draws whichever are shown
+    pub fn clear_areas(&mut self) {
+        self.sidebar = None;
+        self.tracklist = None;
+        self.seek_bar = None;
+        self.lyrics = None;
+    }
+
+    /// Records the click, returning whether it completes a double-click
+    pub fn is_double_click(&mut self, col: u16, row: u16) -> bool {
+        let now = Instant::now();
+        let pos = Position::new(col, row);
+        let double = self
+            .last_click
+            .is_some_and(|(at, last)| last.y == pos.y && now.duration_since(at) < DOUBLE_CLICK);
+
+        // A third click starts over rather than making a second double-click
+        self.last_click = (!double).then_some((now, pos));
+        double
+    }
+}
+
+impl UiState {
+    /// Letting go of the mouse hands text selection back to the terminal
+    pub fn toggle_mouse(&mut self) -> Result<()> {
+        let msg = match self.mouse.enabled {
+            true => {
+                execute!(std::io::stdout(), DisableMouseCapture)?;
+                "Mouse disabled"
+            }
+            false => {
+                execute!(std::io::stdout(), EnableMouseCapture)?;
+                "Mouse enabled"
+            }
+        };
+
+        self.mouse.enabled = !self.mouse.enabled;
+        self.set_notice(msg.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_follow_the_stride_and_offset() {
+        let area = ListArea {
+            rows: Rect::new(2, 10, 40, 9),
+            stride: 3,
+            offset: 5,
+            len: 7,
+        };
+
+        assert_eq!(area.row_at(2, 10), Some(5));
+        assert_eq!(area.row_at(20, 12), Some(5));
+        assert_eq!(area.row_at(20, 13), Some(6));
+        // Past the last entry, and outside the list
+        assert_eq!(area.row_at(20, 16), None);
+        assert_eq!(area.row_at(1, 10), None);
+    }
+}
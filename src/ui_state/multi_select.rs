@@ -1,7 +1,7 @@
 use crate::{
     key_handler::{Director, Incrementor, SelectionType},
     library::SimpleSong,
-    ui_state::{Mode, RowKind, UiState},
+    ui_state::{RowKind, UiState, UndoEntry},
 };
 use anyhow::{Result, anyhow};
 use indexmap::IndexSet;
@@ -14,13 +14,18 @@ impl UiState {
 
     pub fn get_selected_group_label(&self) -> Option<Arc<String>> {
         match &self.selected_row()?.kind {
-            RowKind::Artist { name, .. } => Some(Arc::clone(name)),
+            RowKind::Artist { name, .. } | RowKind::Genre(name) => Some(Arc::clone(name)),
+            RowKind::Folder { label, .. } => Some(Arc::new(label.clone())),
+            RowKind::PlaylistFolder(id) => {
+                Some(Arc::new(self.playlist_folders.get(id)?.name.clone()))
+            }
             _ => None,
         }
     }
 
     pub fn toggle_multi_selection(&mut self, count: usize) -> Result<()> {
         let song_idx = self.nav.get_table_idx()?;
+        self.nav.select_range = None;
 
         if count == 0 {
             match self.nav.multi_select.contains(&song_idx) {
@@ -44,6 +49,31 @@ impl UiState {
         Ok(())
     }
 
+    /// Moves the cursor and selects everything between it and where the
+    /// range started. Moving back over the range shrinks it; moving the
+    /// cursor any other way starts a new one.
+    pub fn extend_multi_selection(&mut self, dir: Incrementor) -> Result<()> {
+        let cursor = self.nav.get_table_idx()?;
+        let last = self.legal_songs.len().saturating_sub(1);
+        let anchor = match self.nav.select_range {
+            Some((anchor, end)) if end == cursor => anchor,
+            _ => cursor,
+        };
+        let target = match dir {
+            Incrementor::Up => cursor.saturating_sub(1),
+            Incrementor::Down => (cursor + 1).min(last),
+        };
+
+        for i in span(anchor, cursor) {
+            self.nav.multi_select.shift_remove(&i);
+        }
+        self.nav.multi_select.extend(span(anchor, target));
+
+        self.nav.table_pos.select(Some(target));
+        self.nav.select_range = Some((anchor, target));
+        Ok(())
+    }
+
     pub fn get_songs_by_selection(
         &mut self,
         sel_type: SelectionType,
@@ -58,15 +88,12 @@ impl UiState {
     }
 
     pub fn multi_select_all(&mut self) -> Result<()> {
-        if let Mode::Queue | Mode::Library = self.get_mode() {
-            let all_selected =
-                (0..self.legal_songs.len()).all(|i| self.nav.multi_select.contains(&i));
-
-            match all_selected {
-                true => self.clear_multi_select(),
-                false => {
-                    self.nav.multi_select = (0..self.legal_songs.len()).collect();
-                }
+        let all_selected = (0..self.legal_songs.len()).all(|i| self.nav.multi_select.contains(&i));
+
+        match all_selected {
+            true => self.clear_multi_select(),
+            false => {
+                self.nav.multi_select = (0..self.legal_songs.len()).collect();
             }
         }
         Ok(())
@@ -87,9 +114,11 @@ impl UiState {
 
     pub fn clear_multi_select(&mut self) {
         self.nav.multi_select.clear();
+        self.nav.select_range = None;
     }
 
     pub fn remove_from_playlist(&mut self) -> Result<()> {
+        self.ensure_manual_playlist()?;
         let song_idx = self.nav.get_table_idx()?;
 
         let playlist_id = self
@@ -108,7 +137,9 @@ impl UiState {
             .ok_or_else(|| anyhow!("Invalid song selection!"))?
             .id;
 
-        self.db_worker.remove_from_playlist(vec![ps_id])?;
+        let songs = self.db_worker.remove_from_playlist(vec![ps_id])?;
+        self.undo
+            .push(UndoEntry::PlaylistSongs { playlist_id, songs });
 
         playlist.tracklist.remove(song_idx);
         playlist.update_length();
@@ -117,6 +148,7 @@ impl UiState {
     }
 
     pub fn remove_from_playlist_multi(&mut self) -> Result<()> {
+        self.ensure_manual_playlist()?;
         // Obtain selected playlist id
         let playlist_id = self
             .get_selected_playlist()
@@ -139,7 +171,9 @@ impl UiState {
                 .collect()
         };
 
-        self.db_worker.remove_from_playlist(ps_ids)?;
+        let songs = self.db_worker.remove_from_playlist(ps_ids)?;
+        self.undo
+            .push(UndoEntry::PlaylistSongs { playlist_id, songs });
 
         // Redeclare to avoid fighting with borrow checker
         let playlist = self
@@ -164,6 +198,7 @@ impl UiState {
     }
 
     pub fn shift_playlist_position(&mut self, dir: Incrementor) -> Result<()> {
+        self.ensure_manual_playlist()?;
         match self.multi_select_empty() {
             true => self.shift_playlist_position_single(dir)?,
             false => self.shift_playlist_position_multi(dir)?,
@@ -255,3 +290,11 @@ impl UiState {
         Ok(())
     }
 }
+
+/// `from` through `to` inclusive, walking from `from`
+fn span(from: usize, to: usize) -> Vec<usize> {
+    match from <= to {
+        true => (from..=to).collect(),
+        false => (to..=from).rev().collect(),
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    gen_config,
+    library::SongDatabase,
+    ui_state::{Mode, UiState},
+};
+use anyhow::Result;
+use std::time::Duration;
+
+/// Further between two frames than this is a seek, which isn't listening
+const MAX_STEP: Duration = Duration::from_secs(1);
+
+/// How much of the playing track has actually been heard. Seeking ahead
+/// adds nothing, so a skip before the threshold never counts as a play.
+#[derive(Default)]
+pub struct PlayTally {
+    song: Option<u64>,
+    heard: Duration,
+    last_position: Duration,
+    counted: bool,
+}
+
+impl PlayTally {
+    /// Starts over for a new play of `song`. A restored song was already
+    /// counted in the session it was started in.
+    pub fn start(&mut self, song: u64, counted: bool) {
+        *self = PlayTally {
+            song: Some(song),
+            counted,
+            ..Default::default()
+        };
+    }
+
+    /// Adds what was played since the last frame, true the first time
+    /// that crosses `threshold`
+    fn listen(&mut self, position: Duration, threshold: Duration) -> bool {
+        let step = position.saturating_sub(self.last_position);
+        self.last_position = position;
+        if step < MAX_STEP {
+            self.heard += step;
+        }
+
+        if self.counted || self.heard < threshold {
+            return false;
+        }
+        self.counted = true;
+        true
+    }
+}
+
+/// Whichever of the share of the track and the fixed time comes first
+fn threshold(length: Duration) -> Duration {
+    let config = gen_config();
+    let share = length.mul_f64(config.play_count_percent as f64 / 100.0);
+    match config.play_count_secs {
+        0 => share,
+        secs => share.min(Duration::from_secs(secs as u64)),
+    }
+}
+
+impl UiState {
+    /// Called once a frame, counting the play once enough has been heard.
+    /// True on the frame it's counted.
+    pub fn update_play_tally(&mut self) -> Result<bool> {
+        if self.metrics.is_paused() || !self.metrics.is_active() {
+            return Ok(false);
+        }
+        let Some(song) = self.get_now_playing().cloned() else {
+            return Ok(false);
+        };
+        if self.play_tally.song != Some(song.id) {
+            return Ok(false);
+        }
+
+        // Nothing counts until the length is known
+        let (position, length) = self.track_progress();
+        if !length.is_zero() && self.play_tally.listen(position, threshold(length)) {
+            song.update_play_count()?;
+            if *self.get_mode() == Mode::Recent {
+                self.set_legal_songs();
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeking_ahead_is_not_listening() {
+        let secs = Duration::from_secs;
+        let mut tally = PlayTally::default();
+        tally.start(1, false);
+
+        // A jump most of the way in, then a little played from there
+        assert!(!tally.listen(secs(150), secs(120)));
+        assert!(!tally.listen(secs(150) + Duration::from_millis(500), secs(120)));
+        assert_eq!(tally.heard, Duration::from_millis(500));
+
+        tally.heard = secs(120);
+        assert!(tally.listen(secs(151), secs(120)));
+        // Only ever counted the once
+        assert!(!tally.listen(secs(152), secs(120)));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
-    library::{Playlist, PlaylistSong},
-    ui_state::{NodeKey, PopupType, Root, UiState},
+    PLAYLIST_DIR,
+    database::PlaylistItem,
+    expand_tilde,
+    library::{Playlist, PlaylistSong, SmartRules},
+    ui_state::{NodeKey, PopupType, Root, RowKind, UiState, UndoEntry},
 };
 use anyhow::{Result, anyhow, bail};
 
@@ -11,16 +14,26 @@ pub enum PlaylistAction {
     Delete,
     Rename,
     CreateWithSongs,
+    CreateSmart,
+    /// Rules for a new smart playlist of the given name, or for the selected
+    /// smart playlist when `None`
+    SmartRules(Option<String>),
+    Import,
+    /// Filing the selected playlist or folder in another folder
+    Move,
+    /// Naming a folder for the selected playlist or folder to go in
+    NewFolder,
 }
 
 impl UiState {
     pub fn get_playlists(&mut self) -> Result<()> {
+        self.load_playlist_folders()?;
         let playlist_db = self.db_worker.build_playlists()?;
         let songs_map = self.library.get_songs_map();
 
         self.playlists = playlist_db
             .iter()
-            .map(|((id, name), track_ids)| {
+            .map(|((id, name, folder), track_ids)| {
                 let tracklist = track_ids
                     .iter()
                     .filter_map(|&s_id| {
@@ -34,10 +47,219 @@ impl UiState {
                     })
                     .collect::<Vec<PlaylistSong>>();
 
-                (*id, Playlist::new(*id, name.to_string(), tracklist))
+                let mut playlist = Playlist::new(*id, name.to_string(), tracklist);
+                playlist.folder = folder.filter(|f| self.playlist_folders.contains_key(f));
+                (*id, playlist)
             })
             .collect();
 
+        for (id, source) in self.db_worker.get_smart_rules()? {
+            let Some(playlist) = self.playlists.get_mut(&id) else {
+                continue;
+            };
+            let Ok(rules) = SmartRules::parse(&source) else {
+                continue;
+            };
+
+            let songs = self
+                .db_worker
+                .run_smart_playlist(rules.clone(), &self.library)?;
+
+            // Smart tracks have no playlist_songs row; the id is only a position
+            playlist.tracklist = songs
+                .into_iter()
+                .enumerate()
+                .map(|(idx, song)| PlaylistSong {
+                    id: idx as i64,
+                    song,
+                })
+                .collect();
+            playlist.rules = Some(rules);
+            playlist.update_length();
+        }
+
+        Ok(())
+    }
+
+    /// A folder whose parents lead back round to it, which nothing here
+    /// would do, is listed at the top level rather than not at all
+    fn load_playlist_folders(&mut self) -> Result<()> {
+        self.playlist_folders = self
+            .db_worker
+            .get_playlist_folders()?
+            .into_iter()
+            .map(|f| (f.id, f))
+            .collect();
+
+        let folders = &self.playlist_folders;
+        let reaches_top = |mut parent: Option<i64>| {
+            for _ in 0..=folders.len() {
+                match parent {
+                    None => return Some(()),
+                    Some(id) => parent = folders.get(&id)?.parent,
+                }
+            }
+            None
+        };
+        let stray = folders
+            .values()
+            .filter(|f| reaches_top(f.parent).is_none())
+            .map(|f| f.id)
+            .collect::<Vec<_>>();
+
+        for id in stray {
+            self.playlist_folders[&id].parent = None;
+        }
+        Ok(())
+    }
+
+    /// Playlists that accept songs by hand, in display order
+    pub fn manual_playlists(&self) -> impl Iterator<Item = &Playlist> {
+        self.playlists.values().filter(|p| !p.is_smart())
+    }
+
+    pub(crate) fn ensure_manual_playlist(&self) -> Result<()> {
+        match self.get_selected_playlist().is_some_and(|p| p.is_smart()) {
+            true => bail!("Smart playlists are filled by their rules!\n\nPress [e] to edit them."),
+            false => Ok(()),
+        }
+    }
+
+    /// Edits the selected smart playlist, or starts creating a new one
+    pub fn smart_playlist_popup(&mut self) {
+        let current = self
+            .get_selected_playlist()
+            .and_then(|p| p.rules.as_ref())
+            .map(|r| r.to_string());
+
+        match current {
+            Some(rules) => {
+                self.show_popup(PopupType::Playlist(PlaylistAction::SmartRules(None)));
+                self.popup.input.insert_str(rules);
+            }
+            None => self.show_popup(PopupType::Playlist(PlaylistAction::CreateSmart)),
+        }
+    }
+
+    pub fn confirm_smart_playlist_name(&mut self) -> Result<()> {
+        let name = self.get_popup_string();
+
+        if name.is_empty() {
+            bail!("Playlist name cannot be empty!");
+        }
+
+        if self
+            .playlists
+            .values()
+            .any(|p| p.name.to_lowercase() == name.to_lowercase())
+        {
+            bail!("Playlist name already exists!");
+        }
+
+        self.show_popup(PopupType::Playlist(PlaylistAction::SmartRules(Some(name))));
+        Ok(())
+    }
+
+    pub fn confirm_smart_rules(&mut self) -> Result<()> {
+        let PopupType::Playlist(PlaylistAction::SmartRules(name)) = self.popup.current.clone()
+        else {
+            return Ok(());
+        };
+
+        let rules = SmartRules::parse(&self.get_popup_string())?;
+
+        let id = match name {
+            Some(name) => {
+                self.db_worker
+                    .create_smart_playlist(name, rules.to_string())?;
+                None
+            }
+            None => {
+                let id = self
+                    .get_selected_playlist()
+                    .ok_or_else(|| anyhow!("No playlist selected!"))?
+                    .id;
+                self.db_worker.set_playlist_rules(id, rules.to_string())?;
+                Some(id)
+            }
+        };
+
+        self.get_playlists()?;
+        self.rebuild_rows();
+
+        // New playlists sort to the top as the most recently updated
+        let id = id.or_else(|| self.playlists.first().map(|(id, _)| *id));
+        if let Some(id) = id {
+            self.select_by_key(&NodeKey::Playlist(id));
+        }
+
+        self.set_legal_songs();
+        self.close_popup();
+        Ok(())
+    }
+
+    /// Writes the selected playlist, smart or not, to the playlists folder
+    pub fn export_playlist(&mut self) -> Result<()> {
+        let playlist = self
+            .get_selected_playlist()
+            .ok_or_else(|| anyhow!("No playlist selected!"))?;
+
+        let file_name = playlist
+            .name
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c => c,
+            })
+            .collect::<String>();
+        let dest = PLAYLIST_DIR.join(format!("{file_name}.m3u8"));
+
+        let song_ids = playlist.tracklist.iter().map(|ps| ps.song.id).collect();
+        self.db_worker
+            .export_playlist_m3u(playlist.name.clone(), song_ids, dest.clone())?;
+
+        self.set_notice(format!("Playlist exported to {}", dest.display()));
+        Ok(())
+    }
+
+    pub fn import_playlist_popup(&mut self) {
+        self.show_popup(PopupType::Playlist(PlaylistAction::Import));
+    }
+
+    pub fn import_playlist(&mut self) -> Result<()> {
+        let input = self.get_popup_string();
+        let input = input.trim_matches(|c| c == '"' || c == '\'');
+        if input.is_empty() {
+            bail!("Path cannot be empty!");
+        }
+
+        let imported = self.db_worker.import_playlist_m3u(expand_tilde(input)?)?;
+
+        self.get_playlists()?;
+        self.rebuild_rows();
+        if let Some(id) = self.playlists.first().map(|(id, _)| *id) {
+            self.select_by_key(&NodeKey::Playlist(id));
+        }
+        self.set_legal_songs();
+        self.close_popup();
+
+        if !imported.missing.is_empty() {
+            const SHOWN: usize = 5;
+            let mut msg = format!(
+                "Imported {} tracks into \"{}\"\n\n{} not found in library:\n",
+                imported.added,
+                imported.name,
+                imported.missing.len()
+            );
+            for entry in imported.missing.iter().take(SHOWN) {
+                msg.push_str(&format!("\n{entry}"));
+            }
+            if imported.missing.len() > SHOWN {
+                msg.push_str(&format!("\n...and {} more", imported.missing.len() - SHOWN));
+            }
+            self.set_info(msg);
+        }
+
         Ok(())
     }
 
@@ -46,8 +268,10 @@ impl UiState {
     }
 
     pub fn create_playlist(&mut self) -> Result<()> {
-        let name = self.get_popup_string();
+        self.create_named_playlist(self.get_popup_string())
+    }
 
+    pub(crate) fn create_named_playlist(&mut self, name: String) -> Result<()> {
         if name.is_empty() {
             bail!("Playlist name cannot be empty!");
         }
@@ -77,12 +301,16 @@ impl UiState {
     }
 
     pub fn rename_playlist_popup(&mut self) {
-        if self.get_selected_playlist().is_some() {
+        if self.selected_playlist_item().is_some() {
             self.show_popup(PopupType::Playlist(PlaylistAction::Rename));
         }
     }
 
     pub fn rename_playlist(&mut self) -> Result<()> {
+        if let Some(PlaylistItem::Folder(id)) = self.selected_playlist_item() {
+            return self.rename_playlist_folder(id);
+        }
+
         let playlist = self
             .get_selected_playlist()
             .ok_or_else(|| anyhow!("No playlist selected!"))?;
@@ -121,7 +349,18 @@ impl UiState {
     pub fn delete_playlist(&mut self) -> Result<()> {
         if let Some(playlist) = self.get_selected_playlist() {
             let playlist_id = playlist.id;
+            let entry = UndoEntry::Playlist {
+                id: playlist_id,
+                name: playlist.name.clone(),
+                rules: playlist.rules.as_ref().map(|r| r.to_string()),
+                folder: playlist.folder,
+                songs: match playlist.is_smart() {
+                    true => Vec::new(),
+                    false => playlist.tracklist.iter().map(|ps| ps.song.id).collect(),
+                },
+            };
             self.db_worker.delete_playlist(playlist_id)?;
+            self.undo.push(entry);
 
             self.get_playlists()?;
             self.rebuild_rows();
@@ -145,31 +384,38 @@ impl UiState {
     pub fn add_to_playlist(&mut self) -> Result<()> {
         match self.popup.selection.selected() {
             Some(playlist_idx) => {
-                let Some((&playlist_id, _)) = self.playlists.get_index(playlist_idx) else {
+                let Some(playlist_id) = self.manual_playlists().nth(playlist_idx).map(|p| p.id)
+                else {
                     return Ok(());
                 };
-                match self.multi_select_empty() {
-                    true => {
-                        let song_id = self.get_selected_song()?.id;
-
-                        self.db_worker.add_to_playlist(song_id, playlist_id)?;
-                    }
-                    false => {
-                        let song_ids = self
-                            .get_multi_select_songs()
-                            .iter()
-                            .map(|s| s.id)
-                            .collect::<Vec<_>>();
-
-                        self.db_worker
-                            .add_to_playlist_multi(song_ids, playlist_id)?;
-                        self.clear_multi_select();
-                    }
-                }
-                self.close_popup()
+                self.add_selection_to_playlist(playlist_id)?;
+                self.close_popup();
+                Ok(())
             }
             None => bail!("Could not add to playlist"),
-        };
+        }
+    }
+
+    /// Adds the multi-selection, or the selected song when there is none
+    pub(crate) fn add_selection_to_playlist(&mut self, playlist_id: i64) -> Result<()> {
+        match self.multi_select_empty() {
+            true => {
+                let song_id = self.get_selected_song()?.id;
+
+                self.db_worker.add_to_playlist(song_id, playlist_id)?;
+            }
+            false => {
+                let song_ids = self
+                    .get_multi_select_songs()
+                    .iter()
+                    .map(|s| s.id)
+                    .collect::<Vec<_>>();
+
+                self.db_worker
+                    .add_to_playlist_multi(song_ids, playlist_id)?;
+                self.clear_multi_select();
+            }
+        }
 
         self.get_playlists()?;
         self.set_legal_songs();
@@ -178,9 +424,14 @@ impl UiState {
     }
 
     pub fn create_playlist_with_songs_popup(&mut self) {
+        if self.legal_songs.is_empty() {
+            return;
+        }
         self.show_popup(PopupType::Playlist(PlaylistAction::CreateWithSongs));
     }
 
+    /// A name already taken gets a number on the end rather than sending the
+    /// user back to think of another
     pub fn create_playlist_with_songs(&mut self) -> Result<()> {
         let name = self.get_popup_string();
 
@@ -188,14 +439,7 @@ impl UiState {
             bail!("Playlist name cannot be empty!");
         }
 
-        if self
-            .playlists
-            .values()
-            .any(|p| p.name.to_lowercase() == name.to_lowercase())
-        {
-            bail!("Playlist name already exists!");
-        }
-
+        let name = self.unused_playlist_name(name);
         self.db_worker.create_playlist(name)?;
         self.get_playlists()?;
 
@@ -222,4 +466,166 @@ impl UiState {
         self.close_popup();
         Ok(())
     }
+
+    /// `name`, or `name (2)`, `name (3)`... for the first that's free
+    pub(crate) fn unused_playlist_name(&self, name: String) -> String {
+        let taken = |n: &str| {
+            self.playlists
+                .values()
+                .any(|p| p.name.to_lowercase() == n.to_lowercase())
+        };
+        if !taken(&name) {
+            return name;
+        }
+        let mut i = 2;
+        while taken(&format!("{name} ({i})")) {
+            i += 1;
+        }
+        format!("{name} ({i})")
+    }
+}
+
+impl UiState {
+    pub(crate) fn selected_playlist_item(&self) -> Option<PlaylistItem> {
+        match self.selected_row()?.kind {
+            RowKind::Playlist(id) => Some(PlaylistItem::Playlist(id)),
+            RowKind::PlaylistFolder(id) => Some(PlaylistItem::Folder(id)),
+            _ => None,
+        }
+    }
+
+    /// Where the selected item can be moved: the top level, then every
+    /// folder in the order listed, with its depth. A folder can't go inside
+    /// itself, so it and everything under it are left out.
+    pub fn folder_targets(&self) -> Vec<(Option<i64>, usize)> {
+        let moving = match self.selected_playlist_item() {
+            Some(PlaylistItem::Folder(id)) => Some(id),
+            _ => None,
+        };
+        let children = |parent: Option<i64>, depth: usize| {
+            self.playlist_folders
+                .values()
+                .filter(move |f| f.parent == parent)
+                .map(move |f| (f.id, depth))
+                .collect::<Vec<_>>()
+        };
+
+        let mut targets = vec![(None, 0)];
+        let mut stack = children(None, 1);
+        stack.reverse();
+        while let Some((id, depth)) = stack.pop() {
+            if Some(id) == moving {
+                continue;
+            }
+            targets.push((Some(id), depth));
+            stack.extend(children(Some(id), depth + 1).into_iter().rev());
+        }
+        targets
+    }
+
+    pub fn move_playlist_popup(&mut self) {
+        let current = match self.selected_playlist_item() {
+            Some(PlaylistItem::Playlist(id)) => self.playlists.get(&id).map(|p| p.folder),
+            Some(PlaylistItem::Folder(id)) => self.playlist_folders.get(&id).map(|f| f.parent),
+            None => return,
+        };
+
+        let idx = self
+            .folder_targets()
+            .iter()
+            .position(|(folder, _)| Some(*folder) == current);
+        self.popup.selection.select(Some(idx.unwrap_or(0)));
+        self.show_popup(PopupType::Playlist(PlaylistAction::Move));
+    }
+
+    pub fn new_folder_popup(&mut self) {
+        self.show_popup(PopupType::Playlist(PlaylistAction::NewFolder));
+    }
+
+    fn chosen_folder(&self) -> Result<Option<i64>> {
+        let idx = self.popup.selection.selected().unwrap_or(0);
+        self.folder_targets()
+            .get(idx)
+            .map(|(folder, _)| *folder)
+            .ok_or_else(|| anyhow!("No folder selected!"))
+    }
+
+    pub fn move_playlist(&mut self) -> Result<()> {
+        let item = self
+            .selected_playlist_item()
+            .ok_or_else(|| anyhow!("No playlist selected!"))?;
+        let folder = self.chosen_folder()?;
+
+        self.db_worker.move_playlist_item(item, folder)?;
+        self.reveal_playlist_item(item, folder)?;
+        self.close_popup();
+        Ok(())
+    }
+
+    pub fn create_playlist_folder(&mut self) -> Result<()> {
+        let name = self.get_popup_string();
+        if name.is_empty() {
+            bail!("Folder name cannot be empty!");
+        }
+        if self.folder_name_taken(&name, None) {
+            bail!("Folder name already exists!");
+        }
+
+        let item = self
+            .selected_playlist_item()
+            .ok_or_else(|| anyhow!("No playlist selected!"))?;
+        let parent = self.chosen_folder()?;
+
+        let id = self.db_worker.create_playlist_folder(name, parent, item)?;
+        self.reveal_playlist_item(item, Some(id))?;
+        self.close_popup();
+        Ok(())
+    }
+
+    /// Reloads the playlists, then opens the way to `item` in `folder` and
+    /// selects it
+    pub(crate) fn reveal_playlist_item(
+        &mut self,
+        item: PlaylistItem,
+        mut folder: Option<i64>,
+    ) -> Result<()> {
+        self.get_playlists()?;
+
+        self.set_collapsed(NodeKey::Root(Root::Playlist), false);
+        while let Some(id) = folder {
+            self.set_collapsed(NodeKey::PlaylistFolder(id), false);
+            folder = self.playlist_folders.get(&id).and_then(|f| f.parent);
+        }
+
+        self.rebuild_rows();
+        self.select_by_key(&match item {
+            PlaylistItem::Playlist(id) => NodeKey::Playlist(id),
+            PlaylistItem::Folder(id) => NodeKey::PlaylistFolder(id),
+        });
+        self.set_legal_songs();
+        Ok(())
+    }
+
+    fn rename_playlist_folder(&mut self, id: i64) -> Result<()> {
+        let name = self.get_popup_string();
+        if name.is_empty() {
+            bail!("Folder name cannot be empty!");
+        }
+        if self.folder_name_taken(&name, Some(id)) {
+            bail!("Folder name already exists!");
+        }
+
+        self.db_worker.rename_playlist_folder(id, name)?;
+        self.get_playlists()?;
+        self.rebuild_rows();
+        self.close_popup();
+        Ok(())
+    }
+
+    fn folder_name_taken(&self, name: &str, except: Option<i64>) -> bool {
+        self.playlist_folders
+            .values()
+            .filter(|f| Some(f.id) != except)
+            .any(|f| f.name.to_lowercase() == name.to_lowercase())
+    }
 }
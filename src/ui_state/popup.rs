@@ -3,7 +3,10 @@ use ratatui_textarea::TextArea;
 
 use crate::{
     get_random_playlist_idea,
-    ui_state::{Pane, SettingsMode, UiState, new_textarea, playlist::PlaylistAction},
+    ui_state::{
+        CommandHint, DuplicateMode, Pane, SettingsMode, UiState, new_textarea,
+        playlist::PlaylistAction,
+    },
 };
 
 #[derive(PartialEq, Clone)]
@@ -11,10 +14,23 @@ pub enum PopupType {
     None,
     Stats,
     Error(String),
+    /// News that takes more than the buffer line to tell
+    Info(String),
     Settings(SettingsMode),
+    Duplicates(DuplicateMode),
     Playlist(PlaylistAction),
     ThemeManager,
     KeymapGuide,
+    /// Choosing which track table columns are shown
+    Columns,
+    /// Tuning how the spectrum's bars are drawn
+    VizSettings,
+    /// Typing a time to seek to, with the last input's problem if it had one
+    Seek(Option<String>),
+    /// Editing the selected tracks' tags, one field at a time
+    TagEdit,
+    /// The `:` command line, with what to show above it
+    Command(Option<CommandHint>),
 }
 
 pub struct PopupState {
@@ -43,6 +59,32 @@ impl PopupState {
                 self.input.set_placeholder_text(format!(" {placeholder} "));
                 self.input.clear();
             }
+            PopupType::Playlist(PlaylistAction::CreateSmart) => {
+                self.input.set_placeholder_text(" Late Night Jazz ");
+                self.input.clear();
+            }
+            PopupType::Playlist(PlaylistAction::SmartRules(_)) => {
+                self.input
+                    .set_placeholder_text(" artist has miles AND year >= 1960 ");
+                self.input.clear();
+            }
+            PopupType::Playlist(PlaylistAction::NewFolder) => {
+                self.input.set_placeholder_text(" Road Trips ");
+                self.input.clear();
+            }
+            PopupType::Playlist(PlaylistAction::Import) => {
+                self.input
+                    .set_placeholder_text(" ~/Music/playlists/road_trip.m3u ");
+                self.input.clear();
+            }
+            PopupType::Seek(_) => {
+                self.input.set_placeholder_text(" 1:23:45 ");
+                self.input.clear();
+            }
+            PopupType::Command(_) => {
+                self.input.set_placeholder_text("");
+                self.input.clear();
+            }
             PopupType::Settings(SettingsMode::ViewRoots) => {
                 self.input.clear();
             }
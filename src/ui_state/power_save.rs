@@ -0,0 +1,88 @@
+use crate::{gen_config, ui_state::UiState};
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+/// How often the power source is looked at, with `power_save_on_battery`
+const BATTERY_CHECK: Duration = Duration::from_secs(30);
+
+/// Power save stops the visualizers, leaving a plain progress bar and a
+/// slower main loop, while playback carries on as ever
+#[derive(Default)]
+pub struct PowerSave {
+    enabled: bool,
+    /// The power source when last checked, so only a change to it overrides
+    /// a toggle by hand
+    on_battery: Option<bool>,
+    checked_at: Option<Instant>,
+}
+
+impl UiState {
+    pub fn power_save(&self) -> bool {
+        self.power_save.enabled
+    }
+
+    pub fn set_power_save(&mut self, enabled: bool) {
+        if self.power_save.enabled && !enabled {
+            // Whatever the tap held from before is long out of date
+            self.viz.flush_tap();
+        }
+        self.power_save.enabled = enabled;
+    }
+
+    pub fn toggle_power_save(&mut self) {
+        self.set_power_save(!self.power_save.enabled);
+        let msg = match self.power_save.enabled {
+            true => "Power save on, visualizers paused",
+            false => "Power save off",
+        };
+        self.set_notice(msg.to_string());
+    }
+
+    /// Follows the power source, on battery meaning power save
+    pub fn sync_power_save(&mut self) {
+        if !gen_config().power_save_on_battery
+            || self
+                .power_save
+                .checked_at
+                .is_some_and(|t| t.elapsed() < BATTERY_CHECK)
+        {
+            return;
+        }
+        self.power_save.checked_at = Some(Instant::now());
+
+        let Some(on_battery) = on_battery() else {
+            return;
+        };
+        if self.power_save.on_battery != Some(on_battery) {
+            self.power_save.on_battery = Some(on_battery);
+            self.set_power_save(on_battery);
+        }
+    }
+}
+
+/// Running off a battery that's draining, where the system says so.
+/// Only Linux is asked, through sysfs; anywhere else it's never known.
+fn on_battery() -> Option<bool> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    let (mut battery, mut draining) = (false, false);
+    for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        let read = |file: &str| fs::read_to_string(path.join(file)).unwrap_or_default();
+
+        match read("type").trim() {
+            "Mains" if read("online").trim() == "1" => return Some(false),
+            // A wireless mouse's battery says nothing about the machine's
+            "Battery" if read("scope").trim() != "Device" => {
+                battery = true;
+                draining |= read("status").trim() == "Discharging";
+            }
+            _ => (),
+        }
+    }
+    battery.then_some(draining)
+}
@@ -0,0 +1,83 @@
+use crate::{
+    gen_config,
+    library::{SimpleSong, SongInfo},
+    ui_state::UiState,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// How often the position of a long track is written down while it plays.
+/// Resuming a few seconds early beats losing the place to a crash.
+const SAVE_EVERY: Duration = Duration::from_secs(5);
+
+/// The long track playing, whose place is kept for next time
+#[derive(Default)]
+pub struct ResumePoint {
+    song: Option<u64>,
+    path: PathBuf,
+    saved_at: Option<Instant>,
+}
+
+impl ResumePoint {
+    /// True if the file `path` is what's being kept track of
+    pub fn is_for(&self, path: &Path) -> bool {
+        self.song.is_some() && self.path == path
+    }
+}
+
+fn is_long(length: Duration) -> bool {
+    let mins = gen_config().resume_min_mins;
+    mins > 0 && length >= Duration::from_secs(mins as u64 * 60)
+}
+
+impl UiState {
+    /// Starts keeping the place of `song` if it's long enough, returning
+    /// where it was left last time
+    pub fn take_resume_point(&mut self, song: &SimpleSong, path: &Path) -> Option<Duration> {
+        self.resume = ResumePoint::default();
+        if !is_long(song.get_duration()) {
+            return None;
+        }
+
+        self.resume = ResumePoint {
+            song: Some(song.id),
+            path: path.to_path_buf(),
+            saved_at: Some(Instant::now()),
+        };
+        let secs = self.db_worker.get_resume_point(song.id).ok()??;
+        Some(Duration::from_secs_f64(secs)).filter(|at| *at < song.get_duration())
+    }
+
+    /// Called once a frame
+    pub fn update_resume_point(&mut self) {
+        let due = self
+            .resume
+            .saved_at
+            .is_none_or(|t| t.elapsed() >= SAVE_EVERY);
+        if due && !self.metrics.is_paused() {
+            self.save_resume_point();
+        }
+    }
+
+    pub fn save_resume_point(&mut self) {
+        let Some(id) = self.resume.song else {
+            return;
+        };
+        if !self.metrics.is_active() || self.get_now_playing().map(|s| s.id) != Some(id) {
+            return;
+        }
+
+        let (position, _) = self.track_progress();
+        self.db_worker.set_resume_point(id, position.as_secs_f64());
+        self.resume.saved_at = Some(Instant::now());
+    }
+
+    /// Played through to the end, so it starts over next time
+    pub fn finish_resume_point(&mut self) {
+        if let Some(id) = self.resume.song.take() {
+            self.db_worker.clear_resume_point(id);
+        }
+    }
+}
@@ -6,31 +6,149 @@ use crate::{
 use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 use ratatui::crossterm::event::KeyEvent;
 use ratatui_textarea::TextArea;
+use regex::{Regex, RegexBuilder};
 use std::{collections::HashMap, sync::Arc};
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 
 const MATCH_THRESHOLD: i64 = 80;
 const MATCH_LIMIT: usize = 1024;
+/// Keeps a pathological pattern from eating memory as it compiles
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
 
-#[derive(Copy, Clone)]
+const FUZZY_PLACEHOLDER: &str = "Enter search query";
+const REGEX_PLACEHOLDER: &str = "Enter regex (e.g. artist:^The )";
+
+#[derive(Copy, Clone, PartialEq)]
 pub enum MatchField {
     Title,
     Artist,
     Album,
 }
 
+/// The best scoring field of a result, and which of its characters matched
+pub struct SearchMatch {
+    pub field: MatchField,
+    pub indices: Vec<usize>,
+}
+
+/// A regex query: `field:pattern`, `field,field:pattern`, or a bare pattern
+/// tried against every field. Kept until the query text changes, since the
+/// results are refreshed far more often than the query is edited.
+struct RegexQuery {
+    source: String,
+    compiled: Result<(Vec<MatchField>, Regex), String>,
+}
+
+impl RegexQuery {
+    fn compile(source: &str) -> Self {
+        let (fields, pattern) = source
+            .split_once(':')
+            .and_then(|(names, pattern)| Some((parse_fields(names)?, pattern)))
+            .unwrap_or_else(|| {
+                let all = vec![MatchField::Title, MatchField::Artist, MatchField::Album];
+                (all, source)
+            });
+
+        let compiled = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map(|re| (fields, re))
+            // The full message draws a caret under the pattern over
+            // several lines; the last one says what's wrong
+            .map_err(|e| {
+                let message = e.to_string();
+                let last = message.lines().last().unwrap_or_default();
+                last.trim_start_matches("error: ").to_string()
+            });
+
+        RegexQuery {
+            source: source.to_string(),
+            compiled,
+        }
+    }
+}
+
+fn parse_fields(names: &str) -> Option<Vec<MatchField>> {
+    names
+        .split(',')
+        .map(|name| match name.trim().to_lowercase().as_str() {
+            "title" => Some(MatchField::Title),
+            "artist" => Some(MatchField::Artist),
+            "album" => Some(MatchField::Album),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lowercased, diacritic-free copies of the searchable fields. Built once
+/// per library rather than on every keystroke.
+struct SearchEntry {
+    song: Arc<SimpleSong>,
+    title: String,
+    artist: String,
+    album: String,
+}
+
 pub struct SearchState {
     pub input: TextArea<'static>,
     matcher: SkimMatcherV2,
-    pub(super) match_fields: HashMap<u64, MatchField>,
+    index: Vec<SearchEntry>,
+    pub(super) matches: HashMap<u64, SearchMatch>,
+    regex_mode: bool,
+    regex: Option<RegexQuery>,
 }
 
 impl SearchState {
     pub fn new() -> Self {
         SearchState {
-            input: new_textarea("Enter search query"),
-            matcher: SkimMatcherV2::default(),
-            match_fields: HashMap::new(),
+            input: new_textarea(FUZZY_PLACEHOLDER),
+            // Both sides are lowercased up front
+            matcher: SkimMatcherV2::default().respect_case(),
+            index: Vec::new(),
+            matches: HashMap::new(),
+            regex_mode: false,
+            regex: None,
+        }
+    }
+
+    pub fn is_regex(&self) -> bool {
+        self.regex_mode
+    }
+
+    pub fn toggle_regex(&mut self) {
+        self.regex_mode = !self.regex_mode;
+        let placeholder = match self.regex_mode {
+            true => REGEX_PLACEHOLDER,
+            false => FUZZY_PLACEHOLDER,
+        };
+        self.input.set_placeholder_text(format!(" {placeholder}: "));
+    }
+
+    /// Why the current regex doesn't compile, if it doesn't
+    pub fn regex_error(&self) -> Option<&str> {
+        match (self.regex_mode, &self.regex) {
+            (true, Some(query)) if query.source == self.get() => query.compiled.as_ref().err(),
+            _ => None,
         }
+        .map(String::as_str)
+    }
+
+    /// Dropped whenever the library changes, and rebuilt on the next search
+    pub(super) fn invalidate_index(&mut self) {
+        self.index.clear();
+    }
+
+    fn build_index(&mut self, songs: &[Arc<SimpleSong>]) {
+        self.index = songs
+            .iter()
+            .map(|song| SearchEntry {
+                song: Arc::clone(song),
+                title: strip_diacritics(song.get_title()),
+                artist: strip_diacritics(song.get_artist()),
+                album: strip_diacritics(song.get_album()),
+            })
+            .collect();
     }
 
     pub fn len(&self) -> usize {
@@ -52,60 +170,134 @@ impl UiState {
     // weight to the title field and returns the highest score.
     // Assuming the score is higher than the threshold, the
     // result is valid. Results are ordered by score.
+    //
+    // Only the results that make the cut are matched a second
+    // time for the positions to highlight.
     pub(crate) fn filter_songs_by_search(&mut self) {
-        let raw_search_str = self.search.get();
-        let query = strip_diacritics(raw_search_str);
+        if self.search.regex_mode {
+            return self.filter_songs_by_regex();
+        }
 
-        let mut scored_songs: Vec<(Arc<SimpleSong>, i64)> = self
-            .library
-            .get_all_songs()
+        let query = strip_diacritics(self.search.get());
+
+        if self.search.index.is_empty() {
+            self.search.build_index(&self.library.get_all_songs());
+        }
+
+        let matcher = &self.search.matcher;
+        let score = |key: &str, weight: f32| {
+            (matcher.fuzzy_match(key, &query).unwrap_or(0) as f32 * weight) as i64
+        };
+
+        let mut scored: Vec<(&SearchEntry, MatchField, i64)> = self
+            .search
+            .index
             .iter()
-            .filter_map(|song| {
-                let title_score = self
-                    .search
-                    .matcher
-                    .fuzzy_match(&strip_diacritics(song.get_title()), &query)
-                    .unwrap_or(0)
-                    * 2;
-
-                let artist_score = (self
-                    .search
-                    .matcher
-                    .fuzzy_match(&strip_diacritics(song.get_artist()), &query)
-                    .unwrap_or(0) as f32
-                    * 1.7) as i64;
-
-                let album_score = (self
-                    .search
-                    .matcher
-                    .fuzzy_match(&strip_diacritics(song.get_album()), &query)
-                    .unwrap_or(0) as f32
-                    * 1.7) as i64;
-
-                let weighted_score = [title_score, artist_score, album_score];
-                let best_score = weighted_score.iter().max().copied().unwrap_or(0);
-
-                if best_score > MATCH_THRESHOLD {
-                    let match_field = if best_score == title_score {
-                        MatchField::Title
-                    } else if best_score == artist_score {
-                        MatchField::Artist
-                    } else {
-                        MatchField::Album
-                    };
-                    self.search.match_fields.insert(song.get_id(), match_field);
-                }
+            .filter_map(|entry| {
+                // Listed last so it wins ties
+                let (field, best_score) = [
+                    (MatchField::Album, score(&entry.album, 1.7)),
+                    (MatchField::Artist, score(&entry.artist, 1.7)),
+                    (MatchField::Title, score(&entry.title, 2.0)),
+                ]
+                .into_iter()
+                .max_by_key(|(_, s)| *s)?;
+
+                (best_score > MATCH_THRESHOLD).then_some((entry, field, best_score))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.2.cmp(&a.2));
+        scored.truncate(MATCH_LIMIT);
+
+        let matches = scored
+            .iter()
+            .map(|(entry, field, _)| {
+                let (key, original) = match field {
+                    MatchField::Title => (&entry.title, entry.song.get_title()),
+                    MatchField::Artist => (&entry.artist, entry.song.get_artist()),
+                    MatchField::Album => (&entry.album, entry.song.get_album()),
+                };
+                let indices = matcher
+                    .fuzzy_indices(key, &query)
+                    .map(|(_, idx)| original_indices(original, &idx))
+                    .unwrap_or_default();
 
-                (best_score > MATCH_THRESHOLD).then(|| (Arc::clone(song), best_score))
+                (
+                    entry.song.get_id(),
+                    SearchMatch {
+                        field: *field,
+                        indices,
+                    },
+                )
             })
             .collect();
 
-        scored_songs.sort_by(|a, b| b.1.cmp(&a.1));
-        self.legal_songs = scored_songs
+        self.legal_songs = scored.iter().map(|(e, ..)| Arc::clone(&e.song)).collect();
+        self.search.matches = matches;
+    }
+
+    /// Results keep library order, as nothing ranks them. Matches are
+    /// tried against the fields as written, accents and all.
+    fn filter_songs_by_regex(&mut self) {
+        let source = self.search.get().to_string();
+        if self
+            .search
+            .regex
+            .as_ref()
+            .is_none_or(|q| q.source != source)
+        {
+            self.search.regex = Some(RegexQuery::compile(&source));
+        }
+
+        let Some(Ok((fields, re))) = self.search.regex.as_ref().map(|q| q.compiled.as_ref()) else {
+            self.legal_songs.clear();
+            self.search.matches.clear();
+            return;
+        };
+
+        let mut matches = HashMap::new();
+        self.legal_songs = self
+            .library
+            .get_all_songs()
             .into_iter()
-            .take(MATCH_LIMIT)
-            .map(|i| i.0)
+            .filter(|song| {
+                let hit = fields.iter().find_map(|&field| {
+                    let text = match field {
+                        MatchField::Title => song.get_title(),
+                        MatchField::Artist => song.get_artist(),
+                        MatchField::Album => song.get_album(),
+                    };
+                    re.find(text).map(|m| (field, text, m.range()))
+                });
+
+                let Some((field, text, range)) = hit else {
+                    return false;
+                };
+                let start = text[..range.start].chars().count();
+                let len = text[range].chars().count();
+                matches.insert(
+                    song.get_id(),
+                    SearchMatch {
+                        field,
+                        indices: (start..start + len).collect(),
+                    },
+                );
+                true
+            })
             .collect();
+
+        self.search.matches = matches;
+        self.sort_by_table_column();
+    }
+
+    pub fn toggle_search_regex(&mut self) {
+        self.search.toggle_regex();
+        self.set_legal_songs();
+        match self.legal_songs.is_empty() {
+            true => self.nav.table_pos.select(None),
+            false => self.nav.table_pos.select(Some(0)),
+        }
     }
 
     pub fn send_search(&mut self) {
@@ -124,7 +316,71 @@ impl UiState {
         }
     }
 
-    pub fn get_match_fields(&self, song_id: u64) -> Option<MatchField> {
-        self.search.match_fields.get(&song_id).copied()
+    pub fn get_search_match(&self, song_id: u64) -> Option<&SearchMatch> {
+        self.search.matches.get(&song_id)
+    }
+}
+
+/// Maps character positions in a folded key back onto the string it was
+/// made from. Folding the odd character yields more than one, so the two
+/// don't always line up.
+fn original_indices(original: &str, key_indices: &[usize]) -> Vec<usize> {
+    let positions = original
+        .chars()
+        .enumerate()
+        .flat_map(|(i, c)| {
+            let folded = c
+                .nfd()
+                .filter(|c| !is_combining_mark(*c))
+                .flat_map(char::to_lowercase)
+                .count();
+            std::iter::repeat_n(i, folded)
+        })
+        .collect::<Vec<_>>();
+
+    let mut indices = key_indices
+        .iter()
+        .filter_map(|&k| positions.get(k).copied())
+        .collect::<Vec<_>>();
+    indices.dedup();
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_land_on_the_original_characters() {
+        let matcher = SkimMatcherV2::default().respect_case();
+        // Hangul syllables fold into several jamo each
+        let original = "한국 Dáft Pünk";
+        let key = strip_diacritics(original);
+
+        let (_, idx) = matcher.fuzzy_indices(&key, "dft pnk").unwrap();
+        let chars = original.chars().collect::<Vec<_>>();
+        let hit = original_indices(original, &idx)
+            .iter()
+            .map(|&i| chars[i])
+            .collect::<String>();
+
+        assert_eq!(hit, "Dft Pnk");
+    }
+
+    #[test]
+    fn regex_queries_pick_their_fields() {
+        let (fields, re) = RegexQuery::compile("artist:^the ").compiled.unwrap();
+        assert!(matches!(fields[..], [MatchField::Artist]));
+        assert!(re.is_match("The Cure") && !re.is_match("Bathe "));
+
+        let (fields, _) = RegexQuery::compile("title,album:remix$").compiled.unwrap();
+        assert_eq!(fields.len(), 2);
+
+        // Not a field list, so the colon is part of the pattern
+        let (fields, re) = RegexQuery::compile("(?x:a b)").compiled.unwrap();
+        assert_eq!(fields.len(), 3);
+        assert!(re.is_match("ab"));
+
+        assert!(RegexQuery::compile("title:(unclosed").compiled.is_err());
     }
 }
@@ -0,0 +1,139 @@
+use crate::{
+    app_core::NoctaVox,
+    database::DuplicateGroup,
+    ui_state::{PopupType, UiState},
+};
+use anyhow::{Result, bail};
+use std::fs;
+
+#[derive(Default, PartialEq, Clone)]
+pub enum DuplicateMode {
+    #[default]
+    Review,
+    Delete,
+    DeleteAll,
+}
+
+/// Groups from the last search, and how many files have been deleted since.
+/// The library is rescanned once the review is closed.
+#[derive(Default)]
+pub struct DuplicateReview {
+    pub groups: Vec<DuplicateGroup>,
+    deleted: usize,
+}
+
+impl UiState {
+    pub fn get_duplicate_mode(&self) -> Option<&DuplicateMode> {
+        match &self.popup.current {
+            PopupType::Duplicates(mode) => Some(mode),
+            _ => None,
+        }
+    }
+
+    pub fn get_selected_duplicates(&self) -> Option<&DuplicateGroup> {
+        self.duplicates.groups.get(self.popup.selection.selected()?)
+    }
+
+    pub fn find_duplicates(&mut self) -> Result<()> {
+        let groups = self.db_worker.find_duplicates()?;
+        if groups.is_empty() {
+            bail!("No duplicates found!");
+        }
+
+        self.duplicates.groups = groups;
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::Duplicates(DuplicateMode::Review));
+
+        Ok(())
+    }
+
+    /// Hides the selected group from future searches
+    pub fn ignore_duplicates(&mut self) {
+        if let Some(idx) = self.popup.selection.selected()
+            && idx < self.duplicates.groups.len()
+        {
+            let group = self.duplicates.groups.remove(idx);
+            self.db_worker.ignore_duplicates(group.ids());
+            self.clamp_duplicate_selection();
+        }
+    }
+
+    pub fn review_duplicates(&mut self, mode: DuplicateMode) {
+        if mode == DuplicateMode::Review || !self.duplicates.groups.is_empty() {
+            self.show_popup(PopupType::Duplicates(mode));
+        }
+    }
+
+    /// Deletes every copy but the best from disk. Groups that couldn't be
+    /// fully cleaned up stay listed; the failed paths are returned.
+    fn delete_duplicates(&mut self, all: bool) -> Vec<String> {
+        let selected = self.popup.selection.selected();
+        let mut failed = Vec::new();
+
+        let mut idx = 0;
+        self.duplicates.groups.retain(|group| {
+            let targeted = all || selected == Some(idx);
+            idx += 1;
+            if !targeted {
+                return true;
+            }
+
+            let mut clean = true;
+            for extra in group.extras() {
+                match fs::remove_file(&extra.path) {
+                    Ok(()) => self.duplicates.deleted += 1,
+                    Err(e) => {
+                        failed.push(format!("{} ({e})", extra.path));
+                        clean = false;
+                    }
+                }
+            }
+            !clean
+        });
+
+        self.clamp_duplicate_selection();
+        failed
+    }
+
+    fn clamp_duplicate_selection(&mut self) {
+        let len = self.duplicates.groups.len();
+        match len {
+            0 => self.popup.selection.select(None),
+            _ => {
+                let idx = self.popup.selection.selected().unwrap_or(0).min(len - 1);
+                self.popup.selection.select(Some(idx));
+            }
+        }
+    }
+}
+
+impl NoctaVox {
+    pub(crate) fn confirm_duplicates(&mut self) -> Result<()> {
+        let all = match self.ui.get_duplicate_mode() {
+            Some(DuplicateMode::Delete) => false,
+            Some(DuplicateMode::DeleteAll) => true,
+            _ => return Ok(()),
+        };
+
+        let failed = self.ui.delete_duplicates(all);
+        self.ui
+            .show_popup(PopupType::Duplicates(DuplicateMode::Review));
+
+        if !failed.is_empty() {
+            self.close_duplicates()?;
+            bail!("Could not delete:\n\n{}", failed.join("\n"));
+        }
+        Ok(())
+    }
+
+    /// Deleted files are dropped from the library by a rescan
+    pub(crate) fn close_duplicates(&mut self) -> Result<()> {
+        self.ui.close_popup();
+        self.ui.duplicates.groups.clear();
+
+        if std::mem::take(&mut self.ui.duplicates.deleted) > 0 {
+            self.update_library()?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+mod duplicates;
 mod root_mgmt;
 
+pub use duplicates::{DuplicateMode, DuplicateReview};
+
 #[derive(Default, PartialEq, Clone)]
 pub enum SettingsMode {
     #[default]
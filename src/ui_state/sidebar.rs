@@ -2,13 +2,22 @@ use crate::{
     SimpleSong,
     ui_state::{AlbumSort, NodeKey, Pane, RowKind, SidebarRow, UiState, domain::Root},
 };
+use anyhow::Result;
 use indexmap::IndexMap;
 use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 use ratatui::widgets::ListState;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Deeper folders are still listed, but no further indented
+const MAX_FOLDER_DEPTH: u8 = 8;
 
 pub struct Sidebar {
     pub album_sort: AlbumSort,
+    /// Nodes folded the other way from their default
     pub collapsed: HashSet<NodeKey>,
     pub rows: Vec<SidebarRow>,
     pub pos: ListState,
@@ -30,8 +39,15 @@ impl Sidebar {
 }
 
 impl UiState {
-    pub(super) fn is_collapsed(&self, key: &NodeKey) -> bool {
-        self.nav.sidebar.collapsed.contains(key)
+    pub(crate) fn is_collapsed(&self, key: &NodeKey) -> bool {
+        self.nav.sidebar.collapsed.contains(key) != key.folded_by_default()
+    }
+
+    pub(super) fn set_collapsed(&mut self, key: NodeKey, collapsed: bool) {
+        match collapsed == key.folded_by_default() {
+            true => self.nav.sidebar.collapsed.remove(&key),
+            false => self.nav.sidebar.collapsed.insert(key),
+        };
     }
 
     pub fn get_selected_root(&self) -> Root {
@@ -45,6 +61,9 @@ impl UiState {
     pub(super) fn select_by_key(&mut self, key: &NodeKey) -> bool {
         match self.nav.sidebar.rows.iter().position(|r| r.key() == *key) {
             Some(i) => {
+                if self.nav.sidebar.pos.selected() != Some(i) {
+                    self.clear_multi_select();
+                }
                 self.nav.sidebar.pos.select(Some(i));
                 true
             }
@@ -52,6 +71,11 @@ impl UiState {
         }
     }
 
+    pub(super) fn load_genres(&mut self) -> Result<()> {
+        self.genres = self.db_worker.get_genres()?.into_iter().collect();
+        Ok(())
+    }
+
     pub(super) fn rebuild_rows(&mut self) {
         let keep = self.selected_row().map(|r| r.key());
 
@@ -80,12 +104,22 @@ impl UiState {
 
         rows.push(SidebarRow::new(RowKind::Category(Root::Playlist), 0));
         if !self.is_collapsed(&NodeKey::Root(Root::Playlist)) {
+            self.playlist_rows(None, 1, &mut rows);
+        }
+
+        rows.push(SidebarRow::new(RowKind::Category(Root::Genre), 0));
+        if !self.is_collapsed(&NodeKey::Root(Root::Genre)) {
             rows.extend(
-                self.playlists
-                    .values()
-                    .map(|p| SidebarRow::new(RowKind::Playlist(p.id), 1)),
+                self.genres
+                    .keys()
+                    .map(|g| SidebarRow::new(RowKind::Genre(Arc::clone(g)), 1)),
             );
         }
+
+        rows.push(SidebarRow::new(RowKind::Category(Root::Folder), 0));
+        if !self.is_collapsed(&NodeKey::Root(Root::Folder)) {
+            rows.extend(self.folder_rows());
+        }
         rows
     }
 
@@ -118,6 +152,87 @@ impl UiState {
         rows
     }
 
+    /// A folder's own folders by name, then its playlists
+    fn playlist_rows(&self, folder: Option<i64>, depth: u8, rows: &mut Vec<SidebarRow>) {
+        for sub in self
+            .playlist_folders
+            .values()
+            .filter(|f| f.parent == folder)
+        {
+            rows.push(SidebarRow::new(RowKind::PlaylistFolder(sub.id), depth));
+            if !self.is_collapsed(&NodeKey::PlaylistFolder(sub.id)) {
+                let child_depth = (depth + 1).min(MAX_FOLDER_DEPTH);
+                self.playlist_rows(Some(sub.id), child_depth, rows);
+            }
+        }
+
+        rows.extend(
+            self.playlists
+                .values()
+                .filter(|p| p.folder == folder)
+                .map(|p| SidebarRow::new(RowKind::Playlist(p.id), depth)),
+        );
+    }
+
+    /// Every playlist in the folder and below, in the order they're listed
+    pub(crate) fn playlists_under(&self, folder: i64) -> Vec<i64> {
+        let mut ids = Vec::new();
+        let mut stack = vec![folder];
+
+        while let Some(folder) = stack.pop() {
+            let subs = self
+                .playlist_folders
+                .values()
+                .filter(|f| f.parent == Some(folder));
+            stack.extend(subs.map(|f| f.id).collect::<Vec<_>>().into_iter().rev());
+            ids.extend(
+                self.playlists
+                    .values()
+                    .filter(|p| p.folder == Some(folder))
+                    .map(|p| p.id),
+            );
+        }
+        ids
+    }
+
+    fn folder_rows(&self) -> Vec<SidebarRow> {
+        let folders = &self.library.folders;
+        let mut rows = Vec::new();
+        let mut stack = folders
+            .top()
+            .iter()
+            .rev()
+            .map(|p| (Arc::clone(p), p.display().to_string(), 1))
+            .collect::<Vec<_>>();
+
+        while let Some((mut path, mut label, depth)) = stack.pop() {
+            while let Some(folder) = folders.get(&path)
+                && folder.songs.is_empty()
+                && let [only] = folder.subfolders.as_slice()
+            {
+                path = Arc::clone(only);
+                label = format!("{label}/{}", folder_name(&path));
+            }
+
+            let key = NodeKey::Folder(Arc::clone(&path));
+            if !self.is_collapsed(&key)
+                && let Some(folder) = folders.get(&path)
+            {
+                let child_depth = (depth + 1).min(MAX_FOLDER_DEPTH);
+                stack.extend(
+                    folder
+                        .subfolders
+                        .iter()
+                        .rev()
+                        .map(|p| (Arc::clone(p), folder_name(p), child_depth)),
+                );
+            }
+
+            rows.push(SidebarRow::new(RowKind::Folder { path, label }, depth));
+        }
+        rows
+    }
+
     pub(super) fn songs_for_row(&self, row: &SidebarRow) -> Vec<Arc<SimpleSong>> {
         match &row.kind {
             RowKind::Album(id) => self
@@ -142,19 +257,56 @@ impl UiState {
                 songs.shuffle(&mut rng);
                 songs
             }
+            RowKind::PlaylistFolder(id) => self
+                .playlists_under(*id)
+                .iter()
+                .filter_map(|id| self.playlists.get(id))
+                .flat_map(|p| p.get_tracklist())
+                .collect(),
             RowKind::Category(Root::Playlist) => self
                 .playlists
                 .iter()
                 .flat_map(|(_, p)| p.tracklist.iter().map(|s| Arc::clone(&s.song)))
                 .collect(),
+            RowKind::Genre(name) => self
+                .db_worker
+                .get_genre_songs(Arc::clone(name))
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| self.library.get_song_by_id(id).cloned())
+                .collect(),
+            RowKind::Category(Root::Genre) => self.library.get_all_songs(),
+            RowKind::Folder { path, .. } => self.folder_songs(&[Arc::clone(path)]),
+            RowKind::Category(Root::Folder) => self.folder_songs(self.library.folders.top()),
         }
     }
 
+    fn folder_songs(&self, dirs: &[Arc<PathBuf>]) -> Vec<Arc<SimpleSong>> {
+        dirs.iter()
+            .flat_map(|d| self.library.folders.songs_under(d))
+            .filter_map(|id| self.library.get_song_by_id(id).cloned())
+            .collect()
+    }
+
     fn parent_key_of(&self, row: &SidebarRow) -> Option<NodeKey> {
         match &row.kind {
+            // A chain of folders shown as one row has no single parent
+            // path, so the row above it stands in
+            RowKind::Folder { .. } => {
+                let idx = self.nav.sidebar.pos.selected()?;
+                self.nav.sidebar.rows[..idx]
+                    .iter()
+                    .rev()
+                    .find(|r| r.depth < row.depth)
+                    .map(|r| r.key())
+            }
             RowKind::Category(_) => None,
             RowKind::Artist { .. } => Some(NodeKey::Root(Root::Library)),
-            RowKind::Playlist { .. } => Some(NodeKey::Root(Root::Playlist)),
+            RowKind::Playlist(id) => Some(playlist_parent(self.playlists.get(id)?.folder)),
+            RowKind::PlaylistFolder(id) => {
+                Some(playlist_parent(self.playlist_folders.get(id)?.parent))
+            }
+            RowKind::Genre(_) => Some(NodeKey::Root(Root::Genre)),
             RowKind::Album(id) if row.depth == 2 => Some(NodeKey::Artist(Arc::clone(
                 &self.library.albums.get(id)?.artist,
             ))),
@@ -172,11 +324,15 @@ impl UiState {
             return;
         };
 
-        if !self.nav.sidebar.collapsed.remove(&key) {
-            self.nav.sidebar.collapsed.insert(key);
-            self.nav.sidebar.prev_folds.push(row);
-        } else {
-            self.nav.sidebar.prev_folds.pop();
+        match self.is_collapsed(&key) {
+            false => {
+                self.set_collapsed(key, true);
+                self.nav.sidebar.prev_folds.push(row);
+            }
+            true => {
+                self.set_collapsed(key, false);
+                self.nav.sidebar.prev_folds.pop();
+            }
         }
 
         self.rebuild_rows();
@@ -190,7 +346,7 @@ impl UiState {
 
         if let Some(key) = row.collapse_key() {
             if !self.is_collapsed(&key) {
-                self.nav.sidebar.collapsed.insert(key.clone());
+                self.set_collapsed(key, true);
                 self.nav.sidebar.prev_folds.push(row);
 
                 self.rebuild_rows();
@@ -204,7 +360,7 @@ impl UiState {
         };
 
         self.nav.sidebar.prev_folds.push(row.clone());
-        self.nav.sidebar.collapsed.insert(parent.clone());
+        self.set_collapsed(parent.clone(), true);
         self.rebuild_rows();
         self.select_by_key(&parent);
         self.set_legal_songs();
@@ -217,7 +373,7 @@ impl UiState {
 
         match row.collapse_key() {
             Some(key) if self.is_collapsed(&key) => {
-                self.nav.sidebar.collapsed.remove(&key);
+                self.set_collapsed(key, false);
 
                 self.rebuild_rows();
                 self.set_legal_songs();
@@ -233,7 +389,12 @@ impl UiState {
     }
 
     pub fn sidebar_expand_all(&mut self) {
-        self.nav.sidebar.collapsed.clear();
+        self.nav.sidebar.collapsed = self
+            .library
+            .folders
+            .paths()
+            .map(|p| NodeKey::Folder(Arc::new(p.clone())))
+            .collect();
         self.nav.sidebar.prev_folds.clear();
         self.rebuild_rows();
         self.set_legal_songs();
@@ -260,3 +421,17 @@ impl UiState {
         self.nav.sidebar.width = (self.nav.sidebar.width as isize + delta).clamp(8, 49) as u16;
     }
 }
+
+fn playlist_parent(folder: Option<i64>) -> NodeKey {
+    match folder {
+        Some(id) => NodeKey::PlaylistFolder(id),
+        None => NodeKey::Root(Root::Playlist),
+    }
+}
+
+fn folder_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+const PRESETS: [u64; 3] = [15, 30, 60];
+
+/// Length of the optional fade out before the timer fires
+const SLEEP_FADE: Duration = Duration::from_secs(30);
+
+/// Countdown to pausing playback. Polled once per frame from the run loop
+/// rather than run on its own thread, so setting, cancelling and restarting
+/// it is just replacing a deadline.
+#[derive(Default)]
+pub struct SleepTimer {
+    deadline: Option<Instant>,
+    preset: Option<usize>,
+}
+
+impl SleepTimer {
+    pub fn set(&mut self, minutes: u64) {
+        self.deadline = Some(Instant::now() + Duration::from_secs(minutes * 60));
+        self.preset = PRESETS.iter().position(|&p| p == minutes);
+    }
+
+    pub fn cancel(&mut self) {
+        self.deadline = None;
+        self.preset = None;
+    }
+
+    /// Off -> 15 -> 30 -> 60 -> Off. A custom duration cycles back to off.
+    pub fn next_preset(&mut self) {
+        let next = match (self.deadline, self.preset) {
+            (None, _) => Some(0),
+            (Some(_), Some(idx)) if idx + 1 < PRESETS.len() => Some(idx + 1),
+            _ => None,
+        };
+
+        match next {
+            Some(idx) => self.set(PRESETS[idx]),
+            None => self.cancel(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// Envelope for the final `SLEEP_FADE` of the countdown
+    pub fn fade_envelope(&self) -> f32 {
+        match self.remaining() {
+            Some(r) if r < SLEEP_FADE => r.as_secs_f32() / SLEEP_FADE.as_secs_f32(),
+            _ => 1.0,
+        }
+    }
+
+    /// True exactly once, on the frame the countdown reaches zero
+    pub fn expired(&mut self) -> bool {
+        if self.remaining() == Some(Duration::ZERO) {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
@@ -1,10 +1,53 @@
-use crate::{SimpleSong, ui_state::UiState};
+use crate::{EXPORT_DIR, SimpleSong, database::HistoryFormat, ui_state::UiState};
 use std::sync::Arc;
 
+/// Period the listening numbers in the stats popup cover. Periods follow
+/// the local calendar; weeks start on Monday.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum StatsWindow {
+    #[default]
+    AllTime,
+    Week,
+    Month,
+    Year,
+}
+
+impl StatsWindow {
+    pub fn next(self) -> Self {
+        match self {
+            StatsWindow::AllTime => StatsWindow::Week,
+            StatsWindow::Week => StatsWindow::Month,
+            StatsWindow::Month => StatsWindow::Year,
+            StatsWindow::Year => StatsWindow::AllTime,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatsWindow::AllTime => "All Time",
+            StatsWindow::Week => "This Week",
+            StatsWindow::Month => "This Month",
+            StatsWindow::Year => "This Year",
+        }
+    }
+
+    /// SQLite date modifiers that take local time back to the period start
+    fn modifiers(&self) -> Option<&'static str> {
+        match self {
+            StatsWindow::AllTime => None,
+            StatsWindow::Week => Some("'start of day', '-6 days', 'weekday 1'"),
+            StatsWindow::Month => Some("'start of month'"),
+            StatsWindow::Year => Some("'start of year'"),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct VoxStats {
+    pub window: StatsWindow,
     pub lib_stats: LibraryStats,
     pub top_played: Vec<(Arc<SimpleSong>, u16)>,
+    pub most_skipped: Vec<(Arc<SimpleSong>, u16)>,
 }
 
 #[derive(Default)]
@@ -19,6 +62,8 @@ pub struct LibraryStats {
     pub total_plays: u32,
     pub total_duration: f32,
     pub play_percentage: f32,
+    pub loved: u32,
+    pub total_skips: u32,
 }
 
 impl VoxStats {
@@ -29,25 +74,68 @@ impl VoxStats {
     pub fn get_most_played(&self) -> &[(Arc<SimpleSong>, u16)] {
         &self.top_played
     }
+
+    pub fn get_most_skipped(&self) -> &[(Arc<SimpleSong>, u16)] {
+        &self.most_skipped
+    }
 }
 
 impl UiState {
     pub fn show_stats_popup(&mut self) -> anyhow::Result<()> {
+        self.stats.window = StatsWindow::AllTime;
         self.update_stats()?;
         self.show_popup(super::PopupType::Stats);
 
         Ok(())
     }
 
+    /// Writes the listening history kept in the database to the exports folder
+    pub fn export_history(&mut self, format: HistoryFormat) -> anyhow::Result<()> {
+        let dest = EXPORT_DIR.join(format!("history.{}", format.extension()));
+        let count = self.db_worker.export_history(dest.clone(), format)?;
+
+        self.set_notice(format!(
+            "Exported {count} history entries to {}",
+            dest.display()
+        ));
+        Ok(())
+    }
+
+    pub fn cycle_stats_window(&mut self) -> anyhow::Result<()> {
+        self.stats.window = self.stats.window.next();
+        self.update_stats()
+    }
+
     fn update_stats(&mut self) -> anyhow::Result<()> {
         self.stats.lib_stats = self.db_worker.get_stats()?;
-        self.stats.top_played = self
-            .db_worker
-            .get_most_played(20)?
-            .into_iter()
-            .filter_map(|(id, plays)| self.library.get_song_by_id(id).cloned().map(|s| (s, plays)))
-            .collect::<Vec<_>>();
+        self.stats.most_skipped = self.resolve_counts(self.db_worker.get_most_skipped(10)?);
+
+        let top_played = match self.stats.window.modifiers() {
+            None => self.db_worker.get_most_played(20)?,
+            Some(modifiers) => {
+                let since = self.db_worker.get_local_period_start(modifiers)?;
+                let (total, unique) = self.db_worker.get_plays_since(since)?;
+
+                let lib_stats = &mut self.stats.lib_stats;
+                lib_stats.total_plays = total;
+                lib_stats.unique_plays = unique;
+                lib_stats.play_percentage = match lib_stats.total_tracks {
+                    0 => 0.0,
+                    n => unique as f32 * 100.0 / n as f32,
+                };
+
+                self.db_worker.get_most_played_since(since, 20)?
+            }
+        };
+        self.stats.top_played = self.resolve_counts(top_played);
 
         Ok(())
     }
+
+    fn resolve_counts(&self, counts: Vec<(u64, u16)>) -> Vec<(Arc<SimpleSong>, u16)> {
+        counts
+            .into_iter()
+            .filter_map(|(id, n)| self.library.get_song_by_id(id).cloned().map(|s| (s, n)))
+            .collect()
+    }
 }
@@ -0,0 +1,184 @@
+use crate::{
+    app_core::NoctaVox,
+    key_handler::Incrementor,
+    library::{SimpleSong, TagChange, TagField, write_tags},
+    ui_state::{PopupType, UiState},
+};
+use anyhow::{Result, bail};
+use std::{path::Path, sync::Arc};
+
+pub struct TagEntry {
+    pub field: TagField,
+    /// What every song had going in, or `None` where they differ
+    pub shared: Option<String>,
+    pub value: String,
+}
+
+impl TagEntry {
+    /// A differing field left blank is kept as it is on each song
+    fn change(&self) -> Option<TagChange> {
+        let value = self.value.trim();
+        match &self.shared {
+            Some(shared) if shared == value => None,
+            None if value.is_empty() => None,
+            _ => Some((self.field, value.to_string())),
+        }
+    }
+}
+
+/// The songs open in the tag editor, and each field as edited so far
+#[derive(Default)]
+pub struct TagEditor {
+    pub songs: Vec<Arc<SimpleSong>>,
+    pub entries: Vec<TagEntry>,
+}
+
+impl TagEditor {
+    fn changes(&self) -> Vec<TagChange> {
+        self.entries.iter().filter_map(TagEntry::change).collect()
+    }
+}
+
+impl UiState {
+    /// Edits the multi-selection, or the selected track when there is none
+    pub fn open_tag_editor(&mut self) -> Result<()> {
+        let songs = match self.multi_select_empty() {
+            true => vec![self.get_selected_song()?],
+            false => self.get_multi_select_songs(),
+        };
+        if songs.iter().any(|s| s.cue().is_some()) {
+            bail!("Tracks split by a cue sheet share one file, so can't be tagged");
+        }
+
+        let mut values = vec![Vec::new(); TagField::ALL.len()];
+        for song in &songs {
+            let album_artist = self
+                .library
+                .albums
+                .get(&song.album_id)
+                .map(|a| a.get_album_artist().to_string());
+            let genres = self.db_worker.get_song_genres(song.id)?.join("; ");
+
+            let song_values = [
+                song.title.clone(),
+                song.artist.to_string(),
+                song.album.to_string(),
+                album_artist.unwrap_or_default(),
+                song.year.map(|y| y.to_string()).unwrap_or_default(),
+                genres,
+                song.track_no.map(|t| t.to_string()).unwrap_or_default(),
+            ];
+            for (all, value) in values.iter_mut().zip(song_values) {
+                all.push(value);
+            }
+        }
+
+        let entries = TagField::ALL
+            .into_iter()
+            .zip(values)
+            .map(|(field, values)| {
+                let shared = values
+                    .iter()
+                    .all(|v| *v == values[0])
+                    .then(|| values[0].clone());
+                TagEntry {
+                    field,
+                    value: shared.clone().unwrap_or_default(),
+                    shared,
+                }
+            })
+            .collect();
+
+        self.tag_editor = TagEditor { songs, entries };
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::TagEdit);
+        self.load_tag_entry();
+        Ok(())
+    }
+
+    pub fn cycle_tag_entry(&mut self, dir: Incrementor) {
+        let len = self.tag_editor.entries.len();
+        let Some(idx) = self.popup.selection.selected() else {
+            return;
+        };
+
+        self.store_tag_entry();
+        let next = match dir {
+            Incrementor::Up => (idx + len - 1) % len,
+            Incrementor::Down => (idx + 1) % len,
+        };
+        self.popup.selection.select(Some(next));
+        self.load_tag_entry();
+    }
+
+    pub fn get_tag_entry(&self) -> Option<&TagEntry> {
+        self.tag_editor
+            .entries
+            .get(self.popup.selection.selected()?)
+    }
+
+    fn store_tag_entry(&mut self) {
+        let value = self.popup.input.lines()[0].to_string();
+        if let Some(idx) = self.popup.selection.selected()
+            && let Some(entry) = self.tag_editor.entries.get_mut(idx)
+        {
+            entry.value = value;
+        }
+    }
+
+    fn load_tag_entry(&mut self) {
+        let (value, placeholder) = match self.get_tag_entry() {
+            Some(entry) if entry.shared.is_none() => (entry.value.clone(), " (various) "),
+            Some(entry) => (entry.value.clone(), ""),
+            None => return,
+        };
+
+        self.popup.input.set_placeholder_text(placeholder);
+        self.popup.input.clear();
+        self.popup.input.insert_str(value);
+    }
+}
+
+impl NoctaVox {
+    /// Writes the changed fields to every file, then rescans so the library
+    /// holds what actually made it to disk
+    pub(crate) fn confirm_tag_edit(&mut self) -> Result<()> {
+        self.ui.store_tag_entry();
+        let editor = std::mem::take(&mut self.ui.tag_editor);
+        self.ui.close_popup();
+
+        let changes = editor.changes();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut written = 0;
+        let mut failed = Vec::new();
+        for song in &editor.songs {
+            let result = self
+                .ui
+                .db_worker
+                .get_song_path(song.id)
+                .and_then(|path| write_tags(Path::new(&path), &changes));
+            match result {
+                Ok(()) => written += 1,
+                Err(e) => failed.push(format!("{e:#}")),
+            }
+        }
+
+        if written > 0 {
+            match self.ui.library_refresh.is_none() {
+                true => self.update_library()?,
+                // Asking for another scan would cancel the one under way
+                false => self.ui.set_notice(String::from(
+                    "Tags saved. Refresh the library once the scan is done to see them.",
+                )),
+            }
+        }
+
+        if !failed.is_empty() {
+            bail!("Could not tag:\n\n{}", failed.join("\n"));
+        }
+        Ok(())
+    }
+}
@@ -1,19 +1,27 @@
-use super::{AlbumSort, Mode, Pane, UiState};
+use super::{AlbumSort, Mode, Pane, Root, TableColumn, TableSort, UiState};
 use crate::{
+    config::StartupView,
+    gen_config,
+    library::SimpleSong,
+    theme::SpectrumColoring,
     ui_state::{LayoutStyle, NodeKey, PlayerSnapshot},
-    visualization::ProgressDisplay,
+    visualization::{AmplitudeScale, BarGap, GradientSpeed, ProgressDisplay, WindowFn},
 };
 use anyhow::Result;
+use std::sync::Arc;
 
 #[derive(Default)]
 pub struct UiSnapshot {
     pub mode: String,
     pub pane: String,
     pub album_sort: String,
+    pub table_sort: String,
+    pub table_columns: String,
     pub sidebar_percentage: u16,
 
     pub layout: String,
     pub theme_name: String,
+    pub theme_manual: bool,
 
     pub song_selection: Option<usize>,
     pub song_sel_offset: usize,
@@ -24,6 +32,23 @@ pub struct UiSnapshot {
 
     pub progress_display: String,
     pub smoothing_factor: f32,
+    pub fft_size: usize,
+    pub window_fn: String,
+    pub amplitude_scale: String,
+    pub stereo_scope: bool,
+    /// Unset for snapshots from before it could be turned off
+    pub scope_trigger: Option<bool>,
+    pub viz_gain: f32,
+    pub viz_auto_gain: bool,
+    pub gradient_speed: String,
+    pub spectrum_peaks: bool,
+    /// The per-band auto-gain bypassed
+    pub spectrum_raw: bool,
+    pub spectrum_mirror: String,
+    pub spectrum_coloring: String,
+    pub spectrum_min_height: String,
+    pub spectrum_gap: String,
+    pub spectrum_decay: String,
 }
 
 impl UiSnapshot {
@@ -32,11 +57,32 @@ impl UiSnapshot {
             ("ui_mode", self.mode.clone()),
             ("ui_pane", self.pane.clone()),
             ("ui_album_sort", self.album_sort.clone()),
+            ("ui_table_sort", self.table_sort.clone()),
+            ("ui_table_columns", self.table_columns.clone()),
             ("ui_theme", self.theme_name.clone()),
+            ("ui_theme_manual", self.theme_manual.to_string()),
             ("ui_layout", self.layout.clone()),
             ("ui_smooth", format!("{:.1}", self.smoothing_factor)),
             ("ui_sidebar_percent", self.sidebar_percentage.to_string()),
             ("ui_progress_display", self.progress_display.clone()),
+            ("ui_fft_size", self.fft_size.to_string()),
+            ("ui_window_fn", self.window_fn.clone()),
+            ("ui_amplitude_scale", self.amplitude_scale.clone()),
+            ("ui_stereo_scope", self.stereo_scope.to_string()),
+            (
+                "ui_scope_trigger",
+                self.scope_trigger.unwrap_or(true).to_string(),
+            ),
+            ("ui_viz_gain", format!("{:.3}", self.viz_gain)),
+            ("ui_viz_auto_gain", self.viz_auto_gain.to_string()),
+            ("ui_gradient_speed", self.gradient_speed.clone()),
+            ("ui_spectrum_peaks", self.spectrum_peaks.to_string()),
+            ("ui_spectrum_raw", self.spectrum_raw.to_string()),
+            ("ui_spectrum_mirror", self.spectrum_mirror.clone()),
+            ("ui_spectrum_coloring", self.spectrum_coloring.clone()),
+            ("ui_spectrum_min_height", self.spectrum_min_height.clone()),
+            ("ui_spectrum_gap", self.spectrum_gap.clone()),
+            ("ui_spectrum_decay", self.spectrum_decay.clone()),
             ("ui_sidebar_key", self.sidebar_key.clone()),
             ("ui_sidebar_offset", self.sidebar_offset.to_string()),
             ("ui_sidebar_collapsed", self.sidebar_collapsed.clone()),
@@ -59,8 +105,11 @@ impl UiSnapshot {
                 "ui_pane" => snapshot.pane = value,
                 "ui_progress_display" => snapshot.progress_display = value,
                 "ui_theme" => snapshot.theme_name = value,
+                "ui_theme_manual" => snapshot.theme_manual = value == "true",
                 "ui_layout" => snapshot.layout = value,
                 "ui_album_sort" => snapshot.album_sort = value,
+                "ui_table_sort" => snapshot.table_sort = value,
+                "ui_table_columns" => snapshot.table_columns = value,
                 "ui_sidebar_key" => snapshot.sidebar_key = value,
                 "ui_sidebar_offset" => {
                     snapshot.sidebar_offset = value.parse::<usize>().unwrap_or(0)
@@ -68,6 +117,21 @@ impl UiSnapshot {
                 "ui_sidebar_collapsed" => snapshot.sidebar_collapsed = value,
                 "ui_song_pos" => snapshot.song_selection = value.parse().ok(),
                 "ui_song_offset" => snapshot.song_sel_offset = value.parse::<usize>().unwrap_or(0),
+                "ui_window_fn" => snapshot.window_fn = value,
+                "ui_amplitude_scale" => snapshot.amplitude_scale = value,
+                "ui_stereo_scope" => snapshot.stereo_scope = value == "true",
+                "ui_scope_trigger" => snapshot.scope_trigger = Some(value == "true"),
+                "ui_viz_gain" => snapshot.viz_gain = value.parse::<f32>().unwrap_or(1.0),
+                "ui_viz_auto_gain" => snapshot.viz_auto_gain = value == "true",
+                "ui_gradient_speed" => snapshot.gradient_speed = value,
+                "ui_spectrum_peaks" => snapshot.spectrum_peaks = value == "true",
+                "ui_spectrum_raw" => snapshot.spectrum_raw = value == "true",
+                "ui_spectrum_mirror" => snapshot.spectrum_mirror = value,
+                "ui_spectrum_coloring" => snapshot.spectrum_coloring = value,
+                "ui_spectrum_min_height" => snapshot.spectrum_min_height = value,
+                "ui_spectrum_gap" => snapshot.spectrum_gap = value,
+                "ui_spectrum_decay" => snapshot.spectrum_decay = value,
+                "ui_fft_size" => snapshot.fft_size = value.parse::<usize>().unwrap_or(0),
                 "ui_smooth" => snapshot.smoothing_factor = value.parse::<f32>().unwrap_or(1.0),
                 "ui_sidebar_percent" => {
                     snapshot.sidebar_percentage = value.parse::<u16>().unwrap_or(30)
@@ -92,9 +156,12 @@ impl UiState {
             mode: self.get_mode().to_string(),
             pane: pane.to_string(),
             album_sort: self.nav.sidebar.album_sort.to_string(),
+            table_sort: self.nav.table_sort.to_string(),
+            table_columns: TableColumn::join(&self.nav.columns),
             sidebar_percentage: self.nav.sidebar.width,
 
             theme_name: self.theme.active.name.to_owned(),
+            theme_manual: self.theme.manual,
             layout: self.layout.to_string(),
 
             song_selection: self.nav.table_pos.selected(),
@@ -118,12 +185,49 @@ impl UiState {
 
             progress_display: self.viz.get_progress_display().to_string(),
             smoothing_factor: self.viz.get_smoothing_factor(),
+            fft_size: self.viz.spectrum().fft_size(),
+            window_fn: self.viz.spectrum().window_fn().to_string(),
+            amplitude_scale: self.viz.spectrum().scale().to_string(),
+            stereo_scope: self.viz.stereo_scope_enabled(),
+            scope_trigger: Some(self.viz.scope_trigger_enabled()),
+            viz_gain: self.viz.gain().manual_factor(),
+            viz_auto_gain: self.viz.gain().is_auto(),
+            gradient_speed: self.viz.gradient_speed().to_string(),
+            spectrum_peaks: self.viz.spectrum().peaks_enabled(),
+            spectrum_raw: !self.viz.spectrum().auto_gain_enabled(),
+            spectrum_mirror: self
+                .viz
+                .spectrum_mirror_override()
+                .map(|m| m.to_string())
+                .unwrap_or_default(),
+            spectrum_coloring: self
+                .viz
+                .spectrum_coloring_override()
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            spectrum_min_height: self
+                .viz
+                .spectrum_look()
+                .min_height_override()
+                .map(|h| format!("{h:.2}"))
+                .unwrap_or_default(),
+            spectrum_gap: self
+                .viz
+                .spectrum_look()
+                .gap_override()
+                .map(|g| g.to_string())
+                .unwrap_or_default(),
+            spectrum_decay: self
+                .viz
+                .spectrum_decay_override()
+                .map(|d| format!("{d:.2}"))
+                .unwrap_or_default(),
         }
     }
 
     pub fn create_player_snapshot(&self) -> PlayerSnapshot {
         PlayerSnapshot {
-            volume: self.metrics.volume(),
+            volume: self.gain.volume(),
         }
     }
 
@@ -134,11 +238,31 @@ impl UiState {
         Ok(())
     }
 
+    /// The playing track and its position already live in `now_playing`,
+    /// so only the queue behind it needs storing
+    pub fn save_session(&mut self) -> Result<()> {
+        self.update_now_playing_elapsed();
+        self.save_resume_point();
+        self.db_worker.save_queue(self.playback.queued_ids())
+    }
+
+    /// The saved queue, less any songs that have left the library since
+    pub fn load_session(&self) -> Result<Vec<Arc<SimpleSong>>> {
+        let songs = self
+            .db_worker
+            .load_queue()?
+            .into_iter()
+            .filter_map(|id| self.library.get_song_by_id(id).map(Arc::clone))
+            .collect();
+
+        Ok(songs)
+    }
+
     pub fn restore_last_state(&mut self) -> Result<()> {
         let player_snap = PlayerSnapshot::from_values(self.db_worker.load_snapshot("player_%")?);
 
         let vol = player_snap.volume;
-        self.metrics.set_volume(vol);
+        self.gain.set_volume(vol);
 
         let ui_pairs = self.db_worker.load_snapshot("ui_%")?;
         if ui_pairs.is_empty() {
@@ -153,6 +277,7 @@ impl UiState {
         {
             self.set_theme(theme.clone());
         }
+        self.theme.manual = ui_snapshot.theme_manual;
 
         self.nav.sidebar.collapsed = ui_snapshot
             .sidebar_collapsed
@@ -161,6 +286,8 @@ impl UiState {
             .collect();
         self.nav.sidebar.album_sort = AlbumSort::from_str(&ui_snapshot.album_sort);
         self.sort_albums();
+        self.nav.table_sort = TableSort::parse_key(&ui_snapshot.table_sort);
+        self.nav.columns = TableColumn::split(&ui_snapshot.table_columns);
         *self.nav.sidebar.pos.offset_mut() = ui_snapshot.sidebar_offset;
 
         let pane_to_restore = match ui_snapshot.pane.as_str() {
@@ -171,14 +298,56 @@ impl UiState {
         self.set_mode(Mode::Library);
         self.set_pane(Pane::from_str(pane_to_restore));
 
-        if let Some(k) = NodeKey::deserialize(&ui_snapshot.sidebar_key) {
-            self.select_by_key(&k);
+        // A playlist deleted since leaves the library selected instead
+        let found =
+            NodeKey::deserialize(&ui_snapshot.sidebar_key).is_some_and(|k| self.select_by_key(&k));
+        if !found {
+            self.select_by_key(&NodeKey::Root(Root::Library));
         }
 
         self.set_legal_songs();
 
         self.viz.set_smoothing_factor(ui_snapshot.smoothing_factor);
 
+        if ui_snapshot.fft_size != 0 {
+            self.viz.set_fft_size(ui_snapshot.fft_size);
+        }
+        self.viz
+            .set_window_fn(WindowFn::from(ui_snapshot.window_fn.as_str()));
+        self.viz
+            .spectrum_mut()
+            .set_scale(AmplitudeScale::from(ui_snapshot.amplitude_scale.as_str()));
+        self.viz.set_stereo_scope(ui_snapshot.stereo_scope);
+        if let Some(trigger) = ui_snapshot.scope_trigger {
+            self.viz.set_scope_trigger(trigger);
+        }
+        self.viz
+            .set_gradient_speed(GradientSpeed::from(ui_snapshot.gradient_speed.as_str()));
+        if ui_snapshot.viz_gain > 0.0 {
+            self.viz
+                .gain_mut()
+                .restore(ui_snapshot.viz_gain, ui_snapshot.viz_auto_gain);
+        }
+        self.viz
+            .spectrum_mut()
+            .set_show_peaks(ui_snapshot.spectrum_peaks);
+        self.viz
+            .spectrum_mut()
+            .set_auto_gain(!ui_snapshot.spectrum_raw);
+        self.viz
+            .set_spectrum_mirror(ui_snapshot.spectrum_mirror.parse::<bool>().ok());
+        self.viz.set_spectrum_coloring(
+            (!ui_snapshot.spectrum_coloring.is_empty())
+                .then(|| SpectrumColoring::from(ui_snapshot.spectrum_coloring.as_str())),
+        );
+        self.viz
+            .set_spectrum_decay(ui_snapshot.spectrum_decay.parse::<f32>().ok());
+        self.viz.spectrum_look_mut().restore(
+            ui_snapshot.spectrum_min_height.parse::<f32>().ok(),
+            (!ui_snapshot.spectrum_gap.is_empty())
+                .then(|| BarGap::from(ui_snapshot.spectrum_gap.as_str())),
+        );
+
         self.viz
             .set_progress_display(ProgressDisplay::from_str(&ui_snapshot.progress_display));
 
@@ -194,3 +363,41 @@ impl UiState {
         Ok(())
     }
 }
+
+impl UiState {
+    /// Switches to the configured view, once the library and queue are back.
+    /// The library view is what `restore_last_state` already leaves up.
+    pub fn open_startup_view(&mut self) {
+        match gen_config().startup_view {
+            StartupView::Library => (),
+            StartupView::Playlists => {
+                self.go_to(Root::Playlist);
+                self.set_pane(Pane::SideBar);
+            }
+            StartupView::Last => {
+                let saved = self
+                    .db_worker
+                    .load_snapshot("ui_mode")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .next()
+                    .map(|(_, mode)| Mode::from_str(&mode));
+
+                // Views left empty stay on the library, rather than opening
+                // on an error
+                let empty = match saved {
+                    Some(Mode::Power) => false,
+                    Some(Mode::Queue) => self.playback.queue_is_empty(),
+                    Some(Mode::Loved) => {
+                        !self.library.get_songs_map().values().any(|s| s.is_loved())
+                    }
+                    Some(Mode::Recent) => self.recently_played().is_empty(),
+                    _ => true,
+                };
+                if let Some(mode) = saved.filter(|_| !empty) {
+                    self.set_mode(mode);
+                }
+            }
+        }
+    }
+}
@@ -2,13 +2,30 @@ use super::{DisplayState, search_state::SearchState};
 use crate::{
     Library, PlaybackSession,
     database::DbWorker,
+    gen_config,
     key_handler::{Incrementor, InputContext, KeyBuffer},
     library::{SimpleSong, SongInfo},
+    playback::ShuffleMode,
+    player::{Output, OutputGain},
     theme::ThemeConfig,
+    tui,
     ui_state::{
-        LayoutStyle, Mode, Pane, PlaylistAction, SettingsMode, ThemeManager, UiState,
+        AbLoop, LayoutStyle, Mode, Pane, PlaylistAction, SettingsMode, SleepTimer, ThemeManager,
+        UiState,
+        album_art::AlbumArt,
+        artist_info::ArtistState,
+        idle::IdleScreen,
+        lyrics::LyricsState,
+        marquee::Marquee,
+        mouse::MouseState,
+        play_tally::PlayTally,
         popup::{PopupState, PopupType},
+        power_save::PowerSave,
+        resume::ResumePoint,
+        settings::DuplicateReview,
         stats::VoxStats,
+        tag_editor::TagEditor,
+        undo::UndoStack,
     },
     visualization::Visualizer,
 };
@@ -16,11 +33,29 @@ use anyhow::anyhow;
 use anyhow::{Error, Result};
 use indexmap::IndexMap;
 use ratatui::widgets::Borders;
-use std::{sync::Arc, time::Duration};
-use voxio::{TapHandle, Vox};
+use std::{
+    collections::HashMap,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use voxio::TapHandle;
+
+/// Longest a seek target stands in for the engine's position
+const SEEK_HOLD: Duration = Duration::from_millis(500);
+/// Close enough to the target to count the seek as done
+const SEEK_LANDED: Duration = Duration::from_millis(250);
+/// How long a notice stays on the buffer line
+const NOTICE_DURATION: Duration = Duration::from_secs(4);
 
 impl UiState {
-    pub fn new(library: Arc<Library>, metrics: Arc<Vox>, tap: TapHandle) -> Self {
+    pub fn new(
+        library: Arc<Library>,
+        metrics: Arc<Output>,
+        tap: Option<TapHandle>,
+        gain: Rc<OutputGain>,
+    ) -> Self {
         UiState {
             library,
             db_worker: DbWorker::new()
@@ -29,10 +64,28 @@ impl UiState {
             nav: DisplayState::new(),
             search: SearchState::new(),
             playback: PlaybackSession::init(),
+            sleep_timer: SleepTimer::default(),
+            ab_loop: AbLoop::default(),
 
             stats: VoxStats::default(),
+            duplicates: DuplicateReview::default(),
+            tag_editor: TagEditor::default(),
+            undo: UndoStack::default(),
             metrics: Arc::clone(&metrics),
-            viz: Visualizer::new(metrics, tap),
+            gain,
+            viz: Visualizer::new(metrics, tap, gen_config().tap_capacity),
+            art: AlbumArt::new(),
+            lyrics: LyricsState::default(),
+            artist_pane: ArtistState::default(),
+            mouse: MouseState::default(),
+            marquee: Marquee::default(),
+            play_tally: PlayTally::default(),
+            resume: ResumePoint::default(),
+            power_save: PowerSave::default(),
+            idle: IdleScreen::default(),
+            beat_detected: false,
+            seek_hold: None,
+            notice: None,
 
             popup: PopupState::new(),
             layout: LayoutStyle::Traditional,
@@ -42,9 +95,15 @@ impl UiState {
 
             albums: Vec::new(),
             playlists: IndexMap::new(),
+            playlist_folders: IndexMap::new(),
+            genres: IndexMap::new(),
+            play_counts: HashMap::new(),
             legal_songs: Vec::new(),
             legal_songs_dur: Duration::default(),
             shuffle_seed: rand::random::<u64>(),
+            shuffle_mode: ShuffleMode::default(),
+            radio: false,
+            source_rate: None,
 
             library_refresh: None,
         }
@@ -58,8 +117,10 @@ impl UiState {
 
     pub fn sync_library(&mut self, library: Arc<Library>) -> Result<()> {
         self.library = library;
+        self.search.invalidate_index();
 
         self.get_playlists()?;
+        self.load_genres()?;
         self.sort_albums();
         self.set_legal_songs();
 
@@ -70,6 +131,23 @@ impl UiState {
         self.show_popup(PopupType::Error(e.to_string()));
     }
 
+    /// For news that isn't an error, which goes by without needing closing
+    pub fn set_notice(&mut self, notice: String) {
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    /// Like a notice, for news that needs more than one line
+    pub fn set_info(&mut self, info: String) {
+        self.show_popup(PopupType::Info(info));
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, t)| t.elapsed() < NOTICE_DURATION)
+            .map(|(n, _)| n.as_str())
+    }
+
     pub fn soft_reset(&mut self) {
         if self.popup.is_open() {
             self.close_popup();
@@ -117,23 +195,153 @@ impl UiState {
                     PopupType::Playlist(PlaylistAction::CreateWithSongs)
                 )
                 | (Pane::Popup, PopupType::Playlist(PlaylistAction::Rename))
+                | (
+                    Pane::Popup,
+                    PopupType::Playlist(PlaylistAction::CreateSmart)
+                )
+                | (
+                    Pane::Popup,
+                    PopupType::Playlist(PlaylistAction::SmartRules(_))
+                )
+                | (Pane::Popup, PopupType::Playlist(PlaylistAction::Import))
+                | (Pane::Popup, PopupType::Playlist(PlaylistAction::NewFolder))
+                | (Pane::Popup, PopupType::Seek(_))
+                | (Pane::Popup, PopupType::TagEdit)
+                | (Pane::Popup, PopupType::Command(_))
         )
     }
 }
 
 impl UiState {
+    /// The library's song stored at `path`
+    pub fn song_at_path(&self, path: &Path) -> Result<Arc<SimpleSong>> {
+        self.db_worker
+            .get_song_id_by_path(path.to_path_buf())?
+            .and_then(|id| self.library().get_song_by_id(id).cloned())
+            .ok_or_else(|| anyhow!("{} is not in the library", path.display()))
+    }
+
     pub fn set_now_playing(&mut self, song: Option<Arc<SimpleSong>>) {
         match &song {
             Some(s) => self.db_worker.set_now_playing_db(s.get_id()),
             None => self.db_worker.clear_now_playing(),
         }
+        self.source_rate = song
+            .as_ref()
+            .and_then(|s| self.db_worker.get_sample_rate(s.get_id()).ok().flatten());
         self.playback.set_now_playing(song);
+        self.apply_measured_gain();
+    }
+
+    /// Songs the engine has no ReplayGain tags for are brought to the same
+    /// level from their measured loudness, once it's known
+    pub(crate) fn apply_measured_gain(&self) {
+        let gain = self.get_now_playing().and_then(|s| {
+            self.db_worker
+                .get_measured_gain(s.get_id(), self.playback.replay_gain())
+                .ok()
+                .flatten()
+        });
+        self.gain.set_loudness(gain.unwrap_or(1.0));
+    }
+
+    /// Play counts, only looked up for the mode that uses them
+    pub(crate) fn shuffle_plays(&self) -> HashMap<u64, u16> {
+        match self.shuffle_mode {
+            ShuffleMode::Weighted => self.db_worker.get_play_counts().unwrap_or_default(),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Every song's play count, for radio to favor the rarely heard
+    pub(crate) fn radio_plays(&self) -> HashMap<u64, u16> {
+        self.db_worker.get_play_counts().unwrap_or_default()
     }
 
     pub fn get_now_playing(&self) -> Option<&Arc<SimpleSong>> {
         self.playback.get_now_playing()
     }
 
+    /// Rates the multi-selection, or the selected track when there is none
+    pub fn rate_selection(&mut self, rating: usize) -> Result<()> {
+        let songs = match self.multi_select_empty() {
+            true => vec![self.get_selected_song()?],
+            false => self.get_multi_select_songs(),
+        };
+
+        for song in &songs {
+            self.rate_song(song, rating);
+        }
+        self.refresh_smart_playlists()
+    }
+
+    pub fn rate_now_playing(&mut self, rating: usize) -> Result<()> {
+        let song = self
+            .get_now_playing()
+            .cloned()
+            .ok_or_else(|| anyhow!("Nothing is playing!"))?;
+
+        self.rate_song(&song, rating);
+        self.refresh_smart_playlists()
+    }
+
+    fn rate_song(&self, song: &Arc<SimpleSong>, rating: usize) {
+        song.set_rating(rating.min(SimpleSong::MAX_RATING as usize) as u8);
+        self.db_worker.set_rating(song.id, song.get_rating());
+    }
+
+    /// Rating rules may have changed what smart playlists contain
+    fn refresh_smart_playlists(&mut self) -> Result<()> {
+        if !self.playlists.values().any(|p| p.is_smart()) {
+            return Ok(());
+        }
+
+        self.get_playlists()?;
+        if self.get_selected_playlist().is_some_and(|p| p.is_smart()) {
+            self.set_legal_songs();
+        }
+        Ok(())
+    }
+
+    /// Loves the selection, or unloves it if every song in it is loved already
+    pub fn toggle_love_selection(&mut self) -> Result<()> {
+        let songs = match self.multi_select_empty() {
+            true => vec![self.get_selected_song()?],
+            false => self.get_multi_select_songs(),
+        };
+
+        let loved = !songs.iter().all(|s| s.is_loved());
+        for song in &songs {
+            self.love_song(song, loved);
+        }
+        self.refresh_loved_view();
+        Ok(())
+    }
+
+    pub fn toggle_love_now_playing(&mut self) -> Result<()> {
+        let song = self
+            .get_now_playing()
+            .cloned()
+            .ok_or_else(|| anyhow!("Nothing is playing!"))?;
+
+        self.love_song(&song, !song.is_loved());
+        self.refresh_loved_view();
+        Ok(())
+    }
+
+    fn love_song(&self, song: &Arc<SimpleSong>, loved: bool) {
+        song.set_loved(loved);
+        self.db_worker.set_loved(song.id, loved);
+    }
+
+    /// Unloved songs drop out of the loved view straight away
+    fn refresh_loved_view(&mut self) {
+        if self.get_mode() == Mode::Loved {
+            self.clear_multi_select();
+            self.set_legal_songs();
+        }
+    }
+
     pub fn swap_layout(&mut self) {
         match self.layout {
             LayoutStyle::Traditional => self.layout = LayoutStyle::Minimal,
@@ -146,6 +354,10 @@ impl UiState {
         self.playback.push_history(song);
     }
 
+    pub fn record_skip(&self, song: &Arc<SimpleSong>) {
+        self.db_worker.update_skip_count(song.id);
+    }
+
     pub fn delete_last_history_entry(&self) {
         self.db_worker.delete_history_latest();
     }
@@ -154,6 +366,42 @@ impl UiState {
         self.db_worker.get_last_played()
     }
 
+    /// Shows `target` as the position until the engine catches up with the
+    /// seek, so the timer and progress bar move on the keypress
+    pub fn hold_seek_position(&mut self, target: Duration) {
+        self.seek_hold = Some((target, Instant::now()));
+    }
+
+    /// The engine's position, or a seek target it hasn't reached yet
+    pub fn displayed_position(&self) -> Duration {
+        let position = self.metrics.position();
+        match self.seek_hold {
+            Some((target, at))
+                if at.elapsed() < SEEK_HOLD && position.abs_diff(target) > SEEK_LANDED =>
+            {
+                target
+            }
+            _ => position,
+        }
+    }
+
+    /// Position and length of the playing track. A cue track is a stretch
+    /// of a larger file, so both are measured from where it starts.
+    pub fn track_progress(&self) -> (Duration, Duration) {
+        let elapsed = self.displayed_position();
+        let duration = self.metrics.duration();
+
+        match self.get_now_playing().and_then(|s| s.cue()) {
+            Some(cue) => {
+                let start = Duration::from_secs_f64(cue.start);
+                let end = cue.end.map_or(duration, Duration::from_secs_f64);
+                let length = end.saturating_sub(start);
+                (elapsed.saturating_sub(start).min(length), length)
+            }
+            None => (elapsed, duration),
+        }
+    }
+
     pub fn update_now_playing_elapsed(&self) {
         let elapsed = self.metrics.position().as_secs_f32();
         self.db_worker.update_now_playing(elapsed);
@@ -166,10 +414,20 @@ impl UiState {
     pub fn set_theme(&mut self, theme: ThemeConfig) {
         self.theme.cached_focused = ThemeManager::set_display_theme(&theme, true);
         self.theme.cached_unfocused = ThemeManager::set_display_theme(&theme, false);
-        self.viz.spectrum_mut().set_decay(theme.spectrum.decay);
+        self.viz.set_theme_decay(theme.spectrum.decay);
         self.theme.active = theme;
     }
 
+    pub fn toggle_spectrum_mirror(&mut self) {
+        let theme_default = self.theme.get_display_theme(true).spectrum.mirror;
+        self.viz.toggle_spectrum_mirror(theme_default);
+    }
+
+    pub fn toggle_spectrum_coloring(&mut self) {
+        let theme_default = self.theme.get_display_theme(true).spectrum.coloring;
+        self.viz.toggle_spectrum_coloring(theme_default);
+    }
+
     pub fn refresh_current_theme(&mut self) {
         self.theme.update_themes();
 
@@ -182,6 +440,57 @@ impl UiState {
                 "Formatting error in theme!\n\nFalling back to last loaded"
             )),
         }
+
+        // Names the file at fault, where the message above can't
+        if let Some(warning) = self.theme.skipped_warning() {
+            self.set_error(warning);
+        }
+    }
+
+    /// Swaps to the built-in theme that suits the terminal's background,
+    /// unless a theme was picked by hand or the current one already suits
+    pub fn match_terminal_background(&mut self) {
+        if self.theme.manual {
+            return;
+        }
+        let Some(light) = tui::query_is_light() else {
+            return;
+        };
+        if self.theme.active.is_dark != light {
+            return;
+        }
+
+        let default = match light {
+            true => ThemeConfig::default_light(),
+            false => ThemeConfig::default(),
+        };
+        let theme = self
+            .theme
+            .find_theme_by_name(&default.name)
+            .cloned()
+            .unwrap_or(default);
+        self.set_theme(theme);
+    }
+
+    /// Called once per frame. With hot reload on, picks up edits to the
+    /// theme files, keeping the current theme if its file stops loading.
+    pub fn watch_themes(&mut self) {
+        if !gen_config().theme_hot_reload || !self.theme.poll_changes() {
+            return;
+        }
+
+        self.theme.update_themes();
+        if let Some(theme) = self.theme.find_theme_by_name(&self.theme.active.name) {
+            self.set_theme(theme.clone());
+        }
+
+        if let Some(first) = self.theme.skipped.first() {
+            let notice = match self.theme.skipped.len() {
+                1 => format!("Invalid theme, {first}"),
+                n => format!("Invalid theme, {first} (+{} more)", n - 1),
+            };
+            self.theme.set_notice(notice);
+        }
     }
 
     pub fn open_theme_manager(&mut self) {
@@ -202,11 +511,16 @@ impl UiState {
         self.show_popup(PopupType::KeymapGuide);
     }
 
+    pub fn show_seek_popup(&mut self) {
+        self.show_popup(PopupType::Seek(None));
+    }
+
     pub fn cycle_theme(&mut self, dir: Incrementor) {
         let len = self.theme.theme_lib.len();
         if len < 2 {
             return;
         }
+        self.theme.manual = true;
 
         let idx = self.theme.get_current_theme_index().unwrap_or(0);
         let new_idx = match dir {
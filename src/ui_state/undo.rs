@@ -0,0 +1,92 @@
+use crate::{
+    database::PlaylistItem, library::SimpleSong, playback::ValidatedSong, ui_state::UiState,
+};
+use anyhow::{Result, bail};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+/// Older actions fall off the bottom. Nothing is kept past the session.
+const UNDO_DEPTH: usize = 10;
+
+/// What a destructive action took away, with enough to put it back
+pub enum UndoEntry {
+    /// Songs taken out of a playlist, by position and song id
+    PlaylistSongs {
+        playlist_id: i64,
+        songs: Vec<(i64, u64)>,
+    },
+    Playlist {
+        id: i64,
+        name: String,
+        rules: Option<String>,
+        folder: Option<i64>,
+        songs: Vec<u64>,
+    },
+    /// The queue when playback was stopped, and the song that was playing
+    /// with how far into it
+    Queue {
+        queue: Vec<Arc<ValidatedSong>>,
+        playing: Option<(Arc<SimpleSong>, Duration)>,
+    },
+}
+
+#[derive(Default)]
+pub struct UndoStack(VecDeque<UndoEntry>);
+
+impl UndoStack {
+    pub fn push(&mut self, entry: UndoEntry) {
+        if self.0.len() == UNDO_DEPTH {
+            self.0.pop_front();
+        }
+        self.0.push_back(entry);
+    }
+
+    pub fn pop(&mut self) -> Option<UndoEntry> {
+        self.0.pop_back()
+    }
+
+    /// A deleted playlist comes back under a new id, which anything removed
+    /// from it before then has to follow
+    fn remap_playlist(&mut self, old: i64, new: i64) {
+        for entry in &mut self.0 {
+            if let UndoEntry::PlaylistSongs { playlist_id, .. } = entry
+                && *playlist_id == old
+            {
+                *playlist_id = new;
+            }
+        }
+    }
+}
+
+impl UiState {
+    pub(crate) fn undo_playlist(&mut self, entry: UndoEntry) -> Result<()> {
+        match entry {
+            UndoEntry::PlaylistSongs { playlist_id, songs } => {
+                if !self.playlists.contains_key(&playlist_id) {
+                    bail!("The playlist those songs came from is gone!");
+                }
+                self.db_worker.restore_playlist_songs(playlist_id, songs)?;
+                self.get_playlists()?;
+            }
+            UndoEntry::Playlist {
+                id,
+                name,
+                rules,
+                folder,
+                songs,
+            } => {
+                let name = self.unused_playlist_name(name);
+                let new_id = self
+                    .db_worker
+                    .restore_playlist(name, rules, folder, songs)?;
+                self.undo.remap_playlist(id, new_id);
+
+                let folder = folder.filter(|f| self.playlist_folders.contains_key(f));
+                self.reveal_playlist_item(PlaylistItem::Playlist(new_id), folder)?;
+            }
+            UndoEntry::Queue { .. } => (),
+        }
+
+        self.set_legal_songs();
+        Ok(())
+    }
+}
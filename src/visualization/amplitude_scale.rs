@@ -0,0 +1,49 @@
+/// Lowest level the logarithmic scale shows, at the bottom of the pane
+const LOG_FLOOR_DB: f32 = -60.0;
+
+/// How spectrum magnitudes map to bar height. Shared by every view drawn
+/// from the spectrum bins.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum AmplitudeScale {
+    #[default]
+    Linear,
+    /// Decibels from `LOG_FLOOR_DB` up to the band's recent peak, which
+    /// lifts quiet detail at the cost of contrast
+    Log,
+}
+
+impl AmplitudeScale {
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::Linear => Self::Log,
+            Self::Log => Self::Linear,
+        }
+    }
+
+    /// `level` is a magnitude relative to its band's peak, from 0 to 1
+    pub fn apply(&self, level: f32) -> f32 {
+        match self {
+            Self::Linear => level,
+            Self::Log if level <= 0.0 => 0.0,
+            Self::Log => (1.0 - 20.0 * level.log10() / LOG_FLOOR_DB).clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl From<&str> for AmplitudeScale {
+    fn from(s: &str) -> Self {
+        match s {
+            "log" => Self::Log,
+            _ => Self::Linear,
+        }
+    }
+}
+
+impl std::fmt::Display for AmplitudeScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmplitudeScale::Linear => write!(f, "linear"),
+            AmplitudeScale::Log => write!(f, "log"),
+        }
+    }
+}
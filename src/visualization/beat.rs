@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+/// How far back the rolling average of bass energy reaches, in seconds
+const AVERAGE_WINDOW: f32 = 1.0;
+/// A beat is bass energy this many times over the rolling average
+const THRESHOLD: f32 = 1.5;
+/// Quieter than this is left alone, so near-silence doesn't flicker
+const MIN_ENERGY: f32 = 1e-4;
+/// No two beats closer than this, roughly 240 BPM
+const REFRACTORY: Duration = Duration::from_millis(250);
+/// How long a beat keeps the pulse lit, long enough to be seen at any
+/// framerate
+const PULSE: Duration = Duration::from_millis(120);
+
+/// Onset detection on the spectrum's low bands
+#[derive(Default)]
+pub struct BeatDetector {
+    average: f32,
+    last_update: Option<Instant>,
+    last_beat: Option<Instant>,
+}
+
+impl BeatDetector {
+    /// Feed the bass energy of the newest spectrum, true on a beat
+    pub fn update(&mut self, energy: f32) -> bool {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map(|t| now.duration_since(t).as_secs_f32())
+            .unwrap_or(0.0);
+        self.last_update = Some(now);
+
+        let rested = self.last_beat.is_none_or(|t| now - t >= REFRACTORY);
+        let beat = rested && energy > MIN_ENERGY && energy > self.average * THRESHOLD;
+        if beat {
+            self.last_beat = Some(now);
+        }
+
+        let blend = 1.0 - (-dt / AVERAGE_WINDOW).exp();
+        self.average += (energy - self.average) * blend;
+
+        beat
+    }
+
+    /// Whether the last beat is recent enough to still be shown
+    pub fn is_pulsing(&self) -> bool {
+        self.last_beat.is_some_and(|t| t.elapsed() < PULSE)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
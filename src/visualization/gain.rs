@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+/// Past these the oscilloscope is either a flat line or a wall of clipping
+const MIN_GAIN: f32 = 0.25;
+const MAX_GAIN: f32 = 8.0;
+/// Each step multiplies or divides the gain by this much
+const GAIN_STEP: f32 = 1.25;
+
+/// Auto-gain scales the recent peak up to this much of the available height
+const AUTO_TARGET: f32 = 0.9;
+/// How much of the recent peak is left after a second without a louder one
+const PEAK_HALF_LIFE: f32 = 0.5;
+/// Quieter than this is treated as silence, which shouldn't be blown up
+const SILENCE: f32 = 1e-3;
+
+/// How long the factor stays on screen after being changed
+const LABEL_DURATION: Duration = Duration::from_millis(1500);
+
+/// Display-only amplitude scaling for the oscilloscope and waveform. Output
+/// volume is left alone.
+pub struct VizGain {
+    factor: f32,
+    auto: bool,
+    peak: f32,
+    last_update: Option<Instant>,
+    changed_at: Option<Instant>,
+}
+
+impl Default for VizGain {
+    fn default() -> Self {
+        VizGain {
+            factor: 1.0,
+            auto: false,
+            peak: 0.0,
+            last_update: None,
+            changed_at: None,
+        }
+    }
+}
+
+impl VizGain {
+    /// Puts back a saved setting, without flashing it on screen
+    pub fn restore(&mut self, factor: f32, auto: bool) {
+        self.factor = factor.clamp(MIN_GAIN, MAX_GAIN);
+        self.auto = auto;
+    }
+
+    pub fn raise(&mut self) {
+        self.set_factor(self.factor * GAIN_STEP);
+    }
+
+    pub fn lower(&mut self) {
+        self.set_factor(self.factor / GAIN_STEP);
+    }
+
+    pub fn set_factor(&mut self, factor: f32) {
+        self.factor = factor.clamp(MIN_GAIN, MAX_GAIN);
+        self.auto = false;
+        self.changed_at = Some(Instant::now());
+    }
+
+    /// The manual factor, which auto-gain leaves alone for when it's
+    /// switched back off
+    pub fn manual_factor(&self) -> f32 {
+        self.factor
+    }
+
+    pub fn is_auto(&self) -> bool {
+        self.auto
+    }
+
+    pub fn toggle_auto(&mut self) {
+        self.auto = !self.auto;
+        self.changed_at = Some(Instant::now());
+    }
+
+    /// The gain for live samples, following the recent peak when auto-gain
+    /// is on
+    pub fn factor(&self) -> f32 {
+        match self.auto {
+            true if self.peak > SILENCE => (AUTO_TARGET / self.peak).clamp(MIN_GAIN, MAX_GAIN),
+            true => 1.0,
+            false => self.factor,
+        }
+    }
+
+    /// Feed the peak of the samples tapped since the last call
+    pub fn update(&mut self, peak: f32) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map(|t| now.duration_since(t).as_secs_f32())
+            .unwrap_or(0.0);
+        self.last_update = Some(now);
+
+        self.peak = peak.max(self.peak * PEAK_HALF_LIFE.powf(dt));
+    }
+
+    pub fn reset(&mut self) {
+        self.peak = 0.0;
+        self.last_update = None;
+    }
+
+    /// What to show over the visualizer, shortly after a change
+    pub fn label(&self) -> Option<String> {
+        self.changed_at
+            .filter(|t| t.elapsed() < LABEL_DURATION)
+            .map(|_| match self.auto {
+                true => String::from("gain auto"),
+                false => format!("gain ×{:.2}", self.factor),
+            })
+    }
+}
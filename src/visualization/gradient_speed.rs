@@ -0,0 +1,58 @@
+/// How fast the spectrum and oscilloscope scroll their theme gradient,
+/// relative to each widget's own rate. Frozen holds the gradient in place,
+/// still spread across the widget.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum GradientSpeed {
+    Frozen,
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    Faster,
+}
+
+impl GradientSpeed {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Normal => Self::Fast,
+            Self::Fast => Self::Faster,
+            Self::Faster => Self::Frozen,
+            Self::Frozen => Self::Slow,
+            Self::Slow => Self::Normal,
+        }
+    }
+
+    pub fn factor(&self) -> f32 {
+        match self {
+            Self::Frozen => 0.0,
+            Self::Slow => 0.5,
+            Self::Normal => 1.0,
+            Self::Fast => 2.0,
+            Self::Faster => 4.0,
+        }
+    }
+}
+
+impl From<&str> for GradientSpeed {
+    fn from(s: &str) -> Self {
+        match s {
+            "frozen" => Self::Frozen,
+            "slow" => Self::Slow,
+            "fast" => Self::Fast,
+            "faster" => Self::Faster,
+            _ => Self::Normal,
+        }
+    }
+}
+
+impl std::fmt::Display for GradientSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GradientSpeed::Frozen => write!(f, "frozen"),
+            GradientSpeed::Slow => write!(f, "slow"),
+            GradientSpeed::Normal => write!(f, "normal"),
+            GradientSpeed::Fast => write!(f, "fast"),
+            GradientSpeed::Faster => write!(f, "faster"),
+        }
+    }
+}
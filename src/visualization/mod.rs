@@ -1,33 +1,104 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use voxio::{TapHandle, Vox};
+use voxio::TapHandle;
 
+use crate::{key_handler::Incrementor, player::Output, theme::SpectrumColoring, user_config};
+
+mod amplitude_scale;
+mod beat;
+mod gain;
+mod gradient_speed;
 mod progress_display;
+mod spectrogram;
 mod spectrum;
+mod spectrum_look;
+mod tap_feed;
+mod vu_meter;
 mod waveform;
+mod window_fn;
 
+pub use amplitude_scale::AmplitudeScale;
+pub use beat::BeatDetector;
+pub use gain::VizGain;
+pub use gradient_speed::GradientSpeed;
 pub use progress_display::ProgressDisplay;
+pub use spectrogram::Spectrogram;
 pub use spectrum::SpectrumState;
+pub use spectrum_look::{BarGap, LookSetting, MAX_MIN_HEIGHT, SpectrumLook};
+pub use tap_feed::{TapChunk, TapFeed, TapSubscriber};
+pub use vu_meter::VuMeter;
 pub use waveform::WaveformManager;
+pub use window_fn::WindowFn;
+
+/// Bounds on how many frames of history the oscilloscope and spectrum keep.
+/// More gives the FFT a longer window for steadier, finer bars, but the
+/// picture then averages over audio from further back, so it lags behind
+/// what's heard, and each redraw has more samples to work through. Less
+/// reacts sooner and costs less, at the price of jumpier bars.
+pub const MIN_TAP_CAPACITY: usize = 512;
+pub const MAX_TAP_CAPACITY: usize = 16384;
 
-pub const TAP_BUFFER_CAPACITY: usize = 2048;
+/// How long the decay stays on screen after being changed
+const DECAY_LABEL_DURATION: Duration = Duration::from_millis(1500);
 
 pub struct Visualizer {
-    metrics: Arc<Vox>,
-    tap: TapHandle,
+    metrics: Arc<Output>,
+    /// Missing until the engine has a device to run on
+    tap: Option<TapHandle>,
+    tap_capacity: usize,
+    feed: TapFeed,
     display_tap: VecDeque<f32>,
+    stereo_tap: VecDeque<[f32; 2]>,
+    stereo_scope: bool,
+    scope_trigger: bool,
+    gain: VizGain,
+    gradient_speed: GradientSpeed,
+    spectrum_mirror: Option<bool>,
+    spectrum_coloring: Option<SpectrumColoring>,
+    spectrum_decay: Option<f32>,
+    decay_changed_at: Option<Instant>,
+    agc_changed_at: Option<Instant>,
     spectrum: SpectrumState,
+    spectrum_look: SpectrumLook,
+    /// Whether the spectrum has seen the latest tap, so the widgets and
+    /// beat detection can share one FFT a frame
+    spectrum_fresh: bool,
+    beat: BeatDetector,
+    spectrogram: Spectrogram,
+    vu_meter: VuMeter,
     waveform: WaveformManager,
     mode: ProgressDisplay,
 }
 
 impl Visualizer {
-    pub fn new(metrics: Arc<Vox>, tap: TapHandle) -> Self {
+    pub fn new(metrics: Arc<Output>, tap: Option<TapHandle>, tap_capacity: usize) -> Self {
+        let tap_capacity = tap_capacity.clamp(MIN_TAP_CAPACITY, MAX_TAP_CAPACITY);
         Visualizer {
             metrics,
             tap,
-            display_tap: VecDeque::with_capacity(TAP_BUFFER_CAPACITY),
-            spectrum: SpectrumState::default(),
+            tap_capacity,
+            feed: TapFeed::default(),
+            display_tap: VecDeque::with_capacity(tap_capacity),
+            stereo_tap: VecDeque::with_capacity(tap_capacity),
+            stereo_scope: false,
+            scope_trigger: true,
+            gain: VizGain::default(),
+            gradient_speed: GradientSpeed::default(),
+            spectrum_mirror: None,
+            spectrum_coloring: None,
+            spectrum_decay: None,
+            decay_changed_at: None,
+            agc_changed_at: None,
+            spectrum: SpectrumState::new(tap_capacity),
+            spectrum_look: SpectrumLook::default(),
+            spectrum_fresh: false,
+            beat: BeatDetector::default(),
+            spectrogram: Spectrogram::default(),
+            vu_meter: VuMeter::new(user_config().general.vu_window_ms),
             waveform: WaveformManager::new(),
             mode: ProgressDisplay::Spectrum,
         }
@@ -41,34 +112,320 @@ impl Visualizer {
         &mut self.spectrum
     }
 
+    pub fn spectrum_look(&self) -> &SpectrumLook {
+        &self.spectrum_look
+    }
+
+    pub fn spectrum_look_mut(&mut self) -> &mut SpectrumLook {
+        &mut self.spectrum_look
+    }
+
+    pub fn gain(&self) -> &VizGain {
+        &self.gain
+    }
+
+    pub fn gain_mut(&mut self) -> &mut VizGain {
+        &mut self.gain
+    }
+
+    pub fn shift_gain(&mut self, direction: Incrementor) {
+        match direction {
+            Incrementor::Up => self.gain.raise(),
+            Incrementor::Down => self.gain.lower(),
+        }
+    }
+
+    pub fn gradient_speed(&self) -> GradientSpeed {
+        self.gradient_speed
+    }
+
+    pub fn set_gradient_speed(&mut self, speed: GradientSpeed) {
+        self.gradient_speed = speed
+    }
+
+    pub fn next_gradient_speed(&mut self) {
+        self.gradient_speed = self.gradient_speed.next()
+    }
+
+    /// Playback time as the gradient scroll sees it, with `rate` the
+    /// widget's own scroll rate
+    pub fn gradient_time(&self, rate: f32) -> f32 {
+        self.metrics.position().as_secs_f32() * rate * self.gradient_speed.factor()
+    }
+
+    pub fn spectrogram(&self) -> &Spectrogram {
+        &self.spectrogram
+    }
+
+    /// Feeds the spectrogram from the same bins the spectrum view uses, so
+    /// switching between the two never runs a second FFT.
+    pub fn update_spectrogram(&mut self, width: usize, height: usize, playing: bool) {
+        self.spectrogram.resize(width, height);
+        if playing {
+            self.update_spectrum();
+            self.spectrum.remap_display(width);
+            self.spectrogram.push(self.spectrum.get_display_bins());
+        }
+    }
+
+    pub fn vu_meter(&self) -> &VuMeter {
+        &self.vu_meter
+    }
+
+    /// The latest frames, as many as the display window covers
     pub fn display_tap(&mut self) -> &[f32] {
-        self.display_tap.make_contiguous()
+        let len = self.window_frames().unwrap_or(usize::MAX);
+        let samples = self.display_tap.make_contiguous();
+        &samples[samples.len().saturating_sub(len)..]
+    }
+
+    /// Per-channel frames, only populated for stereo output
+    pub fn stereo_tap(&mut self) -> &[[f32; 2]] {
+        let len = self.window_frames().unwrap_or(usize::MAX);
+        let frames = self.stereo_tap.make_contiguous();
+        &frames[frames.len().saturating_sub(len)..]
+    }
+
+    /// The frames `viz_window_ms` spans at the current rate, so the
+    /// oscilloscope and spectrum look at the same stretch of time whatever
+    /// the file. None leaves it to `tap_capacity` and the FFT size.
+    fn window_frames(&self) -> Option<usize> {
+        let ms = user_config().general.viz_window_ms as usize;
+        let rate = self.metrics.sample_rate() as usize;
+        match ms > 0 && rate > 0 {
+            true => Some((ms * rate / 1000).clamp(1, MAX_TAP_CAPACITY)),
+            false => None,
+        }
+    }
+
+    pub fn stereo_scope_enabled(&self) -> bool {
+        self.stereo_scope
+    }
+
+    pub fn set_stereo_scope(&mut self, enabled: bool) {
+        self.stereo_scope = enabled
+    }
+
+    pub fn toggle_stereo_scope(&mut self) {
+        self.stereo_scope = !self.stereo_scope
+    }
+
+    pub fn scope_trigger_enabled(&self) -> bool {
+        self.scope_trigger
+    }
+
+    pub fn set_scope_trigger(&mut self, enabled: bool) {
+        self.scope_trigger = enabled
+    }
+
+    pub fn toggle_scope_trigger(&mut self) {
+        self.scope_trigger = !self.scope_trigger
+    }
+
+    pub fn subscribe_tap(&mut self) -> TapSubscriber {
+        self.feed.subscribe()
+    }
+
+    pub fn set_tap(&mut self, tap: TapHandle) {
+        self.tap = Some(tap);
     }
 
     pub fn flush_tap(&mut self) {
         self.display_tap.clear();
-        self.tap.latest(usize::MAX);
+        self.stereo_tap.clear();
+        self.spectrogram.clear();
+        self.vu_meter.reset();
+        self.gain.reset();
+        self.beat.reset();
+        if let Some(tap) = self.tap.as_mut() {
+            tap.latest(usize::MAX);
+        }
         self.spectrum_mut().reset();
     }
 
     pub fn fill_tap(&mut self) {
         let channels = self.metrics.channels();
 
-        let latest = self.tap.latest(TAP_BUFFER_CAPACITY * channels);
+        // Larger FFT sizes need more history than the oscilloscope does.
+        // The window is worked out afresh each frame, so a new rate only
+        // changes how much is kept from here on.
+        let scope_capacity = self.window_frames().unwrap_or(self.tap_capacity);
+        let capacity = scope_capacity.max(self.spectrum.fft_size());
+
+        let Some(latest) = self.tap.as_mut().map(|t| t.latest(capacity * channels)) else {
+            return;
+        };
+        self.feed
+            .publish(latest, channels, self.metrics.sample_rate());
+        let stereo = channels == 2 && self.stereo_scope;
+        if !stereo {
+            self.stereo_tap.clear();
+        }
+
+        let mut sum_sq = [0.0_f32; 2];
+        let mut frames = 0;
+        let mut peak = 0.0_f32;
+
         for frame in latest.chunks_exact(channels) {
             let mono = frame.iter().copied().sum::<f32>() / channels as f32;
             self.display_tap.push_back(mono);
+            peak = peak.max(frame.iter().fold(0.0, |p, s| p.max(s.abs())));
+            if stereo {
+                self.stereo_tap.push_back([frame[0], frame[1]]);
+            }
+
+            // Mono lights both meters identically
+            let (l, r) = match channels {
+                1 => (frame[0], frame[0]),
+                _ => (frame[0], frame[1]),
+            };
+            sum_sq[0] += l * l;
+            sum_sq[1] += r * r;
+            frames += 1;
         }
+        self.vu_meter.update(sum_sq, frames);
+        self.gain.update(peak);
 
-        let overflow = self.display_tap.len().saturating_sub(TAP_BUFFER_CAPACITY);
+        let overflow = self.display_tap.len().saturating_sub(capacity);
         self.display_tap.drain(..overflow);
+        self.spectrum_fresh = false;
+
+        let overflow = self.stereo_tap.len().saturating_sub(scope_capacity);
+        self.stereo_tap.drain(..overflow);
     }
 
     pub fn update_spectrum(&mut self) {
-        if !self.display_tap.is_empty() {
+        if !self.spectrum_fresh && !self.display_tap.is_empty() {
+            let span = self.window_frames();
             let samples = self.display_tap.make_contiguous();
             let sample_rate = self.metrics.sample_rate();
-            self.spectrum.update(samples, sample_rate);
+            self.spectrum.update(samples, sample_rate, span);
+            self.spectrum_fresh = true;
+        }
+    }
+
+    pub fn beat(&self) -> &BeatDetector {
+        &self.beat
+    }
+
+    /// Runs the spectrum if no widget has this frame, true on a beat
+    pub fn detect_beat(&mut self) -> bool {
+        self.update_spectrum();
+        self.beat.update(self.spectrum.bass_energy())
+    }
+
+    pub fn next_fft_size(&mut self) {
+        self.spectrum.next_fft_size();
+    }
+
+    pub fn set_fft_size(&mut self, size: usize) {
+        self.spectrum.set_fft_size(size);
+    }
+
+    /// The user's mirror choice wins over the theme's once it has been toggled
+    pub fn spectrum_mirrored(&self, theme_default: bool) -> bool {
+        self.spectrum_mirror.unwrap_or(theme_default)
+    }
+
+    pub fn spectrum_mirror_override(&self) -> Option<bool> {
+        self.spectrum_mirror
+    }
+
+    pub fn set_spectrum_mirror(&mut self, mirror: Option<bool>) {
+        self.spectrum_mirror = mirror
+    }
+
+    pub fn toggle_spectrum_mirror(&mut self, theme_default: bool) {
+        self.spectrum_mirror = Some(!self.spectrum_mirrored(theme_default))
+    }
+
+    /// Like the mirror, the user's choice wins once it has been toggled
+    pub fn spectrum_coloring(&self, theme_default: SpectrumColoring) -> SpectrumColoring {
+        self.spectrum_coloring.unwrap_or(theme_default)
+    }
+
+    pub fn spectrum_coloring_override(&self) -> Option<SpectrumColoring> {
+        self.spectrum_coloring
+    }
+
+    pub fn set_spectrum_coloring(&mut self, coloring: Option<SpectrumColoring>) {
+        self.spectrum_coloring = coloring
+    }
+
+    pub fn toggle_spectrum_coloring(&mut self, theme_default: SpectrumColoring) {
+        self.spectrum_coloring = Some(self.spectrum_coloring(theme_default).toggle())
+    }
+
+    /// The user's decay wins over the theme's once it has been adjusted
+    pub fn set_theme_decay(&mut self, theme_default: f32) {
+        self.spectrum
+            .set_decay(self.spectrum_decay.unwrap_or(theme_default));
+    }
+
+    pub fn spectrum_decay_override(&self) -> Option<f32> {
+        self.spectrum_decay
+    }
+
+    /// Puts back a saved decay, without flashing it on screen
+    pub fn set_spectrum_decay(&mut self, decay: Option<f32>) {
+        self.spectrum_decay = decay;
+        if let Some(d) = decay {
+            self.spectrum.set_decay(d);
+        }
+    }
+
+    /// Up keeps bars up longer, down lets them drop sooner
+    pub fn shift_spectrum_decay(&mut self, direction: Incrementor) {
+        let step = match direction {
+            Incrementor::Up => spectrum::DECAY_STEP,
+            Incrementor::Down => -spectrum::DECAY_STEP,
+        };
+        // Rounded so repeated steps land back on the theme's values
+        let decay = ((self.spectrum.decay() + step) * 100.0).round() / 100.0;
+
+        self.spectrum.set_decay(decay);
+        self.spectrum_decay = Some(self.spectrum.decay());
+        self.decay_changed_at = Some(Instant::now());
+    }
+
+    pub fn toggle_spectrum_auto_gain(&mut self) {
+        let enabled = !self.spectrum.auto_gain_enabled();
+        self.spectrum.set_auto_gain(enabled);
+        self.agc_changed_at = Some(Instant::now());
+    }
+
+    /// What to show over the spectrum: the latest change for a moment, and
+    /// otherwise a reminder while the auto-gain is bypassed
+    pub fn spectrum_label(&self) -> Option<String> {
+        let recent = |t: &Option<Instant>| t.is_some_and(|t| t.elapsed() < DECAY_LABEL_DURATION);
+        let agc_newer = self.agc_changed_at > self.decay_changed_at;
+
+        match self.spectrum.auto_gain_enabled() {
+            _ if recent(&self.decay_changed_at) && !agc_newer => {
+                Some(format!("decay {:.2}", self.spectrum.decay()))
+            }
+            true if recent(&self.agc_changed_at) => Some("auto-gain".to_string()),
+            true => None,
+            false => Some("raw".to_string()),
         }
     }
+
+    pub fn toggle_spectrum_peaks(&mut self) {
+        let show = !self.spectrum.peaks_enabled();
+        self.spectrum.set_show_peaks(show);
+    }
+
+    pub fn next_window_fn(&mut self) {
+        let next = self.spectrum.window_fn().next();
+        self.spectrum.set_window_fn(next);
+    }
+
+    pub fn set_window_fn(&mut self, window_fn: WindowFn) {
+        self.spectrum.set_window_fn(window_fn);
+    }
+
+    pub fn toggle_amplitude_scale(&mut self) {
+        let next = self.spectrum.scale().toggle();
+        self.spectrum.set_scale(next);
+    }
 }
@@ -7,6 +7,10 @@ pub enum ProgressDisplay {
     ProgressBar,
     #[default]
     Spectrum,
+    /// The spectrum wrapped around a circle
+    RadialSpectrum,
+    Spectrogram,
+    VuMeter,
 }
 
 impl ProgressDisplay {
@@ -15,7 +19,10 @@ impl ProgressDisplay {
             Self::ProgressBar => Self::Waveform,
             Self::Waveform => Self::Oscilloscope,
             Self::Oscilloscope => Self::Spectrum,
-            Self::Spectrum => Self::ProgressBar,
+            Self::Spectrum => Self::RadialSpectrum,
+            Self::RadialSpectrum => Self::Spectrogram,
+            Self::Spectrogram => Self::VuMeter,
+            Self::VuMeter => Self::ProgressBar,
         }
     }
 
@@ -24,6 +31,9 @@ impl ProgressDisplay {
             "waveform" => Self::Waveform,
             "oscilloscope" => Self::Oscilloscope,
             "progress_bar" => Self::ProgressBar,
+            "radial_spectrum" => Self::RadialSpectrum,
+            "spectrogram" => Self::Spectrogram,
+            "vu_meter" => Self::VuMeter,
             _ => Self::Spectrum,
         }
     }
@@ -34,6 +44,9 @@ impl std::fmt::Display for ProgressDisplay {
         match self {
             ProgressDisplay::Waveform => write!(f, "waveform"),
             ProgressDisplay::Spectrum => write!(f, "spectrum"),
+            ProgressDisplay::RadialSpectrum => write!(f, "radial_spectrum"),
+            ProgressDisplay::Spectrogram => write!(f, "spectrogram"),
+            ProgressDisplay::VuMeter => write!(f, "vu_meter"),
             ProgressDisplay::ProgressBar => write!(f, "progress_bar"),
             ProgressDisplay::Oscilloscope => write!(f, "oscilloscope"),
         }
@@ -0,0 +1,64 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How often a new row is pushed, independent of the render rate
+const ROW_INTERVAL: Duration = Duration::from_millis(40);
+
+/// Rolling history of spectrum frames, newest first.
+#[derive(Default)]
+pub struct Spectrogram {
+    rows: VecDeque<Vec<f32>>,
+    width: usize,
+    capacity: usize,
+    last_push: Option<Instant>,
+}
+
+impl Spectrogram {
+    /// Match the pane, padding or truncating stored rows rather than
+    /// discarding the history.
+    pub fn resize(&mut self, width: usize, capacity: usize) {
+        if width != self.width {
+            self.width = width;
+            for row in self.rows.iter_mut() {
+                row.resize(width, 0.0);
+            }
+        }
+
+        self.capacity = capacity;
+        self.rows.truncate(capacity);
+    }
+
+    pub fn push(&mut self, bins: &[f32]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(last) = self.last_push
+            && last.elapsed() < ROW_INTERVAL
+        {
+            return;
+        }
+        self.last_push = Some(Instant::now());
+
+        let mut row = match self.rows.len() >= self.capacity {
+            true => self.rows.pop_back().unwrap_or_default(),
+            false => Vec::with_capacity(self.width),
+        };
+
+        row.clear();
+        row.extend_from_slice(&bins[..bins.len().min(self.width)]);
+        row.resize(self.width, 0.0);
+        self.rows.push_front(row);
+    }
+
+    pub fn rows(&self) -> &VecDeque<Vec<f32>> {
+        &self.rows
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.last_push = None;
+    }
+}
@@ -1,50 +1,73 @@
-use spectrum_analyzer::{FrequencyLimit, samples_fft_to_spectrum, windows::hann_window};
+use super::{AmplitudeScale, WindowFn};
+use spectrum_analyzer::{FrequencyLimit, samples_fft_to_spectrum};
 
 const PEAK_FLOOR: f32 = 3e-3;
 
+const PEAK_FALL_RATE: f32 = 0.015;
+
+/// How much of a bar is kept each frame it isn't pushed back up. Much
+/// lower and the bars flicker, at 1.0 they'd never fall.
+pub const MIN_DECAY: f32 = 0.7;
+pub const MAX_DECAY: f32 = 0.97;
+pub const DECAY_STEP: f32 = 0.03;
+
+/// With the auto-gain bypassed, a full-scale sine through the Hann window
+/// just reaches the top
+const RAW_GAIN: f32 = 2.0;
+
+/// Bands entirely below this count towards the bass energy beats are
+/// detected from
+const BASS_CUTOFF: f32 = 150.0;
+
+pub const MIN_FFT_SIZE: usize = 256;
+pub const MAX_FFT_SIZE: usize = 8192;
+
 pub struct SpectrumState {
     bins: Vec<f32>,
     display_bins: Vec<f32>,
+    display_peaks: Vec<f32>,
+    show_peaks: bool,
     decay_factor: f32,
     bands: Vec<(f32, f32)>,
     band_peaks: Vec<f32>,
+    fft_size: usize,
+    window_fn: WindowFn,
+    scale: AmplitudeScale,
     sample_rate: u32,
     last_display_width: usize,
     bins_dirty: bool,
+    bass_energy: f32,
+    /// Off shows every band against the same full scale, so quiet passages
+    /// look quiet
+    auto_gain: bool,
 }
 
 impl SpectrumState {
-    pub fn update(&mut self, samples: &[f32], sample_rate: u32) {
+    /// `span` is how many of the latest samples go in, if fewer than the
+    /// FFT takes. They're windowed on their own and padded out with silence.
+    pub fn update(&mut self, samples: &[f32], sample_rate: u32, span: Option<usize>) {
         if sample_rate == 0 {
             return;
         }
 
-        let fft_size = super::TAP_BUFFER_CAPACITY;
+        let fft_size = self.fft_size;
 
         if self.sample_rate != sample_rate {
             self.sample_rate = sample_rate;
-            let freq_resolution = sample_rate as f32 / fft_size as f32;
-            self.bands.clear();
-            let mut freq = 20.0_f32;
-            while freq < 20000.0 {
-                let next = (freq * 1.05).max(freq + freq_resolution);
-                self.bands.push((freq, next.min(20000.0)));
-                freq = next;
-            }
-            let n = self.bands.len();
-            self.band_peaks.resize(n, PEAK_FLOOR);
-            self.bins.resize(n, 0.0);
+            self.rebuild_bands();
         }
 
-        if samples.len() < fft_size {
+        let span = span.unwrap_or(fft_size).clamp(1, fft_size);
+        if samples.len() < span {
             for bin in self.bins.iter_mut() {
                 *bin *= self.decay_factor;
             }
             return;
         }
 
-        let start = samples.len() - fft_size;
-        let windowed = hann_window(&samples[start..]);
+        let start = samples.len() - span;
+        let mut windowed = self.window_fn.apply(&samples[start..]);
+        windowed.resize(fft_size, 0.0);
 
         let spectrum = match samples_fft_to_spectrum(
             &windowed,
@@ -62,6 +85,7 @@ impl SpectrumState {
         };
 
         let mut data_iter = spectrum.data().iter().peekable();
+        let mut bass = (0.0_f32, 0_usize);
 
         for i in 0..self.bands.len() {
             let (lo, hi) = self.bands[i];
@@ -83,15 +107,23 @@ impl SpectrumState {
 
             let mag = if count > 0 { sum / count as f32 } else { 0.0 };
             let normalized = mag / (fft_size as f32 / 2.0);
+            if hi <= BASS_CUTOFF {
+                bass = (bass.0 + normalized, bass.1 + 1);
+            }
 
-            // Per-band auto-gain: instant attack, slow release
+            // Per-band auto-gain: instant attack, slow release. Tracked
+            // even while bypassed, so turning it back on doesn't jump.
             if normalized > self.band_peaks[i] {
                 self.band_peaks[i] = normalized;
             } else {
                 self.band_peaks[i] = (self.band_peaks[i] * 0.99).max(1e-3);
             }
 
-            let relative = (normalized / self.band_peaks[i]).clamp(0.0, 1.0);
+            let level = match self.auto_gain {
+                true => normalized / self.band_peaks[i],
+                false => normalized * RAW_GAIN,
+            };
+            let relative = self.scale.apply(level.clamp(0.0, 1.0));
 
             if relative > self.bins[i] {
                 self.bins[i] = relative;
@@ -100,9 +132,34 @@ impl SpectrumState {
             }
         }
 
+        self.bass_energy = bass.0 / bass.1.max(1) as f32;
         self.bins_dirty = true;
     }
 
+    /// Mean magnitude of the low bands in the newest spectrum, before the
+    /// per-band auto-gain flattens it
+    pub fn bass_energy(&self) -> f32 {
+        self.bass_energy
+    }
+
+    fn rebuild_bands(&mut self) {
+        self.bands.clear();
+
+        if self.sample_rate != 0 {
+            let freq_resolution = self.sample_rate as f32 / self.fft_size as f32;
+            let mut freq = 20.0_f32;
+            while freq < 20000.0 {
+                let next = (freq * 1.05).max(freq + freq_resolution);
+                self.bands.push((freq, next.min(20000.0)));
+                freq = next;
+            }
+        }
+
+        // Band edges shift with the resolution, so old peaks and bins are
+        // meaningless. Resetting keeps all three vectors the same length.
+        self.reset();
+    }
+
     pub fn remap_display(&mut self, width: usize) {
         if self.bins.is_empty() || (!self.bins_dirty && self.last_display_width == width) {
             return;
@@ -123,14 +180,112 @@ impl SpectrumState {
             });
         self.last_display_width = width;
         self.bins_dirty = false;
+
+        if self.show_peaks {
+            self.display_peaks.resize(width, 0.0);
+            self.display_peaks
+                .iter_mut()
+                .zip(self.display_bins.iter())
+                .for_each(|(peak, &bin)| {
+                    *peak = match bin >= *peak {
+                        true => bin,
+                        false => (*peak - PEAK_FALL_RATE).max(bin),
+                    }
+                });
+        }
+    }
+
+    /// The frequency at `position` across the display, 0.0 being the
+    /// lowest band, read off the same spread `remap_display` draws from
+    pub fn frequency_at(&self, position: f32) -> f32 {
+        match self.bands.len() {
+            0 => 20.0 * 1000f32.powf(position),
+            n => {
+                let (lo, hi) = self.bands[((position * (n - 1) as f32) as usize).min(n - 1)];
+                (lo * hi).sqrt()
+            }
+        }
     }
 
     pub fn get_display_bins(&self) -> &[f32] {
         &self.display_bins
     }
 
+    /// Peak caps for each display bin, or `None` while peak-hold is off
+    pub fn get_display_peaks(&self) -> Option<&[f32]> {
+        match self.show_peaks {
+            true => Some(&self.display_peaks),
+            false => None,
+        }
+    }
+
+    pub fn auto_gain_enabled(&self) -> bool {
+        self.auto_gain
+    }
+
+    pub fn set_auto_gain(&mut self, enabled: bool) {
+        self.auto_gain = enabled
+    }
+
+    pub fn peaks_enabled(&self) -> bool {
+        self.show_peaks
+    }
+
+    pub fn set_show_peaks(&mut self, show: bool) {
+        self.show_peaks = show;
+        if !show {
+            self.display_peaks.clear();
+        }
+    }
+
+    pub fn decay(&self) -> f32 {
+        self.decay_factor
+    }
+
     pub fn set_decay(&mut self, d: f32) {
-        self.decay_factor = d
+        self.decay_factor = d.clamp(MIN_DECAY, MAX_DECAY)
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Rounds up to the next power of two within `MIN_FFT_SIZE..=MAX_FFT_SIZE`,
+    /// so 3000 becomes 4096
+    pub fn set_fft_size(&mut self, size: usize) {
+        let size = size
+            .clamp(MIN_FFT_SIZE, MAX_FFT_SIZE)
+            .next_power_of_two()
+            .min(MAX_FFT_SIZE);
+
+        if size != self.fft_size {
+            self.fft_size = size;
+            self.rebuild_bands();
+        }
+    }
+
+    pub fn window_fn(&self) -> WindowFn {
+        self.window_fn
+    }
+
+    pub fn set_window_fn(&mut self, window_fn: WindowFn) {
+        self.window_fn = window_fn
+    }
+
+    pub fn scale(&self) -> AmplitudeScale {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: AmplitudeScale) {
+        self.scale = scale
+    }
+
+    pub fn next_fft_size(&mut self) {
+        let next = match self.fft_size >= MAX_FFT_SIZE {
+            true => MIN_FFT_SIZE,
+            false => self.fft_size * 2,
+        };
+        self.set_fft_size(next);
     }
 
     pub fn reset(&mut self) {
@@ -140,21 +295,33 @@ impl SpectrumState {
         self.bins.clear();
         self.bins.resize(n, 0.0);
         self.display_bins.fill(0.0);
+        self.display_peaks.fill(0.0);
+        self.bass_energy = 0.0;
         self.bins_dirty = true;
     }
 }
 
-impl Default for SpectrumState {
-    fn default() -> Self {
-        SpectrumState {
+impl SpectrumState {
+    /// `fft_size` is rounded the same way `set_fft_size` does
+    pub fn new(fft_size: usize) -> Self {
+        let mut state = SpectrumState {
             bins: Vec::new(),
             display_bins: Vec::new(),
+            display_peaks: Vec::new(),
+            show_peaks: false,
             band_peaks: Vec::new(),
             bands: Vec::new(),
             decay_factor: 0.85,
+            fft_size: MIN_FFT_SIZE,
+            window_fn: WindowFn::default(),
+            scale: AmplitudeScale::default(),
             sample_rate: 0,
             last_display_width: 0,
             bins_dirty: false,
-        }
+            bass_energy: 0.0,
+            auto_gain: true,
+        };
+        state.set_fft_size(fft_size);
+        state
     }
 }
@@ -0,0 +1,118 @@
+use crate::{gen_config, key_handler::Incrementor};
+
+/// Past this the bars are mostly floor
+pub const MAX_MIN_HEIGHT: f32 = 0.5;
+const MIN_HEIGHT_STEP: f32 = 0.02;
+/// Wider gaps than this leave narrow bars with nothing between them
+pub const MAX_GAP: u8 = 4;
+
+/// The space between spectrum bars, in canvas dots
+#[derive(Clone, Copy, PartialEq)]
+pub enum BarGap {
+    /// One dot, once a fixed bar count makes the bars wide enough to need it
+    Auto,
+    Dots(u8),
+}
+
+impl BarGap {
+    pub fn step(&self, direction: Incrementor) -> Self {
+        match (self, direction) {
+            (BarGap::Auto, Incrementor::Up) => BarGap::Dots(0),
+            (BarGap::Auto, Incrementor::Down) => BarGap::Auto,
+            (BarGap::Dots(0), Incrementor::Down) => BarGap::Auto,
+            (BarGap::Dots(n), Incrementor::Down) => BarGap::Dots(n - 1),
+            (BarGap::Dots(n), Incrementor::Up) => BarGap::Dots((n + 1).min(MAX_GAP)),
+        }
+    }
+}
+
+impl From<&str> for BarGap {
+    fn from(s: &str) -> Self {
+        match s.trim().parse::<u8>() {
+            Ok(n) => BarGap::Dots(n.min(MAX_GAP)),
+            Err(_) => BarGap::Auto,
+        }
+    }
+}
+
+impl std::fmt::Display for BarGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BarGap::Auto => write!(f, "auto"),
+            BarGap::Dots(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+/// Rows of the visualizer settings popup
+#[derive(Clone, Copy, PartialEq)]
+pub enum LookSetting {
+    MinHeight,
+    Gap,
+}
+
+impl LookSetting {
+    pub const ALL: [LookSetting; 2] = [LookSetting::MinHeight, LookSetting::Gap];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LookSetting::MinHeight => "Minimum bar height",
+            LookSetting::Gap => "Gap between bars",
+        }
+    }
+}
+
+/// How the spectrum's bars are shaped. The config sets them, and anything
+/// changed from the popup wins over it from then on.
+#[derive(Default)]
+pub struct SpectrumLook {
+    min_height: Option<f32>,
+    gap: Option<BarGap>,
+}
+
+impl SpectrumLook {
+    /// The floor every bar is drawn up to, silent or not
+    pub fn min_height(&self) -> f32 {
+        self.min_height.unwrap_or(gen_config().spectrum_min_height)
+    }
+
+    pub fn gap(&self) -> BarGap {
+        self.gap.unwrap_or(gen_config().spectrum_gap)
+    }
+
+    pub fn value(&self, setting: LookSetting) -> String {
+        match setting {
+            LookSetting::MinHeight => format!("{:.2}", self.min_height()),
+            LookSetting::Gap => self.gap().to_string(),
+        }
+    }
+
+    pub fn shift(&mut self, setting: LookSetting, direction: Incrementor) {
+        match setting {
+            LookSetting::MinHeight => {
+                let step = match direction {
+                    Incrementor::Up => MIN_HEIGHT_STEP,
+                    Incrementor::Down => -MIN_HEIGHT_STEP,
+                };
+                // Rounded so repeated steps land back on round values
+                let height = ((self.min_height() + step) * 100.0).round() / 100.0;
+                self.min_height = Some(height.clamp(0.0, MAX_MIN_HEIGHT));
+            }
+            LookSetting::Gap => self.gap = Some(self.gap().step(direction)),
+        }
+    }
+
+    pub fn min_height_override(&self) -> Option<f32> {
+        self.min_height
+    }
+
+    pub fn gap_override(&self) -> Option<BarGap> {
+        self.gap
+    }
+
+    /// Puts back what was saved last session
+    pub fn restore(&mut self, min_height: Option<f32>, gap: Option<BarGap>) {
+        self.min_height = min_height.map(|h| h.clamp(0.0, MAX_MIN_HEIGHT));
+        self.gap = gap;
+    }
+}
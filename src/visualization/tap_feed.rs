@@ -0,0 +1,89 @@
+use crossbeam_channel::{Receiver, Sender, TrySendError, bounded};
+use std::{sync::Arc, time::Duration};
+
+/// Chunks a subscriber can fall behind by before new ones are dropped for it
+const BACKLOG: usize = 64;
+
+/// One read of the engine's tap, about a frame's worth of audio
+pub struct TapChunk {
+    /// Interleaved, `channels` to a frame. These are the samples as sent to
+    /// the device, so they carry the output volume and any fades.
+    pub samples: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+/// A read-only copy of the live output, taken from the samples the
+/// visualizer reads each frame. Subscribers only ever see chunks that were
+/// already read, so they can't hold up playback or the visualizer; one
+/// that falls more than a short backlog behind misses chunks until it
+/// catches up.
+pub struct TapSubscriber {
+    rx: Receiver<Arc<TapChunk>>,
+}
+
+impl TapSubscriber {
+    /// Everything published since the last call, oldest first
+    pub fn try_iter(&self) -> impl Iterator<Item = Arc<TapChunk>> + '_ {
+        self.rx.try_iter()
+    }
+
+    /// `None` on timeout, or once the player has shut down
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Arc<TapChunk>> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+}
+
+#[derive(Default)]
+pub struct TapFeed {
+    subscribers: Vec<Sender<Arc<TapChunk>>>,
+}
+
+impl TapFeed {
+    pub fn subscribe(&mut self) -> TapSubscriber {
+        let (tx, rx) = bounded(BACKLOG);
+        self.subscribers.push(tx);
+        TapSubscriber { rx }
+    }
+
+    /// Shared between subscribers rather than copied for each, and skipped
+    /// entirely while there are none. Dropped subscribers are forgotten.
+    pub fn publish(&mut self, samples: &[f32], channels: usize, sample_rate: u32) {
+        if self.subscribers.is_empty() || samples.is_empty() {
+            return;
+        }
+
+        let chunk = Arc::new(TapChunk {
+            samples: samples.to_vec(),
+            channels,
+            sample_rate,
+        });
+        self.subscribers.retain(|tx| {
+            !matches!(
+                tx.try_send(Arc::clone(&chunk)),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laggards_miss_chunks_and_dropped_subscribers_go() {
+        let mut feed = TapFeed::default();
+        let slow = feed.subscribe();
+        drop(feed.subscribe());
+
+        for i in 0..BACKLOG + 10 {
+            feed.publish(&[i as f32, 0.0], 2, 48_000);
+        }
+
+        assert_eq!(feed.subscribers.len(), 1);
+        let seen = slow.try_iter().map(|c| c.samples[0]).collect::<Vec<_>>();
+        assert_eq!(seen.len(), BACKLOG);
+        assert_eq!(seen[0], 0.0);
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+/// 0 VU sits at -18 dBFS, the usual digital alignment level
+const REFERENCE_DBFS: f32 = -18.0;
+const FLOOR_VU: f32 = -20.0;
+const CEILING_VU: f32 = 3.0;
+
+/// How fast the peak indicator falls back to the level, in VU per second
+const PEAK_FALL_RATE: f32 = 8.0;
+
+pub struct VuMeter {
+    levels: [f32; 2],
+    peaks: [f32; 2],
+    integration: Duration,
+    last_update: Option<Instant>,
+}
+
+impl VuMeter {
+    pub fn new(integration_ms: u32) -> Self {
+        VuMeter {
+            levels: [0.0; 2],
+            peaks: [FLOOR_VU; 2],
+            integration: Duration::from_millis(integration_ms as u64),
+            last_update: None,
+        }
+    }
+
+    /// Feed squared-sample sums for the frames since the last call. With no
+    /// frames (silence, pause) the target is zero and the needles fall back
+    /// to the floor on the same ballistics they rise with.
+    pub fn update(&mut self, sum_sq: [f32; 2], frames: usize) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map(|t| now.duration_since(t).as_secs_f32())
+            .unwrap_or(0.0);
+        self.last_update = Some(now);
+
+        let tau = self.integration.as_secs_f32().max(f32::EPSILON);
+        let alpha = 1.0 - (-dt / tau).exp();
+
+        for ((level, peak), sum) in self.levels.iter_mut().zip(&mut self.peaks).zip(sum_sq) {
+            let rms = match frames {
+                0 => 0.0,
+                n => (sum / n as f32).sqrt(),
+            };
+            *level += (rms - *level) * alpha;
+            *peak = (*peak - PEAK_FALL_RATE * dt).max(Self::to_vu(*level));
+        }
+    }
+
+    /// Level of each channel mapped onto the meter scale, `0.0..=1.0`
+    pub fn levels(&self) -> [f32; 2] {
+        self.levels.map(|l| Self::scale(Self::to_vu(l)))
+    }
+
+    pub fn peaks(&self) -> [f32; 2] {
+        self.peaks.map(Self::scale)
+    }
+
+    /// Where 0 VU falls on the meter scale; anything past it is the hot zone
+    pub fn zero_mark() -> f32 {
+        Self::scale(0.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.levels = [0.0; 2];
+        self.peaks = [FLOOR_VU; 2];
+        self.last_update = None;
+    }
+
+    fn to_vu(level: f32) -> f32 {
+        match level > 0.0 {
+            true => (20.0 * level.log10() - REFERENCE_DBFS).max(FLOOR_VU),
+            false => FLOOR_VU,
+        }
+    }
+
+    fn scale(vu: f32) -> f32 {
+        ((vu - FLOOR_VU) / (CEILING_VU - FLOOR_VU)).clamp(0.0, 1.0)
+    }
+}
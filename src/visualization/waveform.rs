@@ -5,11 +5,14 @@ use voxio::{BinMetric, Waveform, WaveformOptions};
 
 use crate::{
     key_handler::Incrementor,
-    library::{SimpleSong, SongDatabase},
+    library::{SimpleSong, SongDatabase, cue::CueSpan},
     visualization::Visualizer,
 };
 
 const WF_BIN_LEN: usize = 500;
+/// A cue track is cut out of its file's waveform, so the whole file is
+/// drawn at a finer grain to leave the track enough bins
+const CUE_BIN_SCALE: usize = 8;
 const SMOOTHNESS_STEP: f32 = 0.5;
 
 pub struct WaveformManager {
@@ -39,17 +42,24 @@ impl WaveformManager {
 
         if let Ok(path) = song.get_path() {
             let (tx, rx) = crossbeam_channel::bounded(1);
+            let cue = song.cue();
 
             thread::spawn(move || {
                 if let Ok(mut wf) = Waveform::generate(
                     &path,
                     &WaveformOptions {
-                        bins: WF_BIN_LEN,
+                        bins: match cue {
+                            Some(_) => WF_BIN_LEN * CUE_BIN_SCALE,
+                            None => WF_BIN_LEN,
+                        },
                         metric: BinMetric::Rms,
                         highpass_hz: Some(350.0),
                         treble_db: 9.0,
                     },
                 ) {
+                    if let Some(span) = cue {
+                        cut_to_span(&mut wf, span);
+                    }
                     wf.local_normalize(0.8, 20);
                     wf.normalize();
                     wf.contrast(1.5);
@@ -83,6 +93,19 @@ impl WaveformManager {
     }
 }
 
+fn cut_to_span(wf: &mut Waveform, span: CueSpan) {
+    let secs = wf.duration.as_secs_f64();
+    if secs <= 0.0 {
+        return;
+    }
+
+    let len = wf.bins.len();
+    let bin = |t: f64| ((t / secs * len as f64).round() as usize).min(len);
+    let (start, end) = (bin(span.start), bin(span.end.unwrap_or(secs)));
+
+    wf.bins = wf.bins[start..end.max(start)].to_vec();
+}
+
 // Internal waveform methods
 impl WaveformManager {
     fn clear(&mut self) {
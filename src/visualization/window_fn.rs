@@ -0,0 +1,59 @@
+use spectrum_analyzer::windows::{blackman_harris_4term, hamming_window, hann_window};
+
+/// Window applied to the tapped samples before the FFT.
+///
+/// The cosine windows (Hann, Hamming) are a single multiply per sample and cost
+/// roughly the same. Blackman-Harris sums four cosine terms per sample, so it
+/// is the most expensive, though still small next to the FFT itself.
+/// Rectangular skips windowing entirely: cheapest and sharpest resolution, at
+/// the cost of heavy spectral leakage.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum WindowFn {
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+    Rectangular,
+}
+
+impl WindowFn {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Hann => Self::Hamming,
+            Self::Hamming => Self::Blackman,
+            Self::Blackman => Self::Rectangular,
+            Self::Rectangular => Self::Hann,
+        }
+    }
+
+    pub fn apply(&self, samples: &[f32]) -> Vec<f32> {
+        match self {
+            Self::Hann => hann_window(samples),
+            Self::Hamming => hamming_window(samples),
+            Self::Blackman => blackman_harris_4term(samples),
+            Self::Rectangular => samples.to_vec(),
+        }
+    }
+}
+
+impl From<&str> for WindowFn {
+    fn from(s: &str) -> Self {
+        match s {
+            "hamming" => Self::Hamming,
+            "blackman" => Self::Blackman,
+            "rectangular" => Self::Rectangular,
+            _ => Self::Hann,
+        }
+    }
+}
+
+impl std::fmt::Display for WindowFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowFn::Hann => write!(f, "hann"),
+            WindowFn::Hamming => write!(f, "hamming"),
+            WindowFn::Blackman => write!(f, "blackman"),
+            WindowFn::Rectangular => write!(f, "rectangular"),
+        }
+    }
+}